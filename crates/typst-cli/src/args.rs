@@ -5,6 +5,7 @@
 
 use std::fmt::{self, Display, Formatter};
 use std::io::Write;
+use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::ops::RangeInclusive;
 use std::path::PathBuf;
@@ -87,6 +88,12 @@ pub enum Command {
     #[command(visible_alias = "w")]
     Watch(WatchCommand),
 
+    /// Runs a compile server that keeps its state between requests.
+    Serve(ServeCommand),
+
+    /// Runs a language server over stdin and stdout.
+    Lsp(LspCommand),
+
     /// Initializes a new project from a template.
     Init(InitCommand),
 
@@ -97,6 +104,18 @@ pub enum Command {
     /// Evaluates a piece of Typst code, optionally in the context of a document.
     Eval(EvalCommand),
 
+    /// Formats Typst source files.
+    Fmt(FmtCommand),
+
+    /// Checks a project for likely mistakes without compiling it.
+    Check(CheckCommand),
+
+    /// Copies the packages a project depends on into its `vendor` directory.
+    Vendor(VendorCommand),
+
+    /// Validates and bundles a package for publishing.
+    Package(PackageCommand),
+
     /// Lists all discovered fonts in system and custom font paths.
     Fonts(FontsCommand),
 
@@ -132,6 +151,81 @@ pub struct WatchCommand {
     pub server: ServerArgs,
 }
 
+/// Runs a compile server that keeps its state between requests.
+///
+/// The server speaks line-delimited JSON: Each request is a single line with
+/// an `id` and a `method`, and is answered by a single line with the same
+/// `id` and either a `result` or an `error`. The supported methods are:
+///
+/// - `update` with a `path` and a `text`: Replaces the contents of a file with
+///   in-memory text.
+///
+/// - `edit` with a `path`, a byte `range` (with `start` and `end`), and a
+///   `text`: Replaces a range in the current contents of a file.
+///
+/// - `close` with a `path`: Reverts a file to its contents on disk.
+///
+/// - `compile` with an optional `format` (`svg` or `png`): Compiles the
+///   document and responds with its diagnostics and pages. Only pages that
+///   changed since they were last sent carry their data.
+///
+/// - `shutdown`: Stops the server.
+#[derive(Debug, Clone, Parser)]
+pub struct ServeCommand {
+    /// Path to the input Typst file.
+    #[clap(value_parser = input_value_parser(), value_hint = ValueHint::FilePath)]
+    pub input: Input,
+
+    /// Talks to a client over a TCP connection on the given address instead
+    /// of over stdin and stdout, e.g. `127.0.0.1:3999`.
+    ///
+    /// Only loopback addresses are accepted unless `--allow-remote` is given.
+    #[clap(long, value_name = "ADDR")]
+    pub listen: Option<SocketAddr>,
+
+    /// Allows listening on an address that isn't a loopback address.
+    ///
+    /// The protocol is unauthenticated and lets clients replace the contents
+    /// of project files and read the compiled result, so anyone who can reach
+    /// the address can do so.
+    #[clap(long, requires = "listen")]
+    pub allow_remote: bool,
+
+    /// The PPI (pixels per inch) to render PNG pages with.
+    #[arg(long = "ppi", default_value_t = 144.0)]
+    pub ppi: f32,
+
+    /// World arguments.
+    #[clap(flatten)]
+    pub world: WorldArgs,
+
+    /// Processing arguments.
+    #[clap(flatten)]
+    pub process: ProcessArgs,
+}
+
+/// Runs a language server over stdin and stdout.
+///
+/// Besides the standard requests for completion, hover, go-to-definition,
+/// references, renaming, and document symbols, the server answers
+/// `typst/jumpFromCursor` and `typst/jumpFromClick` requests to synchronize a
+/// preview with the editor.
+#[derive(Debug, Clone, Parser)]
+pub struct LspCommand {
+    /// Path to the main Typst file. Defaults to the first file opened in the
+    /// editor.
+    #[clap(value_hint = ValueHint::FilePath)]
+    pub input: Option<PathBuf>,
+
+    /// World arguments.
+    #[clap(flatten)]
+    pub world: WorldArgs,
+
+    /// Processing arguments.
+    #[clap(flatten)]
+    pub process: ProcessArgs,
+}
+
 /// Initializes a new project from a template.
 #[derive(Debug, Clone, Parser)]
 pub struct InitCommand {
@@ -226,6 +320,130 @@ pub struct EvalCommand {
     pub process: ProcessArgs,
 }
 
+/// Formats Typst source files.
+///
+/// Normalizes the spacing and line breaks of code and the indentation of
+/// markup. Comments, raw blocks, and equations are kept as they are.
+#[derive(Debug, Clone, Parser)]
+pub struct FmtCommand {
+    /// Paths to the files or directories to format. Directories are searched
+    /// for `.typ` files recursively, skipping hidden files and those ignored
+    /// by `.gitignore`. Use `-` to read from stdin and write to stdout.
+    #[clap(required = true, value_hint = ValueHint::AnyPath)]
+    pub paths: Vec<PathBuf>,
+
+    /// The maximum number of characters per line.
+    #[clap(long, default_value_t = 80)]
+    pub line_width: usize,
+
+    /// Doesn't write any files, but fails if any of them isn't formatted.
+    #[clap(long)]
+    pub check: bool,
+}
+
+/// Checks a project for likely mistakes without compiling it.
+///
+/// Reports unused bindings and imports, parameters that shadow other
+/// variables, `set` rules without effect, unreachable code, and labels that
+/// are never referenced. Checks the input file and the files it imports or
+/// includes. Fails if there are any warnings.
+///
+/// A warning can be suppressed with a `// lint: allow(name)` comment in the
+/// same or the preceding line.
+#[derive(Debug, Clone, Parser)]
+pub struct CheckCommand {
+    /// Path to the input Typst file. Use `-` to read input from stdin.
+    #[clap(value_parser = input_value_parser(), value_hint = ValueHint::FilePath)]
+    pub input: Input,
+
+    /// World arguments.
+    #[clap(flatten)]
+    pub world: WorldArgs,
+
+    /// Processing arguments.
+    #[clap(flatten)]
+    pub process: ProcessArgs,
+}
+
+/// Copies the packages a project depends on into its `vendor` directory.
+///
+/// Compiles the input file to find all packages it imports, directly or
+/// indirectly, and copies them into the `vendor` directory next to the
+/// project root. Vendored packages take precedence over the local package
+/// directory and the package cache. Together with `--frozen`, this allows
+/// compiling the project without network access.
+#[derive(Debug, Clone, Parser)]
+pub struct VendorCommand {
+    /// Path to the input Typst file. Use `-` to read input from stdin.
+    #[clap(value_parser = input_value_parser(), value_hint = ValueHint::FilePath)]
+    pub input: Input,
+
+    /// World arguments.
+    #[clap(flatten)]
+    pub world: WorldArgs,
+
+    /// Processing arguments.
+    #[clap(flatten)]
+    pub process: ProcessArgs,
+}
+
+/// Validates and bundles a package for publishing.
+#[derive(Debug, Clone, Parser)]
+pub struct PackageCommand {
+    /// The package command to run.
+    #[command(subcommand)]
+    pub command: PackageSubcommand,
+}
+
+/// What to do with a package.
+#[derive(Debug, Clone, Subcommand)]
+pub enum PackageSubcommand {
+    /// Checks that a package is ready to be published.
+    Check(PackageCheckCommand),
+
+    /// Checks a package and bundles it into a `.tar.gz` archive.
+    Bundle(PackageBundleCommand),
+}
+
+/// Checks that a package is ready to be published.
+///
+/// Validates the manifest and the template, applies the `exclude` globs,
+/// compiles the entrypoint, and ensures that the package only uses files that
+/// are part of the bundle. Warns about unknown manifest keys and oversized
+/// files. Fails if there are any errors.
+#[derive(Debug, Clone, Parser)]
+pub struct PackageCheckCommand {
+    /// Path to the package directory containing the `typst.toml` manifest.
+    #[clap(default_value = ".", value_hint = ValueHint::DirPath)]
+    pub dir: PathBuf,
+
+    /// World arguments.
+    #[clap(flatten)]
+    pub world: WorldArgs,
+
+    /// Processing arguments.
+    #[clap(flatten)]
+    pub process: ProcessArgs,
+}
+
+/// Checks a package and bundles it into a `.tar.gz` archive.
+///
+/// The archive contains all files of the package that are not excluded,
+/// hidden, or ignored by `.gitignore`. They are stored in sorted order and
+/// with normalized metadata, so that bundling the same package twice yields
+/// identical archives.
+#[derive(Debug, Clone, Parser)]
+pub struct PackageBundleCommand {
+    /// Arguments for checking the package.
+    #[clap(flatten)]
+    pub check: PackageCheckCommand,
+
+    /// Path to the archive. Defaults to `{name}-{version}.tar.gz` in the
+    /// current working directory.
+    #[clap(long, short, value_hint = ValueHint::FilePath)]
+    pub output: Option<PathBuf>,
+}
+
 /// Lists all discovered fonts in system and custom font paths.
 #[derive(Debug, Clone, Parser)]
 pub struct FontsCommand {
@@ -299,11 +517,11 @@ pub struct CompileArgs {
     /// Path to output file (PDF, PNG, SVG, or HTML). Use `-` to write output to
     /// stdout.
     ///
-    /// For output formats emitting one file per page (PNG & SVG), a page number
-    /// template must be present if the source document renders to multiple
-    /// pages. Use `{p}` for page numbers, `{0p}` for zero padded page numbers
-    /// and `{t}` for page count. For example, `page-{0p}-of-{t}.png` creates
-    /// `page-01-of-10.png`, `page-02-of-10.png`, and so on.
+    /// For output formats emitting one file per page (PNG, JPEG, WebP & SVG), a
+    /// page number template must be present if the source document renders to
+    /// multiple pages. Use `{p}` for page numbers, `{0p}` for zero padded page
+    /// numbers and `{t}` for page count. For example, `page-{0p}-of-{t}.png`
+    /// creates `page-01-of-10.png`, `page-02-of-10.png`, and so on.
     #[clap(
          required_if_eq("input", "-"),
          value_parser = output_value_parser(),
@@ -344,10 +562,90 @@ pub struct CompileArgs {
     #[arg(long = "no-pdf-tags")]
     pub no_pdf_tags: bool,
 
-    /// The PPI (pixels per inch) to use for PNG export.
+    /// Encrypts the PDF and requires this password to open it.
+    #[arg(
+        long = "pdf-user-password",
+        env = "TYPST_PDF_USER_PASSWORD",
+        hide_env_values = true,
+        value_name = "PASSWORD"
+    )]
+    pub pdf_user_password: Option<String>,
+
+    /// Encrypts the PDF and grants all permissions to readers who open it with
+    /// this password.
+    ///
+    /// Without an owner password, permissions denied with `--pdf-deny` cannot
+    /// be lifted.
+    #[arg(
+        long = "pdf-owner-password",
+        env = "TYPST_PDF_OWNER_PASSWORD",
+        hide_env_values = true,
+        value_name = "PASSWORD"
+    )]
+    pub pdf_owner_password: Option<String>,
+
+    /// Encrypts the PDF and denies one (or multiple comma-separated)
+    /// permissions to readers who don't know the owner password.
+    #[arg(long = "pdf-deny", value_delimiter = ',', value_name = "PERMISSION")]
+    pub pdf_deny: Vec<PdfPermission>,
+
+    /// Printer's marks to draw around each page of a PDF, outside of the
+    /// page's bleed. Enlarges the pages to make room for them.
+    #[arg(long = "pdf-marks", value_delimiter = ',', value_name = "MARK")]
+    pub pdf_marks: Vec<PdfMark>,
+
+    /// The PPI (pixels per inch) to use for PNG, JPEG, and WebP export.
     #[arg(long = "ppi", default_value_t = 144.0)]
     pub ppi: f32,
 
+    /// The quality of JPEG output, from 1 to 100. Defaults to 90.
+    #[arg(
+        long = "jpeg-quality",
+        value_name = "QUALITY",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub jpeg_quality: Option<u8>,
+
+    /// The color that transparent parts of pages are composited onto in JPEG
+    /// output, as a hex code like `#ffffff`. Defaults to white.
+    #[arg(
+        long = "jpeg-background",
+        value_name = "COLOR",
+        value_parser = ValueParser::new(parse_hex_color),
+    )]
+    pub jpeg_background: Option<[u8; 3]>,
+
+    /// How long to show each slide before advancing when exporting slides, in
+    /// seconds. Pages can override this with `slide` metadata.
+    ///
+    /// Without a duration, the HTML slideshow doesn't advance on its own and
+    /// animated PNG frames are shown for five seconds.
+    #[arg(long = "slide-duration", value_name = "SECONDS")]
+    pub slide_duration: Option<f64>,
+
+    /// Splits HTML output into multiple files, which are written into the
+    /// output directory.
+    ///
+    /// A new file starts at each `html.split` element. If a heading level is
+    /// given, headings of at most that level also start new files. The first
+    /// file, `index.html`, lists all others.
+    #[arg(long = "html-split", value_name = "LEVEL")]
+    pub html_split: Option<Option<NonZeroUsize>>,
+
+    /// Writes images and frames in HTML output into separate files in an
+    /// `assets` directory next to the HTML, instead of embedding them.
+    ///
+    /// The files are named after a hash of their contents, so that browsers
+    /// can cache them.
+    #[arg(long = "html-assets")]
+    pub html_assets: bool,
+
+    /// Translates text, paragraph, and block styles from set rules into a
+    /// stylesheet in HTML output, so that the document keeps its look in the
+    /// browser.
+    #[arg(long = "html-stylesheet")]
+    pub html_stylesheet: bool,
+
     /// File path to which a Makefile with the current compilation's
     /// dependencies will be written.
     #[clap(long = "make-deps", value_name = "PATH", hide = true)]
@@ -454,6 +752,14 @@ pub struct PackageArgs {
         value_name = "DIR"
     )]
     pub package_cache_path: Option<PathBuf>,
+
+    /// Fails instead of downloading packages and requires packages imported
+    /// without a version to be locked in `typst.lock`.
+    ///
+    /// Packages in the project's `vendor` directory, the local package
+    /// directory, and the package cache remain available.
+    #[clap(long)]
+    pub frozen: bool,
 }
 
 /// Common arguments to customize available fonts.
@@ -597,14 +903,20 @@ impl Write for OpenOutput<'_> {
 pub enum OutputFormat {
     Pdf,
     Png,
+    #[value(alias = "jpg")]
+    Jpeg,
+    Webp,
     Svg,
     Html,
+    Epub,
+    Slides,
+    Apng,
 }
 
 impl OutputFormat {
     /// Whether this format results in a `PagedDocument`.
     pub fn is_paged(&self) -> bool {
-        matches!(self, Self::Pdf | Self::Png | Self::Svg)
+        !matches!(self, Self::Html | Self::Epub)
     }
 }
 
@@ -714,6 +1026,38 @@ pub enum PdfStandard {
 
 display_possible_values!(PdfStandard);
 
+/// A permission that can be denied to readers of an encrypted PDF.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PdfPermission {
+    /// Printing the document.
+    Print,
+    /// Copying text and graphics.
+    Copy,
+    /// Modifying the document, including inserting and deleting pages.
+    Modify,
+    /// Adding and changing annotations. Also allows filling in form fields.
+    Annotate,
+    /// Filling in form fields.
+    FillForms,
+    /// Extracting text and graphics for assistive technology.
+    Accessibility,
+}
+
+display_possible_values!(PdfPermission);
+
+/// A printer's mark that can be drawn around the pages of a PDF.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PdfMark {
+    /// Lines at the corners that show where to cut the page.
+    Crop,
+    /// Crosshairs for aligning the printing plates.
+    Registration,
+    /// Patches of the process colors for checking the print's colors.
+    ColorBars,
+}
+
+display_possible_values!(PdfMark);
+
 /// Output file format for query and info commands
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ValueEnum)]
 pub enum SerializationFormat {
@@ -811,6 +1155,16 @@ fn parse_sys_input_pair(raw: &str) -> Result<(String, String), String> {
     Ok((key, val))
 }
 
+/// Parses a color given as a hex code with or without a leading `#`.
+fn parse_hex_color(raw: &str) -> Result<[u8; 3], String> {
+    let hex = raw.strip_prefix('#').unwrap_or(raw);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("color must be a six-digit hex code like `#ffffff`".to_owned());
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+    Ok([channel(0), channel(2), channel(4)])
+}
+
 /// Parses a UNIX timestamp according to <https://reproducible-builds.org/specs/source-date-epoch/>
 fn parse_source_date_epoch(raw: &str) -> Result<DateTime<Utc>, String> {
     let timestamp: i64 = raw
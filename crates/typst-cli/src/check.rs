@@ -0,0 +1,82 @@
+use ecow::eco_format;
+use rustc_hash::FxHashSet;
+use typst::World;
+use typst::diag::{HintedStrResult, SourceDiagnostic};
+use typst::syntax::ast::{self, AstNode};
+use typst::syntax::{FileId, Source, SyntaxNode};
+
+use crate::args::CheckCommand;
+use crate::compile::print_diagnostics;
+use crate::set_failed;
+use crate::world::SystemWorld;
+
+/// Execute a check command.
+pub fn check(command: &'static CheckCommand) -> HintedStrResult<()> {
+    let world = SystemWorld::new(Some(&command.input), &command.world, &command.process)?;
+    let main = world.source(world.main()).map_err(|err| err.to_string())?;
+
+    // Files with syntax errors are not linted, as the errors would only
+    // cause spurious warnings.
+    let mut errors: Vec<SourceDiagnostic> = vec![];
+    let mut sources = vec![];
+    for source in collect(&world, main) {
+        let syntax = source.root().errors();
+        if syntax.is_empty() {
+            sources.push(source);
+        } else {
+            errors.extend(syntax.into_iter().map(Into::into));
+        }
+    }
+
+    let warnings = typst_eval::lint(&sources);
+    if !errors.is_empty() || !warnings.is_empty() {
+        set_failed();
+    }
+
+    print_diagnostics(&world, &errors, &warnings, command.process.diagnostic_format)
+        .map_err(|err| eco_format!("failed to print diagnostics ({err})"))?;
+
+    Ok(())
+}
+
+/// Collects the main file and the files of the project that it imports or
+/// includes, directly or indirectly, through a literal path.
+fn collect(world: &SystemWorld, main: Source) -> Vec<Source> {
+    let mut seen = FxHashSet::from_iter([main.id()]);
+    let mut sources = vec![main];
+    let mut i = 0;
+    while let Some(source) = sources.get(i) {
+        let mut ids = vec![];
+        dependencies(source.id(), source.root(), &mut ids);
+        for id in ids {
+            if seen.insert(id)
+                && let Ok(source) = world.source(id)
+            {
+                sources.push(source);
+            }
+        }
+        i += 1;
+    }
+    sources
+}
+
+/// Collects the files that a file imports or includes through a literal
+/// path, excluding packages.
+fn dependencies(id: FileId, node: &SyntaxNode, ids: &mut Vec<FileId>) {
+    let path = if let Some(import) = node.cast::<ast::ModuleImport>() {
+        Some(import.source())
+    } else {
+        node.cast::<ast::ModuleInclude>().map(|include| include.source())
+    };
+
+    if let Some(ast::Expr::Str(path)) = path {
+        let path = path.get();
+        if !path.starts_with('@') && path.ends_with(".typ") {
+            ids.push(id.join(&path));
+        }
+    }
+
+    for child in node.children() {
+        dependencies(id, child, ids);
+    }
+}
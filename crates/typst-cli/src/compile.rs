@@ -1,10 +1,13 @@
 use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
 
 use chrono::{DateTime, Datelike, Timelike, Utc};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::term;
-use ecow::eco_format;
+use ecow::{EcoString, eco_format};
+use image::ExtendedColorType;
 use parking_lot::RwLock;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use typst::WorldExt;
@@ -13,18 +16,24 @@ use typst::diag::{
     StrResult, Warned, bail,
 };
 use typst::foundations::{Datetime, Smart};
-use typst::layout::{Page, PageRanges, PagedDocument};
+use typst::layout::{Axes, Page, PageRanges, PagedDocument};
 use typst::syntax::{FileId, Lines, Span};
-use typst_html::HtmlDocument;
-use typst_pdf::{PdfOptions, PdfStandards, Timestamp};
+use typst_html::{
+    EpubOptions, HtmlAssets, HtmlDocument, HtmlOptions, HtmlOutput, HtmlSplitOptions,
+    HtmlSplitOutput,
+};
+use typst_pdf::{
+    PdfEncryption, PdfMarks, PdfOptions, PdfPermissions, PdfStandards, Timestamp,
+};
 
 use crate::args::{
     CompileArgs, CompileCommand, DepsFormat, DiagnosticFormat, Input, Output,
-    OutputFormat, PdfStandard, WatchCommand,
+    OutputFormat, PdfMark, PdfPermission, PdfStandard, WatchCommand,
 };
 use crate::deps::write_deps;
 #[cfg(feature = "http-server")]
 use crate::server::HtmlServer;
+use crate::slides;
 use crate::timings::Timer;
 
 use crate::watch::Status;
@@ -74,12 +83,27 @@ pub struct CompileConfig {
     pub pdf_standards: PdfStandards,
     /// Whether to write PDF (accessibility) tags.
     pub tagged: bool,
+    /// Passwords and permissions to encrypt the PDF with, if any.
+    pub pdf_encryption: Option<PdfEncryption>,
+    /// Which printer's marks to draw around PDF pages.
+    pub pdf_marks: PdfMarks,
     /// A destination to write a list of dependencies to.
     pub deps: Option<Output>,
     /// The format to use for dependencies.
     pub deps_format: DepsFormat,
-    /// The PPI (pixels per inch) to use for PNG export.
+    /// The PPI (pixels per inch) to use for PNG, JPEG, and WebP export.
     pub ppi: f32,
+    /// The quality of JPEG output, from 1 to 100.
+    pub jpeg_quality: u8,
+    /// The color onto which transparent parts of pages are composited in JPEG
+    /// output.
+    pub jpeg_background: [u8; 3],
+    /// How long to show slides without a duration of their own, in seconds.
+    pub slide_duration: Option<f64>,
+    /// How to split HTML output into multiple files, if at all.
+    pub html_split: Option<HtmlSplitOptions>,
+    /// Settings for HTML export.
+    pub html_options: HtmlOptions,
     /// The export cache for images, used for caching output files in `typst
     /// watch` sessions with images.
     pub export_cache: ExportCache,
@@ -110,12 +134,23 @@ impl CompileConfig {
 
         let output_format = if let Some(specified) = args.format {
             specified
+        } else if args.html_split.is_some() {
+            OutputFormat::Html
         } else if let Some(Output::Path(output)) = &args.output {
             match output.extension() {
                 Some(ext) if ext.eq_ignore_ascii_case("pdf") => OutputFormat::Pdf,
                 Some(ext) if ext.eq_ignore_ascii_case("png") => OutputFormat::Png,
+                Some(ext)
+                    if ext.eq_ignore_ascii_case("jpg")
+                        || ext.eq_ignore_ascii_case("jpeg") =>
+                {
+                    OutputFormat::Jpeg
+                }
+                Some(ext) if ext.eq_ignore_ascii_case("webp") => OutputFormat::Webp,
+                Some(ext) if ext.eq_ignore_ascii_case("apng") => OutputFormat::Apng,
                 Some(ext) if ext.eq_ignore_ascii_case("svg") => OutputFormat::Svg,
                 Some(ext) if ext.eq_ignore_ascii_case("html") => OutputFormat::Html,
+                Some(ext) if ext.eq_ignore_ascii_case("epub") => OutputFormat::Epub,
                 _ => bail!(
                     "could not infer output format for path {}.\n\
                      consider providing the format manually with `--format/-f`",
@@ -130,16 +165,69 @@ impl CompileConfig {
             let Input::Path(path) = &input else {
                 panic!("output must be specified when input is from stdin, as guarded by the CLI");
             };
+            // Split HTML is written into a directory named like the input.
+            if args.html_split.is_some() {
+                return Output::Path(path.with_extension(""));
+            }
             Output::Path(path.with_extension(
                 match output_format {
                     OutputFormat::Pdf => "pdf",
                     OutputFormat::Png => "png",
+                    OutputFormat::Jpeg => "jpg",
+                    OutputFormat::Webp => "webp",
+                    OutputFormat::Slides => "html",
+                    OutputFormat::Apng => "png",
                     OutputFormat::Svg => "svg",
                     OutputFormat::Html => "html",
+                    OutputFormat::Epub => "epub",
                 },
             ))
         });
 
+        let html_split =
+            args.html_split.map(|level| HtmlSplitOptions { heading_level: level });
+        if html_split.is_some() {
+            if output_format != OutputFormat::Html {
+                bail!("--html-split can only be used when exporting to HTML");
+            }
+            if matches!(output, Output::Stdout) {
+                bail!("cannot write multiple HTML files to stdout");
+            }
+        }
+
+        let html_options = HtmlOptions {
+            external_assets: args.html_assets,
+            stylesheet: args.html_stylesheet,
+            xhtml: false,
+        };
+        if args.html_assets {
+            if output_format != OutputFormat::Html {
+                bail!("--html-assets can only be used when exporting to HTML");
+            }
+            if matches!(output, Output::Stdout) {
+                bail!("cannot write HTML assets to stdout");
+            }
+        }
+
+        if args.html_stylesheet && output_format != OutputFormat::Html {
+            bail!("--html-stylesheet can only be used when exporting to HTML");
+        }
+
+        if output_format != OutputFormat::Jpeg {
+            if args.jpeg_quality.is_some() {
+                bail!("--jpeg-quality can only be used when exporting to JPEG");
+            }
+            if args.jpeg_background.is_some() {
+                bail!("--jpeg-background can only be used when exporting to JPEG");
+            }
+        }
+
+        if args.slide_duration.is_some()
+            && !matches!(output_format, OutputFormat::Slides | OutputFormat::Apng)
+        {
+            bail!("--slide-duration can only be used when exporting slides");
+        }
+
         let pages = args.pages.as_ref().map(|export_ranges| {
             PageRanges::new(export_ranges.iter().map(|r| r.0.clone()).collect())
         });
@@ -180,6 +268,31 @@ impl CompileConfig {
             &args.pdf_standard.iter().copied().map(Into::into).collect::<Vec<_>>(),
         )?;
 
+        let pdf_encryption = (args.pdf_user_password.is_some()
+            || args.pdf_owner_password.is_some()
+            || !args.pdf_deny.is_empty())
+        .then(|| {
+            let denied = |permission| args.pdf_deny.contains(&permission);
+            PdfEncryption {
+                user_password: args.pdf_user_password.clone().unwrap_or_default(),
+                owner_password: args.pdf_owner_password.clone().unwrap_or_default(),
+                permissions: PdfPermissions {
+                    print: !denied(PdfPermission::Print),
+                    copy: !denied(PdfPermission::Copy),
+                    modify: !denied(PdfPermission::Modify),
+                    annotate: !denied(PdfPermission::Annotate),
+                    fill_forms: !denied(PdfPermission::FillForms),
+                    accessibility: !denied(PdfPermission::Accessibility),
+                },
+            }
+        });
+
+        let pdf_marks = PdfMarks {
+            crop: args.pdf_marks.contains(&PdfMark::Crop),
+            registration: args.pdf_marks.contains(&PdfMark::Registration),
+            color_bars: args.pdf_marks.contains(&PdfMark::ColorBars),
+        };
+
         #[cfg(feature = "http-server")]
         let server = match watch {
             Some(command)
@@ -225,8 +338,15 @@ impl CompileConfig {
             pages,
             pdf_standards,
             tagged,
+            pdf_encryption,
+            pdf_marks,
             creation_timestamp: args.world.creation_timestamp,
             ppi: args.ppi,
+            jpeg_quality: args.jpeg_quality.unwrap_or(90),
+            jpeg_background: args.jpeg_background.unwrap_or([255; 3]),
+            slide_duration: args.slide_duration,
+            html_split,
+            html_options,
             diagnostic_format: args.process.diagnostic_format,
             open: args.open.clone(),
             export_cache: ExportCache::new(),
@@ -297,6 +417,8 @@ pub fn compile_once(
             .map_err(|err| eco_format!("failed to create dependency file ({err})"))?;
     }
 
+    world.write_lock()?;
+
     Ok(())
 }
 
@@ -314,6 +436,15 @@ fn compile_and_export(
                 warnings,
             }
         }
+        OutputFormat::Epub => {
+            let Warned { output, warnings } = typst::compile::<HtmlDocument>(world);
+            let result =
+                output.and_then(|document| export_epub(&document, world, config));
+            Warned {
+                output: result.map(|()| vec![config.output.clone()]),
+                warnings,
+            }
+        }
         _ => {
             let Warned { output, warnings } = typst::compile::<PagedDocument>(world);
             let result = output.and_then(|document| export_paged(&document, config));
@@ -324,12 +455,26 @@ fn compile_and_export(
 
 /// Export to HTML.
 fn export_html(document: &HtmlDocument, config: &CompileConfig) -> SourceResult<()> {
-    let html = typst_html::html(document)?;
-    let result = config.output.write(html.as_bytes());
+    if let Some(split) = &config.html_split {
+        return export_html_split(document, split, config);
+    }
+
+    let HtmlOutput { html, assets } =
+        typst_html::html_with_options(document, &config.html_options)?;
+    let result =
+        config
+            .output
+            .write(html.as_bytes())
+            .and_then(|()| match &config.output {
+                Output::Path(path) => {
+                    write_html_assets(path.parent().unwrap_or(Path::new("")), &assets)
+                }
+                Output::Stdout => Ok(()),
+            });
 
     #[cfg(feature = "http-server")]
     if let Some(server) = &config.server {
-        server.update(html);
+        server.update(html, assets);
     }
 
     result
@@ -337,6 +482,72 @@ fn export_html(document: &HtmlDocument, config: &CompileConfig) -> SourceResult<
         .at(Span::detached())
 }
 
+/// Export to multiple HTML files in the output directory.
+fn export_html_split(
+    document: &HtmlDocument,
+    split: &HtmlSplitOptions,
+    config: &CompileConfig,
+) -> SourceResult<()> {
+    let HtmlSplitOutput { pages, assets } =
+        typst_html::html_split(document, split, &config.html_options)?;
+    let Output::Path(dir) = &config.output else {
+        unreachable!("cannot split HTML to stdout, as guarded by the config");
+    };
+
+    let result = fs::create_dir_all(dir)
+        .and_then(|()| {
+            pages
+                .iter()
+                .try_for_each(|page| fs::write(dir.join(page.path.as_str()), &page.html))
+        })
+        .and_then(|()| write_html_assets(dir, &assets));
+
+    #[cfg(feature = "http-server")]
+    if let Some(server) = &config.server {
+        server.update_pages(
+            pages.into_iter().map(|page| (page.path, page.html)).collect(),
+            assets,
+        );
+    }
+
+    result
+        .map_err(|err| eco_format!("failed to write HTML files ({err})"))
+        .at(Span::detached())
+}
+
+/// Writes the assets of HTML output into the given directory.
+fn write_html_assets(dir: &Path, assets: &HtmlAssets) -> io::Result<()> {
+    for (path, data) in assets {
+        let path = dir.join(path.as_str());
+        // Assets are named after a hash of their contents, so an existing
+        // file is already up to date.
+        if path.exists() {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, data.as_slice())?;
+    }
+    Ok(())
+}
+
+/// Export to an EPUB publication.
+fn export_epub(
+    document: &HtmlDocument,
+    world: &SystemWorld,
+    config: &CompileConfig,
+) -> SourceResult<()> {
+    let modified = convert_datetime(config.creation_timestamp.unwrap_or_else(Utc::now));
+    let options = EpubOptions { modified };
+    let buffer = typst_html::epub(document, world, &options)?;
+    config
+        .output
+        .write(&buffer)
+        .map_err(|err| eco_format!("failed to write EPUB file ({err})"))
+        .at(Span::detached())
+}
+
 /// Export to a paged target format.
 fn export_paged(
     document: &PagedDocument,
@@ -349,13 +560,38 @@ fn export_paged(
         OutputFormat::Png => {
             export_image(document, config, ImageExportFormat::Png).at(Span::detached())
         }
+        OutputFormat::Jpeg => {
+            export_image(document, config, ImageExportFormat::Jpeg).at(Span::detached())
+        }
+        OutputFormat::Webp => {
+            export_image(document, config, ImageExportFormat::Webp).at(Span::detached())
+        }
         OutputFormat::Svg => {
             export_image(document, config, ImageExportFormat::Svg).at(Span::detached())
         }
-        OutputFormat::Html => unreachable!(),
+        OutputFormat::Slides | OutputFormat::Apng => {
+            export_slides(document, config).map(|()| vec![config.output.clone()])
+        }
+        OutputFormat::Html | OutputFormat::Epub => unreachable!(),
     }
 }
 
+/// Export to an HTML slideshow or an animated PNG.
+fn export_slides(document: &PagedDocument, config: &CompileConfig) -> SourceResult<()> {
+    let slides = slides::collect(document, config.pages.as_ref(), config.slide_duration)?;
+    if config.output_format == OutputFormat::Apng {
+        return slides::apng(&slides, config.ppi / 72.0, &config.output)
+            .at(Span::detached());
+    }
+
+    let html = slides::html(document, &slides);
+    config
+        .output
+        .write(html.as_bytes())
+        .map_err(|err| eco_format!("failed to write HTML file ({err})"))
+        .at(Span::detached())
+}
+
 /// Export to a PDF.
 fn export_pdf(document: &PagedDocument, config: &CompileConfig) -> SourceResult<()> {
     // If the timestamp is provided through the CLI, use UTC suffix,
@@ -379,6 +615,11 @@ fn export_pdf(document: &PagedDocument, config: &CompileConfig) -> SourceResult<
         page_ranges: config.pages.clone(),
         standards: config.pdf_standards.clone(),
         tagged: config.tagged,
+        page_layout: None,
+        page_mode: None,
+        viewer_preferences: None,
+        encryption: config.pdf_encryption.clone(),
+        marks: config.pdf_marks,
     };
     let buffer = typst_pdf::pdf(document, &options)?;
     config
@@ -407,6 +648,8 @@ fn convert_datetime<Tz: chrono::TimeZone>(
 #[derive(Copy, Clone)]
 enum ImageExportFormat {
     Png,
+    Jpeg,
+    Webp,
     Svg,
 }
 
@@ -523,14 +766,23 @@ fn export_image_page(
 ) -> StrResult<()> {
     match fmt {
         ImageExportFormat::Png => {
-            let pixmap = typst_render::render(page, config.ppi / 72.0);
-            let buf = pixmap
-                .encode_png()
-                .map_err(|err| eco_format!("failed to encode PNG file ({err})"))?;
-            output
-                .write(&buf)
+            export_png(page, config.ppi / 72.0, output)
                 .map_err(|err| eco_format!("failed to write PNG file ({err})"))?;
         }
+        ImageExportFormat::Jpeg => {
+            export_jpeg(
+                page,
+                config.ppi / 72.0,
+                config.jpeg_quality,
+                config.jpeg_background,
+                output,
+            )
+            .map_err(|err| eco_format!("failed to write JPEG file ({err})"))?;
+        }
+        ImageExportFormat::Webp => {
+            export_webp(page, config.ppi / 72.0, output)
+                .map_err(|err| eco_format!("failed to write WebP file ({err})"))?;
+        }
         ImageExportFormat::Svg => {
             let svg = typst_svg::svg(page);
             output
@@ -541,6 +793,132 @@ fn export_image_page(
     Ok(())
 }
 
+/// The number of pixels that are rendered at once when exporting an image.
+///
+/// Pages up to this size are rendered in one go. Larger ones are rendered in
+/// horizontal strips of about this size, each of which walks the whole page,
+/// so that the full premultiplied image never needs to be in memory.
+const STRIP_PIXELS: u32 = 16 * 1024 * 1024;
+
+/// Renders a page in horizontal strips from top to bottom.
+fn render_strips<E>(
+    page: &Page,
+    pixel_per_pt: f32,
+    mut f: impl FnMut(&tiny_skia::Pixmap) -> Result<(), E>,
+) -> Result<(), E> {
+    let size = typst_render::render_size(page, pixel_per_pt);
+    let pixels = u64::from(size.x) * u64::from(size.y);
+    let height = if pixels <= u64::from(STRIP_PIXELS) {
+        size.y
+    } else {
+        (STRIP_PIXELS / size.x).max(1)
+    };
+
+    typst_render::render_tiled(page, pixel_per_pt, Axes::new(size.x, height), |tile| {
+        f(&tile.pixmap)
+    })
+}
+
+/// Export a page as a PNG, encoding each strip as soon as it is rendered.
+fn export_png(
+    page: &Page,
+    pixel_per_pt: f32,
+    output: &Output,
+) -> Result<(), png::EncodingError> {
+    let size = typst_render::render_size(page, pixel_per_pt);
+    let mut encoder = png::Encoder::new(output.open()?, size.x, size.y);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    let mut stream = writer.stream_writer()?;
+    render_strips(page, pixel_per_pt, |strip| stream.write_all(&demultiply(strip)))?;
+    stream.finish()?;
+    writer.finish()
+}
+
+/// Export a page as a JPEG.
+///
+/// The encoder needs the whole image, so unlike for PNG, memory use grows
+/// with the page's pixel count: Only the flattened RGB form is kept, which
+/// takes three bytes per pixel.
+fn export_jpeg(
+    page: &Page,
+    pixel_per_pt: f32,
+    quality: u8,
+    background: [u8; 3],
+    output: &Output,
+) -> Result<(), image::ImageError> {
+    let size = typst_render::render_size(page, pixel_per_pt);
+    let mut rgb = Vec::with_capacity(3 * size.x as usize * size.y as usize);
+    render_strips(page, pixel_per_pt, |strip| {
+        rgb.extend(flatten(strip, background));
+        Ok::<_, image::ImageError>(())
+    })?;
+
+    let mut writer = output.open()?;
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut writer, quality).encode(
+        &rgb,
+        size.x,
+        size.y,
+        ExtendedColorType::Rgb8,
+    )
+}
+
+/// Export a page as a lossless WebP.
+///
+/// Like for JPEG, the encoder needs the whole image. Only the straight-alpha
+/// form is kept in memory, which takes four bytes per pixel.
+fn export_webp(
+    page: &Page,
+    pixel_per_pt: f32,
+    output: &Output,
+) -> Result<(), image::ImageError> {
+    let size = typst_render::render_size(page, pixel_per_pt);
+    let mut rgba = Vec::with_capacity(4 * size.x as usize * size.y as usize);
+    render_strips(page, pixel_per_pt, |strip| {
+        rgba.extend(demultiply(strip));
+        Ok::<_, image::ImageError>(())
+    })?;
+
+    let mut writer = output.open()?;
+    image::codecs::webp::WebPEncoder::new_lossless(&mut writer).encode(
+        &rgba,
+        size.x,
+        size.y,
+        ExtendedColorType::Rgba8,
+    )
+}
+
+/// Converts a pixmap into RGBA bytes with straight alpha, as expected by image
+/// encoders.
+pub fn demultiply(pixmap: &tiny_skia::Pixmap) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(pixmap.data().len());
+    for pixel in pixmap.pixels() {
+        let color = pixel.demultiply();
+        rgba.extend([color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+    rgba
+}
+
+/// Converts a pixmap into RGB bytes by compositing it onto an opaque
+/// background color.
+fn flatten(pixmap: &tiny_skia::Pixmap, background: [u8; 3]) -> Vec<u8> {
+    let mut rgb = Vec::with_capacity(pixmap.pixels().len() * 3);
+    for pixel in pixmap.pixels() {
+        // The pixmap is premultiplied, so the background only needs to be
+        // weighted by the remaining transparency.
+        let rest = 255 - u16::from(pixel.alpha());
+        for (channel, bg) in
+            [pixel.red(), pixel.green(), pixel.blue()].into_iter().zip(background)
+        {
+            let blended = u16::from(channel) + (u16::from(bg) * rest + 127) / 255;
+            rgb.push(blended.min(255) as u8);
+        }
+    }
+    rgb
+}
+
 /// Caches exported files so that we can avoid re-exporting them if they haven't
 /// changed.
 ///
@@ -587,6 +965,12 @@ fn open_output(config: &mut CompileConfig) -> StrResult<()> {
     // Can't open stdout.
     let Output::Path(path) = &config.output else { return Ok(()) };
 
+    // When splitting HTML, the output is a directory with an index page.
+    let path = match config.html_split {
+        Some(_) => path.join("index.html"),
+        None => path.clone(),
+    };
+
     // Some resource openers require the path to be canonicalized.
     let path = path
         .canonicalize()
@@ -764,3 +1148,86 @@ impl From<PdfStandard> for typst_pdf::PdfStandard {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, GenericImageView};
+    use typst::foundations::Content;
+    use typst::layout::{Abs, Frame, FrameItem, Point, Sides, Size};
+    use typst::visualize::{Color, Geometry};
+
+    use super::*;
+
+    /// A transparent page of 20x30pt with a red square in its top-left
+    /// corner.
+    fn page() -> Page {
+        let mut frame = Frame::hard(Size::new(Abs::pt(20.0), Abs::pt(30.0)));
+        let square = Geometry::Rect(Size::splat(Abs::pt(10.0))).filled(Color::RED);
+        frame.push(Point::zero(), FrameItem::Shape(square, Span::detached()));
+        Page {
+            frame,
+            fill: Smart::Custom(None),
+            bleed: Sides::splat(Abs::zero()),
+            numbering: None,
+            supplement: Content::empty(),
+            number: 1,
+            transition: None,
+            duration: None,
+        }
+    }
+
+    /// Exports the page with the given function and decodes the result.
+    fn export(
+        extension: &str,
+        f: impl FnOnce(&Output) -> Result<(), EcoString>,
+    ) -> (Vec<u8>, DynamicImage) {
+        let dir = tempfile::tempdir().unwrap();
+        let output = Output::Path(dir.path().join(format!("page.{extension}")));
+        f(&output).unwrap();
+        let Output::Path(path) = output else { unreachable!() };
+        let data = fs::read(path).unwrap();
+        let image = image::load_from_memory(&data).unwrap();
+        (data, image)
+    }
+
+    /// The straight-alpha pixels of a full rendering of the page.
+    fn rendered() -> Vec<u8> {
+        demultiply(&typst_render::render(&page(), 1.0))
+    }
+
+    #[test]
+    fn test_export_png() {
+        let (_, image) = export("png", |output| {
+            export_png(&page(), 1.0, output).map_err(|err| eco_format!("{err}"))
+        });
+        assert_eq!(image.dimensions(), (20, 30));
+        assert_eq!(image.to_rgba8().into_raw(), rendered());
+    }
+
+    #[test]
+    fn test_export_jpeg() {
+        let (_, image) = export("jpg", |output| {
+            export_jpeg(&page(), 1.0, 100, [0, 0, 255], output)
+                .map_err(|err| eco_format!("{err}"))
+        });
+        assert_eq!(image.dimensions(), (20, 30));
+
+        // Transparent parts are composited onto the background.
+        let close = |actual: [u8; 3], expected: [u8; 3]| {
+            actual.iter().zip(expected).all(|(&a, e)| a.abs_diff(e) <= 8)
+        };
+        let rgb = image.to_rgb8();
+        assert!(close(rgb.get_pixel(2, 2).0, [255, 0, 0]));
+        assert!(close(rgb.get_pixel(15, 25).0, [0, 0, 255]));
+    }
+
+    #[test]
+    fn test_export_webp() {
+        let (data, image) = export("webp", |output| {
+            export_webp(&page(), 1.0, output).map_err(|err| eco_format!("{err}"))
+        });
+        assert_eq!(&data[12..16], b"VP8L");
+        assert_eq!(image.dimensions(), (20, 30));
+        assert_eq!(image.to_rgba8().into_raw(), rendered());
+    }
+}
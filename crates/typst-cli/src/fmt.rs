@@ -0,0 +1,100 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use ecow::eco_format;
+use typst::diag::{HintedStrResult, StrResult, bail};
+use typst::syntax::{FormatConfig, FormatError};
+
+use crate::args::FmtCommand;
+use crate::walk::walk;
+use crate::{print_error, set_failed};
+
+/// Execute a formatting command.
+pub fn fmt(command: &'static FmtCommand) -> HintedStrResult<()> {
+    let config = FormatConfig { width: command.line_width };
+
+    let mut unformatted = 0;
+    for path in collect(&command.paths)? {
+        match format_file(&path, config, command.check) {
+            Ok(changed) => unformatted += changed as usize,
+            Err(message) => {
+                set_failed();
+                print_error(&message).map_err(|err| eco_format!("{err}"))?;
+            }
+        }
+    }
+
+    if unformatted > 0 {
+        bail!(
+            "{unformatted} file{} would be reformatted",
+            if unformatted == 1 { "" } else { "s" };
+            hint: "run `typst fmt` without `--check` to format them"
+        );
+    }
+
+    Ok(())
+}
+
+/// Formats a single file, or stdin if the path is `-`.
+///
+/// Returns whether the file is unformatted in check mode.
+fn format_file(path: &Path, config: FormatConfig, check: bool) -> StrResult<bool> {
+    let stdin = path.as_os_str() == "-";
+    let text = if stdin {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|err| eco_format!("failed to read from stdin ({err})"))?;
+        text
+    } else {
+        fs::read_to_string(path)
+            .map_err(|err| eco_format!("failed to read {} ({err})", path.display()))?
+    };
+
+    let name = if stdin { "stdin".into() } else { path.display().to_string() };
+    let formatted = typst::syntax::format(&text, config).map_err(|err| match err {
+        FormatError::Syntax => eco_format!("failed to format {name} ({err})"),
+        FormatError::Unstable => {
+            eco_format!("failed to format {name} ({err}, this is a bug)")
+        }
+    })?;
+
+    if check {
+        if formatted != text {
+            println!("{name} is not formatted");
+            return Ok(true);
+        }
+    } else if stdin {
+        io::stdout()
+            .write_all(formatted.as_bytes())
+            .map_err(|err| eco_format!("failed to write to stdout ({err})"))?;
+    } else if formatted != text {
+        fs::write(path, formatted)
+            .map_err(|err| eco_format!("failed to write {} ({err})", path.display()))?;
+    }
+
+    Ok(false)
+}
+
+/// Expands directories into the Typst files they contain.
+fn collect(paths: &[PathBuf]) -> StrResult<Vec<PathBuf>> {
+    let mut files = vec![];
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+
+        let start = files.len();
+        for file in walk(path) {
+            let file = file?;
+            if file.extension().is_some_and(|ext| ext == "typ") {
+                files.push(file);
+            }
+        }
+        files[start..].sort();
+    }
+
+    Ok(files)
+}
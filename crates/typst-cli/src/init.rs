@@ -54,7 +54,7 @@ pub fn init(command: &InitCommand) -> StrResult<()> {
 }
 
 /// Parses the manifest of the package located at `package_path`.
-fn parse_manifest(package_path: &Path) -> StrResult<PackageManifest> {
+pub fn parse_manifest(package_path: &Path) -> StrResult<PackageManifest> {
     let toml_path = package_path.join("typst.toml");
     let string = std::fs::read_to_string(&toml_path).map_err(|err| {
         eco_format!(
@@ -0,0 +1,1061 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use ecow::{EcoString, eco_format};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use typst::diag::{HintedStrResult, Severity, SourceDiagnostic, StrResult, Warned};
+use typst::layout::{Abs, PagedDocument, Point, Position};
+use typst::syntax::ast::AstNode;
+use typst::syntax::{
+    FileId, Lines, LinkedNode, Side, Source, Span, SyntaxKind, VirtualPath, ast,
+};
+use typst::{World, WorldExt};
+use typst_ide::{
+    CompletionKind, Definition, IdeWorld, Jump, Tooltip, autocomplete, definition,
+    jump_from_click, jump_from_cursor, references, rename, tooltip,
+};
+
+use crate::args::{Input, LspCommand};
+use crate::world::SystemWorld;
+
+/// How long the server waits for further edits before it recompiles.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Execute a language server command.
+pub fn lsp(command: &'static LspCommand) -> HintedStrResult<()> {
+    let input = command.input.clone().map(Input::Path);
+    let world = SystemWorld::new(input.as_ref(), &command.world, &command.process)?;
+    let mut server = Server::new(world, command.input.is_some());
+    server.run(BufReader::new(io::stdin()), io::stdout().lock())
+}
+
+/// The state of the language server.
+struct Server {
+    /// The world the server compiles in.
+    world: SystemWorld,
+    /// Whether the main file was already determined.
+    has_main: bool,
+    /// The contents of the documents that are open in the editor.
+    open: FxHashMap<FileId, String>,
+    /// The last successfully compiled document.
+    document: Option<PagedDocument>,
+    /// The files for which diagnostics were last published.
+    published: FxHashSet<FileId>,
+    /// Notifications that are waiting to be sent to the client.
+    outbox: Vec<Value>,
+    /// Whether documents changed since the last compilation.
+    stale: bool,
+    /// Whether the client asked the server to exit.
+    exit: bool,
+}
+
+impl Server {
+    /// Creates a server for the given world.
+    fn new(world: SystemWorld, has_main: bool) -> Self {
+        Self {
+            world,
+            has_main,
+            open: FxHashMap::default(),
+            document: None,
+            published: FxHashSet::default(),
+            outbox: vec![],
+            stale: false,
+            exit: false,
+        }
+    }
+
+    /// Handles messages until the client closes the connection or asks the
+    /// server to exit.
+    ///
+    /// Messages are read on a separate thread so that edits can be debounced:
+    /// The document is only recompiled once no further message arrived for
+    /// a short while.
+    fn run(
+        &mut self,
+        mut reader: impl BufRead + Send + 'static,
+        mut writer: impl Write,
+    ) -> StrResult<()> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            loop {
+                let message = read_message(&mut reader);
+                let done = !matches!(message, Ok(Some(_)));
+                if sender.send(message).is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        while !self.exit {
+            let received = if self.stale {
+                match receiver.recv_timeout(DEBOUNCE) {
+                    Err(RecvTimeoutError::Timeout) => {
+                        self.compile();
+                        self.flush(&mut writer)?;
+                        continue;
+                    }
+                    received => received.ok(),
+                }
+            } else {
+                receiver.recv().ok()
+            };
+
+            let Some(Some(message)) = received.transpose()? else { break };
+            let response = match message {
+                Ok(message) => self.handle(message),
+                // The id of a malformed message is unknown, so the error is
+                // reported with a null id.
+                Err(error) => Some(respond(Value::Null, Err(error))),
+            };
+            if let Some(response) = response {
+                write_message(&mut writer, &response)?;
+            }
+            self.flush(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Sends the notifications that are waiting in the outbox.
+    fn flush(&mut self, writer: &mut impl Write) -> StrResult<()> {
+        for notification in std::mem::take(&mut self.outbox) {
+            write_message(writer, &notification)?;
+        }
+        Ok(())
+    }
+
+    /// Handles a message from the client and returns the response if it was a
+    /// request.
+    fn handle(&mut self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let Some(id) = id else {
+            self.notification(method, params);
+            return None;
+        };
+
+        Some(respond(id, self.request(method, params)))
+    }
+
+    /// Handles a request.
+    fn request(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(capabilities()),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/completion" => self.completion(parse(params)?),
+            "textDocument/hover" => self.hover(parse(params)?),
+            "textDocument/definition" => self.definition(parse(params)?),
+            "textDocument/references" => self.references(parse(params)?),
+            "textDocument/rename" => self.rename(parse(params)?),
+            "textDocument/documentSymbol" => self.symbols(parse(params)?),
+            "typst/jumpFromCursor" => self.jump_from_cursor(parse(params)?),
+            "typst/jumpFromClick" => self.jump_from_click(parse(params)?),
+            _ => Err(RpcError::method_not_found(eco_format!(
+                "unsupported method {method}"
+            ))),
+        }
+    }
+
+    /// Handles a notification. Errors can't be reported back to the client
+    /// for notifications, so malformed ones are ignored.
+    fn notification(&mut self, method: &str, params: Value) {
+        match method {
+            "exit" => self.exit = true,
+            "textDocument/didOpen" => {
+                let Ok(params) = parse::<DidOpenParams>(params) else { return };
+                let Some(id) = self.resolve(&params.text_document.uri) else { return };
+                if !self.has_main {
+                    self.world.set_main(id);
+                    self.has_main = true;
+                }
+                self.set_text(id, Some(params.text_document.text));
+                self.world.forget_project_sources();
+                self.compile();
+            }
+            "textDocument/didChange" => {
+                let Ok(params) = parse::<DidChangeParams>(params) else { return };
+                let Some(id) = self.resolve(&params.text_document.uri) else { return };
+                let Some(mut text) = self.open.get(&id).cloned() else { return };
+                for change in params.content_changes {
+                    match change.range {
+                        Some(range) => {
+                            let (start, end) = {
+                                let lines = Lines::new(text.as_str());
+                                let start = to_offset(&lines, range.start);
+                                (start, to_offset(&lines, range.end).max(start))
+                            };
+                            text.replace_range(start..end, &change.text);
+                        }
+                        None => text = change.text,
+                    }
+                }
+                self.set_text(id, Some(text));
+                self.stale = true;
+            }
+            "textDocument/didClose" => {
+                let Ok(params) = parse::<DocumentParams>(params) else { return };
+                let Some(id) = self.resolve(&params.text_document.uri) else { return };
+                self.set_text(id, None);
+                self.world.forget_project_sources();
+                self.compile();
+            }
+            "workspace/didChangeWatchedFiles" => self.world.forget_project_sources(),
+            _ => {}
+        }
+    }
+
+    /// Provides completions at the cursor.
+    fn completion(&self, params: CompletionParams) -> Result<Value, RpcError> {
+        let (source, cursor) = self.locate(&params.text_document, params.position)?;
+        let explicit = params.context.is_none_or(|context| context.trigger_kind == 1);
+        let Some((offset, completions)) =
+            autocomplete(&self.world, self.document.as_ref(), &source, cursor, explicit)
+        else {
+            return Ok(Value::Null);
+        };
+
+        let range = to_range(source.lines(), offset..cursor);
+        let items: Vec<Value> = completions
+            .into_iter()
+            .map(|completion| {
+                let apply = completion.apply.as_ref().unwrap_or(&completion.label);
+                json!({
+                    "label": completion.label,
+                    "kind": completion_kind(&completion.kind),
+                    "detail": completion.detail,
+                    "insertTextFormat": 2,
+                    "textEdit": { "range": range, "newText": to_snippet(apply) },
+                })
+            })
+            .collect();
+
+        Ok(json!({ "isIncomplete": false, "items": items }))
+    }
+
+    /// Provides a tooltip for the hovered item.
+    fn hover(&self, params: PositionParams) -> Result<Value, RpcError> {
+        let (source, cursor) = self.locate(&params.text_document, params.position)?;
+        let Some(tooltip) =
+            tooltip(&self.world, self.document.as_ref(), &source, cursor, Side::Before)
+        else {
+            return Ok(Value::Null);
+        };
+
+        let value = match tooltip {
+            Tooltip::Text(text) => text,
+            Tooltip::Code(code) => eco_format!("```typc\n{code}\n```"),
+        };
+
+        Ok(json!({ "contents": { "kind": "markdown", "value": value } }))
+    }
+
+    /// Finds the definition of the item under the cursor.
+    fn definition(&self, params: PositionParams) -> Result<Value, RpcError> {
+        let (source, cursor) = self.locate(&params.text_document, params.position)?;
+        let Some(Definition::Span(span)) = definition(
+            &self.world,
+            self.document.as_ref(),
+            &source,
+            cursor,
+            Side::Before,
+        ) else {
+            return Ok(Value::Null);
+        };
+
+        Ok(self.location(span).unwrap_or(Value::Null))
+    }
+
+    /// Finds all references to the item under the cursor.
+    fn references(&self, params: PositionParams) -> Result<Value, RpcError> {
+        let (source, cursor) = self.locate(&params.text_document, params.position)?;
+        let locations = references(&self.world, &source, cursor)
+            .into_iter()
+            .filter_map(|span| self.location(span))
+            .collect();
+        Ok(Value::Array(locations))
+    }
+
+    /// Renames the item under the cursor throughout the project.
+    fn rename(&self, params: RenameParams) -> Result<Value, RpcError> {
+        let (source, cursor) = self.locate(&params.text_document, params.position)?;
+        let edits = rename(&self.world, &source, cursor, &params.new_name)
+            .map_err(RpcError::request_failed)?;
+
+        let mut changes = serde_json::Map::new();
+        for edit in edits {
+            let (Some(uri), Ok(source)) = (self.uri(edit.id), self.world.source(edit.id))
+            else {
+                continue;
+            };
+            let entry = changes.entry(uri.as_str()).or_insert_with(|| json!([]));
+            if let Value::Array(edits) = entry {
+                edits.push(json!({
+                    "range": to_range(source.lines(), edit.range),
+                    "newText": edit.text,
+                }));
+            }
+        }
+
+        Ok(json!({ "changes": changes }))
+    }
+
+    /// Lists the headings, bindings, and labels in a file.
+    fn symbols(&self, params: DocumentParams) -> Result<Value, RpcError> {
+        let source = self.source(&params.text_document.uri)?;
+        let root = LinkedNode::new(source.root());
+        let mut symbols = vec![];
+        collect_symbols(&root, &root, source.lines(), &mut symbols);
+        Ok(Value::Array(symbols))
+    }
+
+    /// Finds the positions in the document that the cursor corresponds to.
+    fn jump_from_cursor(&self, params: PositionParams) -> Result<Value, RpcError> {
+        let (source, cursor) = self.locate(&params.text_document, params.position)?;
+        let Some(document) = &self.document else { return Ok(json!([])) };
+        let positions: Vec<Value> = jump_from_cursor(document, &source, cursor)
+            .into_iter()
+            .map(|position| {
+                json!({
+                    "page": position.page,
+                    "x": position.point.x.to_pt(),
+                    "y": position.point.y.to_pt(),
+                })
+            })
+            .collect();
+        Ok(Value::Array(positions))
+    }
+
+    /// Determines where a click at a point in the document leads to.
+    fn jump_from_click(&self, params: ClickParams) -> Result<Value, RpcError> {
+        let Some(document) = &self.document else { return Ok(Value::Null) };
+        let Some(page) = NonZeroUsize::new(params.page) else {
+            return Err(RpcError::invalid_params("pages are numbered from 1".into()));
+        };
+
+        let position = Position {
+            page,
+            point: Point::new(Abs::pt(params.x), Abs::pt(params.y)),
+        };
+
+        Ok(match jump_from_click(&self.world, document, &position) {
+            Some(Jump::File(id, offset)) => self
+                .world
+                .source(id)
+                .ok()
+                .zip(self.uri(id))
+                .map(|(source, uri)| {
+                    let range = to_range(source.lines(), offset..offset);
+                    json!({ "uri": uri, "range": range })
+                })
+                .unwrap_or(Value::Null),
+            Some(Jump::Url(url)) => json!({ "url": url.into_inner() }),
+            Some(Jump::Position(position)) => json!({
+                "page": position.page,
+                "x": position.point.x.to_pt(),
+                "y": position.point.y.to_pt(),
+            }),
+            None => Value::Null,
+        })
+    }
+
+    /// Compiles the document and publishes its diagnostics.
+    fn compile(&mut self) {
+        self.stale = false;
+        self.world.reset();
+
+        let Warned { output, warnings } = typst::compile::<PagedDocument>(&self.world);
+        let errors = match output {
+            Ok(document) => {
+                self.document = Some(document);
+                vec![]
+            }
+            Err(errors) => errors.into_iter().collect(),
+        };
+
+        let mut files: FxHashMap<FileId, Vec<Value>> = FxHashMap::default();
+        for diagnostic in errors.iter().chain(&warnings) {
+            let id = diagnostic.span.id().unwrap_or(self.world.main());
+            if let Some(diagnostic) = self.diagnostic(id, diagnostic) {
+                files.entry(id).or_default().push(diagnostic);
+            }
+        }
+
+        // Clear the diagnostics of files that don't have any anymore.
+        for id in std::mem::take(&mut self.published) {
+            files.entry(id).or_default();
+        }
+
+        #[allow(clippy::iter_over_hash_type, reason = "order does not matter")]
+        for (id, diagnostics) in files {
+            let Some(uri) = self.uri(id) else { continue };
+            if !diagnostics.is_empty() {
+                self.published.insert(id);
+            }
+            self.outbox.push(json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/publishDiagnostics",
+                "params": { "uri": uri, "diagnostics": diagnostics },
+            }));
+        }
+
+        comemo::evict(10);
+    }
+
+    /// Converts a diagnostic into its LSP representation.
+    fn diagnostic(&self, id: FileId, diagnostic: &SourceDiagnostic) -> Option<Value> {
+        let source = self.world.source(id).ok()?;
+        let range = self.world.range(diagnostic.span).unwrap_or(0..0);
+
+        let mut message = diagnostic.message.clone();
+        for hint in &diagnostic.hints {
+            write!(message, "\nhint: {}", hint.v).ok();
+        }
+
+        Some(json!({
+            "range": to_range(source.lines(), range),
+            "severity": match diagnostic.severity {
+                Severity::Error => 1,
+                Severity::Warning => 2,
+            },
+            "source": "typst",
+            "message": message,
+        }))
+    }
+
+    /// Sets or clears the contents of an open document.
+    fn set_text(&mut self, id: FileId, text: Option<String>) {
+        match &text {
+            Some(text) => self.open.insert(id, text.clone()),
+            None => self.open.remove(&id),
+        };
+        self.world.set_overlay(id, text);
+    }
+
+    /// Retrieves the source and the cursor offset for a position in a
+    /// document.
+    fn locate(
+        &self,
+        document: &TextDocument,
+        position: LspPosition,
+    ) -> Result<(Source, usize), RpcError> {
+        let source = self.source(&document.uri)?;
+        let cursor = to_offset(source.lines(), position);
+        Ok((source, cursor))
+    }
+
+    /// Retrieves the source for a document.
+    fn source(&self, uri: &str) -> Result<Source, RpcError> {
+        let id = self.resolve(uri).ok_or_else(|| {
+            RpcError::invalid_params(eco_format!("{uri} is not in the project"))
+        })?;
+        self.world
+            .source(id)
+            .map_err(|err| RpcError::internal(err.to_string().into()))
+    }
+
+    /// Resolves a document URI to a file in the project.
+    fn resolve(&self, uri: &str) -> Option<FileId> {
+        let path = uri_to_path(uri)?;
+        let path = path.canonicalize().unwrap_or(path);
+        let vpath = VirtualPath::within_root(&path, self.world.root())?;
+        Some(FileId::new(None, vpath))
+    }
+
+    /// Determines the URI of a file.
+    fn uri(&self, id: FileId) -> Option<EcoString> {
+        self.world.path(id).ok().map(|path| path_to_uri(&path))
+    }
+
+    /// Determines the LSP location of a span.
+    fn location(&self, span: Span) -> Option<Value> {
+        let id = span.id()?;
+        let source = self.world.source(id).ok()?;
+        let range = self.world.range(span)?;
+        Some(json!({ "uri": self.uri(id)?, "range": to_range(source.lines(), range) }))
+    }
+}
+
+impl IdeWorld for SystemWorld {
+    fn upcast(&self) -> &dyn World {
+        self
+    }
+
+    fn files(&self) -> Vec<FileId> {
+        self.project_sources().to_vec()
+    }
+}
+
+/// The capabilities the server announces to the client.
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "positionEncoding": "utf-16",
+            "textDocumentSync": { "openClose": true, "change": 2 },
+            "completionProvider": {
+                "triggerCharacters": ["#", ".", "@", "<", "(", ",", ":", "/", "\"", " "],
+            },
+            "hoverProvider": true,
+            "definitionProvider": true,
+            "referencesProvider": true,
+            "renameProvider": true,
+            "documentSymbolProvider": true,
+        },
+        "serverInfo": {
+            "name": "typst",
+            "version": typst_utils::version().raw(),
+        },
+    })
+}
+
+/// Collects the document symbols in a syntax tree.
+fn collect_symbols(
+    root: &LinkedNode,
+    node: &LinkedNode,
+    lines: &Lines<String>,
+    symbols: &mut Vec<Value>,
+) {
+    let mut push =
+        |name: &str, kind: u8, range: Range<usize>, selection: Range<usize>| {
+            symbols.push(json!({
+                "name": name,
+                "kind": kind,
+                "range": to_range(lines, range),
+                "selectionRange": to_range(lines, selection),
+            }));
+        };
+
+    if let Some(heading) = node.cast::<ast::Heading>() {
+        let name = heading.body().to_untyped().clone().into_text();
+        push(name.trim(), 3, node.range(), node.range());
+    } else if let Some(label) = node.cast::<ast::Label>() {
+        push(label.get(), 20, node.range(), node.range());
+    } else if let Some(binding) = node.cast::<ast::LetBinding>() {
+        let kind = match binding.kind() {
+            ast::LetBindingKind::Closure(_) => 12,
+            ast::LetBindingKind::Normal(_) => 13,
+        };
+        for ident in binding.kind().bindings() {
+            if let Some(found) = root.find(ident.span()) {
+                push(ident.get(), kind, node.range(), found.range());
+            }
+        }
+    }
+
+    for child in node.children() {
+        if !matches!(child.kind(), SyntaxKind::LineComment | SyntaxKind::BlockComment) {
+            collect_symbols(root, &child, lines, symbols);
+        }
+    }
+}
+
+/// Maps a completion kind onto an LSP completion item kind.
+fn completion_kind(kind: &CompletionKind) -> u8 {
+    match kind {
+        CompletionKind::Syntax => 15,
+        CompletionKind::Func => 3,
+        CompletionKind::Type => 7,
+        CompletionKind::Param => 6,
+        CompletionKind::Constant => 21,
+        CompletionKind::Path => 17,
+        CompletionKind::Package => 9,
+        CompletionKind::Label => 18,
+        CompletionKind::Font => 1,
+        CompletionKind::Symbol(_) => 1,
+    }
+}
+
+/// Turns the snippet syntax of Typst's completions (`${name}`) into numbered
+/// LSP snippet placeholders (`${1:name}`).
+fn to_snippet(apply: &str) -> EcoString {
+    let mut snippet = EcoString::new();
+    let mut index = 0;
+    let mut rest = apply;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else { break };
+        snippet.push_str(&escape_snippet(&rest[..start]));
+        index += 1;
+        let name = &rest[start + 2..start + len];
+        if name.is_empty() {
+            write!(snippet, "${{{index}}}").ok();
+        } else {
+            write!(snippet, "${{{index}:{}}}", escape_snippet(name)).ok();
+        }
+        rest = &rest[start + len + 1..];
+    }
+    snippet.push_str(&escape_snippet(rest));
+    snippet
+}
+
+/// Escapes the characters that have a special meaning in LSP snippets.
+fn escape_snippet(text: &str) -> EcoString {
+    let mut escaped = EcoString::new();
+    for c in text.chars() {
+        if matches!(c, '$' | '}' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Converts a byte range into an LSP range.
+fn to_range(lines: &Lines<String>, range: Range<usize>) -> Value {
+    json!({ "start": to_position(lines, range.start), "end": to_position(lines, range.end) })
+}
+
+/// Converts a byte offset into an LSP position with UTF-16 columns.
+fn to_position(lines: &Lines<String>, offset: usize) -> Value {
+    let offset = offset.min(lines.len_bytes());
+    let line = lines.byte_to_line(offset).unwrap_or_default();
+    let start = lines.line_to_byte(line).unwrap_or_default();
+    let character = lines.byte_to_utf16(offset).unwrap_or_default()
+        - lines.byte_to_utf16(start).unwrap_or_default();
+    json!({ "line": line, "character": character })
+}
+
+/// Converts an LSP position with UTF-16 columns into a byte offset, clamping
+/// it to the text.
+fn to_offset<T: AsRef<str>>(lines: &Lines<T>, position: LspPosition) -> usize {
+    let Some(range) = lines.line_to_range(position.line) else {
+        return lines.len_bytes();
+    };
+
+    // Don't move past the end of the line into the next one.
+    let text = &lines.text()[range.clone()];
+    let end = range.start + text.trim_end_matches(['\n', '\r']).len();
+
+    let start16 = lines.byte_to_utf16(range.start).unwrap_or_default();
+    let end16 = lines.byte_to_utf16(end).unwrap_or_default();
+    lines
+        .utf16_to_byte((start16 + position.character).min(end16))
+        .unwrap_or(end)
+}
+
+/// Converts a `file:` URI into a path.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+
+    // Skip the host, which is empty for local files.
+    let rest = &rest[rest.find('/')?..];
+
+    let mut bytes = vec![];
+    let mut iter = rest.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+
+    let path = String::from_utf8(bytes).ok()?;
+
+    // On Windows, URIs look like `file:///C:/...`.
+    if cfg!(windows) && path.as_bytes().get(2) == Some(&b':') {
+        return Some(PathBuf::from(&path[1..]));
+    }
+
+    Some(PathBuf::from(path))
+}
+
+/// Converts a path into a `file:` URI.
+fn path_to_uri(path: &Path) -> EcoString {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = EcoString::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/:".contains(&b) {
+            uri.push(b as char);
+        } else {
+            write!(uri, "%{b:02X}").ok();
+        }
+    }
+    uri
+}
+
+/// Builds the response to a request.
+fn respond(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+/// Reads a message with a `Content-Length` header from the client.
+///
+/// Returns `None` once the client closed the connection. A body that isn't
+/// valid JSON yields an inner error, which is reported to the client, while
+/// a broken header or stream is fatal since the next message can't be found.
+fn read_message(reader: &mut impl BufRead) -> StrResult<Option<Result<Value, RpcError>>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|err| eco_format!("failed to read message header ({err})"))?;
+        if read == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let Some(length) = length else {
+        return Err("message is missing a valid Content-Length header".into());
+    };
+
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|err| eco_format!("failed to read message ({err})"))?;
+
+    Ok(Some(serde_json::from_slice(&body).map_err(|err| {
+        RpcError::parse_error(eco_format!("failed to parse message ({err})"))
+    })))
+}
+
+/// Writes a message with a `Content-Length` header to the client.
+fn write_message(writer: &mut impl Write, message: &Value) -> StrResult<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())
+        .and_then(|_| writer.flush())
+        .map_err(|err| eco_format!("failed to write message ({err})"))
+}
+
+/// Parses the parameters of a request or notification.
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params)
+        .map_err(|err| RpcError::invalid_params(eco_format!("{err}")))
+}
+
+/// An error response to a request.
+struct RpcError {
+    code: i32,
+    message: EcoString,
+}
+
+impl RpcError {
+    /// The message isn't valid JSON.
+    fn parse_error(message: EcoString) -> Self {
+        Self { code: -32700, message }
+    }
+
+    /// The requested method is not supported.
+    fn method_not_found(message: EcoString) -> Self {
+        Self { code: -32601, message }
+    }
+
+    /// The request's parameters are malformed.
+    fn invalid_params(message: EcoString) -> Self {
+        Self { code: -32602, message }
+    }
+
+    /// The request couldn't be handled for some other reason.
+    fn internal(message: EcoString) -> Self {
+        Self { code: -32603, message }
+    }
+
+    /// The request was valid, but couldn't be fulfilled.
+    fn request_failed(message: EcoString) -> Self {
+        Self { code: -32803, message }
+    }
+}
+
+/// A reference to a document.
+#[derive(Deserialize)]
+struct TextDocument {
+    uri: EcoString,
+}
+
+/// A document that was opened in the editor.
+#[derive(Deserialize)]
+struct OpenedDocument {
+    uri: EcoString,
+    text: String,
+}
+
+/// A position in a document with a UTF-16 column.
+#[derive(Debug, Copy, Clone, Deserialize)]
+struct LspPosition {
+    line: usize,
+    character: usize,
+}
+
+/// A range in a document.
+#[derive(Deserialize)]
+struct LspRange {
+    start: LspPosition,
+    end: LspPosition,
+}
+
+/// Parameters that refer to a document.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentParams {
+    text_document: TextDocument,
+}
+
+/// Parameters that refer to a position in a document.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionParams {
+    text_document: TextDocument,
+    position: LspPosition,
+}
+
+/// Parameters of a `textDocument/rename` request.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RenameParams {
+    text_document: TextDocument,
+    position: LspPosition,
+    new_name: EcoString,
+}
+
+/// Parameters of a `textDocument/didOpen` notification.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidOpenParams {
+    text_document: OpenedDocument,
+}
+
+/// Parameters of a `textDocument/didChange` notification.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidChangeParams {
+    text_document: TextDocument,
+    content_changes: Vec<ContentChange>,
+}
+
+/// A change to a document. Replaces the whole document if there is no range.
+#[derive(Deserialize)]
+struct ContentChange {
+    range: Option<LspRange>,
+    text: String,
+}
+
+/// Parameters of a `textDocument/completion` request.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletionParams {
+    text_document: TextDocument,
+    position: LspPosition,
+    context: Option<CompletionContext>,
+}
+
+/// How a completion was triggered.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletionContext {
+    trigger_kind: u8,
+}
+
+/// Parameters of a `typst/jumpFromClick` request: A 1-based page number and
+/// a point on it in points.
+#[derive(Deserialize)]
+struct ClickParams {
+    page: usize,
+    x: f64,
+    y: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::world::tests::world;
+
+    /// Creates a server for a project with a single empty main file.
+    fn server(name: &str) -> (Server, EcoString) {
+        let (world, main) = world(&format!("lsp-{name}"), "");
+        (Server::new(world, true), path_to_uri(&main.canonicalize().unwrap()))
+    }
+
+    /// Sends the raw message bodies with `Content-Length` headers and returns
+    /// the parsed messages the server wrote back.
+    fn exchange(server: &mut Server, bodies: &[String]) -> Vec<Value> {
+        exchange_paused(server, &[bodies])
+    }
+
+    /// Like [`exchange`], but pauses between the batches of messages for
+    /// longer than the server debounces edits.
+    fn exchange_paused(server: &mut Server, batches: &[&[String]]) -> Vec<Value> {
+        let batches = batches
+            .iter()
+            .map(|bodies| {
+                bodies
+                    .iter()
+                    .map(|body| format!("Content-Length: {}\r\n\r\n{body}", body.len()))
+                    .collect::<String>()
+                    .into_bytes()
+            })
+            .collect();
+        let mut output = vec![];
+        server.run(BufReader::new(Paused(batches)), &mut output).unwrap();
+
+        let mut reader = output.as_slice();
+        let mut messages = vec![];
+        while let Some(message) = read_message(&mut reader).unwrap() {
+            messages.push(message.ok().unwrap());
+        }
+        messages
+    }
+
+    /// A reader that pauses before each batch of input after the first, like
+    /// an editor whose user stops typing.
+    struct Paused(VecDeque<Vec<u8>>);
+
+    impl Read for Paused {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            while self.0.front().is_some_and(Vec::is_empty) {
+                self.0.pop_front();
+                if !self.0.is_empty() {
+                    thread::sleep(DEBOUNCE * 4);
+                }
+            }
+            let Some(batch) = self.0.front_mut() else { return Ok(0) };
+            let len = buf.len().min(batch.len());
+            buf[..len].copy_from_slice(&batch[..len]);
+            batch.drain(..len);
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_lsp_parse_error_keeps_running() {
+        let (mut server, _) = server("parse-error");
+        let messages = exchange(
+            &mut server,
+            &[
+                "{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\"".into(),
+                json!({ "jsonrpc": "2.0", "id": 2, "method": "initialize" }).to_string(),
+                json!({ "jsonrpc": "2.0", "id": 3, "method": "unknown" }).to_string(),
+                json!({ "jsonrpc": "2.0", "method": "exit" }).to_string(),
+                json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }).to_string(),
+            ],
+        );
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["id"], Value::Null);
+        assert_eq!(messages[0]["error"]["code"], -32700);
+        assert_eq!(messages[1]["id"], 2);
+        assert!(messages[1]["result"]["capabilities"].is_object());
+        assert_eq!(messages[2]["id"], 3);
+        assert_eq!(messages[2]["error"]["code"], -32601);
+        assert!(server.exit);
+    }
+
+    #[test]
+    fn test_lsp_missing_header_is_fatal() {
+        let (mut server, _) = server("missing-header");
+        let mut output = vec![];
+        assert!(server.run("X-Foo: 1\r\n\r\n{}".as_bytes(), &mut output).is_err());
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_lsp_diagnostics_and_hover() {
+        let (mut server, uri) = server("diagnostics");
+        let change = |text: &str| {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didChange",
+                "params": {
+                    "textDocument": { "uri": uri },
+                    "contentChanges": [{ "text": text }],
+                },
+            })
+            .to_string()
+        };
+        let messages = exchange_paused(
+            &mut server,
+            &[
+                &[
+                    json!({
+                        "jsonrpc": "2.0",
+                        "method": "textDocument/didOpen",
+                        "params": { "textDocument": { "uri": uri, "text": "#let x = (" } },
+                    })
+                    .to_string(),
+                    change("#let x = 1\n#"),
+                    change("#let x = 1\n#x"),
+                ],
+                &[json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "method": "textDocument/hover",
+                    "params": {
+                        "textDocument": { "uri": uri },
+                        "position": { "line": 1, "character": 2 },
+                    },
+                })
+                .to_string()],
+            ],
+        );
+
+        // The broken document publishes an error. Both edits are compiled
+        // together once the client paused and the fixed document clears it.
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(messages[0]["params"]["uri"], uri.as_str());
+        let diagnostics = messages[0]["params"]["diagnostics"].as_array().unwrap();
+        assert!(!diagnostics.is_empty());
+        assert_eq!(diagnostics[0]["severity"], 1);
+        assert_eq!(messages[1]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(messages[1]["params"]["diagnostics"], json!([]));
+
+        assert_eq!(messages[2]["id"], 1);
+        assert!(messages[2]["result"]["contents"].is_object());
+    }
+
+    #[test]
+    fn test_lsp_files_are_cached_typst_files() {
+        let (mut world, main) = world("lsp-files", "");
+        let dir = main.parent().unwrap();
+        std::fs::create_dir_all(dir.join("out")).unwrap();
+        std::fs::write(dir.join(".gitignore"), "out/\n").unwrap();
+        std::fs::write(dir.join("out/page.typ"), "").unwrap();
+        std::fs::write(dir.join("image.png"), "").unwrap();
+        std::fs::remove_file(dir.join("chapter.typ")).ok();
+
+        let id = |path| FileId::new(None, VirtualPath::new(path));
+        assert_eq!(IdeWorld::files(&world), [id("main.typ")]);
+
+        // New files are only picked up once the list is forgotten.
+        std::fs::write(dir.join("chapter.typ"), "").unwrap();
+        assert_eq!(IdeWorld::files(&world), [id("main.typ")]);
+        world.forget_project_sources();
+        let mut files = IdeWorld::files(&world);
+        files.sort_by_key(|id| id.vpath().as_rootless_path().to_owned());
+        assert_eq!(files, [id("chapter.typ"), id("main.typ")]);
+    }
+}
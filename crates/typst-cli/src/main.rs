@@ -1,21 +1,29 @@
 mod args;
+mod check;
 mod compile;
 mod completions;
 mod deps;
 mod download;
 mod eval;
+mod fmt;
 mod fonts;
 mod greet;
 mod info;
 mod init;
+mod lsp;
 mod package;
+mod publish;
 mod query;
+mod serve;
 #[cfg(feature = "http-server")]
 mod server;
+mod slides;
 mod terminal;
 mod timings;
 #[cfg(feature = "self-update")]
 mod update;
+mod vendor;
+mod walk;
 mod watch;
 mod world;
 
@@ -76,9 +84,15 @@ fn dispatch() -> HintedStrResult<()> {
     match &ARGS.command {
         Command::Compile(command) => crate::compile::compile(&mut timer, command)?,
         Command::Watch(command) => crate::watch::watch(&mut timer, command)?,
+        Command::Serve(command) => crate::serve::serve(command)?,
+        Command::Lsp(command) => crate::lsp::lsp(command)?,
         Command::Init(command) => crate::init::init(command)?,
         Command::Query(command) => crate::query::query(command)?,
         Command::Eval(command) => crate::eval::eval(command)?,
+        Command::Fmt(command) => crate::fmt::fmt(command)?,
+        Command::Check(command) => crate::check::check(command)?,
+        Command::Vendor(command) => crate::vendor::vendor(command)?,
+        Command::Package(command) => crate::publish::package(command)?,
         Command::Fonts(command) => crate::fonts::fonts(command),
         Command::Update(command) => crate::update::update(command)?,
         Command::Completions(command) => crate::completions::completions(command),
@@ -1,8 +1,31 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ecow::eco_format;
+use serde::Deserialize;
+use typst::diag::StrResult;
+use typst::syntax::package::{Dependencies, PackageLock};
 use typst_kit::package::PackageStorage;
 
 use crate::args::PackageArgs;
 use crate::download;
 
+/// The name of the project manifest that declares dependencies.
+const MANIFEST: &str = "typst.toml";
+
+/// The name of the lockfile that is written next to the manifest.
+const LOCKFILE: &str = "typst.lock";
+
+/// The name of the project-local directory with vendored packages.
+pub const VENDOR_DIR: &str = "vendor";
+
+/// The header written at the top of the lockfile.
+const LOCKFILE_HEADER: &str = "\
+# This file is automatically generated by Typst.
+# It is not intended for manual editing.
+";
+
 /// Returns a new package storage for the given args.
 pub fn storage(args: &PackageArgs) -> PackageStorage {
     PackageStorage::new(
@@ -10,4 +33,75 @@ pub fn storage(args: &PackageArgs) -> PackageStorage {
         args.package_path.clone(),
         download::downloader(),
     )
+    .with_frozen(args.frozen)
+}
+
+/// Returns a new package storage for the given args that resolves packages
+/// imported without a version through the dependencies and lockfile of the
+/// project at `root` and prefers packages vendored into it.
+pub fn project_storage(args: &PackageArgs, root: &Path) -> StrResult<PackageStorage> {
+    let dependencies = match read(&root.join(MANIFEST))? {
+        Some(string) => dependencies(&string)?,
+        None => Dependencies::new(),
+    };
+
+    let lock = match read(&root.join(LOCKFILE))? {
+        Some(string) => toml::from_str(&string)
+            .map_err(|err| eco_format!("{LOCKFILE} is malformed ({})", err.message()))?,
+        None => PackageLock::default(),
+    };
+
+    let mut storage = storage(args).with_lock(dependencies, lock);
+    let vendor_dir = root.join(VENDOR_DIR);
+    if vendor_dir.is_dir() {
+        storage = storage.with_vendor_path(vendor_dir);
+    }
+
+    Ok(storage)
+}
+
+/// Parses the dependencies from the manifest of a project or package.
+pub fn dependencies(manifest: &str) -> StrResult<Dependencies> {
+    /// The parts of a manifest relevant for dependency resolution. Unlike a
+    /// package manifest, it does not require a `[package]` section.
+    #[derive(Deserialize)]
+    struct Manifest {
+        #[serde(default)]
+        dependencies: Dependencies,
+    }
+
+    toml::from_str::<Manifest>(manifest)
+        .map(|manifest| manifest.dependencies)
+        .map_err(|err| eco_format!("{MANIFEST} is malformed ({})", err.message()))
+}
+
+/// Writes the current lockfile of the storage to the project at `root` if it
+/// differs from the one on disk.
+///
+/// Does nothing if the project neither has dependencies nor locked packages.
+pub fn write_lock(storage: &PackageStorage, root: &Path) -> StrResult<()> {
+    let lock = storage.lock();
+    if storage.dependencies().is_empty() && lock.packages.is_empty() {
+        return Ok(());
+    }
+
+    let path = root.join(LOCKFILE);
+    let body = toml::to_string(&lock)
+        .map_err(|err| eco_format!("failed to serialize {LOCKFILE} ({err})"))?;
+    let string = format!("{LOCKFILE_HEADER}\n{body}");
+    if read(&path)?.as_deref() == Some(string.as_str()) {
+        return Ok(());
+    }
+
+    fs::write(&path, string)
+        .map_err(|err| eco_format!("failed to write {LOCKFILE} ({err})"))
+}
+
+/// Reads a file to a string, returning `None` if it does not exist.
+fn read(path: &Path) -> StrResult<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(string) => Ok(Some(string)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(eco_format!("failed to read {} ({err})", path.display())),
+    }
 }
@@ -0,0 +1,424 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use codespan_reporting::term;
+use codespan_reporting::term::termcolor::WriteColor;
+use ecow::{EcoString, eco_format};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use typst::diag::{HintedStrResult, SourceDiagnostic, StrResult, Warned, bail};
+use typst::layout::PagedDocument;
+use typst::syntax::package::{PackageManifest, PackageVersion, UnknownFields};
+use typst::syntax::{FileId, Span, VirtualPath, is_ident};
+
+use crate::args::{
+    Input, PackageBundleCommand, PackageCheckCommand, PackageCommand, PackageSubcommand,
+};
+use crate::compile::print_diagnostics;
+use crate::set_failed;
+use crate::walk::walk;
+use crate::world::SystemWorld;
+
+/// Files larger than this cause a warning, as they bloat every download of
+/// the package.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Execute a package command.
+pub fn package(command: &'static PackageCommand) -> HintedStrResult<()> {
+    match &command.command {
+        PackageSubcommand::Check(command) => check(command).map(|_| ()),
+        PackageSubcommand::Bundle(command) => bundle(command),
+    }
+}
+
+/// A package that passed all checks.
+struct Checked {
+    /// The package's manifest.
+    manifest: PackageManifest,
+    /// The files that are part of the bundle, relative to the package
+    /// directory, with forward slashes, and in sorted order.
+    files: Vec<EcoString>,
+}
+
+/// Checks a package, printing all problems. Returns `None` if there were
+/// errors.
+fn check(command: &'static PackageCheckCommand) -> HintedStrResult<Option<Checked>> {
+    let dir = &command.dir;
+    let manifest = crate::init::parse_manifest(dir)?;
+
+    // Using the manifest as the input makes the package directory the root,
+    // so that the package cannot access any files outside of it.
+    let input = Input::Path(dir.join("typst.toml"));
+    let mut world = SystemWorld::new(Some(&input), &command.world, &command.process)?;
+
+    let mut errors = vec![];
+    let mut warnings = vec![];
+    let error = |message: EcoString| SourceDiagnostic::error(Span::detached(), message);
+    let warning =
+        |message: EcoString| SourceDiagnostic::warning(Span::detached(), message);
+
+    // Validate the manifest itself.
+    let info = &manifest.package;
+    let no_fields = UnknownFields::new();
+    let unknown = [
+        ("", &manifest.unknown_fields),
+        ("package.", &info.unknown_fields),
+        (
+            "template.",
+            manifest.template.as_ref().map_or(&no_fields, |t| &t.unknown_fields),
+        ),
+    ];
+    for (prefix, fields) in unknown {
+        for key in fields.keys() {
+            warnings.push(warning(eco_format!("unknown manifest key `{prefix}{key}`")));
+        }
+    }
+
+    if !is_ident(&info.name) {
+        errors.push(error(eco_format!("`{}` is not a valid package name", info.name)));
+    }
+
+    if let Some(required) = info.compiler {
+        let current = PackageVersion::compiler();
+        if !current.matches_ge(&required) {
+            errors.push(
+                error(eco_format!(
+                    "package requires Typst {required} or newer \
+                     (current version is {current})"
+                ))
+                .with_hint("the package cannot be checked with this version of Typst"),
+            );
+        }
+    }
+
+    // Determine the files that are part of the bundle.
+    let bundle_name = eco_format!("{}-{}.tar.gz", info.name, info.version);
+    let mut files = vec![];
+    for path in walk(dir) {
+        let path = path?;
+        let relative = path.strip_prefix(dir).unwrap_or(&path);
+        if relative.to_str().is_none() {
+            bail!("file name {} is not valid UTF-8", path.display());
+        }
+        let metadata = fs::metadata(&path)
+            .map_err(|err| eco_format!("failed to read {} ({err})", path.display()))?;
+        files.push((slashed(relative), metadata.len()));
+    }
+    files.retain(|(path, _)| *path != bundle_name);
+    files.sort();
+
+    let mut globs = vec![];
+    for glob in &info.exclude {
+        let matcher = match matcher([glob.as_str()]) {
+            Ok(matcher) => matcher,
+            Err(err) => {
+                errors
+                    .push(error(eco_format!("exclude glob `{glob}` is invalid ({err})")));
+                continue;
+            }
+        };
+
+        // A negated glob re-includes files, so it doesn't exclude anything on
+        // its own.
+        if !glob.starts_with('!')
+            && !files.iter().any(|(path, _)| is_excluded(&matcher, path))
+        {
+            warnings.push(warning(eco_format!(
+                "exclude glob `{glob}` does not match any file"
+            )));
+        }
+
+        globs.push(glob.as_str());
+    }
+
+    let exclude = matcher(globs)
+        .map_err(|err| eco_format!("failed to build exclude globs ({err})"))?;
+    let excluded = |path: &str| is_excluded(&exclude, path);
+    files.retain(|(path, _)| !excluded(path));
+    let included = |path: &str| files.iter().any(|(p, _)| p == path);
+
+    for (path, size) in &files {
+        if *size > MAX_FILE_SIZE {
+            warnings.push(
+                warning(eco_format!(
+                    "file `{path}` is large ({:.1} MiB)",
+                    *size as f64 / MAX_FILE_SIZE as f64
+                ))
+                .with_hint(
+                    "consider excluding it with the `exclude` key in the manifest",
+                ),
+            );
+        }
+    }
+
+    if excluded("typst.toml") {
+        errors.push(error("the manifest `typst.toml` must not be excluded".into()));
+    }
+
+    // Validate the template.
+    if let Some(template) = &manifest.template {
+        let path = template.path.trim_end_matches('/');
+        let entrypoint = eco_format!("{path}/{}", template.entrypoint);
+        if !dir.join(path).is_dir() {
+            errors.push(error(eco_format!("template directory `{path}` does not exist")));
+        } else if !included(&entrypoint) {
+            errors.push(error(eco_format!(
+                "template entrypoint `{entrypoint}` does not exist or is excluded"
+            )));
+        }
+
+        if let Some(thumbnail) = &template.thumbnail {
+            if !dir.join(thumbnail.as_str()).is_file() {
+                errors.push(error(eco_format!("thumbnail `{thumbnail}` does not exist")));
+            } else if !thumbnail.ends_with(".png") && !thumbnail.ends_with(".webp") {
+                errors.push(error(eco_format!(
+                    "thumbnail `{thumbnail}` must be a PNG or WebP image"
+                )));
+            }
+        }
+    }
+
+    // Compile the entrypoint and ensure that everything it uses is bundled.
+    if !included(&info.entrypoint) {
+        errors.push(error(eco_format!(
+            "entrypoint `{}` does not exist or is excluded",
+            info.entrypoint
+        )));
+    } else {
+        world.set_main(FileId::new(None, VirtualPath::new(info.entrypoint.as_str())));
+        let Warned { output, warnings: compile_warnings } =
+            typst::compile::<PagedDocument>(&world);
+        if let Err(compile_errors) = output {
+            errors.extend(compile_errors);
+        }
+        warnings.extend(compile_warnings);
+
+        let mut used: Vec<_> = world
+            .files()
+            .filter(|id| id.package().is_none())
+            .map(|id| slashed(id.vpath().as_rootless_path()))
+            .collect();
+        used.sort();
+        for path in used {
+            if !included(&path) {
+                errors.push(error(eco_format!(
+                    "package uses `{path}`, which is excluded from the bundle"
+                )));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        set_failed();
+    }
+
+    print_diagnostics(&world, &errors, &warnings, command.process.diagnostic_format)
+        .map_err(|err| eco_format!("failed to print diagnostics ({err})"))?;
+
+    Ok(errors.is_empty().then(|| Checked {
+        manifest,
+        files: files.into_iter().map(|(path, _)| path).collect(),
+    }))
+}
+
+/// Checks a package and bundles it.
+fn bundle(command: &'static PackageBundleCommand) -> HintedStrResult<()> {
+    let Some(Checked { manifest, files }) = check(&command.check)? else {
+        return Ok(());
+    };
+
+    let info = &manifest.package;
+    let output = match &command.output {
+        Some(output) => output.clone(),
+        None => PathBuf::from(format!("{}-{}.tar.gz", info.name, info.version)),
+    };
+
+    write_bundle(&command.check.dir, &files, &output)?;
+
+    let styles = term::Styles::default();
+    let mut out = crate::terminal::out();
+    let _ = out.set_color(&styles.header_help);
+    let _ = write!(out, "bundled");
+    let _ = out.reset();
+    let _ = writeln!(out, " {}:{} into {}", info.name, info.version, output.display());
+
+    Ok(())
+}
+
+/// Writes the files into a gzipped tarball.
+///
+/// All metadata that could differ between machines (timestamps, owners,
+/// permissions) is normalized so that the archive is reproducible.
+fn write_bundle(dir: &Path, files: &[EcoString], output: &Path) -> StrResult<()> {
+    let failed = |err: io::Error| eco_format!("failed to write bundle ({err})");
+    let file = File::create(output).map_err(failed)?;
+    let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    for path in files {
+        let data = fs::read(dir.join(path.as_str()))
+            .map_err(|err| eco_format!("failed to read `{path}` ({err})"))?;
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        builder
+            .append_data(&mut header, path.as_str(), data.as_slice())
+            .map_err(failed)?;
+    }
+
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(failed)?;
+    Ok(())
+}
+
+/// Joins the components of a relative path with forward slashes.
+fn slashed(path: &Path) -> EcoString {
+    let mut slashed = EcoString::new();
+    for (i, component) in path.components().enumerate() {
+        if i > 0 {
+            slashed.push('/');
+        }
+        slashed.push_str(&component.as_os_str().to_string_lossy());
+    }
+    slashed
+}
+
+/// Builds a matcher for `exclude` globs.
+///
+/// Globs follow the conventions of `.gitignore` files: A glob without a slash
+/// matches in any directory, while a glob with a slash is relative to the
+/// package directory. `*` and `?` match within a path segment and `**`
+/// matches any number of segments. A glob that matches a directory matches
+/// everything in it and a glob starting with `!` re-includes what earlier
+/// globs excluded.
+fn matcher<'a>(
+    globs: impl IntoIterator<Item = &'a str>,
+) -> Result<Gitignore, ignore::Error> {
+    let mut builder = GitignoreBuilder::new("");
+    for glob in globs {
+        builder.add_line(None, glob)?;
+    }
+    builder.build()
+}
+
+/// Whether a file path relative to the package directory is excluded.
+fn is_excluded(matcher: &Gitignore, path: &str) -> bool {
+    matcher.matched_path_or_any_parents(path, false).is_ignore()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    #[track_caller]
+    fn test(globs: &[&str], path: &str, expected: bool) {
+        let matcher = matcher(globs.iter().copied()).unwrap();
+        assert_eq!(is_excluded(&matcher, path), expected, "{globs:?} on {path}");
+    }
+
+    #[test]
+    fn test_exclude_unanchored() {
+        test(&["*.pdf"], "a.pdf", true);
+        test(&["*.pdf"], "docs/a.pdf", true);
+        test(&["*.pdf"], "a.pdf.typ", false);
+        test(&["a?.typ"], "ab.typ", true);
+        test(&["a?.typ"], "a.typ", false);
+        test(&["a*"], "b/a", true);
+        test(&["*"], "lib.typ", true);
+    }
+
+    #[test]
+    fn test_exclude_anchored() {
+        test(&["/docs"], "docs/manual.typ", true);
+        test(&["/docs"], "src/docs/manual.typ", false);
+        test(&["docs/*.png"], "docs/a.png", true);
+        test(&["docs/*.png"], "docs/sub/a.png", false);
+        test(&["docs/*.png"], "src/docs/a.png", false);
+        test(&["src/*"], "src/a/b.typ", true);
+    }
+
+    #[test]
+    fn test_exclude_double_star() {
+        test(&["tests/**/*.png"], "tests/a.png", true);
+        test(&["tests/**/*.png"], "tests/a/b/c.png", true);
+        test(&["tests/**/*.png"], "tests/a/b/c.typ", false);
+        test(&["**/ref"], "ref/a.png", true);
+        test(&["**/ref"], "a/b/ref/c.png", true);
+        test(&["docs/**"], "docs/a/b.typ", true);
+        test(&["docs/**"], "docs.typ", false);
+    }
+
+    #[test]
+    fn test_exclude_directories() {
+        test(&["build/"], "build/out.pdf", true);
+        test(&["build/"], "src/build/out.pdf", true);
+        test(&["build/"], "build", false);
+        test(&["examples"], "examples/a/b.typ", true);
+        test(&["examples"], "examples.typ", false);
+    }
+
+    #[test]
+    fn test_exclude_negation() {
+        test(&["*.png", "!thumbnail.png"], "thumbnail.png", false);
+        test(&["*.png", "!thumbnail.png"], "docs/a.png", true);
+        test(&["!thumbnail.png", "*.png"], "thumbnail.png", true);
+        test(&["\\!important.typ"], "!important.typ", true);
+    }
+
+    #[test]
+    fn test_exclude_invalid() {
+        assert!(matcher(["a{b"]).is_err());
+    }
+
+    #[test]
+    fn test_bundle_reproducible() {
+        let dir = std::env::temp_dir().join("typst-bundle-test");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("typst.toml"), "[package]").unwrap();
+        fs::write(dir.join("src/lib.typ"), "#let x = 1").unwrap();
+
+        let files = ["src/lib.typ".into(), "typst.toml".into()];
+        let first = dir.join("first.tar.gz");
+        let second = dir.join("second.tar.gz");
+        write_bundle(&dir, &files, &first).unwrap();
+
+        // Metadata of the source files must not leak into the archive.
+        fs::write(dir.join("src/lib.typ"), "#let x = 1").unwrap();
+        write_bundle(&dir, &files, &second).unwrap();
+
+        let bytes = fs::read(&first).unwrap();
+        assert_eq!(bytes, fs::read(&second).unwrap());
+
+        let decoder = flate2::read::GzDecoder::new(bytes.as_slice());
+        let mut archive = tar::Archive::new(decoder);
+        let mut entries = vec![];
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let header = entry.header();
+            assert_eq!(header.entry_type(), tar::EntryType::Regular);
+            assert_eq!(header.mode().unwrap(), 0o644);
+            assert_eq!(header.mtime().unwrap(), 0);
+            assert_eq!((header.uid().unwrap(), header.gid().unwrap()), (0, 0));
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut data = String::new();
+            entry.read_to_string(&mut data).unwrap();
+            entries.push((path, data));
+        }
+
+        assert_eq!(
+            entries,
+            [
+                ("src/lib.typ".into(), "#let x = 1".into()),
+                ("typst.toml".into(), "[package]".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,512 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use codespan_reporting::files::Files;
+use ecow::{EcoString, eco_format};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use typst::WorldExt;
+use typst::diag::{HintedStrResult, Severity, SourceDiagnostic, StrResult, Warned, bail};
+use typst::layout::{Page, PagedDocument};
+use typst::syntax::{FileId, Span, VirtualPath};
+
+use crate::args::{Input, ServeCommand};
+use crate::world::SystemWorld;
+
+/// Execute a serve command.
+///
+/// The server reads one JSON request per line and answers each with one JSON
+/// response per line, either over stdin and stdout or over a TCP connection.
+/// Its state, i.e. the in-memory file contents and the incremental compilation
+/// caches, persists across requests.
+pub fn serve(command: &'static ServeCommand) -> HintedStrResult<()> {
+    if matches!(command.input, Input::Stdin) {
+        bail!(
+            "cannot read input from stdin in serve mode";
+            hint: "pass the path to the main file instead"
+        );
+    }
+
+    if let Some(addr) = command.listen
+        && !addr.ip().is_loopback()
+        && !command.allow_remote
+    {
+        bail!(
+            "refusing to listen on {addr}, which isn't a loopback address";
+            hint: "anyone who can reach the address could control the server";
+            hint: "pass `--allow-remote` to listen on it anyway"
+        );
+    }
+
+    let world = SystemWorld::new(Some(&command.input), &command.world, &command.process)?;
+    let mut server = Server::new(world, command.ppi);
+
+    let Some(addr) = command.listen else {
+        server.run(io::stdin().lock(), io::stdout().lock())?;
+        return Ok(());
+    };
+
+    let listener = TcpListener::bind(addr)
+        .map_err(|err| eco_format!("failed to listen on {addr} ({err})"))?;
+    let addr = listener.local_addr().unwrap_or(addr);
+    eprintln!("Listening on {addr}");
+
+    // Serve one client after another until one of them requests a shutdown.
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .map_err(|err| eco_format!("failed to accept connection ({err})"))?;
+        let reader = stream
+            .try_clone()
+            .map_err(|err| eco_format!("failed to set up connection ({err})"))?;
+
+        // A new client hasn't seen any pages yet.
+        server.sent = None;
+        if server.run(BufReader::new(reader), stream)? {
+            return Ok(());
+        }
+    }
+}
+
+/// The state of a compile server.
+struct Server {
+    /// The world the server compiles in.
+    world: SystemWorld,
+    /// The PPI to render PNG pages with.
+    ppi: f32,
+    /// The in-memory contents of files that the client provided.
+    overlays: FxHashMap<FileId, String>,
+    /// The format and hashes of the pages last sent to the client.
+    sent: Option<(PageFormat, Vec<u128>)>,
+}
+
+impl Server {
+    /// Create a new server.
+    fn new(world: SystemWorld, ppi: f32) -> Self {
+        Self {
+            world,
+            ppi,
+            overlays: FxHashMap::default(),
+            sent: None,
+        }
+    }
+
+    /// Answers requests until the client disconnects or requests a shutdown.
+    ///
+    /// Returns whether a shutdown was requested.
+    fn run(&mut self, reader: impl BufRead, mut writer: impl Write) -> StrResult<bool> {
+        for line in reader.lines() {
+            let line =
+                line.map_err(|err| eco_format!("failed to read request ({err})"))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (id, reply, shutdown) = match serde_json::from_str::<Message>(&line) {
+                Ok(Message { id, request }) => {
+                    let shutdown = matches!(request, Request::Shutdown);
+                    let reply = match self.handle(request) {
+                        Ok(result) => Reply::Result(result),
+                        Err(message) => Reply::Error(message),
+                    };
+                    (id, reply, shutdown)
+                }
+                Err(err) => {
+                    (None, Reply::Error(eco_format!("invalid request ({err})")), false)
+                }
+            };
+
+            serde_json::to_writer(&mut writer, &Response { id, reply })
+                .map_err(|err| eco_format!("failed to serialize response ({err})"))?;
+            writeln!(writer)
+                .and_then(|_| writer.flush())
+                .map_err(|err| eco_format!("failed to write response ({err})"))?;
+
+            if shutdown {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Handles a single request.
+    fn handle(&mut self, request: Request) -> StrResult<Option<Compiled>> {
+        match request {
+            Request::Update { path, text } => {
+                let id = self.resolve(&path)?;
+                self.set_overlay(id, Some(text));
+            }
+            Request::Edit { path, range, text } => {
+                let id = self.resolve(&path)?;
+                let mut current = match self.overlays.get(&id) {
+                    Some(current) => current.clone(),
+                    None => self.read(&path)?,
+                };
+                if current.get(range.clone()).is_none() {
+                    bail!(
+                        "range {}..{} is out of bounds or not on a character boundary",
+                        range.start,
+                        range.end,
+                    );
+                }
+                current.replace_range(range, &text);
+                self.set_overlay(id, Some(current));
+            }
+            Request::Close { path } => {
+                let id = self.resolve(&path)?;
+                self.set_overlay(id, None);
+            }
+            Request::Compile { format } => return Ok(Some(self.compile(format))),
+            Request::Shutdown => {}
+        }
+        Ok(None)
+    }
+
+    /// Compiles the document and collects diagnostics and changed pages.
+    fn compile(&mut self, format: PageFormat) -> Compiled {
+        self.world.reset();
+
+        let Warned { output, warnings } = typst::compile::<PagedDocument>(&self.world);
+        let (errors, pages) = match output {
+            Ok(document) => (vec![], Some(self.pages(&document, format))),
+            Err(errors) => (errors.into_iter().collect(), None),
+        };
+
+        let diagnostics = errors
+            .iter()
+            .chain(&warnings)
+            .map(|diagnostic| self.diagnostic(diagnostic))
+            .collect();
+
+        comemo::evict(10);
+
+        Compiled { diagnostics, pages }
+    }
+
+    /// Describes the pages of a document, including the data of those that
+    /// changed since they were last sent to the client.
+    fn pages(&mut self, document: &PagedDocument, format: PageFormat) -> Vec<PageInfo> {
+        let hashes: Vec<u128> =
+            document.pages.iter().map(typst::utils::hash128).collect();
+        let prev = match &self.sent {
+            Some((sent, prev)) if *sent == format => prev.as_slice(),
+            _ => &[],
+        };
+
+        let pages = document
+            .pages
+            .iter()
+            .zip(&hashes)
+            .enumerate()
+            .map(|(i, (page, &hash))| {
+                let unchanged = prev.get(i) == Some(&hash);
+                let hash = eco_format!("{hash:032x}");
+                if unchanged {
+                    return PageInfo { hash, data: None, error: None };
+                }
+                match export(page, format, self.ppi) {
+                    Ok(data) => PageInfo { hash, data: Some(data), error: None },
+                    Err(error) => PageInfo { hash, data: None, error: Some(error) },
+                }
+            })
+            .collect();
+
+        self.sent = Some((format, hashes));
+        pages
+    }
+
+    /// Converts a diagnostic into its serializable form.
+    fn diagnostic(&self, diagnostic: &SourceDiagnostic) -> Diagnostic {
+        let (path, range) = self.locate(diagnostic.span).unzip();
+        Diagnostic {
+            severity: match diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            },
+            message: diagnostic.message.clone(),
+            path,
+            range,
+            hints: diagnostic.hints.iter().map(|hint| hint.v.clone()).collect(),
+        }
+    }
+
+    /// Determines the file and range a span points to.
+    fn locate(&self, span: Span) -> Option<(String, Range<Position>)> {
+        let id = span.id()?;
+        let range = self.world.range(span)?;
+        let lines = self.world.lookup(id);
+        let position = |byte| {
+            let (line, column) = lines.byte_to_line_column(byte)?;
+            Some(Position { byte, line, column })
+        };
+        let path = self.world.name(id).ok()?;
+        Some((path, position(range.start)?..position(range.end)?))
+    }
+
+    /// Resolves a path from a request to a file id in the project.
+    fn resolve(&self, path: &Path) -> StrResult<FileId> {
+        let abs = self.world.workdir().join(path);
+        let Some(vpath) = VirtualPath::within_root(&abs, self.world.root()) else {
+            bail!("{} is not contained in the project root", path.display());
+        };
+        Ok(FileId::new(None, vpath))
+    }
+
+    /// Reads a file's contents from disk.
+    fn read(&self, path: &Path) -> StrResult<String> {
+        let abs = self.world.workdir().join(path);
+        fs::read_to_string(&abs)
+            .map_err(|err| eco_format!("failed to read {} ({err})", path.display()))
+    }
+
+    /// Sets or clears the in-memory contents of a file.
+    fn set_overlay(&mut self, id: FileId, text: Option<String>) {
+        match &text {
+            Some(text) => self.overlays.insert(id, text.clone()),
+            None => self.overlays.remove(&id),
+        };
+        self.world.set_overlay(id, text);
+    }
+}
+
+/// Exports a page in the given format. PNG data is base64-encoded.
+fn export(page: &Page, format: PageFormat, ppi: f32) -> Result<String, EcoString> {
+    match format {
+        PageFormat::Svg => Ok(typst_svg::svg(page)),
+        PageFormat::Png => {
+            let pixmap = typst_render::render(page, ppi / 72.0);
+            let buf = pixmap
+                .encode_png()
+                .map_err(|err| eco_format!("failed to encode PNG ({err})"))?;
+            Ok(base64::engine::general_purpose::STANDARD.encode(buf))
+        }
+    }
+}
+
+/// A request from the client, tagged with an identifier that is echoed in
+/// the response.
+#[derive(Deserialize)]
+struct Message {
+    id: Option<u64>,
+    #[serde(flatten)]
+    request: Request,
+}
+
+/// What the client asks the server to do.
+#[derive(Deserialize)]
+#[serde(tag = "method", rename_all = "kebab-case")]
+enum Request {
+    /// Replaces the contents of a file with in-memory text.
+    Update { path: PathBuf, text: String },
+    /// Replaces a byte range in the current contents of a file.
+    Edit { path: PathBuf, range: Range<usize>, text: String },
+    /// Discards the in-memory text of a file in favor of its contents on disk.
+    Close { path: PathBuf },
+    /// Compiles the document.
+    Compile {
+        #[serde(default)]
+        format: PageFormat,
+    },
+    /// Stops the server.
+    Shutdown,
+}
+
+/// The format in which pages are sent to the client.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum PageFormat {
+    #[default]
+    Svg,
+    Png,
+}
+
+/// The server's answer to a request.
+#[derive(Serialize)]
+struct Response {
+    id: Option<u64>,
+    #[serde(flatten)]
+    reply: Reply,
+}
+
+/// The outcome of a request.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Reply {
+    /// The request succeeded, possibly with a result.
+    Result(Option<Compiled>),
+    /// The request failed.
+    Error(EcoString),
+}
+
+/// The result of a compilation.
+#[derive(Serialize)]
+struct Compiled {
+    /// All errors and warnings.
+    diagnostics: Vec<Diagnostic>,
+    /// The document's pages, or `None` if compilation failed.
+    pages: Option<Vec<PageInfo>>,
+}
+
+/// A page of the compiled document.
+#[derive(Serialize)]
+struct PageInfo {
+    /// A hash of the page's contents.
+    hash: EcoString,
+    /// The exported page, if it changed since it was last sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+    /// Why the page could not be exported, if it couldn't.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<EcoString>,
+}
+
+/// An error or warning.
+#[derive(Serialize)]
+struct Diagnostic {
+    severity: &'static str,
+    message: EcoString,
+    /// The path of the file the diagnostic points to.
+    path: Option<String>,
+    /// The range in the file the diagnostic points to.
+    range: Option<Range<Position>>,
+    hints: Vec<EcoString>,
+}
+
+/// A position in a file. Lines and columns are zero-based and columns count
+/// characters.
+#[derive(Serialize)]
+struct Position {
+    byte: usize,
+    line: usize,
+    column: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use clap::Parser;
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::world::tests::world;
+
+    /// Creates a server for a project with a single empty main file.
+    fn server(name: &str) -> (Server, PathBuf) {
+        let (world, main) = world(&format!("serve-{name}"), "");
+        (Server::new(world, 144.0), main)
+    }
+
+    /// Sends the requests, one per line, and returns the parsed responses and
+    /// whether a shutdown was requested.
+    fn exchange(server: &mut Server, requests: &[Value]) -> (Vec<Value>, bool) {
+        let input: String =
+            requests.iter().map(|request| format!("{request}\n")).collect();
+        let mut output = vec![];
+        let shutdown = server.run(input.as_bytes(), &mut output).unwrap();
+        let responses = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        (responses, shutdown)
+    }
+
+    #[test]
+    fn test_serve_remote_address() {
+        static COMMAND: LazyLock<ServeCommand> = LazyLock::new(|| {
+            ServeCommand::try_parse_from(["serve", "main.typ", "--listen", "0.0.0.0:0"])
+                .unwrap()
+        });
+        let err = serve(&COMMAND).unwrap_err();
+        assert!(err.message().starts_with("refusing to listen on 0.0.0.0:0"));
+    }
+
+    #[test]
+    fn test_serve_round_trip() {
+        let (mut server, main) = server("round-trip");
+        let page = "#set page(width: 20pt, height: 20pt)\n#rect(width: 5pt)";
+        let (responses, shutdown) = exchange(
+            &mut server,
+            &[
+                json!({ "id": 1, "method": "update", "path": main, "text": page }),
+                json!({ "id": 2, "method": "compile" }),
+                json!({ "id": 3, "method": "compile" }),
+            ],
+        );
+
+        assert!(!shutdown);
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0], json!({ "id": 1, "result": null }));
+
+        // The first compilation sends the page, the second one only its hash.
+        let first = &responses[1]["result"];
+        assert_eq!(first["diagnostics"], json!([]));
+        let pages = first["pages"].as_array().unwrap();
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0]["data"].as_str().unwrap().starts_with("<svg"));
+
+        let second = &responses[2]["result"]["pages"][0];
+        assert_eq!(second["hash"], pages[0]["hash"]);
+        assert!(second.get("data").is_none());
+    }
+
+    #[test]
+    fn test_serve_overlays() {
+        let (mut server, main) = server("overlays");
+        let (responses, _) = exchange(
+            &mut server,
+            &[
+                json!({ "id": 1, "method": "update", "path": main, "text": "#let x = 1" }),
+                json!({ "id": 2, "method": "edit", "path": main, "range": { "start": 9, "end": 10 }, "text": "(" }),
+                json!({ "id": 3, "method": "compile" }),
+                json!({ "id": 4, "method": "edit", "path": main, "range": { "start": 20, "end": 30 }, "text": "" }),
+                json!({ "id": 5, "method": "close", "path": main }),
+                json!({ "id": 6, "method": "compile" }),
+            ],
+        );
+
+        assert_eq!(responses[1], json!({ "id": 2, "result": null }));
+
+        // The edited overlay doesn't parse.
+        let diagnostics = responses[2]["result"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics[0]["severity"], "error");
+        assert!(diagnostics[0]["path"].as_str().unwrap().ends_with("main.typ"));
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 0);
+        assert_eq!(responses[2]["result"]["pages"], Value::Null);
+
+        // Out-of-bounds edits fail without affecting the server.
+        assert_eq!(responses[3]["id"], 4);
+        assert!(responses[3]["error"].as_str().unwrap().contains("out of bounds"));
+
+        // After closing the file, its empty contents on disk are compiled.
+        assert_eq!(responses[5]["result"]["diagnostics"], json!([]));
+        assert_eq!(responses[5]["result"]["pages"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_serve_invalid_and_shutdown() {
+        let (mut server, _) = server("shutdown");
+        let input = "not json\n\n{\"id\": 7, \"method\": \"shutdown\"}\n{\"id\": 8}\n";
+        let mut output = vec![];
+        assert!(server.run(input.as_bytes(), &mut output).unwrap());
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        // Invalid requests are answered with an error, but don't stop the
+        // server. Nothing after the shutdown is read.
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], Value::Null);
+        assert!(responses[0]["error"].as_str().unwrap().starts_with("invalid request"));
+        assert_eq!(responses[1], json!({ "id": 7, "result": null }));
+    }
+}
@@ -2,17 +2,30 @@ use std::io::{self, Write};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
 use std::sync::Arc;
 
-use ecow::eco_format;
+use ecow::{EcoString, eco_format};
 use parking_lot::{Condvar, Mutex, MutexGuard};
 use tiny_http::{Header, Request, Response, StatusCode};
 use typst::diag::{StrResult, bail};
+use typst_html::HtmlAssets;
 
 use crate::args::{Input, ServerArgs};
 
 /// Serves HTML with live reload.
 pub struct HtmlServer {
     addr: SocketAddr,
-    bucket: Arc<Bucket<String>>,
+    bucket: Arc<Bucket<Site>>,
+}
+
+/// The served HTML pages along with their paths. The page with the path
+/// `index.html` is served at the root.
+type Pages = Vec<(EcoString, String)>;
+
+/// Everything that is served.
+struct Site {
+    /// The HTML pages.
+    pages: Pages,
+    /// Images and frames referenced by the pages.
+    assets: HtmlAssets,
 }
 
 impl HtmlServer {
@@ -22,7 +35,10 @@ impl HtmlServer {
         let (addr, server) = start_server(args.port)?;
 
         let placeholder = PLACEHOLDER_HTML.replace("{INPUT}", &input.to_string());
-        let bucket = Arc::new(Bucket::new(placeholder));
+        let bucket = Arc::new(Bucket::new(Site {
+            pages: vec![("index.html".into(), placeholder)],
+            assets: HtmlAssets::new(),
+        }));
         let bucket2 = bucket.clone();
 
         std::thread::spawn(move || {
@@ -40,8 +56,14 @@ impl HtmlServer {
     }
 
     /// Updates the HTML, triggering a reload all connected browsers.
-    pub fn update(&self, html: String) {
-        self.bucket.put(html);
+    pub fn update(&self, html: String, assets: HtmlAssets) {
+        self.update_pages(vec![("index.html".into(), html)], assets);
+    }
+
+    /// Updates the HTML of a document that was split into multiple pages,
+    /// triggering a reload all connected browsers.
+    pub fn update_pages(&self, pages: Pages, assets: HtmlAssets) {
+        self.bucket.put(Site { pages, assets });
     }
 }
 
@@ -84,18 +106,38 @@ fn start_server(port: Option<u16>) -> StrResult<(SocketAddr, tiny_http::Server)>
 }
 
 /// Handles a request.
-fn handle(req: Request, reload: bool, bucket: &Arc<Bucket<String>>) -> io::Result<()> {
+fn handle(req: Request, reload: bool, bucket: &Arc<Bucket<Site>>) -> io::Result<()> {
     let path = req.url();
     match path {
-        "/" => handle_root(req, reload, bucket),
+        "/" => handle_page(req, "index.html", reload, bucket),
         "/events" => handle_events(req, bucket.clone()),
-        _ => req.respond(Response::new_empty(StatusCode(404))),
+        _ => {
+            let path = path.trim_start_matches('/').to_owned();
+            handle_page(req, &path, reload, bucket)
+        }
     }
 }
 
-/// Handles for the `/` route. Serves the compiled HTML.
-fn handle_root(req: Request, reload: bool, bucket: &Bucket<String>) -> io::Result<()> {
-    let mut html = bucket.get().clone();
+/// Handles the route of a page or asset. Serves the compiled HTML.
+fn handle_page(
+    req: Request,
+    path: &str,
+    reload: bool,
+    bucket: &Bucket<Site>,
+) -> io::Result<()> {
+    let (page, asset) = {
+        let site = bucket.get();
+        let page = site.pages.iter().find(|(p, _)| p == path);
+        (page.map(|(_, html)| html.clone()), site.assets.get(path).cloned())
+    };
+
+    let Some(mut html) = page else {
+        return match asset {
+            Some(data) => handle_asset(req, path, data.as_slice()),
+            None => req.respond(Response::new_empty(StatusCode(404))),
+        };
+    };
+
     if reload {
         inject_live_reload_script(&mut html);
     }
@@ -108,8 +150,27 @@ fn handle_root(req: Request, reload: bool, bucket: &Bucket<String>) -> io::Resul
     ))
 }
 
+/// Serves an image or frame referenced by the HTML.
+fn handle_asset(req: Request, path: &str, data: &[u8]) -> io::Result<()> {
+    let content_type = match path.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("png") => "image/png",
+        Some("jpg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    };
+    req.respond(Response::new(
+        StatusCode(200),
+        vec![Header::from_bytes("Content-Type", content_type).unwrap()],
+        data,
+        Some(data.len()),
+        None,
+    ))
+}
+
 /// Handler for the `/events` route.
-fn handle_events(req: Request, bucket: Arc<Bucket<String>>) -> io::Result<()> {
+fn handle_events(req: Request, bucket: Arc<Bucket<Site>>) -> io::Result<()> {
     std::thread::spawn(move || {
         // When this returns an error, the client is disconnected and we can
         // terminate the thread.
@@ -119,7 +180,7 @@ fn handle_events(req: Request, bucket: Arc<Bucket<String>>) -> io::Result<()> {
 }
 
 /// Event stream for the `/events` route.
-fn handle_events_blocking(req: Request, bucket: &Bucket<String>) -> io::Result<()> {
+fn handle_events_blocking(req: Request, bucket: &Bucket<Site>) -> io::Result<()> {
     let mut writer = req.into_writer();
     let writer: &mut dyn Write = &mut *writer;
 
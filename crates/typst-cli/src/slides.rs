@@ -0,0 +1,397 @@
+//! Export of paged documents as slideshows.
+//!
+//! Speaker notes and durations are read from `metadata` elements with a
+//! `slide` key, e.g. `[#metadata((slide: (notes: [...], duration: 10s)))]`.
+//! They apply to the page the metadata ends up on. A `slide` value that is a
+//! string or content is taken as the notes directly.
+
+use std::fmt::Write;
+
+use base64::Engine;
+use ecow::{EcoString, eco_format};
+use typst::diag::{SourceResult, StrResult, bail};
+use typst::foundations::{NativeElement, Value};
+use typst::introspection::MetadataElem;
+use typst::layout::{Axes, Page, PageRanges, PagedDocument};
+
+use crate::args::Output;
+
+/// How long a frame of an animated PNG is shown if neither the page nor the
+/// command line specify a duration, in seconds.
+const DEFAULT_FRAME_DURATION: f64 = 5.0;
+
+/// A page to be shown as a slide.
+pub struct Slide<'a> {
+    /// The page.
+    pub page: &'a Page,
+    /// How long to show the slide before advancing, in seconds.
+    pub duration: Option<f64>,
+    /// The speaker notes, one entry per `metadata` element.
+    pub notes: Vec<EcoString>,
+}
+
+/// Collects the slides of a document, along with their notes and durations.
+///
+/// Pages without a duration of their own use the default duration.
+pub fn collect<'a>(
+    document: &'a PagedDocument,
+    pages: Option<&PageRanges>,
+    default_duration: Option<f64>,
+) -> SourceResult<Vec<Slide<'a>>> {
+    let mut slides: Vec<_> = document
+        .pages
+        .iter()
+        .map(|page| Slide {
+            page,
+            duration: page.duration.map(|d| d.seconds()).or(default_duration),
+            notes: vec![],
+        })
+        .collect();
+
+    let introspector = &document.introspector;
+    for elem in introspector.query(&MetadataElem::ELEM.select()) {
+        let (Some(meta), Some(location)) =
+            (elem.to_packed::<MetadataElem>(), elem.location())
+        else {
+            continue;
+        };
+        let Value::Dict(dict) = &meta.value else { continue };
+        let Ok(value) = dict.get("slide") else { continue };
+
+        let slide = &mut slides[introspector.page(location).get() - 1];
+        let (notes, duration) = match value {
+            Value::Dict(dict) => (dict.get("notes").ok(), dict.get("duration").ok()),
+            other => (Some(other), None),
+        };
+
+        match notes {
+            None => {}
+            Some(Value::Str(text)) => slide.notes.push(text.as_str().into()),
+            Some(Value::Content(content)) => slide.notes.push(content.plain_text()),
+            Some(other) => bail!(
+                elem.span(),
+                "expected string or content for slide notes, found {}",
+                other.ty(),
+            ),
+        }
+
+        slide.duration = match duration {
+            None => slide.duration,
+            Some(Value::Duration(duration)) => Some(duration.seconds()),
+            Some(Value::Int(seconds)) => Some(*seconds as f64),
+            Some(Value::Float(seconds)) => Some(*seconds),
+            Some(other) => bail!(
+                elem.span(),
+                "expected duration or number of seconds for slide duration, found {}",
+                other.ty(),
+            ),
+        };
+    }
+
+    Ok(slides
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| pages.is_none_or(|pages| pages.includes_page_index(*i)))
+        .map(|(_, slide)| slide)
+        .collect())
+}
+
+/// Encodes slides into a self-contained HTML slideshow.
+///
+/// The slides are navigated with the arrow keys, space, and page up/down.
+/// Pressing `p` opens a presenter view with the notes, the next slide, and a
+/// timer in a separate window, which stays in sync with the slideshow.
+/// Pressing `f` toggles fullscreen.
+pub fn html(document: &PagedDocument, slides: &[Slide]) -> String {
+    let title = document.info.title.as_deref().unwrap_or("Slides");
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>").unwrap();
+    writeln!(html, "<html>").unwrap();
+    writeln!(html, "<head>").unwrap();
+    writeln!(html, "<meta charset=\"utf-8\">").unwrap();
+    writeln!(html, "<title>{}</title>", escape(title)).unwrap();
+    writeln!(html, "<style>{STYLE}</style>").unwrap();
+    writeln!(html, "</head>").unwrap();
+    writeln!(html, "<body>").unwrap();
+
+    for (i, slide) in slides.iter().enumerate() {
+        let svg = typst_svg::svg(slide.page);
+        let data = base64::engine::general_purpose::STANDARD.encode(svg);
+        write!(html, "<section class=\"slide\"").unwrap();
+        if let Some(duration) = slide.duration {
+            write!(html, " data-duration=\"{duration}\"").unwrap();
+        }
+        write!(
+            html,
+            "><img src=\"data:image/svg+xml;base64,{data}\" alt=\"Slide {}\">",
+            i + 1
+        )
+        .unwrap();
+        if !slide.notes.is_empty() {
+            write!(html, "<aside class=\"notes\">").unwrap();
+            for note in &slide.notes {
+                write!(html, "<p>{}</p>", escape(note)).unwrap();
+            }
+            write!(html, "</aside>").unwrap();
+        }
+        writeln!(html, "</section>").unwrap();
+    }
+
+    writeln!(
+        html,
+        "<div id=\"panel\"><div id=\"status\"></div><div id=\"notes\"></div></div>"
+    )
+    .unwrap();
+    writeln!(html, "<script>{SCRIPT}</script>").unwrap();
+    writeln!(html, "</body>").unwrap();
+    writeln!(html, "</html>").unwrap();
+    html
+}
+
+/// Encodes slides into an animated PNG that loops forever.
+///
+/// All slides must have the same size. Slides without a duration are shown
+/// for five seconds.
+pub fn apng(slides: &[Slide], pixel_per_pt: f32, output: &Output) -> StrResult<()> {
+    let Some(first) = slides.first() else { return Ok(()) };
+    let size = typst_render::render_size(first.page, pixel_per_pt);
+    if slides
+        .iter()
+        .any(|slide| typst_render::render_size(slide.page, pixel_per_pt) != size)
+    {
+        bail!("cannot export pages of different sizes as an animated PNG");
+    }
+
+    encode_apng(slides, pixel_per_pt, size, output)
+        .map_err(|err| eco_format!("failed to write PNG file ({err})"))
+}
+
+/// Writes the frames of an animated PNG.
+fn encode_apng(
+    slides: &[Slide],
+    pixel_per_pt: f32,
+    size: Axes<u32>,
+    output: &Output,
+) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(output.open()?, size.x, size.y);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(slides.len() as u32, 0)?;
+
+    let mut writer = encoder.write_header()?;
+    for slide in slides {
+        let duration = slide.duration.unwrap_or(DEFAULT_FRAME_DURATION);
+        let millis = (duration * 1000.0).round().clamp(1.0, u16::MAX as f64) as u16;
+        writer.set_frame_delay(millis, 1000)?;
+
+        let pixmap = typst_render::render(slide.page, pixel_per_pt);
+        writer.write_image_data(&crate::compile::demultiply(&pixmap))?;
+    }
+    writer.finish()
+}
+
+/// Escapes text for use in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// The stylesheet of the slideshow.
+const STYLE: &str = "
+body { margin: 0; background: black; color: white; font-family: sans-serif; }
+.slide { display: none; position: fixed; inset: 0; }
+.slide.current { display: block; }
+.slide img { width: 100%; height: 100%; object-fit: contain; }
+.notes, #panel { display: none; }
+body.presenter {
+  display: grid; grid-template: 1fr 2fr / 3fr 2fr; gap: 16px;
+  height: 100vh; padding: 16px; box-sizing: border-box;
+}
+body.presenter .slide { position: static; min-height: 0; }
+body.presenter .slide.current { grid-area: 1 / 1 / 3 / 2; }
+body.presenter .slide.next { display: block; grid-area: 1 / 2; opacity: 0.7; }
+body.presenter #panel { display: block; grid-area: 2 / 2; overflow: auto; }
+#status { font-size: 1.5em; margin-bottom: 1em; }
+#notes { font-size: 1.25em; line-height: 1.4; }
+";
+
+/// The script that drives navigation, auto-advancing, and the presenter view.
+const SCRIPT: &str = r##"
+const slides = document.querySelectorAll(".slide");
+const presenter = location.hash === "#presenter";
+let peer = presenter ? window.opener : null;
+let index = 0;
+let timeout = null;
+const start = Date.now();
+
+if (presenter) document.body.classList.add("presenter");
+
+function show(i, notify = true) {
+  index = Math.max(0, Math.min(slides.length - 1, i));
+  slides.forEach((slide, j) => {
+    slide.classList.toggle("current", j === index);
+    slide.classList.toggle("next", j === index + 1);
+  });
+  const notes = slides[index].querySelector(".notes");
+  document.getElementById("notes").innerHTML = notes ? notes.innerHTML : "";
+  status();
+  if (notify && peer && !peer.closed) peer.postMessage({ slide: index }, "*");
+  clearTimeout(timeout);
+  const duration = parseFloat(slides[index].dataset.duration);
+  if (!presenter && duration > 0 && index < slides.length - 1) {
+    timeout = setTimeout(() => show(index + 1), duration * 1000);
+  }
+}
+
+function status() {
+  const seconds = Math.floor((Date.now() - start) / 1000);
+  const time = Math.floor(seconds / 60) + ":" + String(seconds % 60).padStart(2, "0");
+  document.getElementById("status").textContent =
+    (index + 1) + " / " + slides.length + " · " + time;
+}
+
+document.addEventListener("keydown", (event) => {
+  switch (event.key) {
+    case "ArrowRight": case "ArrowDown": case "PageDown": case " ":
+      show(index + 1); break;
+    case "ArrowLeft": case "ArrowUp": case "PageUp": case "Backspace":
+      show(index - 1); break;
+    case "Home": show(0); break;
+    case "End": show(slides.length - 1); break;
+    case "p":
+      if (!presenter) {
+        peer = window.open(location.href.split("#")[0] + "#presenter");
+      }
+      break;
+    case "f":
+      if (document.fullscreenElement) document.exitFullscreen();
+      else document.documentElement.requestFullscreen();
+      break;
+    default: return;
+  }
+  event.preventDefault();
+});
+
+document.addEventListener("click", () => show(index + 1));
+window.addEventListener("message", (event) => {
+  if (event.source !== peer) return;
+  if (event.data.ready) show(index);
+  else show(event.data.slide, false);
+});
+
+show(0, false);
+if (presenter) {
+  setInterval(status, 1000);
+  if (peer) peer.postMessage({ ready: true }, "*");
+}
+"##;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Cursor;
+    use std::num::NonZeroUsize;
+
+    use super::*;
+    use crate::world::tests::world;
+
+    /// Compiles a project with a single main file.
+    fn compile(name: &str, source: &str) -> SourceResult<PagedDocument> {
+        let (world, _) = world(&format!("slides-{name}"), source);
+        typst::compile::<PagedDocument>(&world).output
+    }
+
+    /// A document with three pages, whose notes and durations are given in
+    /// different ways.
+    fn document(name: &str) -> PagedDocument {
+        compile(
+            name,
+            "#set page(width: 20pt, height: 10pt)\n\
+             #metadata((slide: (notes: [First _notes_], duration: 2.5)))\n\
+             #metadata((slide: \"More <notes>\"))\n\
+             #pagebreak()\n\
+             #metadata((slide: [Second]))\n\
+             #page(duration: 7s)[#metadata((slide: (duration: 3s)))]\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_slides_collect() {
+        let document = document("collect");
+        let slides = collect(&document, None, Some(4.0)).unwrap();
+        assert_eq!(slides.len(), 3);
+        assert_eq!(slides[0].notes, ["First notes", "More <notes>"]);
+        assert_eq!(slides[0].duration, Some(2.5));
+        assert_eq!(slides[1].notes, ["Second"]);
+        assert_eq!(slides[1].duration, Some(4.0));
+        assert!(slides[2].notes.is_empty());
+        assert_eq!(slides[2].duration, Some(3.0));
+
+        let slides = collect(&document, None, None).unwrap();
+        assert_eq!(slides[1].duration, None);
+
+        let ranges = PageRanges::new(vec![NonZeroUsize::new(2)..=None]);
+        let slides = collect(&document, Some(&ranges), None).unwrap();
+        assert_eq!(slides.len(), 2);
+        assert_eq!(slides[0].notes, ["Second"]);
+    }
+
+    #[test]
+    fn test_slides_page_duration() {
+        let document = compile("page-duration", "#page(duration: 7s)[]").unwrap();
+        let slides = collect(&document, None, Some(4.0)).unwrap();
+        assert_eq!(slides[0].duration, Some(7.0));
+    }
+
+    #[test]
+    fn test_slides_invalid_notes() {
+        let document = compile("invalid", "#metadata((slide: (notes: 1)))").unwrap();
+        let errors = collect(&document, None, None).err().unwrap();
+        assert_eq!(
+            errors[0].message,
+            "expected string or content for slide notes, found integer"
+        );
+    }
+
+    #[test]
+    fn test_slides_html() {
+        let document = document("html");
+        let slides = collect(&document, None, None).unwrap();
+        let html = html(&document, &slides);
+        assert!(html.contains("<title>Slides</title>"));
+        assert_eq!(html.matches("<section class=\"slide\"").count(), 3);
+        assert!(html.contains(
+            "<section class=\"slide\" data-duration=\"2.5\"><img \
+             src=\"data:image/svg+xml;base64,"
+        ));
+        assert!(html.contains(
+            "<aside class=\"notes\"><p>First notes</p><p>More &lt;notes&gt;</p></aside>"
+        ));
+        assert!(html.contains("<section class=\"slide\"><img"));
+    }
+
+    #[test]
+    fn test_slides_apng() {
+        let document = document("apng");
+        let slides = collect(&document, None, None).unwrap();
+        let path = std::env::temp_dir().join("typst-slides-apng").join("slides.png");
+        apng(&slides, 1.0, &Output::Path(path.clone())).unwrap();
+
+        let decoder = png::Decoder::new(Cursor::new(fs::read(path).unwrap()));
+        let mut reader = decoder.read_info().unwrap();
+        let control = reader.info().animation_control.unwrap();
+        assert_eq!(control.num_frames, 3);
+        assert_eq!(control.num_plays, 0);
+
+        let mut delays = vec![];
+        let mut buf = vec![0; reader.output_buffer_size()];
+        for _ in 0..3 {
+            reader.next_frame(&mut buf).unwrap();
+            let frame = reader.info().frame_control.unwrap();
+            assert_eq!((frame.width, frame.height), (20, 10));
+            delays.push((frame.delay_num, frame.delay_den));
+        }
+        assert_eq!(delays, [(2500, 1000), (5000, 1000), (3000, 1000)]);
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use codespan_reporting::term::termcolor::WriteColor;
+use codespan_reporting::{files, term};
+use ecow::eco_format;
+use fs_extra::dir::CopyOptions;
+use typst::diag::{HintedStrResult, StrResult, Warned};
+use typst::layout::PagedDocument;
+use typst::syntax::package::PackageSpec;
+
+use crate::args::VendorCommand;
+use crate::compile::print_diagnostics;
+use crate::package::VENDOR_DIR;
+use crate::set_failed;
+use crate::world::SystemWorld;
+
+/// Execute a vendor command.
+pub fn vendor(command: &'static VendorCommand) -> HintedStrResult<()> {
+    let mut world =
+        SystemWorld::new(Some(&command.input), &command.world, &command.process)?;
+
+    // Compile the project to discover all packages it imports, including the
+    // ones imported by other packages.
+    let Warned { output, warnings } = typst::compile::<PagedDocument>(&world);
+    let format = command.process.diagnostic_format;
+    let failed = |err: files::Error| eco_format!("failed to print diagnostics ({err})");
+    match output {
+        Ok(_) => print_diagnostics(&world, &[], &warnings, format).map_err(failed)?,
+        Err(errors) => {
+            set_failed();
+            print_diagnostics(&world, &errors, &warnings, format).map_err(failed)?;
+            return Ok(());
+        }
+    }
+
+    world.write_lock()?;
+
+    let vendor_dir = world.root().join(VENDOR_DIR);
+    for spec in world.packages() {
+        let dest = vendor_dir
+            .join(spec.namespace.as_str())
+            .join(spec.name.as_str())
+            .join(spec.version.to_string());
+        if dest.exists() {
+            continue;
+        }
+
+        let src = world.prepare_package(&spec)?;
+        copy_package(&spec, &src, &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Copies the package at `src` to `dest`.
+fn copy_package(spec: &PackageSpec, src: &Path, dest: &Path) -> StrResult<()> {
+    let styles = term::Styles::default();
+    let mut out = crate::terminal::out();
+    let _ = out.set_color(&styles.header_help);
+    let _ = write!(out, "vendoring");
+    let _ = out.reset();
+    let _ = writeln!(out, " {spec}");
+
+    // Don't leave a partial copy behind, as it would be picked up instead of
+    // the full package later on.
+    fs_extra::dir::copy(src, dest, &CopyOptions::new().content_only(true)).map_err(
+        |err| {
+            _ = fs::remove_dir_all(dest);
+            eco_format!("failed to vendor {spec} ({err})")
+        },
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::path::{Path, PathBuf};
+
+use ecow::eco_format;
+use ignore::WalkBuilder;
+use typst::diag::StrResult;
+
+/// Lists the files in a directory recursively.
+///
+/// Hidden files and directories as well as everything matched by `.gitignore`
+/// and `.ignore` files are skipped. Symbolic links are not followed, so the
+/// walk stays within the directory and can't loop.
+pub fn walk(dir: &Path) -> impl Iterator<Item = StrResult<PathBuf>> {
+    WalkBuilder::new(dir).require_git(false).build().filter_map(
+        move |entry| match entry {
+            Ok(entry) => entry
+                .file_type()
+                .is_some_and(|ty| ty.is_file())
+                .then(|| Ok(entry.into_path())),
+            Err(err) => {
+                Some(Err(eco_format!("failed to read {} ({err})", dir.display())))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_walk_skips_hidden_and_ignored_files() {
+        let dir = std::env::temp_dir().join("typst-walk");
+        fs::create_dir_all(dir.join("out")).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::write(dir.join(".gitignore"), "out/\n*.pdf\n").unwrap();
+        fs::write(dir.join(".git/config"), "").unwrap();
+        fs::write(dir.join("out/main.typ"), "").unwrap();
+        fs::write(dir.join("main.pdf"), "").unwrap();
+        fs::write(dir.join("main.typ"), "").unwrap();
+
+        let files: Vec<_> = walk(&dir).map(Result::unwrap).collect();
+        assert_eq!(files, [dir.join("main.typ")]);
+    }
+}
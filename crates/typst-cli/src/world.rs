@@ -7,8 +7,13 @@ use chrono::{DateTime, Datelike, FixedOffset, Local, Utc};
 use ecow::{EcoString, eco_format};
 use parking_lot::Mutex;
 use rustc_hash::FxHashMap;
-use typst::diag::{FileError, FileResult};
+use typst::diag::{
+    FileError, FileResult, Hint, HintedStrResult, PackageResult, StrResult,
+};
 use typst::foundations::{Bytes, Datetime, Dict, IntoValue};
+use typst::syntax::package::{
+    PackageLock, PackageSpec, PackageVersion, VersionlessPackageSpec,
+};
 use typst::syntax::{FileId, Lines, Source, VirtualPath};
 use typst::text::{Font, FontBook};
 use typst::utils::LazyHash;
@@ -20,6 +25,7 @@ use typst_timing::timed;
 use crate::args::{Feature, FontArgs, Input, ProcessArgs, WorldArgs};
 use crate::download::PrintDownload;
 use crate::package;
+use crate::walk::walk;
 
 /// Static `FileId` allocated for stdin.
 /// This is to ensure that a file is read in the correct way.
@@ -45,8 +51,12 @@ pub struct SystemWorld {
     fonts: LazyLock<Fonts, Box<dyn Fn() -> Fonts + Send + Sync>>,
     /// Maps file ids to source files and buffers.
     slots: Mutex<FxHashMap<FileId, FileSlot>>,
+    /// The Typst files in the project, listed on first use.
+    project_sources: OnceLock<Vec<FileId>>,
     /// Holds information about where packages are stored.
     package_storage: PackageStorage,
+    /// The lockfile as it was last read from or written to disk.
+    locked: PackageLock,
     /// The current datetime if requested. This is stored here to ensure it is
     /// always the same within one compilation.
     /// Reset between compilations if not [`Now::Fixed`].
@@ -135,6 +145,10 @@ impl SystemWorld {
             None => Now::System(OnceLock::new()),
         };
 
+        let package_storage = package::project_storage(&world_args.package, &root)
+            .map_err(WorldCreationError::Project)?;
+        let locked = package_storage.lock();
+
         Ok(Self {
             workdir: std::env::current_dir().ok(),
             root,
@@ -142,7 +156,9 @@ impl SystemWorld {
             library: LazyHash::new(library),
             fonts: LazyLock::new(Box::new(|| scan_fonts(&world_args.font))),
             slots: Mutex::new(FxHashMap::default()),
-            package_storage: package::storage(&world_args.package),
+            project_sources: OnceLock::new(),
+            package_storage,
+            locked,
             now,
         })
     }
@@ -152,6 +168,11 @@ impl SystemWorld {
         self.main
     }
 
+    /// Sets the main source file.
+    pub fn set_main(&mut self, id: FileId) {
+        self.main = id;
+    }
+
     /// The root relative to which absolute paths are resolved.
     pub fn root(&self) -> &Path {
         &self.root
@@ -162,6 +183,11 @@ impl SystemWorld {
         self.workdir.as_deref().unwrap_or(Path::new("."))
     }
 
+    /// Resolves the path of a file on the system.
+    pub fn path(&self, id: FileId) -> FileResult<PathBuf> {
+        system_path(&self.root, id, &self.package_storage)
+    }
+
     /// Return all paths the last compilation depended on.
     pub fn dependencies(&mut self) -> impl Iterator<Item = PathBuf> + '_ {
         self.slots
@@ -173,6 +199,30 @@ impl SystemWorld {
             })
     }
 
+    /// Return the ids of all files the last compilation depended on.
+    pub fn files(&mut self) -> impl Iterator<Item = FileId> + '_ {
+        self.slots
+            .get_mut()
+            .values()
+            .filter(|slot| slot.accessed())
+            .map(|slot| slot.id)
+    }
+
+    /// Return all packages the last compilation depended on.
+    pub fn packages(&mut self) -> Vec<PackageSpec> {
+        let mut packages: Vec<PackageSpec> =
+            self.files().filter_map(|id| id.package().cloned()).collect();
+        packages.sort_by_cached_key(ToString::to_string);
+        packages.dedup();
+        packages
+    }
+
+    /// Makes a package available on-disk and returns the path at which it is
+    /// located.
+    pub fn prepare_package(&self, spec: &PackageSpec) -> PackageResult<PathBuf> {
+        self.package_storage.prepare_package(spec, &mut PrintDownload(spec))
+    }
+
     /// Reset the compilation state in preparation of a new compilation.
     pub fn reset(&mut self) {
         #[allow(clippy::iter_over_hash_type, reason = "order does not matter")]
@@ -184,6 +234,28 @@ impl SystemWorld {
         }
     }
 
+    /// The Typst files in the project root, skipping hidden and ignored ones.
+    ///
+    /// The list is kept until [`forget_project_sources`] is called.
+    ///
+    /// [`forget_project_sources`]: Self::forget_project_sources
+    pub fn project_sources(&self) -> &[FileId] {
+        self.project_sources.get_or_init(|| {
+            walk(&self.root)
+                .flatten()
+                .filter(|path| path.extension().is_some_and(|ext| ext == "typ"))
+                .filter_map(|path| VirtualPath::within_root(&path, &self.root))
+                .map(|vpath| FileId::new(None, vpath))
+                .collect()
+        })
+    }
+
+    /// Forgets the listed project files because files may have been created
+    /// or deleted.
+    pub fn forget_project_sources(&mut self) {
+        self.project_sources.take();
+    }
+
     /// Lookup line metadata for a file by id.
     #[track_caller]
     pub fn lookup(&self, id: FileId) -> Lines<String> {
@@ -200,6 +272,36 @@ impl SystemWorld {
         })
     }
 
+    /// Replaces the contents of a file with in-memory text, or restores its
+    /// contents on disk if `text` is `None`.
+    ///
+    /// The change is picked up by the next compilation after a [`reset`].
+    ///
+    /// [`reset`]: Self::reset
+    pub fn set_overlay(&mut self, id: FileId, text: Option<String>) {
+        self.slots
+            .get_mut()
+            .entry(id)
+            .or_insert_with(|| FileSlot::new(id))
+            .overlay = text;
+    }
+
+    /// Writes the project's lockfile if packages imported without a version
+    /// were resolved to new versions.
+    ///
+    /// Does nothing if the resolved versions did not change since the lockfile
+    /// was last read or written, e.g. between compilations in watch mode.
+    pub fn write_lock(&mut self) -> StrResult<()> {
+        let lock = self.package_storage.lock();
+        if lock == self.locked {
+            return Ok(());
+        }
+
+        package::write_lock(&self.package_storage, &self.root)?;
+        self.locked = lock;
+        Ok(())
+    }
+
     /// Forcibly scan fonts instead of doing it lazily upon the first access.
     ///
     /// Does nothing if the fonts were already scanned.
@@ -257,6 +359,37 @@ impl World for SystemWorld {
             with_offset.day().try_into().ok()?,
         )
     }
+
+    fn package_version(
+        &self,
+        spec: &VersionlessPackageSpec,
+        within: Option<&PackageSpec>,
+    ) -> HintedStrResult<PackageVersion> {
+        let Some(package) = within else {
+            let result = self.package_storage.resolve_version(spec);
+            if self.package_storage.dependencies().contains_key(spec) {
+                return Ok(result?);
+            }
+            return result.hint(
+                "add the package to the `[dependencies]` of the project's `typst.toml`",
+            );
+        };
+
+        let id = FileId::new(Some(package.clone()), VirtualPath::new("typst.toml"));
+        let manifest = self.file(id).map_err(|err| {
+            eco_format!("failed to read the manifest of {package} ({err})")
+        })?;
+        let manifest = std::str::from_utf8(&manifest)
+            .map_err(|_| eco_format!("manifest of {package} is not valid UTF-8"))?;
+        let dependencies = package::dependencies(manifest).map_err(|err| {
+            eco_format!("failed to read the dependencies of {package} ({err})")
+        })?;
+        Ok(self.package_storage.resolve_dependency_version(
+            spec,
+            package,
+            &dependencies,
+        )?)
+    }
 }
 
 impl SystemWorld {
@@ -280,12 +413,19 @@ struct FileSlot {
     source: SlotCell<Source>,
     /// The lazily loaded raw byte buffer.
     file: SlotCell<Bytes>,
+    /// In-memory text that takes precedence over the file on disk.
+    overlay: Option<String>,
 }
 
 impl FileSlot {
     /// Create a new file slot.
     fn new(id: FileId) -> Self {
-        Self { id, file: SlotCell::new(), source: SlotCell::new() }
+        Self {
+            id,
+            file: SlotCell::new(),
+            source: SlotCell::new(),
+            overlay: None,
+        }
     }
 
     /// Whether the file was accessed in the ongoing compilation.
@@ -307,7 +447,7 @@ impl FileSlot {
         package_storage: &PackageStorage,
     ) -> FileResult<Source> {
         self.source.get_or_init(
-            || read(self.id, project_root, package_storage),
+            || load(self.id, self.overlay.as_deref(), project_root, package_storage),
             |data, prev| {
                 let text = decode_utf8(&data)?;
                 if let Some(mut prev) = prev {
@@ -327,7 +467,7 @@ impl FileSlot {
         package_storage: &PackageStorage,
     ) -> FileResult<Bytes> {
         self.file.get_or_init(
-            || read(self.id, project_root, package_storage),
+            || load(self.id, self.overlay.as_deref(), project_root, package_storage),
             |data, _| Ok(Bytes::new(data)),
         )
     }
@@ -428,6 +568,19 @@ fn system_path(
     id.vpath().resolve(root).ok_or(FileError::AccessDenied)
 }
 
+/// Loads a file's contents, preferring in-memory text over the file itself.
+fn load(
+    id: FileId,
+    overlay: Option<&str>,
+    project_root: &Path,
+    package_storage: &PackageStorage,
+) -> FileResult<Vec<u8>> {
+    match overlay {
+        Some(text) => Ok(text.as_bytes().to_vec()),
+        None => read(id, project_root, package_storage),
+    }
+}
+
 /// Reads a file from a `FileId`.
 ///
 /// - If the ID represents stdin it will read from standard input.
@@ -491,6 +644,8 @@ pub enum WorldCreationError {
     InputOutsideRoot,
     /// The root directory does not appear to exist.
     RootNotFound(PathBuf),
+    /// The project's manifest or lockfile could not be loaded.
+    Project(EcoString),
     /// Another type of I/O error.
     Io(io::Error),
 }
@@ -507,6 +662,7 @@ impl fmt::Display for WorldCreationError {
             WorldCreationError::RootNotFound(path) => {
                 write!(f, "root directory not found (searched at {})", path.display())
             }
+            WorldCreationError::Project(err) => write!(f, "{err}"),
             WorldCreationError::Io(err) => write!(f, "{err}"),
         }
     }
@@ -517,3 +673,33 @@ impl From<WorldCreationError> for EcoString {
         eco_format!("{err}")
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::LazyLock;
+
+    use clap::Parser;
+
+    use super::SystemWorld;
+    use crate::args::{Input, LspCommand};
+
+    /// Creates a world for a temporary project whose main file has the given
+    /// source. Returns the world and the path to the main file.
+    pub fn world(name: &str, source: &str) -> (SystemWorld, PathBuf) {
+        // Worlds borrow their arguments statically, so the default arguments
+        // are parsed once for all tests.
+        static ARGS: LazyLock<LspCommand> =
+            LazyLock::new(|| LspCommand::try_parse_from(["lsp"]).unwrap());
+
+        let dir = std::env::temp_dir().join(format!("typst-{name}"));
+        fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("main.typ");
+        fs::write(&main, source).unwrap();
+
+        let input = Input::Path(main.clone());
+        let world = SystemWorld::new(Some(&input), &ARGS.world, &ARGS.process).unwrap();
+        (world, main)
+    }
+}
@@ -2,12 +2,12 @@ use comemo::TrackedMut;
 use ecow::{EcoString, eco_format, eco_vec};
 use typst_library::World;
 use typst_library::diag::{
-    At, FileError, SourceResult, Trace, Tracepoint, bail, error, warning,
+    At, FileError, HintedStrResult, SourceResult, Trace, Tracepoint, bail, error, warning,
 };
 use typst_library::engine::Engine;
 use typst_library::foundations::{Binding, Content, Module, Value};
 use typst_syntax::ast::{self, AstNode, BareImportError};
-use typst_syntax::package::{PackageManifest, PackageSpec};
+use typst_syntax::package::{PackageManifest, PackageSpec, VersionlessPackageSpec};
 use typst_syntax::{FileId, Span, VirtualPath};
 
 use crate::{Eval, Vm, eval};
@@ -184,7 +184,7 @@ impl Eval for ast::ModuleInclude<'_> {
 /// Process an import of a package or file relative to the current location.
 pub fn import(engine: &mut Engine, from: &str, span: Span) -> SourceResult<Module> {
     if from.starts_with('@') {
-        let spec = from.parse::<PackageSpec>().at(span)?;
+        let spec = resolve_spec(engine, from, span).at(span)?;
         import_package(engine, spec, span)
     } else {
         let id = span.resolve_path(from).at(span)?;
@@ -192,6 +192,24 @@ pub fn import(engine: &mut Engine, from: &str, span: Span) -> SourceResult<Modul
     }
 }
 
+/// Parse a package specification, filling in the version from the world if it
+/// was omitted. An import within a package is resolved against that package's
+/// dependencies.
+fn resolve_spec(engine: &Engine, from: &str, span: Span) -> HintedStrResult<PackageSpec> {
+    let err = match from.parse::<PackageSpec>() {
+        Ok(spec) => return Ok(spec),
+        Err(err) => err,
+    };
+
+    let Ok(versionless) = from.parse::<VersionlessPackageSpec>() else {
+        bail!("{err}");
+    };
+
+    let within = span.id().and_then(|id| id.package());
+    let version = engine.world.package_version(&versionless, within)?;
+    Ok(versionless.at(version))
+}
+
 /// Import a file from a path. The path is resolved relative to the given
 /// `span`.
 fn import_file(engine: &mut Engine, id: FileId, span: Span) -> SourceResult<Module> {
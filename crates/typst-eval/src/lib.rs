@@ -8,6 +8,7 @@ mod call;
 mod code;
 mod flow;
 mod import;
+mod lint;
 mod markup;
 mod math;
 mod methods;
@@ -17,6 +18,7 @@ mod vm;
 pub use self::call::{CapturesVisitor, eval_closure};
 pub use self::flow::FlowEvent;
 pub use self::import::import;
+pub use self::lint::lint;
 pub use self::vm::{Vm, hint_if_shadowed_std};
 
 use self::access::*;
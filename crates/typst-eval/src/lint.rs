@@ -0,0 +1,571 @@
+use ecow::{EcoString, EcoVec, eco_format};
+use rustc_hash::{FxHashMap, FxHashSet};
+use typst_library::diag::SourceDiagnostic;
+use typst_library::foundations::{Binding, Scopes, Value};
+use typst_syntax::ast::{self, AstNode};
+use typst_syntax::{Source, Span, SyntaxKind, SyntaxNode};
+
+/// Checks source files for likely mistakes that don't cause errors.
+///
+/// Returns warnings for
+/// - `let` bindings in blocks and functions that are never used,
+/// - imports in blocks and functions that are never used,
+/// - parameters that shadow a variable of an enclosing scope,
+/// - `set` rules without effect because no content follows them,
+/// - code after a `return`, `break`, or `continue`,
+/// - labels that are never referenced in any of the files.
+///
+/// Top-level `let` bindings and imports are not reported since they may be
+/// imported by other files.
+///
+/// A warning is suppressed by a `// lint: allow(name)` comment in the same or
+/// the preceding line, where `name` is the lint's name given in the warning's
+/// hint. Multiple names are separated by commas.
+pub fn lint(sources: &[Source]) -> EcoVec<SourceDiagnostic> {
+    let mut referenced = FxHashSet::default();
+    for source in sources {
+        collect_references(source.root(), &mut referenced);
+    }
+
+    let mut diags = EcoVec::new();
+    for source in sources {
+        let mut found = vec![];
+
+        let mut linter = Linter::new(&mut found);
+        linter.visit(source.root());
+        linter.finish();
+
+        check_sequences(source.root(), &mut found);
+        check_labels(source.root(), SyntaxKind::End, &referenced, &mut found);
+
+        let allowed = suppressions(source);
+        let line = |span: Span| {
+            let range = source.range(span)?;
+            source.lines().byte_to_line(range.start)
+        };
+
+        found.retain(|(lint, diag)| {
+            line(diag.span).is_none_or(|line| !allowed.contains(&(line, *lint)))
+        });
+        found.sort_by_key(|(_, diag)| source.range(diag.span).map(|range| range.start));
+
+        diags.extend(found.into_iter().map(|(lint, diag)| {
+            diag.with_hint(eco_format!(
+                "use `// lint: allow({})` to suppress this warning",
+                lint.name(),
+            ))
+        }));
+    }
+
+    diags
+}
+
+/// A kind of mistake that the linter reports.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Lint {
+    UnusedBinding,
+    UnusedImport,
+    ShadowedParam,
+    UselessSet,
+    Unreachable,
+    UnreferencedLabel,
+}
+
+impl Lint {
+    /// All lints.
+    const ALL: [Self; 6] = [
+        Self::UnusedBinding,
+        Self::UnusedImport,
+        Self::ShadowedParam,
+        Self::UselessSet,
+        Self::Unreachable,
+        Self::UnreferencedLabel,
+    ];
+
+    /// The name of the lint in suppression comments.
+    fn name(self) -> &'static str {
+        match self {
+            Self::UnusedBinding => "unused-binding",
+            Self::UnusedImport => "unused-import",
+            Self::ShadowedParam => "shadowed-param",
+            Self::UselessSet => "useless-set",
+            Self::Unreachable => "unreachable",
+            Self::UnreferencedLabel => "unreferenced-label",
+        }
+    }
+}
+
+/// Tracks bindings through scopes to find unused and shadowing ones.
+///
+/// Traverses the syntax tree just like the
+/// [`CapturesVisitor`](crate::CapturesVisitor).
+struct Linter<'a> {
+    scopes: Scopes<'static>,
+    /// The bindings that should be reported if unused, with the lint that
+    /// reports them.
+    checked: FxHashMap<Span, Lint>,
+    /// The definition sites of all bindings that were used.
+    used: FxHashSet<Span>,
+    found: &'a mut Vec<(Lint, SourceDiagnostic)>,
+}
+
+impl<'a> Linter<'a> {
+    /// Create a new linter that reports into `found`.
+    fn new(found: &'a mut Vec<(Lint, SourceDiagnostic)>) -> Self {
+        Self {
+            scopes: Scopes::new(None),
+            checked: FxHashMap::default(),
+            used: FxHashSet::default(),
+            found,
+        }
+    }
+
+    /// Report the unused bindings of the top-level scope.
+    fn finish(mut self) {
+        let top = std::mem::take(&mut self.scopes.top);
+        for (name, binding) in top.iter() {
+            self.check_unused(name, binding.span());
+        }
+    }
+
+    /// Visit any node.
+    fn visit(&mut self, node: &SyntaxNode) {
+        match node.cast() {
+            Some(ast::Expr::Ident(ident)) => self.use_var(ident.get()),
+            Some(ast::Expr::MathIdent(ident)) => self.use_var(ident.get()),
+
+            // Code and content blocks create a scope.
+            Some(ast::Expr::CodeBlock(_) | ast::Expr::ContentBlock(_)) => {
+                self.scopes.enter();
+                for child in node.children() {
+                    self.visit(child);
+                }
+                self.exit();
+            }
+
+            // The field of a field access is not a variable.
+            Some(ast::Expr::FieldAccess(access)) => {
+                self.visit(access.target().to_untyped());
+            }
+
+            // Parameters are bound in a new scope, after the default values of
+            // named parameters are evaluated.
+            Some(ast::Expr::Closure(expr)) => {
+                for param in expr.params().children() {
+                    if let ast::Param::Named(named) = param {
+                        self.visit(named.expr().to_untyped());
+                    }
+                }
+
+                self.scopes.enter();
+                if let Some(name) = expr.name() {
+                    self.bind(name.get().clone(), name.span());
+                }
+
+                for param in expr.params().children() {
+                    match param {
+                        ast::Param::Pos(pattern) => {
+                            for ident in pattern.bindings() {
+                                self.bind_param(ident);
+                            }
+                        }
+                        ast::Param::Named(named) => self.bind_param(named.name()),
+                        ast::Param::Spread(spread) => {
+                            if let Some(ident) = spread.sink_ident() {
+                                self.bind_param(ident);
+                            }
+                        }
+                    }
+                }
+
+                self.visit(expr.body().to_untyped());
+                self.exit();
+            }
+
+            // A let binding is only active after its initializer.
+            Some(ast::Expr::LetBinding(expr)) => {
+                if let Some(init) = expr.init() {
+                    self.visit(init.to_untyped());
+                }
+
+                // Top-level bindings are exported from the module.
+                let top_level = self.scopes.scopes.is_empty();
+                for ident in expr.kind().bindings() {
+                    self.bind(ident.get().clone(), ident.span());
+                    if !top_level {
+                        self.checked.insert(ident.span(), Lint::UnusedBinding);
+                    }
+                }
+            }
+
+            // The loop variables are active in the body only.
+            Some(ast::Expr::ForLoop(expr)) => {
+                self.visit(expr.iterable().to_untyped());
+                self.scopes.enter();
+                for ident in expr.pattern().bindings() {
+                    self.bind(ident.get().clone(), ident.span());
+                }
+                self.visit(expr.body().to_untyped());
+                self.exit();
+            }
+
+            // Imported items are only active after the source is evaluated.
+            Some(ast::Expr::ModuleImport(expr)) => {
+                let source = expr.source();
+                self.visit(source.to_untyped());
+
+                if let Some(new_name) = expr.new_name() {
+                    self.bind_import(new_name.get().clone(), new_name.span());
+                }
+
+                match expr.imports() {
+                    None if expr.new_name().is_none() => {
+                        if let ast::Expr::Str(_) = source
+                            && let Ok(name) = expr.bare_name()
+                        {
+                            self.bind_import(name, source.span());
+                        }
+                    }
+                    Some(ast::Imports::Items(items)) => {
+                        for item in items.iter() {
+                            let name = item.bound_name();
+                            self.bind_import(name.get().clone(), name.span());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            _ => {
+                // The name of a named pair is not a variable.
+                if let Some(named) = node.cast::<ast::Named>() {
+                    self.visit(named.expr().to_untyped());
+                    return;
+                }
+
+                for child in node.children() {
+                    self.visit(child);
+                }
+            }
+        }
+    }
+
+    /// Mark the binding a variable refers to as used.
+    fn use_var(&mut self, name: &str) {
+        if let Ok(binding) = self.scopes.get(name) {
+            self.used.insert(binding.span());
+        }
+    }
+
+    /// Bind a variable in the current scope.
+    fn bind(&mut self, name: EcoString, span: Span) {
+        // A binding that is replaced in the same scope can't be used anymore.
+        if let Some(prev) = self.scopes.top.get(&name).map(Binding::span) {
+            self.check_unused(&name, prev);
+        }
+
+        // The value does not matter as we only use the scoping mechanism of
+        // `Scopes`, not the values themselves.
+        self.scopes.top.bind(name, Binding::new(Value::None, span));
+    }
+
+    /// Bind an imported item.
+    fn bind_import(&mut self, name: EcoString, span: Span) {
+        // Top-level imports are re-exported from the module.
+        let top_level = self.scopes.scopes.is_empty();
+        self.bind(name, span);
+        if !top_level {
+            self.checked.insert(span, Lint::UnusedImport);
+        }
+    }
+
+    /// Bind a parameter and check that it doesn't shadow another variable.
+    fn bind_param(&mut self, ident: ast::Ident) {
+        if let Ok(binding) = self.scopes.get(ident.get()) {
+            let diag = SourceDiagnostic::warning(
+                ident.span(),
+                eco_format!("parameter `{}` shadows an existing variable", ident.get()),
+            )
+            .with_spanned_hint("the shadowed variable is defined here", binding.span())
+            .with_hint("rename the parameter to keep the variable accessible");
+            self.found.push((Lint::ShadowedParam, diag));
+        }
+        self.bind(ident.get().clone(), ident.span());
+    }
+
+    /// Exit the current scope and report its unused bindings.
+    fn exit(&mut self) {
+        let top = std::mem::take(&mut self.scopes.top);
+        self.scopes.exit();
+        for (name, binding) in top.iter() {
+            self.check_unused(name, binding.span());
+        }
+    }
+
+    /// Report a binding if it should be used but isn't.
+    fn check_unused(&mut self, name: &EcoString, span: Span) {
+        let Some(lint) = self.checked.remove(&span) else { return };
+        if self.used.contains(&span) || name.starts_with('_') {
+            return;
+        }
+
+        let diag = match lint {
+            Lint::UnusedImport => {
+                SourceDiagnostic::warning(span, eco_format!("unused import `{name}`"))
+                    .with_hint("remove the import if it is not needed")
+            }
+            _ => SourceDiagnostic::warning(span, eco_format!("unused variable `{name}`"))
+                .with_hint(eco_format!(
+                    "if this is intentional, prefix the name with an underscore: `_{name}`"
+                )),
+        };
+        self.found.push((lint, diag));
+    }
+}
+
+/// Report `set` rules without effect and unreachable code in the sequences of
+/// markup and code.
+fn check_sequences(node: &SyntaxNode, found: &mut Vec<(Lint, SourceDiagnostic)>) {
+    if matches!(node.kind(), SyntaxKind::Markup | SyntaxKind::Code) {
+        let children = node.children().as_slice();
+
+        // Set rules only apply to the content that follows them in the same
+        // sequence, which doesn't include bindings and other rules.
+        for child in children.iter().rev().take_while(|child| {
+            child.kind().is_trivia()
+                || matches!(
+                    child.kind(),
+                    SyntaxKind::Semicolon
+                        | SyntaxKind::Hash
+                        | SyntaxKind::LetBinding
+                        | SyntaxKind::ModuleImport
+                        | SyntaxKind::SetRule
+                        | SyntaxKind::ShowRule
+                )
+        }) {
+            if child.kind() == SyntaxKind::SetRule {
+                let diag =
+                    SourceDiagnostic::warning(child.span(), "set rule has no effect")
+                        .with_hint(
+                            "set rules only apply to the content that follows them \
+                         in the same block",
+                        );
+                found.push((Lint::UselessSet, diag));
+            }
+        }
+
+        // Nothing after a jump is ever evaluated.
+        let jump = children.iter().position(|child| {
+            matches!(
+                child.kind(),
+                SyntaxKind::FuncReturn | SyntaxKind::LoopBreak | SyntaxKind::LoopContinue
+            )
+        });
+        if let Some(i) = jump
+            && let Some(next) = children[i + 1..].iter().find(|child| {
+                !child.kind().is_trivia()
+                    && !matches!(child.kind(), SyntaxKind::Semicolon | SyntaxKind::Hash)
+            })
+        {
+            let keyword = match children[i].kind() {
+                SyntaxKind::FuncReturn => "return",
+                SyntaxKind::LoopBreak => "break",
+                _ => "continue",
+            };
+            let diag = SourceDiagnostic::warning(next.span(), "unreachable code")
+                .with_spanned_hint(
+                    eco_format!("any code after this `{keyword}` is never evaluated"),
+                    children[i].span(),
+                );
+            found.push((Lint::Unreachable, diag));
+        }
+    }
+
+    for child in node.children() {
+        check_sequences(child, found);
+    }
+}
+
+/// Collect the names of all labels that are referenced by a reference, in
+/// code, or through a `label` call.
+fn collect_references(node: &SyntaxNode, referenced: &mut FxHashSet<EcoString>) {
+    if let Some(reference) = node.cast::<ast::Ref>() {
+        referenced.insert(reference.target().into());
+    } else if let Some(call) = node.cast::<ast::FuncCall>()
+        && let ast::Expr::Ident(callee) = call.callee()
+        && callee.as_str() == "label"
+        && let Some(ast::Arg::Pos(ast::Expr::Str(string))) = call.args().items().next()
+    {
+        referenced.insert(string.get());
+    }
+
+    for child in node.children() {
+        // Labels in markup are attached to content, while labels in code
+        // refer to it.
+        if let Some(label) = child.cast::<ast::Label>()
+            && node.kind() != SyntaxKind::Markup
+        {
+            referenced.insert(label.get().into());
+        }
+        collect_references(child, referenced);
+    }
+}
+
+/// Report labels in markup that are never referenced.
+fn check_labels(
+    node: &SyntaxNode,
+    parent: SyntaxKind,
+    referenced: &FxHashSet<EcoString>,
+    found: &mut Vec<(Lint, SourceDiagnostic)>,
+) {
+    if let Some(label) = node.cast::<ast::Label>()
+        && parent == SyntaxKind::Markup
+        && !referenced.contains(label.get())
+    {
+        let diag = SourceDiagnostic::warning(
+            node.span(),
+            eco_format!("label `<{}>` is never referenced", label.get()),
+        )
+        .with_hint("remove the label if it is not needed");
+        found.push((Lint::UnreferencedLabel, diag));
+    }
+
+    for child in node.children() {
+        check_labels(child, node.kind(), referenced, found);
+    }
+}
+
+/// Collect the lines and lints that are suppressed by `// lint: allow(..)`
+/// comments. A comment applies to its own line and the next one.
+fn suppressions(source: &Source) -> FxHashSet<(usize, Lint)> {
+    let mut allowed = FxHashSet::default();
+    let mut stack = vec![source.root()];
+    while let Some(node) = stack.pop() {
+        stack.extend(node.children());
+        if node.kind() != SyntaxKind::LineComment {
+            continue;
+        }
+
+        let Some(names) = node
+            .text()
+            .trim_start_matches('/')
+            .trim()
+            .strip_prefix("lint:")
+            .and_then(|rest| rest.trim().strip_prefix("allow("))
+            .and_then(|rest| rest.trim_end().strip_suffix(')'))
+        else {
+            continue;
+        };
+
+        let Some(line) = source
+            .range(node.span())
+            .and_then(|range| source.lines().byte_to_line(range.start))
+        else {
+            continue;
+        };
+
+        for name in names.split(',') {
+            if let Some(lint) =
+                Lint::ALL.into_iter().find(|lint| lint.name() == name.trim())
+            {
+                allowed.insert((line, lint));
+                allowed.insert((line + 1, lint));
+            }
+        }
+    }
+    allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use typst_syntax::{FileId, VirtualPath};
+
+    use super::*;
+
+    #[track_caller]
+    fn test(text: &str, expected: &[(&str, &str)]) {
+        let source =
+            Source::new(FileId::new(None, VirtualPath::new("main.typ")), text.into());
+        let diags = lint(std::slice::from_ref(&source));
+        let found: Vec<_> = diags
+            .iter()
+            .map(|diag| {
+                let range = source.range(diag.span).unwrap();
+                (&text[range], diag.message.as_str())
+            })
+            .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_lint_unused_binding() {
+        test("#let x = 1", &[]);
+        test("#{ let x = 1 }", &[("x", "unused variable `x`")]);
+        test("#{ let x = 1; x }", &[]);
+        test("#{ let _x = 1 }", &[]);
+        test("#{ let (a, b) = (1, 2); a }", &[("b", "unused variable `b`")]);
+        test("#{ let x = 1; let x = 2; x }", &[("x", "unused variable `x`")]);
+        test("#{ let x = 1; let x = x + 1; x }", &[]);
+        test("#let f() = { let y = 1; () => y }", &[]);
+    }
+
+    #[test]
+    fn test_lint_unused_import() {
+        test("#{ import \"a.typ\": x, y as z; x }", &[("z", "unused import `z`")]);
+        test("#{ import \"a.typ\" }", &[("\"a.typ\"", "unused import `a`")]);
+        test("#{ import \"a.typ\" as b; b.c }", &[]);
+        test("#{ import \"a.typ\": * }", &[]);
+        test("#let f() = { import \"a.typ\": x; 1 }", &[("x", "unused import `x`")]);
+
+        // Top-level imports are re-exported.
+        test("#import \"a.typ\": x, y as z", &[]);
+        test("#import \"a.typ\"", &[]);
+        test("#import \"a.typ\" as b", &[]);
+    }
+
+    #[test]
+    fn test_lint_shadowed_param() {
+        test(
+            "#let x = 1\n#let f(x) = x\n#f(x)",
+            &[("x", "parameter `x` shadows an existing variable")],
+        );
+        test("#let f(x) = x\n#let g(x) = x", &[]);
+        test("#let f(x) = y => x + y", &[]);
+    }
+
+    #[test]
+    fn test_lint_useless_set() {
+        test("#set text(red)\nHello", &[]);
+        test("#[#set text(red)]", &[("set text(red)", "set rule has no effect")]);
+        test(
+            "#if true { set text(red) }",
+            &[("set text(red)", "set rule has no effect")],
+        );
+        test("#let f(body) = { set text(red); body }", &[]);
+    }
+
+    #[test]
+    fn test_lint_unreachable() {
+        test("#let f() = { return 1; 2 }", &[("2", "unreachable code")]);
+        test("#for x in () { break\n x }", &[("x", "unreachable code")]);
+        test("#let f() = { if true { return 1 }; 2 }", &[]);
+    }
+
+    #[test]
+    fn test_lint_unreferenced_label() {
+        test("= A <a>\n@a", &[]);
+        test("= A <a>\n#ref(<a>)", &[]);
+        test("= A <a>\n#label(\"a\")", &[]);
+        test("= A <a>", &[("<a>", "label `<a>` is never referenced")]);
+    }
+
+    #[test]
+    fn test_lint_suppression() {
+        test("#{\n  // lint: allow(unused-binding)\n  let x = 1\n}", &[]);
+        test("#{ let x = 1 } // lint: allow(unused-binding, unreachable)", &[]);
+        test(
+            "#{\n  // lint: allow(unreachable)\n  let x = 1\n}",
+            &[("x", "unused variable `x`")],
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Emission of images and frames as separate files.
+
+use std::collections::BTreeMap;
+
+use ecow::{EcoString, eco_format};
+use typst_library::foundations::Bytes;
+use typst_library::layout::{Frame, FrameItem};
+use typst_library::visualize::Image;
+
+use crate::HtmlFrame;
+
+/// Files referenced by exported HTML, keyed by their path relative to the
+/// HTML file.
+pub type HtmlAssets = BTreeMap<EcoString, Bytes>;
+
+/// The directory, relative to the HTML file, into which assets are written.
+const DIR: &str = "assets";
+
+/// Adds an asset and returns its path.
+///
+/// The path is derived from the data's hash, so that identical assets are
+/// only stored once and that browsers can cache them indefinitely.
+pub fn add(assets: &mut HtmlAssets, data: Bytes, extension: &str) -> EcoString {
+    let hash = typst_utils::hash128(data.as_slice());
+    let path = eco_format!("{DIR}/{hash:032x}.{extension}");
+    assets.entry(path.clone()).or_insert(data);
+    path
+}
+
+/// Adds an image as an asset and returns its path.
+pub fn add_image(assets: &mut HtmlAssets, image: &Image) -> EcoString {
+    let (format, data) = typst_svg::convert_image_to_bytes(image);
+    let extension = match format {
+        "jpeg" => "jpg",
+        "svg+xml" => "svg",
+        _ => format,
+    };
+    add(assets, data, extension)
+}
+
+/// Whether a frame can be emitted as an image file instead of being inlined.
+///
+/// That is not the case if something links to it or it contains links, as
+/// those would stop working.
+pub fn is_external(frame: &HtmlFrame) -> bool {
+    frame.id.is_none() && frame.link_points.is_empty() && !has_links(&frame.inner)
+}
+
+/// Whether a frame contains links.
+fn has_links(frame: &Frame) -> bool {
+    frame.items().any(|(_, item)| match item {
+        FrameItem::Link(..) => true,
+        FrameItem::Group(group) => has_links(&group.frame),
+        _ => false,
+    })
+}
+
+/// Extracts the text of a frame, which describes it when it is embedded as
+/// an image.
+pub fn text(frame: &Frame) -> EcoString {
+    let mut text = EcoString::new();
+    collect_text(frame, &mut text);
+    text
+}
+
+/// Appends the text in a frame, separating runs with spaces.
+fn collect_text(frame: &Frame, buf: &mut EcoString) {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => collect_text(&group.frame, buf),
+            FrameItem::Text(run) => {
+                let run = run.text.trim();
+                if run.is_empty() {
+                    continue;
+                }
+                if !buf.is_empty() {
+                    buf.push(' ');
+                }
+                buf.push_str(run);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ecow::eco_vec;
+    use typst_library::foundations::StyleChain;
+    use typst_library::introspection::Introspector;
+    use typst_library::layout::{Abs, Size};
+    use typst_library::visualize::SvgImage;
+    use typst_syntax::Span;
+
+    use super::*;
+    use crate::{HtmlElement, HtmlNode, attr, tag};
+
+    const SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4"><rect width="4" height="4"/></svg>"#;
+
+    fn encode(body: HtmlNode, assets: Option<&mut HtmlAssets>) -> String {
+        let body = HtmlElement::new(tag::body).with_children(eco_vec![body]);
+        let root = HtmlElement::new(tag::html).with_children(eco_vec![body.into()]);
+        crate::encode::encode(&root, &Introspector::default(), assets, false).unwrap()
+    }
+
+    fn image() -> HtmlNode {
+        let svg = SvgImage::new(Bytes::from_string(SVG)).unwrap();
+        let mut img = HtmlElement::new(tag::img).with_attr(attr::alt, "A square");
+        img.image = Some(Image::plain(svg));
+        img.into()
+    }
+
+    fn frame(alt: Option<&str>) -> HtmlNode {
+        let inner = Frame::soft(Size::new(Abs::pt(22.0), Abs::pt(11.0)));
+        let mut frame = HtmlFrame::new(inner, StyleChain::default(), Span::detached());
+        frame.alt = alt.map(Into::into);
+        frame.into()
+    }
+
+    #[test]
+    fn test_image_asset() {
+        let mut assets = HtmlAssets::new();
+        let html = encode(image(), Some(&mut assets));
+
+        let (path, data) = assets.iter().next().unwrap();
+        assert_eq!(assets.len(), 1);
+        assert!(path.starts_with("assets/") && path.ends_with(".svg"));
+        assert_eq!(data.as_slice(), SVG.as_bytes());
+        assert!(html.contains(&format!(r#"<img src="{path}" alt="A square">"#)));
+
+        // Identical images are only stored once.
+        encode(image(), Some(&mut assets));
+        assert_eq!(assets.len(), 1);
+    }
+
+    #[test]
+    fn test_image_data_url() {
+        let html = encode(image(), None);
+        assert!(html.contains(r#"<img src="data:image/svg+xml;base64,"#));
+        assert!(html.contains(r#"alt="A square">"#));
+    }
+
+    #[test]
+    fn test_frame_asset() {
+        let mut assets = HtmlAssets::new();
+        let html = encode(frame(Some("A plot")), Some(&mut assets));
+        let path = assets.keys().next().unwrap();
+        assert!(html.contains(&format!(
+            r#"<img class="typst-frame" src="{path}" alt="A plot" style="width: 2em; height: 1em;">"#
+        )));
+
+        // Without a description or text, the frame is decorative.
+        let html = encode(frame(None), Some(&mut assets));
+        assert!(html.contains(r#" alt style="#));
+        assert_eq!(assets.len(), 1);
+    }
+}
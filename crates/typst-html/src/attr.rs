@@ -193,3 +193,14 @@ pub const value: HtmlAttr = HtmlAttr::constant("value");
 pub const width: HtmlAttr = HtmlAttr::constant("width");
 pub const wrap: HtmlAttr = HtmlAttr::constant("wrap");
 pub const writingsuggestions: HtmlAttr = HtmlAttr::constant("writingsuggestions");
+
+// MathML Core § 2.1.3 Global attributes and § 3 Presentation markup
+
+pub const accent: HtmlAttr = HtmlAttr::constant("accent");
+pub const accentunder: HtmlAttr = HtmlAttr::constant("accentunder");
+pub const alttext: HtmlAttr = HtmlAttr::constant("alttext");
+pub const display: HtmlAttr = HtmlAttr::constant("display");
+pub const linethickness: HtmlAttr = HtmlAttr::constant("linethickness");
+pub const mathvariant: HtmlAttr = HtmlAttr::constant("mathvariant");
+pub const movablelimits: HtmlAttr = HtmlAttr::constant("movablelimits");
+pub const stretchy: HtmlAttr = HtmlAttr::constant("stretchy");
@@ -12,6 +12,7 @@ use typst_library::text::{
 use typst_syntax::Span;
 
 use crate::fragment::{html_block_fragment, html_inline_fragment};
+use crate::stylesheet::SheetStyles;
 use crate::{FrameElem, HtmlElem, HtmlElement, HtmlFrame, HtmlNode, attr, css, tag};
 
 /// What and how to convert.
@@ -135,7 +136,9 @@ fn handle(
             styles.chain(&style),
             Region::new(Size::splat(Abs::inf()), Axes::splat(false)),
         )?;
-        converter.push(HtmlFrame::new(frame, styles, elem.span()));
+        let mut frame = HtmlFrame::new(frame, styles, elem.span());
+        frame.alt = elem.alt.get_cloned(styles);
+        converter.push(frame);
     } else {
         converter.engine.sink.warn(warning!(
             child.span(),
@@ -178,7 +181,16 @@ fn handle_html_elem(
             styles
         };
 
-        if tag::is_block_by_default(elem.tag) {
+        if elem.tag == tag::math {
+            // The body of a `<math>` element is Typst math, which is converted
+            // into MathML instead of HTML.
+            children = crate::math::convert_math(
+                converter.engine,
+                converter.locator,
+                body,
+                styles,
+            )?;
+        } else if tag::is_block_by_default(elem.tag) {
             children = html_block_fragment(
                 converter.engine,
                 body,
@@ -209,6 +221,17 @@ fn handle_html_elem(
         attrs.push(attr::role, role);
     }
 
+    let mut sheet = SheetStyles::new(styles);
+    if elem.tag == tag::p {
+        let follows_par = converter.output.iter().rev().find_map(|node| match node {
+            HtmlNode::Tag(_) => None,
+            HtmlNode::Element(element) => Some(element.tag == tag::p),
+            _ => Some(false),
+        });
+        sheet = sheet.with_par(styles, follows_par.unwrap_or(false));
+    }
+    sheet = sheet.with_block(elem.block_styles.get_cloned(styles));
+
     converter.push(HtmlElement {
         tag: elem.tag,
         attrs,
@@ -216,6 +239,8 @@ fn handle_html_elem(
         parent: elem.parent,
         span: elem.span(),
         pre_span: false,
+        image: elem.image.get_cloned(styles),
+        sheet,
     });
 
     Ok(())
@@ -16,6 +16,7 @@ use typst_utils::Protected;
 
 use crate::convert::{ConversionLevel, Whitespace};
 use crate::rules::FootnoteContainer;
+use crate::stylesheet::SheetStyles;
 use crate::{HtmlDocument, HtmlElem, HtmlElement, HtmlNode, HtmlSliceExt, attr, tag};
 
 /// Produce an HTML document from content.
@@ -232,6 +233,8 @@ fn finalize_dom(
 
     let body = if needs_body {
         let mut body = HtmlElement::new(tag::body).with_children(output);
+        // The footnote styles are the ones shared by the whole document.
+        body.sheet = SheetStyles::new(footnote_styles);
         let footnotes = crate::fragment::html_block_fragment(
             engine,
             FootnoteContainer::shared(),
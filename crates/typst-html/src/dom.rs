@@ -7,9 +7,11 @@ use typst_library::introspection::{Introspector, Location, Tag};
 use typst_library::layout::{Abs, Frame, Point};
 use typst_library::model::DocumentInfo;
 use typst_library::text::TextElem;
+use typst_library::visualize::Image;
 use typst_syntax::Span;
 use typst_utils::{PicoStr, ResolvedPicoStr};
 
+use crate::stylesheet::SheetStyles;
 use crate::{attr, charsets, css};
 
 /// An HTML document.
@@ -128,6 +130,13 @@ pub struct HtmlElement {
     /// the `white-space` CSS property is enough), it ensures that formatters
     /// won't mess up the output.
     pub pre_span: bool,
+    /// The image displayed by an `<img>` element created from an
+    /// [`ImageElem`](typst_library::visualize::ImageElem). Its `src`
+    /// attribute is only determined during encoding.
+    pub(crate) image: Option<Image>,
+    /// The styles the element was created with, as far as a generated
+    /// stylesheet reflects them.
+    pub(crate) sheet: SheetStyles,
 }
 
 impl HtmlElement {
@@ -140,6 +149,8 @@ impl HtmlElement {
             parent: None,
             span: Span::detached(),
             pre_span: false,
+            image: None,
+            sheet: SheetStyles::default(),
         }
     }
 
@@ -419,6 +430,9 @@ pub struct HtmlFrame {
     pub link_points: EcoVec<(Point, EcoString)>,
     /// The span from which the frame originated.
     pub span: Span,
+    /// An alternative description, used if the frame is embedded as an
+    /// image.
+    pub(crate) alt: Option<EcoString>,
 }
 
 impl HtmlFrame {
@@ -430,6 +444,7 @@ impl HtmlFrame {
             id: None,
             link_points: EcoVec::new(),
             span,
+            alt: None,
         }
     }
 }
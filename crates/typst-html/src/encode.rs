@@ -1,21 +1,88 @@
+use std::borrow::Cow;
 use std::fmt::Write;
 
 use ecow::{EcoString, eco_format};
 use typst_library::diag::{At, SourceResult, StrResult, bail};
-use typst_library::foundations::Repr;
+use typst_library::foundations::{Bytes, Repr};
 use typst_library::introspection::Introspector;
 use typst_syntax::Span;
 
+use crate::asset::{self, HtmlAssets};
 use crate::{
-    HtmlDocument, HtmlElement, HtmlFrame, HtmlNode, HtmlTag, attr, charsets, tag,
+    HtmlAttr, HtmlDocument, HtmlElement, HtmlFrame, HtmlNode, HtmlTag, attr, charsets,
+    tag,
 };
 
+/// Settings for HTML export.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct HtmlOptions {
+    /// Whether to write images and frames into separate files that are
+    /// referenced by relative URL, instead of embedding them into the HTML.
+    ///
+    /// Frames that contain links are always embedded, as the links would not
+    /// work otherwise.
+    pub external_assets: bool,
+    /// Whether to translate supported styles from set rules into a generated
+    /// stylesheet in the `<head>`, so that the document keeps its look in
+    /// the browser.
+    pub stylesheet: bool,
+    /// Whether to write XHTML, that is HTML in XML syntax, as required by
+    /// formats like EPUB.
+    pub xhtml: bool,
+}
+
+/// An HTML document encoded into a string, along with its assets.
+#[derive(Debug, Clone)]
+pub struct HtmlOutput {
+    /// The encoded HTML.
+    pub html: String,
+    /// Files referenced by the HTML. Empty unless assets are external.
+    pub assets: HtmlAssets,
+}
+
 /// Encodes an HTML document into a string.
 pub fn html(document: &HtmlDocument) -> SourceResult<String> {
-    let mut w = Writer::new(&document.introspector, true);
+    encode(&document.root, &document.introspector, None, false)
+}
+
+/// Encodes an HTML document into a string with the given settings.
+pub fn html_with_options(
+    document: &HtmlDocument,
+    options: &HtmlOptions,
+) -> SourceResult<HtmlOutput> {
+    let mut root = Cow::Borrowed(&document.root);
+    if options.stylesheet {
+        crate::stylesheet::embed(root.to_mut(), false);
+    }
+
+    let mut assets = HtmlAssets::new();
+    let html = encode(
+        &root,
+        &document.introspector,
+        options.external_assets.then_some(&mut assets),
+        options.xhtml,
+    )?;
+    Ok(HtmlOutput { html, assets })
+}
+
+/// Encodes a root element and its descendants into a string.
+///
+/// If `assets` is given, images and frames are added to it instead of being
+/// embedded.
+pub(crate) fn encode(
+    root: &HtmlElement,
+    introspector: &Introspector,
+    assets: Option<&mut HtmlAssets>,
+    xhtml: bool,
+) -> SourceResult<String> {
+    let mut w = Writer::new(introspector, assets, xhtml, true);
+    if w.xhtml {
+        w.buf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        write_indent(&mut w);
+    }
     w.buf.push_str("<!DOCTYPE html>");
     write_indent(&mut w);
-    write_element(&mut w, &document.root)?;
+    write_element(&mut w, root)?;
     if w.pretty {
         w.buf.push('\n');
     }
@@ -30,14 +97,30 @@ struct Writer<'a> {
     level: usize,
     /// The document's introspector.
     introspector: &'a Introspector,
+    /// Where to put images and frames, if they are not embedded.
+    assets: Option<&'a mut HtmlAssets>,
+    /// Whether to write XHTML instead of HTML.
+    xhtml: bool,
     /// Whether pretty printing is enabled.
     pretty: bool,
 }
 
 impl<'a> Writer<'a> {
     /// Creates a new writer.
-    fn new(introspector: &'a Introspector, pretty: bool) -> Self {
-        Self { buf: String::new(), level: 0, introspector, pretty }
+    fn new(
+        introspector: &'a Introspector,
+        assets: Option<&'a mut HtmlAssets>,
+        xhtml: bool,
+        pretty: bool,
+    ) -> Self {
+        Self {
+            buf: String::new(),
+            level: 0,
+            introspector,
+            assets,
+            xhtml,
+            pretty,
+        }
     }
 }
 
@@ -57,7 +140,7 @@ fn write_node(w: &mut Writer, node: &HtmlNode, escape_text: bool) -> SourceResul
         HtmlNode::Tag(_) => {}
         HtmlNode::Text(text, span) => write_text(w, text, *span, escape_text)?,
         HtmlNode::Element(element) => write_element(w, element)?,
-        HtmlNode::Frame(frame) => write_frame(w, frame),
+        HtmlNode::Frame(frame) => write_frame(w, frame)?,
     }
     Ok(())
 }
@@ -79,43 +162,52 @@ fn write_element(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
     w.buf.push('<');
     w.buf.push_str(&element.tag.resolve());
 
+    if let Some(image) = &element.image {
+        let src = match &mut w.assets {
+            Some(assets) => asset::add_image(assets, image),
+            None => typst_svg::convert_image_to_base64_url(image),
+        };
+        write_attr(w, attr::src, &src, element.span)?;
+    }
+
     for (attr, value) in &element.attrs.0 {
-        w.buf.push(' ');
-        w.buf.push_str(&attr.resolve());
-
-        // If the string is empty, we can use shorthand syntax.
-        // `<elem attr="">..</div` is equivalent to `<elem attr>..</div>`
-        if !value.is_empty() {
-            w.buf.push('=');
-            w.buf.push('"');
-            for c in value.chars() {
-                if charsets::is_valid_in_attribute_value(c) {
-                    w.buf.push(c);
-                } else {
-                    write_escape(w, c).at(element.span)?;
-                }
-            }
-            w.buf.push('"');
-        }
+        write_attr(w, *attr, value, element.span)?;
     }
 
-    w.buf.push('>');
+    // In HTML, these elements are implicitly in their own namespace.
+    if w.xhtml
+        && let Some(namespace) = namespace(element.tag)
+        && element
+            .attrs
+            .0
+            .iter()
+            .all(|(attr, _)| attr.resolve().as_str() != "xmlns")
+    {
+        write!(w.buf, " xmlns=\"{namespace}\"").unwrap();
+    }
 
     if tag::is_void(element.tag) {
         if !element.children.is_empty() {
             bail!(element.span, "HTML void elements must not have children");
         }
+        w.buf.push_str(if w.xhtml { " />" } else { ">" });
         return Ok(());
     }
 
-    // See HTML spec § 13.1.2.5.
-    if matches!(element.tag, tag::pre | tag::textarea) && starts_with_newline(element) {
+    w.buf.push('>');
+
+    // See HTML spec § 13.1.2.5. XML parsers don't strip the newline.
+    if matches!(element.tag, tag::pre | tag::textarea)
+        && !w.xhtml
+        && starts_with_newline(element)
+    {
         w.buf.push('\n');
     }
 
-    if tag::is_raw(element.tag) {
+    // XML has no raw text elements, so their contents are escaped instead.
+    if tag::is_raw(element.tag) && !w.xhtml {
         write_raw(w, element)?;
-    } else if tag::is_escapable_raw(element.tag) {
+    } else if tag::is_raw(element.tag) || tag::is_escapable_raw(element.tag) {
         write_escapable_raw(w, element)?;
     } else if !element.children.is_empty() {
         write_children(w, element)?;
@@ -128,6 +220,35 @@ fn write_element(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
     Ok(())
 }
 
+/// Encodes an attribute of an element into the writer.
+fn write_attr(
+    w: &mut Writer,
+    attr: HtmlAttr,
+    value: &str,
+    span: Span,
+) -> SourceResult<()> {
+    w.buf.push(' ');
+    w.buf.push_str(&attr.resolve());
+
+    // If the string is empty, we can use shorthand syntax.
+    // `<elem attr="">..</div` is equivalent to `<elem attr>..</div>`
+    // XML has no such shorthand.
+    if !value.is_empty() || w.xhtml {
+        w.buf.push('=');
+        w.buf.push('"');
+        for c in value.chars() {
+            if charsets::is_valid_in_attribute_value(c) {
+                w.buf.push(c);
+            } else {
+                write_escape(w, c).at(span)?;
+            }
+        }
+        w.buf.push('"');
+    }
+
+    Ok(())
+}
+
 /// Encodes the children of an element.
 fn write_children(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
     let pretty = w.pretty;
@@ -290,6 +411,15 @@ impl RawMode {
     }
 }
 
+/// The XML namespace that an element with the given tag introduces, if any.
+fn namespace(tag: HtmlTag) -> Option<&'static str> {
+    match tag {
+        tag::html => Some("http://www.w3.org/1999/xhtml"),
+        tag::math => Some("http://www.w3.org/1998/Math/MathML"),
+        _ => None,
+    }
+}
+
 /// Whether we are allowed to add an extra newline at the start and end of the
 /// element's contents.
 ///
@@ -337,7 +467,33 @@ fn unencodable(c: char) -> EcoString {
 }
 
 /// Encode a laid out frame into the writer.
-fn write_frame(w: &mut Writer, frame: &HtmlFrame) {
+fn write_frame(w: &mut Writer, frame: &HtmlFrame) -> SourceResult<()> {
+    if let Some(assets) = &mut w.assets
+        && asset::is_external(frame)
+    {
+        let svg = typst_svg::svg_html_frame(
+            &frame.inner,
+            frame.text_size,
+            None,
+            &[],
+            w.introspector,
+        );
+        let path = asset::add(assets, Bytes::from_string(svg), "svg");
+        let alt = frame.alt.clone().unwrap_or_else(|| asset::text(&frame.inner));
+        w.buf.push_str("<img class=\"typst-frame\"");
+        write_attr(w, attr::src, &path, frame.span)?;
+        write_attr(w, attr::alt, &alt, frame.span)?;
+        write!(
+            w.buf,
+            " style=\"width: {}em; height: {}em;\"{}>",
+            frame.inner.width() / frame.text_size,
+            frame.inner.height() / frame.text_size,
+            if w.xhtml { " /" } else { "" },
+        )
+        .unwrap();
+        return Ok(());
+    }
+
     let svg = typst_svg::svg_html_frame(
         &frame.inner,
         frame.text_size,
@@ -346,4 +502,5 @@ fn write_frame(w: &mut Writer, frame: &HtmlFrame) {
         w.introspector,
     );
     w.buf.push_str(&svg);
+    Ok(())
 }
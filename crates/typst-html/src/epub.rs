@@ -0,0 +1,724 @@
+//! Packaging of HTML documents as EPUB 3 publications.
+
+use std::fmt::Write as _;
+use std::io::{Cursor, Write};
+use std::num::NonZeroUsize;
+
+use ecow::{EcoString, EcoVec, eco_format, eco_vec};
+use rustc_hash::FxHashMap;
+use typst_library::World;
+use typst_library::diag::{At, SourceResult};
+use typst_library::foundations::{Bytes, Datetime, Smart, StyleChain};
+use typst_library::introspection::Tag;
+use typst_library::model::{HeadingElem, OutlineElem};
+use typst_library::text::{FontStyle, FontVariant, LocalName};
+use typst_syntax::Span;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::{
+    HtmlAssets, HtmlAttr, HtmlDocument, HtmlElement, HtmlNode, HtmlTag, asset, attr,
+    split, tag,
+};
+
+/// Settings for EPUB export.
+#[derive(Debug, Default, Clone)]
+pub struct EpubOptions {
+    /// The time of the publication's last modification, in UTC.
+    ///
+    /// EPUB requires this. If it is `None`, the document's date is used or,
+    /// failing that, the start of the Unix epoch.
+    pub modified: Option<Datetime>,
+}
+
+/// The directory in the container that holds the publication's files.
+const DIR: &str = "EPUB";
+
+/// The container document, which points to the package document in `DIR`.
+const CONTAINER: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+    "<container version=\"1.0\" ",
+    "xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n",
+    "  <rootfiles>\n",
+    "    <rootfile full-path=\"EPUB/package.opf\" ",
+    "media-type=\"application/oebps-package+xml\"/>\n",
+    "  </rootfiles>\n",
+    "</container>\n",
+);
+
+/// Exports an HTML document as an EPUB 3 publication.
+///
+/// The document is split into one XHTML file per top-level heading and
+/// `html.split` element. All files share a stylesheet generated from the
+/// document's set rules. Images and frames are included as separate files, as
+/// are the fonts in which text is displayed: for each family and variant, the
+/// best match that the `world` provides. The navigation document lists all
+/// outlined headings.
+pub fn epub(
+    document: &HtmlDocument,
+    world: &dyn World,
+    options: &EpubOptions,
+) -> SourceResult<Vec<u8>> {
+    let info = &document.info;
+    let locale = info.locale.unwrap_or_default();
+
+    let mut fonts = Vec::new();
+    crate::stylesheet::used_fonts(&document.root, &mut fonts);
+
+    // Unlike browsers, e-book readers differ wildly in their default styles,
+    // so we declare all of them.
+    let mut root = document.root.clone();
+    crate::stylesheet::embed(&mut root, true);
+
+    let mut chunks = split::split_body(split::find_body(&root)?, NonZeroUsize::new(1));
+    split::assign_paths(&mut chunks, "xhtml");
+    let mut titles = split::page_titles(&chunks, info.title.as_ref());
+
+    // If the document starts with a heading, there is nothing before it.
+    if chunks.len() > 1
+        && chunks[0].nodes.iter().all(|node| matches!(node, HtmlNode::Tag(_)))
+    {
+        chunks.remove(0);
+        titles.remove(0);
+    }
+
+    let mut headings = Vec::new();
+    for chunk in &mut chunks {
+        collect_headings(&mut chunk.nodes, &chunk.path, &mut headings);
+    }
+
+    let mut owners = FxHashMap::default();
+    for (i, chunk) in chunks.iter().enumerate() {
+        split::collect_ids(&chunk.nodes, i, &mut owners);
+    }
+
+    let links: Vec<_> = chunks
+        .iter()
+        .zip(&titles)
+        .map(|(chunk, title)| (&chunk.path, title))
+        .collect();
+
+    let mut assets = HtmlAssets::new();
+    let font_faces = font_faces(world, &fonts, &mut assets);
+
+    let mut files: Vec<(EcoString, Bytes)> = vec![];
+    let mut manifest = vec![];
+    let mut spine = vec![];
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut children = chunk.nodes.clone();
+        split::rewrite_links(&mut children, i, &owners, &links);
+
+        let mut page = root.clone();
+        for node in page.children.make_mut() {
+            let HtmlNode::Element(element) = node else { continue };
+            if element.tag == tag::head {
+                split::set_title(element, titles[i].clone());
+                if !font_faces.is_empty() {
+                    let link = HtmlElement::new(tag::link)
+                        .with_attr(attr::rel, "stylesheet")
+                        .with_attr(attr::href, "fonts.css");
+                    element.children.push(link.into());
+                }
+            } else if element.tag == tag::body {
+                element.children = children.clone();
+            }
+        }
+
+        let xhtml = crate::encode::encode(
+            &page,
+            &document.introspector,
+            Some(&mut assets),
+            true,
+        )?;
+
+        let id = eco_format!("chapter-{}", i + 1);
+        let mut properties = vec![];
+        collect_properties(&children, &mut properties);
+
+        manifest.push(Item {
+            id: id.clone(),
+            href: chunk.path.clone(),
+            properties,
+        });
+        spine.push(id);
+        files.push((chunk.path.clone(), Bytes::from_string(xhtml)));
+    }
+
+    let contents = OutlineElem::local_name(locale.lang, locale.region);
+    let nav = navigation(&root, contents, &headings, &links);
+    let nav = crate::encode::encode(&nav, &document.introspector, None, true)?;
+    manifest.push(Item {
+        id: "nav".into(),
+        href: "nav.xhtml".into(),
+        properties: vec!["nav"],
+    });
+    files.push(("nav.xhtml".into(), Bytes::from_string(nav)));
+
+    if !font_faces.is_empty() {
+        manifest.push(Item {
+            id: "fonts".into(),
+            href: "fonts.css".into(),
+            properties: vec![],
+        });
+        files.push(("fonts.css".into(), Bytes::from_string(font_faces)));
+    }
+
+    for (i, (path, data)) in assets.into_iter().enumerate() {
+        let id = eco_format!("asset-{}", i + 1);
+        manifest.push(Item { id, href: path.clone(), properties: vec![] });
+        files.push((path, data));
+    }
+
+    // The identifier must be unique, but should stay the same when the
+    // publication is exported again.
+    let ident = typst_utils::hash128(&(&info.title, &info.author, &files));
+    let title = info.title.clone().or_else(|| titles.first().cloned());
+    let package = package(document, options, ident, title, &manifest, &spine);
+
+    archive(package, files)
+        .map_err(|err| eco_format!("failed to write EPUB archive ({err})"))
+        .at(Span::detached())
+}
+
+/// An outlined heading, as listed in the navigation document.
+struct Heading {
+    /// The heading's level.
+    level: NonZeroUsize,
+    /// The heading's text.
+    title: EcoString,
+    /// A link to the heading.
+    href: EcoString,
+}
+
+/// Collects the outlined headings in the nodes, giving the elements they
+/// produced IDs if necessary.
+fn collect_headings(
+    nodes: &mut EcoVec<HtmlNode>,
+    path: &EcoString,
+    out: &mut Vec<Heading>,
+) {
+    let mut pending = None;
+    for node in nodes.make_mut() {
+        match node {
+            HtmlNode::Tag(Tag::Start(elem, _)) => {
+                if let Some(heading) = elem.to_packed::<HeadingElem>()
+                    && heading.outlined.get(StyleChain::default())
+                {
+                    let level = heading.resolve_level(StyleChain::default());
+                    pending = Some((level, heading.body.plain_text()));
+                }
+            }
+            HtmlNode::Element(element) => {
+                if let Some((level, title)) = pending.take() {
+                    let id = match element.attrs.get(attr::id) {
+                        Some(id) => id.clone(),
+                        None => {
+                            let id = eco_format!("heading-{}", out.len() + 1);
+                            element.attrs.push(attr::id, id.clone());
+                            id
+                        }
+                    };
+                    out.push(Heading { level, title, href: eco_format!("{path}#{id}") });
+                }
+                collect_headings(&mut element.children, path, out);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Creates the navigation document.
+///
+/// If there are no outlined headings, it lists the chapters instead.
+fn navigation(
+    root: &HtmlElement,
+    title: &str,
+    headings: &[Heading],
+    chapters: &[(&EcoString, &EcoString)],
+) -> HtmlElement {
+    let list = if headings.is_empty() {
+        let items = chapters.iter().map(|&(path, title)| {
+            HtmlNode::from(
+                HtmlElement::new(tag::li)
+                    .with_children(eco_vec![split::link(path, title).into()]),
+            )
+        });
+        HtmlElement::new(tag::ol).with_children(items.collect())
+    } else {
+        nested_list(headings)
+    };
+
+    let title = EcoString::from(title);
+    let text = || eco_vec![HtmlNode::text(title.clone(), Span::detached())];
+    let nav = HtmlElement::new(tag::nav)
+        .with_attr(HtmlAttr::intern("epub:type").unwrap(), "toc")
+        .with_attr(attr::id, "toc")
+        .with_children(eco_vec![
+            HtmlElement::new(tag::h1).with_children(text()).into(),
+            list.into(),
+        ]);
+
+    let head = HtmlElement::new(tag::head).with_children(eco_vec![
+        HtmlElement::new(tag::meta).with_attr(attr::charset, "utf-8").into(),
+        HtmlElement::new(tag::title).with_children(text()).into(),
+    ]);
+
+    let mut html = HtmlElement::new(tag::html);
+    html.attrs = root.attrs.clone();
+    html.attrs
+        .push(HtmlAttr::intern("xmlns:epub").unwrap(), "http://www.idpf.org/2007/ops");
+    html.with_children(eco_vec![
+        head.into(),
+        HtmlElement::new(tag::body).with_children(eco_vec![nav.into()]).into(),
+    ])
+}
+
+/// Creates an ordered list for headings, nesting those of higher levels.
+fn nested_list(headings: &[Heading]) -> HtmlElement {
+    let mut items = EcoVec::new();
+    let mut i = 0;
+    while i < headings.len() {
+        let heading = &headings[i];
+        let end = headings[i + 1..]
+            .iter()
+            .position(|next| next.level <= heading.level)
+            .map_or(headings.len(), |offset| i + 1 + offset);
+
+        let mut item = HtmlElement::new(tag::li)
+            .with_children(eco_vec![split::link(&heading.href, &heading.title).into()]);
+        if end > i + 1 {
+            item.children.push(nested_list(&headings[i + 1..end]).into());
+        }
+
+        items.push(item.into());
+        i = end;
+    }
+    HtmlElement::new(tag::ol).with_children(items)
+}
+
+/// Collects the manifest properties that a content document with the given
+/// body nodes requires.
+fn collect_properties(nodes: &[HtmlNode], properties: &mut Vec<&'static str>) {
+    for node in nodes {
+        let property = match node {
+            HtmlNode::Element(element) => {
+                collect_properties(&element.children, properties);
+                if element.attrs.get(attr::src).is_some_and(|src| {
+                    src.starts_with("http:") || src.starts_with("https:")
+                }) && !properties.contains(&"remote-resources")
+                {
+                    properties.push("remote-resources");
+                }
+                if element.tag == tag::math {
+                    "mathml"
+                } else if element.tag == HtmlTag::constant("svg") {
+                    "svg"
+                } else {
+                    continue;
+                }
+            }
+            // Frames that can't be emitted as images are inlined as SVG.
+            HtmlNode::Frame(frame) if !asset::is_external(frame) => "svg",
+            _ => continue,
+        };
+        if !properties.contains(&property) {
+            properties.push(property);
+        }
+    }
+}
+
+/// Adds the fonts that best match the used variants of each family to the
+/// assets and returns the `@font-face` rules that refer to them.
+fn font_faces(
+    world: &dyn World,
+    fonts: &[(EcoString, FontVariant)],
+    assets: &mut HtmlAssets,
+) -> String {
+    let mut css = String::new();
+    let mut added = vec![];
+    for (family, variant) in fonts {
+        let Some(id) = world.book().select(family, *variant) else { continue };
+        if added.contains(&id) {
+            continue;
+        }
+        added.push(id);
+
+        let Some(font) = world.font(id) else { continue };
+        let data = font.data();
+
+        // Readers can't pick a font from a collection.
+        if font.index() != 0 || data.starts_with(b"ttcf") {
+            continue;
+        }
+
+        let extension = if data.starts_with(b"OTTO") { "otf" } else { "ttf" };
+        let path = asset::add(assets, data.clone(), extension);
+        let variant = font.info().variant;
+        let style = match variant.style {
+            FontStyle::Normal => "normal",
+            FontStyle::Italic => "italic",
+            FontStyle::Oblique => "oblique",
+        };
+
+        writeln!(
+            css,
+            "@font-face {{ font-family: \"{}\"; src: url(\"{path}\"); \
+             font-style: {style}; font-weight: {}; font-stretch: {}%; }}",
+            family.replace('\\', "\\\\").replace('"', "\\\""),
+            variant.weight.to_number(),
+            typst_utils::round_with_precision(
+                variant.stretch.to_ratio().get() * 100.0,
+                2
+            ),
+        )
+        .unwrap();
+    }
+    css
+}
+
+/// A file in the publication's manifest.
+struct Item {
+    /// The item's ID, referenced from the spine.
+    id: EcoString,
+    /// The path of the file, relative to the package document.
+    href: EcoString,
+    /// Properties of the file, e.g. whether it contains MathML.
+    properties: Vec<&'static str>,
+}
+
+/// Creates the package document, which holds the publication's metadata and
+/// lists its files.
+fn package(
+    document: &HtmlDocument,
+    options: &EpubOptions,
+    ident: u128,
+    title: Option<EcoString>,
+    manifest: &[Item],
+    spine: &[EcoString],
+) -> String {
+    let info = &document.info;
+    let lang = info.locale.unwrap_or_default().rfc_3066();
+    let date = match info.date {
+        Smart::Custom(date) => date,
+        Smart::Auto => options.modified,
+    };
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        xml,
+        "<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" \
+         unique-identifier=\"uid\" xml:lang=\"{}\">",
+        escape(&lang),
+    )
+    .unwrap();
+
+    let uuid = eco_format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        ident >> 96,
+        (ident >> 80) & 0xffff,
+        (ident >> 64) & 0xffff,
+        (ident >> 48) & 0xffff,
+        ident & 0xffff_ffff_ffff,
+    );
+
+    xml.push_str("  <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n");
+    writeln!(xml, "    <dc:identifier id=\"uid\">urn:uuid:{uuid}</dc:identifier>")
+        .unwrap();
+    let title = title.unwrap_or_else(|| "Untitled".into());
+    writeln!(xml, "    <dc:title>{}</dc:title>", escape(&title)).unwrap();
+    writeln!(xml, "    <dc:language>{}</dc:language>", escape(&lang)).unwrap();
+    for author in &info.author {
+        writeln!(xml, "    <dc:creator>{}</dc:creator>", escape(author)).unwrap();
+    }
+    if let Some(date) = date.and_then(|date| format_date(&date)) {
+        writeln!(xml, "    <dc:date>{date}</dc:date>").unwrap();
+    }
+    for keyword in &info.keywords {
+        writeln!(xml, "    <dc:subject>{}</dc:subject>", escape(keyword)).unwrap();
+    }
+    if let Some(description) = &info.description {
+        writeln!(xml, "    <dc:description>{}</dc:description>", escape(description))
+            .unwrap();
+    }
+    let modified = options
+        .modified
+        .or(date)
+        .and_then(|date| format_timestamp(&date))
+        .unwrap_or_else(|| "1970-01-01T00:00:00Z".into());
+    writeln!(xml, "    <meta property=\"dcterms:modified\">{modified}</meta>").unwrap();
+    xml.push_str("  </metadata>\n");
+
+    xml.push_str("  <manifest>\n");
+    for item in manifest {
+        write!(
+            xml,
+            "    <item id=\"{}\" href=\"{}\" media-type=\"{}\"",
+            item.id,
+            escape(&item.href),
+            media_type(&item.href),
+        )
+        .unwrap();
+        if !item.properties.is_empty() {
+            write!(xml, " properties=\"{}\"", item.properties.join(" ")).unwrap();
+        }
+        xml.push_str("/>\n");
+    }
+    xml.push_str("  </manifest>\n");
+
+    xml.push_str("  <spine>\n");
+    for id in spine {
+        writeln!(xml, "    <itemref idref=\"{id}\"/>").unwrap();
+    }
+    xml.push_str("  </spine>\n");
+    xml.push_str("</package>\n");
+    xml
+}
+
+/// Formats the date part of a datetime, if it has one.
+fn format_date(datetime: &Datetime) -> Option<EcoString> {
+    Some(eco_format!(
+        "{:04}-{:02}-{:02}",
+        datetime.year()?,
+        datetime.month()?,
+        datetime.day()?
+    ))
+}
+
+/// Formats a datetime as a UTC timestamp, assuming midnight if it has no
+/// time.
+fn format_timestamp(datetime: &Datetime) -> Option<EcoString> {
+    Some(eco_format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        format_date(datetime)?,
+        datetime.hour().unwrap_or(0),
+        datetime.minute().unwrap_or(0),
+        datetime.second().unwrap_or(0),
+    ))
+}
+
+/// The media type of a file in the publication.
+fn media_type(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("xhtml") => "application/xhtml+xml",
+        Some("css") => "text/css",
+        Some("png") => "image/png",
+        Some("jpg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("otf") => "font/otf",
+        Some("ttf") => "font/ttf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Escapes text for use in XML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Packs the publication's files into an OCF container, i.e. a ZIP archive.
+fn archive(
+    package: String,
+    files: Vec<(EcoString, Bytes)>,
+) -> zip::result::ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    // The media type must come first and be uncompressed, so that it can be
+    // recognized from the first bytes of the file.
+    let stored =
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+
+    let deflated =
+        SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(CONTAINER.as_bytes())?;
+
+    zip.start_file(format!("{DIR}/package.opf"), deflated)?;
+    zip.write_all(package.as_bytes())?;
+
+    for (path, data) in files {
+        zip.start_file(format!("{DIR}/{path}"), deflated)?;
+        zip.write_all(data.as_slice())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use typst_library::diag::FileResult;
+    use typst_library::introspection::Introspector;
+    use typst_library::layout::{Abs, Frame, Size};
+    use typst_library::model::DocumentInfo;
+    use typst_library::text::{Font, FontBook};
+    use typst_library::{Library, World};
+    use typst_syntax::{FileId, Source};
+    use typst_utils::LazyHash;
+    use zip::ZipArchive;
+
+    use super::*;
+    use crate::HtmlFrame;
+
+    /// A world without fonts. Export only needs the font book.
+    struct FontlessWorld(LazyHash<FontBook>);
+
+    impl World for FontlessWorld {
+        fn library(&self) -> &LazyHash<Library> {
+            unreachable!()
+        }
+
+        fn book(&self) -> &LazyHash<FontBook> {
+            &self.0
+        }
+
+        fn main(&self) -> FileId {
+            unreachable!()
+        }
+
+        fn source(&self, _: FileId) -> FileResult<Source> {
+            unreachable!()
+        }
+
+        fn file(&self, _: FileId) -> FileResult<Bytes> {
+            unreachable!()
+        }
+
+        fn font(&self, _: usize) -> Option<Font> {
+            None
+        }
+
+        fn today(&self, _: Option<i64>) -> Option<Datetime> {
+            None
+        }
+    }
+
+    fn text(text: &str) -> HtmlNode {
+        HtmlNode::text(text, Span::detached())
+    }
+
+    fn element(tag: HtmlTag, children: Vec<HtmlNode>) -> HtmlElement {
+        HtmlElement::new(tag).with_children(children.into())
+    }
+
+    /// Extracts the value of the first attribute with the given name.
+    fn attr_value<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+        let start = text.find(&format!(" {name}=\""))? + name.len() + 3;
+        let end = start + text[start..].find('"')?;
+        Some(&text[start..end])
+    }
+
+    #[test]
+    fn test_epub_structure() {
+        // A frame that something links to stays inline.
+        let mut frame = HtmlFrame::new(
+            Frame::soft(Size::new(Abs::pt(10.0), Abs::pt(10.0))),
+            StyleChain::default(),
+            Span::detached(),
+        );
+        frame.id = Some("figure".into());
+
+        let body = element(
+            tag::body,
+            vec![
+                element(tag::p, vec![text("Intro")]).into(),
+                element(tag::math, vec![]).into(),
+                element(tag::p, vec![frame.into()]).into(),
+                HtmlElement::new(tag::img)
+                    .with_attr(attr::src, "https://example.com/a.png")
+                    .with_attr(attr::alt, "")
+                    .into(),
+            ],
+        );
+        let document = HtmlDocument {
+            root: element(
+                tag::html,
+                vec![element(tag::head, vec![]).into(), body.into()],
+            ),
+            info: DocumentInfo { title: Some("Book".into()), ..Default::default() },
+            introspector: Introspector::default(),
+        };
+
+        let world = FontlessWorld(LazyHash::new(FontBook::new()));
+        let bytes = epub(&document, &world, &EpubOptions::default()).unwrap();
+        let mut zip = ZipArchive::new(Cursor::new(bytes)).unwrap();
+
+        // The media type comes first and is stored uncompressed.
+        let first = zip.by_index(0).unwrap();
+        assert_eq!(first.name(), "mimetype");
+        assert_eq!(first.compression(), CompressionMethod::Stored);
+        drop(first);
+
+        let mut read = |name: &str| {
+            let mut buf = String::new();
+            zip.by_name(name).unwrap().read_to_string(&mut buf).unwrap();
+            buf
+        };
+        assert_eq!(read("mimetype"), "application/epub+zip");
+
+        // The container points to the package document.
+        let container = read("META-INF/container.xml");
+        let path = attr_value(&container, "full-path").unwrap();
+        let package = read(path);
+        let dir = path.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+        let uid = attr_value(&package, "unique-identifier").unwrap();
+        assert!(package.contains(&format!("<dc:identifier id=\"{uid}\">")));
+        assert!(package.contains("<dc:title>Book</dc:title>"));
+        assert!(package.contains("<dc:language>"));
+        assert!(package.contains("<meta property=\"dcterms:modified\">"));
+
+        // Every item exists, and each spine entry refers to a content
+        // document.
+        let items: Vec<_> = package
+            .lines()
+            .filter(|line| line.trim_start().starts_with("<item "))
+            .collect();
+        let mut navs = 0;
+        for item in &items {
+            let href = attr_value(item, "href").unwrap();
+            read(&format!("{dir}/{href}"));
+            if attr_value(item, "properties").is_some_and(|p| p.contains("nav")) {
+                navs += 1;
+            }
+        }
+        assert_eq!(navs, 1);
+
+        let spine: Vec<_> =
+            package.lines().filter_map(|line| attr_value(line, "idref")).collect();
+        assert!(!spine.is_empty());
+        for idref in spine {
+            let item = items
+                .iter()
+                .find(|item| attr_value(item, "id") == Some(idref))
+                .unwrap();
+            assert_eq!(attr_value(item, "media-type"), Some("application/xhtml+xml"));
+        }
+
+        // The chapter declares what it contains.
+        let chapter = items
+            .iter()
+            .find(|item| attr_value(item, "id") == Some("chapter-1"))
+            .unwrap();
+        assert_eq!(
+            attr_value(chapter, "properties"),
+            Some("mathml svg remote-resources")
+        );
+    }
+}
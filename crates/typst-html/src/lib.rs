@@ -1,5 +1,6 @@
 //! Typst's HTML exporter.
 
+mod asset;
 mod attr;
 mod charsets;
 mod convert;
@@ -7,21 +8,31 @@ mod css;
 mod document;
 mod dom;
 mod encode;
+mod epub;
 mod fragment;
 mod link;
+mod math;
 mod rules;
+mod split;
+mod stylesheet;
 mod tag;
 mod typed;
 
+pub use self::asset::HtmlAssets;
 pub use self::document::html_document;
 pub use self::dom::*;
-pub use self::encode::html;
+pub use self::encode::{HtmlOptions, HtmlOutput, html, html_with_options};
+pub use self::epub::{EpubOptions, epub};
 pub use self::rules::{html_span_filled, register};
+pub use self::split::{HtmlPage, HtmlSplitOptions, HtmlSplitOutput, html_split};
 
 use ecow::EcoString;
 use typst_library::Category;
 use typst_library::foundations::{Content, Module, Scope};
-use typst_library::introspection::Location;
+use typst_library::introspection::{Locatable, Location};
+use typst_library::visualize::Image;
+
+use crate::stylesheet::BlockStyles;
 use typst_macros::elem;
 
 /// Creates the module with all HTML definitions.
@@ -30,6 +41,7 @@ pub fn module() -> Module {
     html.start_category(Category::Html);
     html.define_elem::<HtmlElem>();
     html.define_elem::<FrameElem>();
+    html.define_elem::<SplitElem>();
     crate::typed::define(&mut html);
     Module::new("html", html)
 }
@@ -89,6 +101,17 @@ pub struct HtmlElem {
     #[internal]
     #[ghost]
     pub role: Option<EcoString>,
+
+    /// The styles of the block this element was created from, which end up in
+    /// the generated stylesheet, if any.
+    #[internal]
+    pub block_styles: Option<BlockStyles>,
+
+    /// The image displayed by an `<img>` element. Its `src` attribute is
+    /// written during encoding, either as a data URL or as the path of an
+    /// external asset.
+    #[internal]
+    pub image: Option<Image>,
 }
 
 impl HtmlElem {
@@ -121,8 +144,19 @@ impl HtmlElem {
 
     /// Checks whether the given element is an inline-level HTML element.
     fn is_inline(elem: &Content) -> bool {
-        elem.to_packed::<HtmlElem>()
-            .is_some_and(|elem| tag::is_inline_by_default(elem.tag))
+        elem.to_packed::<HtmlElem>().is_some_and(|elem| {
+            tag::is_inline_by_default(elem.tag)
+                && !(elem.tag == tag::math && elem.is_display_math())
+        })
+    }
+
+    /// Whether this is a `<math>` element with `display="block"`.
+    fn is_display_math(&self) -> bool {
+        self.attrs
+            .as_option()
+            .as_ref()
+            .and_then(|attrs| attrs.get(attr::display))
+            .is_some_and(|display| display == "block")
     }
 }
 
@@ -142,4 +176,40 @@ pub struct FrameElem {
     #[positional]
     #[required]
     pub body: Content,
+
+    /// An alternative description of the frame.
+    ///
+    /// This is only used when the frame is written to a separate file and
+    /// embedded as an image. If `{none}`, the text within the frame serves as
+    /// the description.
+    pub alt: Option<EcoString>,
+}
+
+/// Starts a new file when exporting to multiple HTML files.
+///
+/// When a document is split into multiple files, each `html.split` element
+/// that is a direct child of the document's body starts a new file. Headings
+/// can also start new files, depending on the export options. Links between
+/// the resulting files are adjusted automatically.
+///
+/// When exporting to a single file or to a paged format, this element has no
+/// effect.
+///
+/// ```typ
+/// #html.split(title: "Installation", path: "install")
+/// Download the latest release ...
+/// ```
+#[elem(Locatable)]
+pub struct SplitElem {
+    /// The title of the new file's page, used in its `<title>` and in links
+    /// to it from other pages.
+    ///
+    /// If `{none}`, the first heading on the page is used.
+    pub title: Option<EcoString>,
+
+    /// The name of the new file, without extension.
+    ///
+    /// If `{none}`, it is derived from the element's label or the page's
+    /// title.
+    pub path: Option<EcoString>,
 }
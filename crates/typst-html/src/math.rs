@@ -0,0 +1,787 @@
+//! Conversion of equations into MathML.
+
+use codex::styling::{MathStyle, to_style};
+use ecow::{EcoString, EcoVec, eco_format, eco_vec};
+use typst_library::diag::{SourceResult, warning};
+use typst_library::engine::Engine;
+use typst_library::foundations::{Content, Packed, Style, StyleChain, SymbolElem};
+use typst_library::introspection::{SplitLocator, TagElem};
+use typst_library::layout::{HAlignment, HElem, Spacing};
+use typst_library::math::*;
+use typst_library::routines::{Arenas, Pair, RealizationKind};
+use typst_library::text::{LinebreakElem, SmartQuoter, SpaceElem, TextElem};
+use typst_syntax::Span;
+use typst_utils::{LazyHash, Numeric, default_math_class};
+use unicode_math_class::MathClass;
+
+use crate::convert::Whitespace;
+use crate::fragment::html_inline_fragment;
+use crate::{HtmlAttr, HtmlElement, HtmlNode, HtmlTag, attr, css, tag};
+
+/// Converts the body of a `<math>` element into MathML nodes.
+///
+/// Line breaks and alignment points at the top level turn the equation into
+/// an `<mtable>` with one row per line.
+pub fn convert_math(
+    engine: &mut Engine,
+    locator: &mut SplitLocator,
+    body: &Content,
+    styles: StyleChain,
+) -> SourceResult<EcoVec<HtmlNode>> {
+    let mut converter = MathConverter { engine, locator };
+    let grid = converter.grid(body, styles)?;
+    if let [row] = grid.as_slice()
+        && let [cell] = row.as_slice()
+    {
+        // The `<math>` element itself behaves like an `<mrow>`.
+        return Ok(cell.clone());
+    }
+
+    // Like in layout, alignment points alternate between right- and
+    // left-aligned columns. Lines without any are centered.
+    let columns = grid.iter().map(Vec::len).max().unwrap_or(1);
+    let table = table(grid, |i| match (columns, i % 2) {
+        (1, _) => None,
+        (_, 0) => Some("right"),
+        _ => Some("left"),
+    });
+    Ok(eco_vec![table.into()])
+}
+
+/// Converts realized math into MathML.
+struct MathConverter<'a, 'y, 'z> {
+    engine: &'a mut Engine<'y>,
+    locator: &'a mut SplitLocator<'z>,
+}
+
+impl MathConverter<'_, '_, '_> {
+    /// Realizes math content, applying show rules to it.
+    fn realize<'a>(
+        &mut self,
+        arenas: &'a Arenas,
+        content: &'a Content,
+        styles: StyleChain<'a>,
+    ) -> SourceResult<Vec<Pair<'a>>> {
+        (self.engine.routines.realize)(
+            RealizationKind::Math,
+            self.engine,
+            self.locator,
+            arenas,
+            content,
+            styles,
+        )
+    }
+
+    /// Converts content into a list of MathML nodes.
+    fn nodes(
+        &mut self,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<EcoVec<HtmlNode>> {
+        let arenas = Arenas::default();
+        let mut nodes = EcoVec::new();
+        for (elem, styles) in self.realize(&arenas, content, styles)? {
+            self.handle(elem, styles, &mut nodes)?;
+        }
+        Ok(nodes)
+    }
+
+    /// Converts content into exactly one MathML element, wrapping it in an
+    /// `<mrow>` if necessary.
+    fn row(
+        &mut self,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<EcoVec<HtmlNode>> {
+        self.nodes(content, styles).map(mrow)
+    }
+
+    /// Converts content into rows (separated by line breaks) of cells
+    /// (separated by alignment points).
+    fn grid(
+        &mut self,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<Vec<Vec<EcoVec<HtmlNode>>>> {
+        let arenas = Arenas::default();
+        let mut rows = vec![vec![EcoVec::new()]];
+        for (elem, styles) in self.realize(&arenas, content, styles)? {
+            let row = rows.last_mut().unwrap();
+            if elem.is::<LinebreakElem>() {
+                rows.push(vec![EcoVec::new()]);
+            } else if elem.is::<AlignPointElem>() {
+                row.push(EcoVec::new());
+            } else {
+                self.handle(elem, styles, row.last_mut().unwrap())?;
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Converts a realized element into MathML node(s).
+    fn handle(
+        &mut self,
+        elem: &Content,
+        styles: StyleChain,
+        out: &mut EcoVec<HtmlNode>,
+    ) -> SourceResult<()> {
+        let span = elem.span();
+        let element = if let Some(elem) = elem.to_packed::<TagElem>() {
+            out.push(elem.tag.clone().into());
+            return Ok(());
+        } else if elem.is::<SpaceElem>()
+            || elem.is::<LinebreakElem>()
+            || elem.is::<AlignPointElem>()
+        {
+            // Spacing is up to the MathML renderer. Line breaks and alignment
+            // points are only supported at the top level, see `grid`.
+            return Ok(());
+        } else if let Some(elem) = elem.to_packed::<HElem>() {
+            let Spacing::Rel(rel) = elem.amount else { return Ok(()) };
+            if rel.abs.is_zero() || !rel.rel.is_zero() {
+                return Ok(());
+            }
+            HtmlElement::new(tag::mspace)
+                .with_attr(attr::width, eco_format!("{}", css::length(rel.abs)))
+        } else if let Some(elem) = elem.to_packed::<TextElem>() {
+            convert_text(elem, styles)
+        } else if let Some(elem) = elem.to_packed::<SymbolElem>() {
+            convert_symbol(elem, styles)
+        } else if let Some(elem) = elem.to_packed::<ClassElem>() {
+            let style = EquationElem::class.set(Some(elem.class)).wrap();
+            out.extend(self.nodes(&elem.body, styles.chain(&style))?);
+            return Ok(());
+        } else if let Some(elem) = elem.to_packed::<AccentElem>() {
+            self.convert_accent(elem, styles)?
+        } else if let Some(elem) = elem.to_packed::<AttachElem>() {
+            self.convert_attach(elem, styles)?
+        } else if let Some(elem) = elem.to_packed::<PrimesElem>() {
+            token(tag::mo, primes(elem.count), span)
+        } else if let Some(elem) = elem.to_packed::<ScriptsElem>() {
+            out.extend(self.nodes(&elem.body, styles)?);
+            return Ok(());
+        } else if let Some(elem) = elem.to_packed::<LimitsElem>() {
+            out.extend(self.nodes(&elem.body, styles)?);
+            return Ok(());
+        } else if let Some(elem) = elem.to_packed::<StretchElem>() {
+            out.extend(self.nodes(&elem.body, styles)?);
+            return Ok(());
+        } else if let Some(elem) = elem.to_packed::<CancelElem>() {
+            self.engine.sink.warn(warning!(
+                span,
+                "cancel lines are not supported in MathML and were omitted"
+            ));
+            out.extend(self.nodes(&elem.body, styles)?);
+            return Ok(());
+        } else if let Some(elem) = elem.to_packed::<FracElem>() {
+            self.convert_frac(elem, styles)?
+        } else if let Some(elem) = elem.to_packed::<BinomElem>() {
+            self.convert_binom(elem, styles)?
+        } else if let Some(elem) = elem.to_packed::<LrElem>() {
+            HtmlElement::new(tag::mrow).with_children(self.nodes(&elem.body, styles)?)
+        } else if let Some(elem) = elem.to_packed::<MidElem>() {
+            let mut nodes = self.nodes(&elem.body, styles)?;
+            set_on_mo(&mut nodes, attr::stretchy, "true");
+            out.extend(nodes);
+            return Ok(());
+        } else if let Some(elem) = elem.to_packed::<VecElem>() {
+            self.convert_vec(elem, styles)?
+        } else if let Some(elem) = elem.to_packed::<MatElem>() {
+            self.convert_mat(elem, styles)?
+        } else if let Some(elem) = elem.to_packed::<CasesElem>() {
+            self.convert_cases(elem, styles)?
+        } else if let Some(elem) = elem.to_packed::<OpElem>() {
+            if let Some(text) = elem.text.to_packed::<TextElem>() {
+                let mut token = token(tag::mi, text.text.clone(), span);
+                if text.text.chars().count() == 1 {
+                    token = token.with_attr(attr::mathvariant, "normal");
+                }
+                token
+            } else {
+                out.extend(self.nodes(&elem.text, styles)?);
+                return Ok(());
+            }
+        } else if let Some(elem) = elem.to_packed::<RootElem>() {
+            self.convert_root(elem, styles)?
+        } else if let Some(elem) = elem.to_packed::<UnderlineElem>() {
+            self.convert_underover(&elem.body, None, '_', true, styles)?
+        } else if let Some(elem) = elem.to_packed::<OverlineElem>() {
+            self.convert_underover(&elem.body, None, '‾', false, styles)?
+        } else if let Some(elem) = elem.to_packed::<UnderbraceElem>() {
+            let annotation = elem.annotation.get_cloned(styles);
+            self.convert_underover(&elem.body, annotation, '⏟', true, styles)?
+        } else if let Some(elem) = elem.to_packed::<OverbraceElem>() {
+            let annotation = elem.annotation.get_cloned(styles);
+            self.convert_underover(&elem.body, annotation, '⏞', false, styles)?
+        } else if let Some(elem) = elem.to_packed::<UnderbracketElem>() {
+            let annotation = elem.annotation.get_cloned(styles);
+            self.convert_underover(&elem.body, annotation, '⎵', true, styles)?
+        } else if let Some(elem) = elem.to_packed::<OverbracketElem>() {
+            let annotation = elem.annotation.get_cloned(styles);
+            self.convert_underover(&elem.body, annotation, '⎴', false, styles)?
+        } else if let Some(elem) = elem.to_packed::<UnderparenElem>() {
+            let annotation = elem.annotation.get_cloned(styles);
+            self.convert_underover(&elem.body, annotation, '⏝', true, styles)?
+        } else if let Some(elem) = elem.to_packed::<OverparenElem>() {
+            let annotation = elem.annotation.get_cloned(styles);
+            self.convert_underover(&elem.body, annotation, '⏜', false, styles)?
+        } else if let Some(elem) = elem.to_packed::<UndershellElem>() {
+            let annotation = elem.annotation.get_cloned(styles);
+            self.convert_underover(&elem.body, annotation, '⏡', true, styles)?
+        } else if let Some(elem) = elem.to_packed::<OvershellElem>() {
+            let annotation = elem.annotation.get_cloned(styles);
+            self.convert_underover(&elem.body, annotation, '⏠', false, styles)?
+        } else {
+            // Non-mathematical content (e.g. a box) is embedded as HTML. The
+            // `<mtext>` element is an integration point for HTML content.
+            let children = html_inline_fragment(
+                self.engine,
+                elem,
+                self.locator,
+                &mut SmartQuoter::new(),
+                styles,
+                Whitespace::Normal,
+            )?;
+            HtmlElement::new(tag::mtext).with_children(children)
+        };
+
+        out.push(element.spanned(span).into());
+        Ok(())
+    }
+
+    /// Converts an attachment.
+    fn convert_attach(
+        &mut self,
+        elem: &Packed<AttachElem>,
+        styles: StyleChain,
+    ) -> SourceResult<HtmlElement> {
+        let merged = elem.merge_base();
+        let elem = merged.as_ref().unwrap_or(elem);
+
+        let limits = Limits::of(&elem.base, styles);
+        let active = limits.active(styles);
+        let tl = elem.tl.get_cloned(styles);
+        let tr = elem.tr.get_cloned(styles);
+        let primed = tr.as_ref().is_some_and(|content| content.is::<PrimesElem>());
+        let t = elem.t.get_cloned(styles);
+        let bl = elem.bl.get_cloned(styles);
+        let br = elem.br.get_cloned(styles);
+        let b = elem.b.get_cloned(styles);
+
+        // Top and bottom attachments become scripts if limits are inactive,
+        // just like in layout.
+        let (t, tr) = match (t, tr) {
+            (Some(t), Some(tr)) if primed && !active => (None, Some(tr + t)),
+            (Some(t), None) if !active => (None, Some(t)),
+            (t, tr) => (t, tr),
+        };
+        let (b, br) = if active || br.is_some() { (b, br) } else { (None, b) };
+
+        let mut base = self.row(&elem.base, styles)?;
+        if let Limits::Always = limits {
+            // Prevent the renderer from turning limits into scripts in inline
+            // math for operators that have movable limits by default.
+            set_on_mo(&mut base, attr::movablelimits, "false");
+        }
+
+        let style = script_style(styles);
+        let styles = styles.chain(&style);
+        let mut script = |content: Option<Content>| {
+            content.map(|content| self.row(&content, styles)).transpose()
+        };
+        let (t, b) = (script(t)?, script(b)?);
+        let (tl, bl, tr, br) = (script(tl)?, script(bl)?, script(tr)?, script(br)?);
+
+        let base = match (b, t) {
+            (None, None) => base,
+            (Some(b), None) => eco_vec![element(tag::munder, [base, b]).into()],
+            (None, Some(t)) => eco_vec![element(tag::mover, [base, t]).into()],
+            (Some(b), Some(t)) => eco_vec![element(tag::munderover, [base, b, t]).into()],
+        };
+
+        Ok(if tl.is_some() || bl.is_some() {
+            let none = || eco_vec![HtmlElement::new(tag::mrow).into()];
+            element(
+                tag::mmultiscripts,
+                [
+                    base,
+                    br.unwrap_or_else(none),
+                    tr.unwrap_or_else(none),
+                    eco_vec![HtmlElement::new(tag::mprescripts).into()],
+                    bl.unwrap_or_else(none),
+                    tl.unwrap_or_else(none),
+                ],
+            )
+        } else {
+            match (br, tr) {
+                (Some(br), Some(tr)) => element(tag::msubsup, [base, br, tr]),
+                (Some(br), None) => element(tag::msub, [base, br]),
+                (None, Some(tr)) => element(tag::msup, [base, tr]),
+                (None, None) => element(tag::mrow, [base]),
+            }
+        })
+    }
+
+    /// Converts a fraction.
+    fn convert_frac(
+        &mut self,
+        elem: &Packed<FracElem>,
+        styles: StyleChain,
+    ) -> SourceResult<HtmlElement> {
+        let style = frac_style(styles);
+        let inner = styles.chain(&style);
+        Ok(match elem.style.get(styles) {
+            FracStyle::Vertical => element(
+                tag::mfrac,
+                [self.row(&elem.num, inner)?, self.row(&elem.denom, inner)?],
+            ),
+            FracStyle::Skewed => element(
+                tag::mrow,
+                [
+                    self.row(&elem.num, styles)?,
+                    eco_vec![token(tag::mo, '\u{2044}', elem.span()).into()],
+                    self.row(&elem.denom, styles)?,
+                ],
+            ),
+            FracStyle::Horizontal => element(
+                tag::mrow,
+                [
+                    self.horizontal_part(
+                        &elem.num,
+                        elem.num_deparenthesized.get(styles),
+                        styles,
+                    )?,
+                    eco_vec![token(tag::mo, '/', elem.span()).into()],
+                    self.horizontal_part(
+                        &elem.denom,
+                        elem.denom_deparenthesized.get(styles),
+                        styles,
+                    )?,
+                ],
+            ),
+        })
+    }
+
+    /// Converts the numerator or denominator of a horizontal fraction.
+    fn horizontal_part(
+        &mut self,
+        content: &Content,
+        deparenthesized: bool,
+        styles: StyleChain,
+    ) -> SourceResult<EcoVec<HtmlNode>> {
+        let nodes = self.nodes(content, styles)?;
+        // Parentheses that were absorbed by the fraction during parsing need
+        // to come back.
+        Ok(if deparenthesized {
+            eco_vec![fenced(Some('('), nodes, Some(')')).into()]
+        } else {
+            mrow(nodes)
+        })
+    }
+
+    /// Converts a binomial.
+    fn convert_binom(
+        &mut self,
+        elem: &Packed<BinomElem>,
+        styles: StyleChain,
+    ) -> SourceResult<HtmlElement> {
+        let style = frac_style(styles);
+        let styles = styles.chain(&style);
+        let upper = self.row(&elem.upper, styles)?;
+        let mut lower = EcoVec::new();
+        for (i, content) in elem.lower.iter().enumerate() {
+            if i > 0 {
+                lower.push(token(tag::mo, ',', elem.span()).into());
+            }
+            lower.extend(self.nodes(content, styles)?);
+        }
+        let frac =
+            element(tag::mfrac, [upper, mrow(lower)]).with_attr(attr::linethickness, "0");
+        Ok(fenced(Some('('), eco_vec![frac.into()], Some(')')))
+    }
+
+    /// Converts a root.
+    fn convert_root(
+        &mut self,
+        elem: &Packed<RootElem>,
+        styles: StyleChain,
+    ) -> SourceResult<HtmlElement> {
+        let radicand = self.nodes(&elem.radicand, styles)?;
+        Ok(match elem.index.get_ref(styles) {
+            Some(index) => {
+                let style = EquationElem::size.set(MathSize::ScriptScript).wrap();
+                let index = self.row(index, styles.chain(&style))?;
+                element(tag::mroot, [mrow(radicand), index])
+            }
+            None => HtmlElement::new(tag::msqrt).with_children(radicand),
+        })
+    }
+
+    /// Converts an accent.
+    fn convert_accent(
+        &mut self,
+        elem: &Packed<AccentElem>,
+        styles: StyleChain,
+    ) -> SourceResult<HtmlElement> {
+        let base = self.row(&elem.base, styles)?;
+        let accent =
+            eco_vec![token(tag::mo, spacing_accent(elem.accent.0), elem.span()).into()];
+        Ok(if elem.accent.is_bottom() {
+            element(tag::munder, [base, accent]).with_attr(attr::accentunder, "true")
+        } else {
+            element(tag::mover, [base, accent]).with_attr(attr::accent, "true")
+        })
+    }
+
+    /// Converts an underline, overline, or a brace, bracket, parenthesis, or
+    /// shell with an optional annotation.
+    fn convert_underover(
+        &mut self,
+        body: &Content,
+        annotation: Option<Content>,
+        mark: char,
+        under: bool,
+        styles: StyleChain,
+    ) -> SourceResult<HtmlElement> {
+        let body = self.row(body, styles)?;
+        let mark = eco_vec![token(tag::mo, mark, Span::detached()).into()];
+        let mut elem = if under {
+            element(tag::munder, [body, mark]).with_attr(attr::accentunder, "true")
+        } else {
+            element(tag::mover, [body, mark]).with_attr(attr::accent, "true")
+        };
+
+        if let Some(annotation) = annotation {
+            let style = script_style(styles);
+            let annotation = self.row(&annotation, styles.chain(&style))?;
+            let tag = if under { tag::munder } else { tag::mover };
+            elem = element(tag, [eco_vec![elem.into()], annotation]);
+        }
+
+        Ok(elem)
+    }
+
+    /// Converts a vector.
+    fn convert_vec(
+        &mut self,
+        elem: &Packed<VecElem>,
+        styles: StyleChain,
+    ) -> SourceResult<HtmlElement> {
+        let style = frac_style(styles);
+        let inner = styles.chain(&style);
+        let mut rows = vec![];
+        for child in &elem.children {
+            rows.push(vec![self.nodes(child, inner)?]);
+        }
+        let align = text_align(elem.align.get(styles));
+        let delim = elem.delim.get(styles);
+        Ok(fenced(delim.open(), eco_vec![table(rows, |_| align).into()], delim.close()))
+    }
+
+    /// Converts a matrix.
+    fn convert_mat(
+        &mut self,
+        elem: &Packed<MatElem>,
+        styles: StyleChain,
+    ) -> SourceResult<HtmlElement> {
+        let style = frac_style(styles);
+        let inner = styles.chain(&style);
+        let mut rows = vec![];
+        for row in &elem.rows {
+            let mut cells = vec![];
+            for cell in row {
+                cells.push(self.nodes(cell, inner)?);
+            }
+            rows.push(cells);
+        }
+        let align = text_align(elem.align.get(styles));
+        let delim = elem.delim.get(styles);
+        Ok(fenced(delim.open(), eco_vec![table(rows, |_| align).into()], delim.close()))
+    }
+
+    /// Converts cases.
+    fn convert_cases(
+        &mut self,
+        elem: &Packed<CasesElem>,
+        styles: StyleChain,
+    ) -> SourceResult<HtmlElement> {
+        let style = frac_style(styles);
+        let inner = styles.chain(&style);
+        let mut rows = vec![];
+        for child in &elem.children {
+            rows.extend(self.grid(child, inner)?);
+        }
+        let table = eco_vec![table(rows, |_| Some("start")).into()];
+        let delim = elem.delim.get(styles);
+        Ok(if elem.reverse.get(styles) {
+            fenced(None, table, delim.close())
+        } else {
+            fenced(delim.open(), table, None)
+        })
+    }
+}
+
+/// Converts text into an `<mn>` for numbers and an `<mtext>` otherwise.
+fn convert_text(elem: &Packed<TextElem>, styles: StyleChain) -> HtmlElement {
+    let variant = styles.get(EquationElem::variant);
+    let bold = styles.get(EquationElem::bold);
+    // Disable auto-italic.
+    let italic = styles.get(EquationElem::italic).or(Some(false));
+    let text: EcoString = elem
+        .text
+        .chars()
+        .flat_map(|c| to_style(c, MathStyle::select(c, variant, bold, italic)))
+        .collect();
+
+    let tag = if elem.text.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        tag::mn
+    } else {
+        tag::mtext
+    };
+
+    token(tag, text, elem.span())
+}
+
+/// Converts a symbol into an `<mi>`, `<mn>`, or `<mo>` depending on its math
+/// class.
+///
+/// Styling is applied by mapping to the respective Unicode math alphanumeric
+/// characters, as MathML Core only supports `mathvariant="normal"`.
+fn convert_symbol(elem: &Packed<SymbolElem>, styles: StyleChain) -> HtmlElement {
+    let variant = styles.get(EquationElem::variant);
+    let bold = styles.get(EquationElem::bold);
+    let italic = styles.get(EquationElem::italic);
+    let text: EcoString = elem
+        .text
+        .chars()
+        .flat_map(|c| to_style(c, MathStyle::select(c, variant, bold, italic)))
+        .collect();
+
+    let mut chars = elem.text.chars();
+    let single = chars.next().filter(|_| chars.next().is_none());
+    let class = styles
+        .get(EquationElem::class)
+        .or_else(|| single.and_then(default_math_class));
+
+    let tag = match (single, class) {
+        (Some(c), _) if c.is_numeric() => tag::mn,
+        (_, None | Some(MathClass::Alphabetic)) => tag::mi,
+        (Some(c), Some(MathClass::Normal)) if !c.is_ascii_punctuation() => tag::mi,
+        _ => tag::mo,
+    };
+
+    // Renderers italicize single-character identifiers by default, so we
+    // need to opt out for letters that Typst keeps upright (e.g. capital
+    // Greek letters or `upright(x)`).
+    let upright =
+        tag == tag::mi && single.is_some_and(|c| c.is_alphabetic()) && text == elem.text;
+
+    let token = token(tag, text, elem.span());
+    if upright { token.with_attr(attr::mathvariant, "normal") } else { token }
+}
+
+/// Describes in which situation an attachment's top and bottom attachments
+/// should be displayed as limits rather than scripts.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Limits {
+    /// Always scripts.
+    Never,
+    /// Limits only in display math.
+    Display,
+    /// Always limits.
+    Always,
+}
+
+impl Limits {
+    /// The limit configuration for an attachment base.
+    fn of(base: &Content, styles: StyleChain) -> Self {
+        if let Some(elem) = base.to_packed::<LimitsElem>() {
+            if elem.inline.get(styles) { Self::Always } else { Self::Display }
+        } else if base.is::<ScriptsElem>() {
+            Self::Never
+        } else if let Some(elem) = base.to_packed::<OpElem>() {
+            if elem.limits.get(styles) { Self::Display } else { Self::Never }
+        } else if let Some(elem) = base.to_packed::<ClassElem>() {
+            match elem.class {
+                MathClass::Large => Self::Display,
+                MathClass::Relation => Self::Always,
+                _ => Self::Never,
+            }
+        } else if let Some(elem) = base.to_packed::<SymbolElem>()
+            && let Ok(c) = elem.text.parse::<char>()
+        {
+            match default_math_class(c) {
+                Some(MathClass::Large) if is_integral_char(c) => Self::Never,
+                Some(MathClass::Large) => Self::Display,
+                Some(MathClass::Relation) => Self::Always,
+                _ => Self::Never,
+            }
+        } else if let Some(elem) = base.to_packed::<EquationElem>() {
+            Self::of(&elem.body, styles)
+        } else {
+            Self::Never
+        }
+    }
+
+    /// Whether limits should be displayed in this context.
+    fn active(self, styles: StyleChain) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Display => styles.get(EquationElem::size) == MathSize::Display,
+            Self::Never => false,
+        }
+    }
+}
+
+/// Determines if the character is one of a variety of integral signs.
+fn is_integral_char(c: char) -> bool {
+    ('∫'..='∳').contains(&c) || ('⨋'..='⨜').contains(&c)
+}
+
+/// The style for sub- and superscripts and limits.
+fn script_style(styles: StyleChain) -> LazyHash<Style> {
+    let size = match styles.get(EquationElem::size) {
+        MathSize::Display | MathSize::Text => MathSize::Script,
+        MathSize::Script | MathSize::ScriptScript => MathSize::ScriptScript,
+    };
+    EquationElem::size.set(size).wrap()
+}
+
+/// The style for the numerator and denominator of fractions and the cells
+/// of matrices.
+fn frac_style(styles: StyleChain) -> LazyHash<Style> {
+    let size = match styles.get(EquationElem::size) {
+        MathSize::Display => MathSize::Text,
+        MathSize::Text => MathSize::Script,
+        MathSize::Script | MathSize::ScriptScript => MathSize::ScriptScript,
+    };
+    EquationElem::size.set(size).wrap()
+}
+
+/// Creates a token element with text content.
+fn token(tag: HtmlTag, text: impl Into<EcoString>, span: Span) -> HtmlElement {
+    HtmlElement::new(tag)
+        .with_children(eco_vec![HtmlNode::text(text, span)])
+        .spanned(span)
+}
+
+/// Creates an element whose children are the given argument lists.
+fn element(
+    tag: HtmlTag,
+    arguments: impl IntoIterator<Item = EcoVec<HtmlNode>>,
+) -> HtmlElement {
+    HtmlElement::new(tag).with_children(arguments.into_iter().flatten().collect())
+}
+
+/// Wraps nodes in an `<mrow>` unless they already consist of exactly one
+/// element, so that they can serve as an argument of an element like
+/// `<mfrac>`.
+fn mrow(nodes: EcoVec<HtmlNode>) -> EcoVec<HtmlNode> {
+    let count = nodes.iter().filter(|node| !matches!(node, HtmlNode::Tag(_))).count();
+    if count == 1 {
+        nodes
+    } else {
+        eco_vec![HtmlElement::new(tag::mrow).with_children(nodes).into()]
+    }
+}
+
+/// Surrounds nodes with delimiters.
+fn fenced(
+    open: Option<char>,
+    nodes: EcoVec<HtmlNode>,
+    close: Option<char>,
+) -> HtmlElement {
+    let mut children = EcoVec::new();
+    if let Some(open) = open {
+        children.push(token(tag::mo, open, Span::detached()).into());
+    }
+    children.extend(nodes);
+    if let Some(close) = close {
+        children.push(token(tag::mo, close, Span::detached()).into());
+    }
+    HtmlElement::new(tag::mrow).with_children(children)
+}
+
+/// Creates an `<mtable>`, with the text alignment of the cells in each column
+/// determined by a function.
+fn table(
+    rows: Vec<Vec<EcoVec<HtmlNode>>>,
+    align: impl Fn(usize) -> Option<&'static str>,
+) -> HtmlElement {
+    let rows = rows.into_iter().map(|row| {
+        let cells = row.into_iter().enumerate().map(|(i, cell)| {
+            let mut properties = css::Properties::new();
+            if let Some(align) = align(i) {
+                properties.push("text-align", align);
+            }
+            HtmlElement::new(tag::mtd)
+                .with_children(cell)
+                .with_styles(properties)
+                .into()
+        });
+        HtmlElement::new(tag::mtr).with_children(cells.collect()).into()
+    });
+    HtmlElement::new(tag::mtable).with_children(rows.collect())
+}
+
+/// The CSS text alignment for a horizontal alignment, if it isn't the
+/// default of table cells.
+fn text_align(align: HAlignment) -> Option<&'static str> {
+    match align {
+        HAlignment::Start => Some("start"),
+        HAlignment::Left => Some("left"),
+        HAlignment::Center => None,
+        HAlignment::Right => Some("right"),
+        HAlignment::End => Some("end"),
+    }
+}
+
+/// Sets an attribute on the nodes if they consist of a single `<mo>`.
+fn set_on_mo(nodes: &mut EcoVec<HtmlNode>, attr: HtmlAttr, value: &str) {
+    let mut elements = nodes.make_mut().iter_mut().filter_map(|node| match node {
+        HtmlNode::Element(element) => Some(element),
+        _ => None,
+    });
+    if let Some(element) = elements.next()
+        && element.tag == tag::mo
+        && elements.next().is_none()
+    {
+        element.attrs.push(attr, value);
+    }
+}
+
+/// The text for a number of primes.
+fn primes(count: usize) -> EcoString {
+    match count {
+        1 => '′'.into(),
+        2 => '″'.into(),
+        3 => '‴'.into(),
+        4 => '⁗'.into(),
+        _ => "′".repeat(count).into(),
+    }
+}
+
+/// The spacing variant of a combining accent, which renders better as a
+/// standalone `<mo>`.
+fn spacing_accent(c: char) -> char {
+    match c {
+        '\u{0300}' => '`',
+        '\u{0301}' => '´',
+        '\u{0302}' => '^',
+        '\u{0303}' => '~',
+        '\u{0304}' => '¯',
+        '\u{0305}' => '‾',
+        '\u{0306}' => '˘',
+        '\u{0307}' => '˙',
+        '\u{0308}' => '¨',
+        '\u{030a}' => '˚',
+        '\u{030b}' => '˝',
+        '\u{030c}' => 'ˇ',
+        '\u{20d6}' => '←',
+        '\u{20d7}' => '→',
+        '\u{20e1}' => '↔',
+        '\u{20d0}' => '↼',
+        '\u{20d1}' => '⇀',
+        c => c,
+    }
+}
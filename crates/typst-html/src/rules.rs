@@ -5,8 +5,8 @@ use ecow::{EcoString, EcoVec, eco_format};
 use typst_library::diag::{At, SourceDiagnostic, SourceResult, bail, error, warning};
 use typst_library::engine::Engine;
 use typst_library::foundations::{
-    Content, Context, NativeElement, NativeRuleMap, Selector, ShowFn, Smart, StyleChain,
-    Target,
+    Content, Context, NativeElement, NativeRuleMap, Packed, Selector, ShowFn, Smart, Str,
+    StyleChain, Target,
 };
 use typst_library::introspection::{
     Counter, History, Introspect, Introspector, Location, QueryIntrospection,
@@ -15,12 +15,14 @@ use typst_library::layout::resolve::{Cell, CellGrid, Entry, Header};
 use typst_library::layout::{
     BlockBody, BlockElem, BoxElem, HElem, OuterVAlignment, Sizing,
 };
+use typst_library::math::EquationElem;
 use typst_library::model::{
     Attribution, BibliographyElem, CiteElem, CiteGroup, CslIndentElem, CslLightElem,
-    Destination, DirectLinkElem, EmphElem, EnumElem, FigureCaption, FigureElem,
-    FootnoteElem, FootnoteEntry, FootnoteMarker, HeadingElem, LinkElem, LinkTarget,
-    ListElem, OutlineElem, OutlineEntry, OutlineNode, ParElem, ParbreakElem, QuoteElem,
-    RefElem, StrongElem, TableCell, TableElem, TermsElem, TitleElem, Works,
+    Destination, DirectLinkElem, EmphElem, EndnotesElem, EnumElem, FigureCaption,
+    FigureElem, FootnoteElem, FootnoteEntry, FootnoteMarker, HeadingElem, LinkElem,
+    LinkTarget, ListElem, MarginNoteElem, OutlineElem, OutlineEntry, OutlineNode,
+    ParElem, ParbreakElem, QuoteElem, RefElem, StrongElem, TableCell, TableElem,
+    TermsElem, TitleElem, Works,
 };
 use typst_library::text::{
     HighlightElem, LinebreakElem, OverlineElem, RawElem, RawLine, SmallcapsElem,
@@ -31,7 +33,10 @@ use typst_macros::elem;
 use typst_syntax::Span;
 use typst_utils::singleton;
 
-use crate::{FrameElem, HtmlAttr, HtmlAttrs, HtmlElem, HtmlTag, attr, css, tag};
+use crate::stylesheet::BlockStyles;
+use crate::{
+    FrameElem, HtmlAttr, HtmlAttrs, HtmlElem, HtmlTag, SplitElem, attr, css, tag,
+};
 
 /// Registers show rules for the [HTML target](Target::Html).
 pub fn register(rules: &mut NativeRuleMap) {
@@ -55,6 +60,8 @@ pub fn register(rules: &mut NativeRuleMap) {
     rules.register(Html, FOOTNOTE_MARKER_RULE);
     rules.register(Html, FOOTNOTE_CONTAINER_RULE);
     rules.register(Html, FOOTNOTE_ENTRY_RULE);
+    rules.register(Html, ENDNOTES_RULE);
+    rules.register(Html, MARGIN_NOTE_RULE);
     rules.register(Html, OUTLINE_RULE);
     rules.register(Html, OUTLINE_ENTRY_RULE);
     rules.register(Html, REF_RULE);
@@ -79,6 +86,9 @@ pub fn register(rules: &mut NativeRuleMap) {
     rules.register(Html, BLOCK_RULE);
     rules.register(Html, BOX_RULE);
 
+    // Math.
+    rules.register(Html, EQUATION_RULE);
+
     // Visualize.
     rules.register(Html, IMAGE_RULE);
 
@@ -86,6 +96,11 @@ pub fn register(rules: &mut NativeRuleMap) {
     // it should be a no-op so that nested frames don't break (things like `show
     // math.equation: html.frame` can result in nested ones).
     rules.register::<FrameElem>(Paged, |elem, _, _| Ok(elem.body.clone()));
+
+    // `html.split` only marks a position for multi-file export and produces
+    // nothing by itself.
+    rules.register::<SplitElem>(Html, |_, _, _| Ok(Content::empty()));
+    rules.register::<SplitElem>(Paged, |_, _, _| Ok(Content::empty()));
 }
 
 const PAR_RULE: ShowFn<ParElem> =
@@ -344,6 +359,12 @@ const FOOTNOTE_RULE: ShowFn<FootnoteElem> = |elem, engine, styles| {
         .pack()
         .styled(HtmlElem::role.set(Some("doc-noteref".into())));
 
+    // Endnotes are listed by an `endnotes` element rather than by the footnote
+    // container.
+    if elem.is_endnote() {
+        return Ok(HElem::hole().clone() + link);
+    }
+
     // Indicates the presence of a default footnote rule to emit an error when
     // no footnote container is available.
     let marker = FootnoteMarker::new().pack().spanned(span);
@@ -394,27 +415,42 @@ const FOOTNOTE_CONTAINER_RULE: ShowFn<FootnoteContainer> = |elem, engine, _| {
     let notes =
         engine.introspect(QueryIntrospection(FootnoteElem::ELEM.select(), elem.span()));
 
-    if notes.is_empty() {
-        return Ok(Content::empty());
-    }
-
-    // Create entries for all footnotes in the document.
-    let items = notes.into_iter().filter_map(|note| {
+    // Group the footnotes in the document by series, in the order in which the
+    // series first appear.
+    let mut groups: Vec<(Option<Str>, Vec<Packed<FootnoteElem>>)> = vec![];
+    for note in notes {
         let note = note.into_packed::<FootnoteElem>().unwrap();
-        if note.is_ref() {
-            return None;
+        if note.is_ref() || note.is_endnote() {
+            continue;
+        }
+
+        let series = note.series.get_cloned(StyleChain::default());
+        match groups.iter_mut().find(|(s, _)| *s == series) {
+            Some((_, group)) => group.push(note),
+            None => groups.push((series, vec![note])),
         }
+    }
+
+    Ok(Content::sequence(groups.into_iter().map(|(_, notes)| notes_section(None, notes))))
+};
 
+const ENDNOTES_RULE: ShowFn<EndnotesElem> = |elem, engine, styles| {
+    let title = elem.realize_title(styles);
+    let notes = elem.notes(engine, styles);
+    Ok(notes_section(title, notes))
+};
+
+/// Creates a section with entries for the given notes.
+fn notes_section(title: Option<Content>, notes: Vec<Packed<FootnoteElem>>) -> Content {
+    let items = notes.into_iter().map(|note| {
         let loc = note.location().unwrap();
         let span = note.span();
-        Some(
-            HtmlElem::new(tag::li)
-                .with_body(Some(FootnoteEntry::new(note).pack().spanned(span)))
-                .with_parent(loc)
-                .pack()
-                .located(loc.variant(1))
-                .spanned(span),
-        )
+        HtmlElem::new(tag::li)
+            .with_body(Some(FootnoteEntry::new(note).pack().spanned(span)))
+            .with_parent(loc)
+            .pack()
+            .located(loc.variant(1))
+            .spanned(span)
     });
 
     // There can be multiple footnotes in a container, so they semantically
@@ -427,12 +463,12 @@ const FOOTNOTE_CONTAINER_RULE: ShowFn<FootnoteContainer> = |elem, engine, _| {
 
     // The user may want to style the whole footnote element so we wrap it in an
     // additional selectable container. This is also how it's done in the ARIA
-    // spec (although there, the section also contains an additional heading).
-    Ok(HtmlElem::new(tag::section)
+    // spec, where the section may also contain a heading.
+    HtmlElem::new(tag::section)
         .with_attr(attr::role, "doc-endnotes")
-        .with_body(Some(list))
-        .pack())
-};
+        .with_body(Some(Content::sequence(title.into_iter().chain(Some(list)))))
+        .pack()
+}
 
 const FOOTNOTE_ENTRY_RULE: ShowFn<FootnoteEntry> = |elem, engine, styles| {
     let (prefix, body) = elem.realize(engine, styles)?;
@@ -449,6 +485,16 @@ const FOOTNOTE_ENTRY_RULE: ShowFn<FootnoteEntry> = |elem, engine, styles| {
     Ok(backlink + body)
 };
 
+// HTML has no notion of page margins, so the note stays in the running text.
+// The `note` role marks it as parenthetic to the main content.
+const MARGIN_NOTE_RULE: ShowFn<MarginNoteElem> = |elem, _, _| {
+    Ok(HtmlElem::new(tag::span)
+        .with_attr(attr::class, "margin-note")
+        .with_attr(attr::role, "note")
+        .with_body(Some(elem.body.clone()))
+        .pack())
+};
+
 const OUTLINE_RULE: ShowFn<OutlineElem> = |elem, engine, styles| {
     fn convert_list(list: Vec<OutlineNode>) -> Content {
         // The Digital Publishing ARIA spec also proposed to add
@@ -798,7 +844,10 @@ const BLOCK_RULE: ShowFn<BlockElem> = |elem, _, styles| {
         }
     };
 
-    Ok(HtmlElem::new(tag::div).with_body(body).pack())
+    Ok(HtmlElem::new(tag::div)
+        .with_body(body)
+        .with_block_styles(Some(BlockStyles::new(elem, styles)))
+        .pack())
 };
 
 // TODO: This is rather incomplete.
@@ -809,12 +858,38 @@ const BOX_RULE: ShowFn<BoxElem> = |elem, _, styles| {
         .pack())
 };
 
+const EQUATION_RULE: ShowFn<EquationElem> = |elem, engine, styles| {
+    let block = elem.block.get(styles);
+    let alt = elem.alt.get_cloned(styles).or_else(|| equation_alt(engine, elem));
+    Ok(HtmlElem::new(tag::math)
+        .with_optional_attr(attr::display, block.then_some("block"))
+        .with_optional_attr(attr::alttext, alt)
+        .with_body(Some(elem.body.clone()))
+        .pack())
+};
+
+/// Derives an alternative description for an equation without an explicit
+/// `alt` text from its source code (or its plain text if it was not written
+/// in `$` delimiters).
+fn equation_alt(engine: &Engine, elem: &Packed<EquationElem>) -> Option<EcoString> {
+    let span = elem.span();
+    let source = span.id().and_then(|id| engine.world.source(id).ok());
+    let text = match source.as_ref().and_then(|source| {
+        source
+            .text()
+            .get(source.range(span)?)
+            .filter(|text| text.starts_with('$'))
+    }) {
+        Some(text) => text.trim_matches('$').trim().into(),
+        None => elem.body.plain_text(),
+    };
+    (!text.is_empty()).then_some(text)
+}
+
 const IMAGE_RULE: ShowFn<ImageElem> = |elem, engine, styles| {
     let image = elem.decode(engine, styles)?;
 
     let mut attrs = HtmlAttrs::new();
-    attrs.push(attr::src, typst_svg::convert_image_to_base64_url(&image));
-
     if let Some(alt) = elem.alt.get_cloned(styles) {
         attrs.push(attr::alt, alt);
     }
@@ -839,5 +914,9 @@ const IMAGE_RULE: ShowFn<ImageElem> = |elem, engine, styles| {
         Sizing::Fr(_) => {}
     }
 
-    Ok(HtmlElem::new(tag::img).with_attrs(attrs).with_styles(inline).pack())
+    Ok(HtmlElem::new(tag::img)
+        .with_attrs(attrs)
+        .with_styles(inline)
+        .with_image(Some(image))
+        .pack())
 };
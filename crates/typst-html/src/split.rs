@@ -0,0 +1,576 @@
+//! Splitting of HTML documents into multiple files.
+
+use std::num::NonZeroUsize;
+
+use ecow::{EcoString, EcoVec, eco_format, eco_vec};
+use rustc_hash::{FxHashMap, FxHashSet};
+use typst_library::diag::{SourceResult, bail};
+use typst_library::foundations::StyleChain;
+use typst_library::introspection::Tag;
+use typst_library::model::HeadingElem;
+use typst_syntax::Span;
+
+use crate::{
+    HtmlAssets, HtmlDocument, HtmlElement, HtmlNode, HtmlOptions, SplitElem, attr, tag,
+};
+
+/// Settings for splitting an HTML document into multiple files.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct HtmlSplitOptions {
+    /// If set, each heading of at most this level starts a new file, in
+    /// addition to each `html.split` element.
+    pub heading_level: Option<NonZeroUsize>,
+}
+
+/// One file of a document that was split into multiple files.
+#[derive(Debug, Clone)]
+pub struct HtmlPage {
+    /// The file's path, relative to the output directory.
+    ///
+    /// The first page is always `index.html`.
+    pub path: EcoString,
+    /// The page's title, as used in links to it.
+    pub title: EcoString,
+    /// The encoded HTML.
+    pub html: String,
+}
+
+/// A document split into multiple files, along with their assets.
+#[derive(Debug, Clone)]
+pub struct HtmlSplitOutput {
+    /// The pages, in document order.
+    pub pages: Vec<HtmlPage>,
+    /// Files referenced by the pages. Empty unless assets are external.
+    pub assets: HtmlAssets,
+}
+
+/// Encodes an HTML document into multiple files.
+///
+/// Only split points that are direct children of the `<body>` start new
+/// files. The content before the first split point forms the index page,
+/// which additionally lists all other pages unless it already contains an
+/// outline. Each page ends with links to the previous and next page.
+///
+/// Links to elements that ended up on another page (e.g. from references,
+/// footnotes, and outline entries) are rewritten to point to the right file.
+/// Links within `html.frame` elements currently always point into the same
+/// page.
+pub fn html_split(
+    document: &HtmlDocument,
+    split: &HtmlSplitOptions,
+    options: &HtmlOptions,
+) -> SourceResult<HtmlSplitOutput> {
+    let root = &document.root;
+    let body = find_body(root)?;
+    let mut chunks = split_body(body, split.heading_level);
+    assign_paths(&mut chunks, "html");
+
+    let doc_title = document.info.title.clone();
+    let titles = page_titles(&chunks, doc_title.as_ref());
+
+    // Determine on which page each element ID ended up.
+    let mut owners = FxHashMap::default();
+    for (i, chunk) in chunks.iter().enumerate() {
+        collect_ids(&chunk.nodes, i, &mut owners);
+    }
+
+    let links: Vec<_> = chunks
+        .iter()
+        .zip(&titles)
+        .map(|(chunk, title)| (&chunk.path, title))
+        .collect();
+
+    let mut assets = HtmlAssets::new();
+    let mut pages = Vec::with_capacity(chunks.len());
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut children = chunk.nodes.clone();
+        rewrite_links(&mut children, i, &owners, &links);
+
+        if i == 0 && links.len() > 1 && !contains_outline(&children) {
+            children.push(contents(&links[1..]).into());
+        }
+
+        if links.len() > 1 {
+            children.push(navigation(&links, i).into());
+        }
+
+        let title = match &doc_title {
+            Some(doc_title) if i > 0 => eco_format!("{} – {doc_title}", titles[i]),
+            _ => titles[i].clone(),
+        };
+
+        let mut page = root.clone();
+        for node in page.children.make_mut() {
+            let HtmlNode::Element(element) = node else { continue };
+            if element.tag == tag::head {
+                set_title(element, title.clone());
+            } else if element.tag == tag::body {
+                element.children = children.clone();
+            }
+        }
+
+        if options.stylesheet {
+            crate::stylesheet::embed(&mut page, false);
+        }
+
+        pages.push(HtmlPage {
+            path: chunk.path.clone(),
+            title: titles[i].clone(),
+            html: crate::encode::encode(
+                &page,
+                &document.introspector,
+                options.external_assets.then_some(&mut assets),
+                options.xhtml,
+            )?,
+        });
+    }
+
+    Ok(HtmlSplitOutput { pages, assets })
+}
+
+/// Finds the `<body>` of a document to split.
+pub(crate) fn find_body(root: &HtmlElement) -> SourceResult<&HtmlElement> {
+    match root.children.iter().find_map(|node| match node {
+        HtmlNode::Element(element) if element.tag == tag::body => Some(element),
+        _ => None,
+    }) {
+        Some(body) => Ok(body),
+        None => bail!(root.span, "cannot split a document without a `<body>` element"),
+    }
+}
+
+/// A part of the body that becomes its own page.
+#[derive(Default)]
+pub(crate) struct Chunk {
+    /// The nodes on the page.
+    pub nodes: EcoVec<HtmlNode>,
+    /// The page's title, if known.
+    pub title: Option<EcoString>,
+    /// A name from which to derive the file name.
+    pub name: Option<EcoString>,
+    /// The final file path.
+    pub path: EcoString,
+}
+
+/// Splits the body's children at split points.
+pub(crate) fn split_body(body: &HtmlElement, level: Option<NonZeroUsize>) -> Vec<Chunk> {
+    let mut chunks = vec![Chunk::default()];
+    for node in &body.children {
+        if let HtmlNode::Tag(Tag::Start(elem, _)) = node {
+            let label = || elem.label().map(|label| label.resolve().as_str().into());
+            if let Some(split) = elem.to_packed::<SplitElem>() {
+                chunks.push(Chunk {
+                    title: split.title.get_cloned(StyleChain::default()),
+                    name: split.path.get_cloned(StyleChain::default()).or_else(label),
+                    ..Default::default()
+                });
+            } else if let Some(heading) = elem.to_packed::<HeadingElem>() {
+                let text = heading.body.plain_text();
+                if level.is_some_and(|level| {
+                    heading.resolve_level(StyleChain::default()) <= level
+                }) {
+                    chunks.push(Chunk {
+                        title: Some(text),
+                        name: label(),
+                        ..Default::default()
+                    });
+                } else {
+                    // Pages started by `html.split` without a title are
+                    // named after their first heading.
+                    let last = chunks.last_mut().unwrap();
+                    if last.title.is_none() {
+                        last.title = Some(text);
+                    }
+                }
+            }
+        }
+
+        chunks.last_mut().unwrap().nodes.push(node.clone());
+    }
+    chunks
+}
+
+/// Assigns unique file paths with the given extension to the chunks.
+pub(crate) fn assign_paths(chunks: &mut [Chunk], extension: &str) {
+    let mut used = FxHashSet::default();
+    used.insert(EcoString::from("index"));
+
+    for (i, chunk) in chunks.iter_mut().enumerate() {
+        if i == 0 {
+            chunk.path = eco_format!("index.{extension}");
+            continue;
+        }
+
+        let base = [&chunk.name, &chunk.title]
+            .into_iter()
+            .flatten()
+            .map(|name| slugify(name))
+            .find(|slug| !slug.is_empty())
+            .unwrap_or_else(|| eco_format!("page-{}", i + 1));
+
+        let mut stem = base.clone();
+        let mut n = 2;
+        while !used.insert(stem.clone()) {
+            stem = eco_format!("{base}-{n}");
+            n += 1;
+        }
+
+        chunk.path = eco_format!("{stem}.{extension}");
+    }
+}
+
+/// Determines the titles of the pages.
+///
+/// The first page is named after the document, if it has a title.
+pub(crate) fn page_titles(
+    chunks: &[Chunk],
+    doc_title: Option<&EcoString>,
+) -> Vec<EcoString> {
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| match (i, &chunk.title, doc_title) {
+            (0, _, Some(title)) | (_, Some(title), _) => title.clone(),
+            (0, None, None) => "Index".into(),
+            _ => eco_format!("Page {}", i + 1),
+        })
+        .collect()
+}
+
+/// Turns text into a lowercase, dash-separated file name.
+fn slugify(text: &str) -> EcoString {
+    let mut slug = EcoString::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            for lower in c.to_lowercase() {
+                slug.push(lower);
+            }
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    if slug.ends_with('-') {
+        slug.pop();
+    }
+
+    slug
+}
+
+/// Records the page of each element ID in the nodes.
+pub(crate) fn collect_ids(
+    nodes: &[HtmlNode],
+    page: usize,
+    owners: &mut FxHashMap<EcoString, usize>,
+) {
+    for node in nodes {
+        if let HtmlNode::Element(element) = node {
+            if let Some(id) = element.attrs.get(attr::id) {
+                owners.insert(id.clone(), page);
+            }
+            collect_ids(&element.children, page, owners);
+        }
+    }
+}
+
+/// Rewrites fragment links to elements on other pages so that they point to
+/// the right file.
+pub(crate) fn rewrite_links(
+    nodes: &mut EcoVec<HtmlNode>,
+    page: usize,
+    owners: &FxHashMap<EcoString, usize>,
+    links: &[(&EcoString, &EcoString)],
+) {
+    for node in nodes.make_mut() {
+        let HtmlNode::Element(element) = node else { continue };
+        for (key, value) in element.attrs.0.make_mut() {
+            if *key == attr::href
+                && let Some(id) = value.strip_prefix('#')
+                && let Some(&owner) = owners.get(id)
+                && owner != page
+            {
+                *value = eco_format!("{}#{id}", links[owner].0);
+            }
+        }
+        rewrite_links(&mut element.children, page, owners, links);
+    }
+}
+
+/// Whether the nodes contain an outline.
+fn contains_outline(nodes: &[HtmlNode]) -> bool {
+    nodes.iter().any(|node| match node {
+        HtmlNode::Element(element) => {
+            (element.tag == tag::nav
+                && element.attrs.get(attr::role).is_some_and(|role| role == "doc-toc"))
+                || contains_outline(&element.children)
+        }
+        _ => false,
+    })
+}
+
+/// Creates a list of links to the given pages for the index page.
+fn contents(links: &[(&EcoString, &EcoString)]) -> HtmlElement {
+    let items = links
+        .iter()
+        .map(|&(path, title)| {
+            HtmlNode::from(
+                HtmlElement::new(tag::li)
+                    .with_children(eco_vec![link(path, title).into()]),
+            )
+        })
+        .collect();
+
+    let list = HtmlElement::new(tag::ol)
+        .with_attr(attr::style, "list-style-type: none")
+        .with_children(items);
+
+    HtmlElement::new(tag::nav)
+        .with_attr(attr::role, "doc-toc")
+        .with_children(eco_vec![list.into()])
+}
+
+/// Creates links to the previous and next page.
+fn navigation(links: &[(&EcoString, &EcoString)], page: usize) -> HtmlElement {
+    let mut children = EcoVec::new();
+    if let Some(&(path, title)) = page.checked_sub(1).and_then(|i| links.get(i)) {
+        children.push(link(path, title).with_attr(attr::rel, "prev").into());
+    }
+    if let Some(&(path, title)) = links.get(page + 1) {
+        children.push(link(path, title).with_attr(attr::rel, "next").into());
+    }
+    HtmlElement::new(tag::nav)
+        .with_attr(attr::class, "pagination")
+        .with_children(children)
+}
+
+/// Creates a link with the given destination and text.
+pub(crate) fn link(href: &EcoString, text: &EcoString) -> HtmlElement {
+    HtmlElement::new(tag::a)
+        .with_attr(attr::href, href.clone())
+        .with_children(eco_vec![HtmlNode::text(text.clone(), Span::detached())])
+}
+
+/// Sets the contents of the `<title>` element in the `<head>`.
+pub(crate) fn set_title(head: &mut HtmlElement, title: EcoString) {
+    let text = eco_vec![HtmlNode::text(title, Span::detached())];
+    for node in head.children.make_mut() {
+        if let HtmlNode::Element(element) = node
+            && element.tag == tag::title
+        {
+            element.children = text;
+            return;
+        }
+    }
+
+    // Keep the `<title>` after the `<meta charset>` and viewport elements,
+    // where it would have been generated.
+    let index = head.children.len().min(2);
+    head.children
+        .insert(index, HtmlElement::new(tag::title).with_children(text).into());
+}
+
+#[cfg(test)]
+mod tests {
+    use typst_library::foundations::{Content, Label, NativeElement, Smart};
+    use typst_library::introspection::{Introspector, TagFlags};
+    use typst_library::model::DocumentInfo;
+    use typst_library::text::TextElem;
+    use typst_utils::PicoStr;
+
+    use super::*;
+    use crate::HtmlTag;
+
+    fn text(text: &str) -> HtmlNode {
+        HtmlNode::text(text, Span::detached())
+    }
+
+    fn start(elem: Content) -> HtmlNode {
+        HtmlNode::Tag(Tag::Start(elem, TagFlags { introspectable: true, tagged: false }))
+    }
+
+    fn heading(level: usize, title: &str) -> Content {
+        HeadingElem::new(TextElem::packed(title))
+            .with_level(Smart::Custom(NonZeroUsize::new(level).unwrap()))
+            .pack()
+    }
+
+    fn label(name: &str) -> Label {
+        Label::new(PicoStr::intern(name)).unwrap()
+    }
+
+    fn element(tag: HtmlTag, id: Option<&str>, children: Vec<HtmlNode>) -> HtmlNode {
+        let mut element = HtmlElement::new(tag).with_children(children.into());
+        if let Some(id) = id {
+            element = element.with_attr(attr::id, id);
+        }
+        element.into()
+    }
+
+    fn anchor(href: &str) -> HtmlNode {
+        HtmlElement::new(tag::a)
+            .with_attr(attr::href, href)
+            .with_children(eco_vec![text("link")])
+            .into()
+    }
+
+    fn chunk(name: Option<&str>, title: Option<&str>) -> Chunk {
+        Chunk {
+            name: name.map(Into::into),
+            title: title.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    fn body(children: Vec<HtmlNode>) -> HtmlElement {
+        HtmlElement::new(tag::body).with_children(children.into())
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Getting Started"), "getting-started");
+        assert_eq!(slugify("  What's new?  "), "what-s-new");
+        assert_eq!(slugify("Über Ärger"), "über-ärger");
+        assert_eq!(slugify("1.2 -- Setup"), "1-2-setup");
+        assert_eq!(slugify("!?"), "");
+    }
+
+    #[test]
+    fn test_assign_paths() {
+        let mut chunks = [
+            chunk(Some("intro"), None),
+            chunk(None, Some("Setup")),
+            chunk(None, Some("Setup")),
+            chunk(Some("setup"), Some("Other")),
+            chunk(Some("index"), None),
+            chunk(Some("?"), Some("Fallback")),
+            chunk(None, Some("!!")),
+            chunk(None, None),
+        ];
+        assign_paths(&mut chunks, "html");
+        let paths: Vec<_> = chunks.iter().map(|chunk| chunk.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "index.html",
+                "setup.html",
+                "setup-2.html",
+                "setup-3.html",
+                "index-2.html",
+                "fallback.html",
+                "page-7.html",
+                "page-8.html",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_body() {
+        let split = SplitElem::new().with_path(Some("custom".into())).pack();
+        let labelled = SplitElem::new().pack().labelled(label("labelled"));
+        let body = body(vec![
+            text("A"),
+            start(heading(1, "First")),
+            text("B"),
+            start(split),
+            start(heading(2, "Named")),
+            start(heading(2, "Ignored")),
+            start(labelled),
+            text("C"),
+        ]);
+
+        let chunks = split_body(&body, None);
+        let summary: Vec<_> = chunks
+            .iter()
+            .map(|chunk| {
+                (chunk.nodes.len(), chunk.title.as_deref(), chunk.name.as_deref())
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (3, Some("First"), None),
+                (3, Some("Named"), Some("custom")),
+                (2, None, Some("labelled")),
+            ]
+        );
+
+        // With a heading level, headings up to that level start new pages.
+        let chunks = split_body(&body, NonZeroUsize::new(1));
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[0].nodes.len(), 1);
+        assert_eq!(chunks[1].title.as_deref(), Some("First"));
+    }
+
+    #[test]
+    fn test_rewrite_links() {
+        let pages = [
+            vec![element(tag::p, Some("a"), vec![anchor("#b"), anchor("#a")])],
+            vec![element(tag::div, None, vec![element(tag::p, Some("b"), vec![])])],
+            vec![anchor("#a"), anchor("#missing"), anchor("https://typst.app")],
+        ];
+
+        let mut owners = FxHashMap::default();
+        for (i, nodes) in pages.iter().enumerate() {
+            collect_ids(nodes, i, &mut owners);
+        }
+
+        let paths: Vec<EcoString> =
+            vec!["index.html".into(), "b.html".into(), "c.html".into()];
+        let titles: Vec<EcoString> = vec!["A".into(), "B".into(), "C".into()];
+        let links: Vec<_> = paths.iter().zip(&titles).collect();
+
+        let hrefs = |i: usize| {
+            let mut nodes: EcoVec<HtmlNode> = pages[i].clone().into();
+            rewrite_links(&mut nodes, i, &owners, &links);
+            let mut hrefs = vec![];
+            collect_hrefs(&nodes, &mut hrefs);
+            hrefs
+        };
+
+        assert_eq!(hrefs(0), ["b.html#b", "#a"]);
+        assert_eq!(hrefs(2), ["index.html#a", "#missing", "https://typst.app"]);
+    }
+
+    fn collect_hrefs(nodes: &[HtmlNode], hrefs: &mut Vec<EcoString>) {
+        for node in nodes {
+            if let HtmlNode::Element(element) = node {
+                hrefs.extend(element.attrs.get(attr::href).cloned());
+                collect_hrefs(&element.children, hrefs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_html_split() {
+        let head = element(tag::head, None, vec![]);
+        let body = body(vec![
+            element(tag::p, Some("intro"), vec![anchor("#usage")]),
+            start(SplitElem::new().with_title(Some("Usage".into())).pack()),
+            element(tag::p, Some("usage"), vec![anchor("#intro")]),
+        ]);
+        let document = HtmlDocument {
+            root: HtmlElement::new(tag::html).with_children(eco_vec![head, body.into()]),
+            info: DocumentInfo { title: Some("Manual".into()), ..Default::default() },
+            introspector: Introspector::default(),
+        };
+
+        let output =
+            html_split(&document, &HtmlSplitOptions::default(), &HtmlOptions::default())
+                .unwrap();
+        let [index, usage] = output.pages.as_slice() else { panic!() };
+
+        assert_eq!(index.path, "index.html");
+        assert_eq!(index.title, "Manual");
+        assert!(index.html.contains("<title>Manual</title>"));
+        assert!(index.html.contains(r#"<a href="usage.html#usage">"#));
+        assert!(index.html.contains(r#"<nav role="doc-toc">"#));
+        assert!(index.html.contains(r#"<a href="usage.html" rel="next">Usage</a>"#));
+        assert!(!index.html.contains("rel=\"prev\""));
+
+        assert_eq!(usage.path, "usage.html");
+        assert!(usage.html.contains("<title>Usage – Manual</title>"));
+        assert!(usage.html.contains(r#"<a href="index.html#intro">"#));
+        assert!(usage.html.contains(r#"<a href="index.html" rel="prev">Manual</a>"#));
+        assert!(!usage.html.contains("doc-toc"));
+    }
+}
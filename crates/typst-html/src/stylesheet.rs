@@ -0,0 +1,583 @@
+//! Generation of a stylesheet from Typst styles.
+//!
+//! During conversion, each HTML element records the subset of its styles that
+//! we can translate (see [`SheetStyles`]). Only if a stylesheet is requested,
+//! [`embed`] translates them into CSS declarations, drops those that an element
+//! would inherit from its parent anyway, gives each element with remaining
+//! declarations a class, and emits the corresponding rules into a `<style>`
+//! element in the `<head>`.
+//!
+//! Style changes within a paragraph that don't produce an element of their
+//! own (e.g. `[#text(red)[word]]`) are not reflected.
+
+use std::fmt::Write;
+
+use ecow::{EcoString, EcoVec, eco_format, eco_vec};
+use rustc_hash::FxHashMap;
+use typst_library::foundations::{Packed, Smart, StyleChain};
+use typst_library::layout::{Abs, BlockElem, Length, Rel, Sides, Spacing};
+use typst_library::model::ParElem;
+use typst_library::text::{FontList, FontStyle, FontVariant, FontWeight, TextElem};
+use typst_library::visualize::{FixedStroke, Paint, Stroke};
+use typst_syntax::Span;
+use typst_utils::Numeric;
+
+use crate::{HtmlElement, HtmlNode, attr, css, tag};
+
+/// Properties that descendants inherit in CSS. Declaring them with the
+/// parent's value is redundant.
+const INHERITED: &[&str] =
+    &["font-family", "font-size", "color", "font-weight", "font-style"];
+
+/// The styles of an HTML element that a generated stylesheet reflects.
+///
+/// They are recorded as plain values during conversion and only translated
+/// into CSS if a stylesheet is requested.
+#[derive(Debug, Default, Clone, PartialEq, Hash)]
+pub struct SheetStyles {
+    text: Option<TextStyles>,
+    par: Option<ParStyles>,
+    block: Option<BlockStyles>,
+}
+
+impl SheetStyles {
+    /// Records the text styles in the style chain.
+    pub fn new(styles: StyleChain) -> Self {
+        Self {
+            text: Some(TextStyles {
+                font: styles.get_cloned(TextElem::font),
+                size: styles.resolve(TextElem::size),
+                fill: styles.get_cloned(TextElem::fill),
+                variant: typst_library::text::variant(styles),
+            }),
+            ..Self::default()
+        }
+    }
+
+    /// Additionally records the styles of a paragraph.
+    ///
+    /// Whether the paragraph directly follows another one determines whether
+    /// the first-line indent applies.
+    pub fn with_par(mut self, styles: StyleChain, follows_par: bool) -> Self {
+        let indent = styles.get(ParElem::first_line_indent);
+        self.par = Some(ParStyles {
+            justify: styles.get(ParElem::justify),
+            indent: (indent.all || follows_par).then_some(indent.amount),
+            spacing: styles.get(ParElem::spacing),
+        });
+        self
+    }
+
+    /// Additionally records the styles of the block an element was created
+    /// from.
+    pub fn with_block(mut self, block: Option<BlockStyles>) -> Self {
+        self.block = block;
+        self
+    }
+
+    /// Translates the styles into CSS declarations.
+    fn declarations(&self) -> Declarations {
+        let mut declarations = Declarations::default();
+        if let Some(text) = &self.text {
+            text.declare(&mut declarations);
+        }
+        if let Some(par) = &self.par {
+            par.declare(&mut declarations);
+        }
+        if let Some(block) = &self.block {
+            block.declare(&mut declarations);
+        }
+        declarations
+    }
+}
+
+/// The text styles of an element.
+#[derive(Debug, Clone, PartialEq, Hash)]
+struct TextStyles {
+    font: FontList,
+    size: Abs,
+    fill: Paint,
+    variant: FontVariant,
+}
+
+impl TextStyles {
+    fn declare(&self, declarations: &mut Declarations) {
+        let mut families = EcoString::new();
+        for family in &self.font {
+            if !families.is_empty() {
+                families.push_str(", ");
+            }
+            let name = family.as_str().replace('\\', "\\\\").replace('"', "\\\"");
+            write!(families, "\"{name}\"").unwrap();
+        }
+        declarations.set("font-family", families);
+
+        let size = Length::from(self.size);
+        declarations.set("font-size", eco_format!("{}", css::length(size)));
+
+        // Gradients and tilings have no equivalent text color.
+        if let Paint::Solid(color) = self.fill {
+            declarations.set("color", eco_format!("{}", css::color(color)));
+        }
+
+        declarations
+            .set("font-weight", eco_format!("{}", self.variant.weight.to_number()));
+        declarations.set(
+            "font-style",
+            match self.variant.style {
+                FontStyle::Normal => "normal",
+                FontStyle::Italic => "italic",
+                FontStyle::Oblique => "oblique",
+            },
+        );
+    }
+}
+
+/// The styles of a paragraph.
+#[derive(Debug, Clone, PartialEq, Hash)]
+struct ParStyles {
+    justify: bool,
+    /// The first-line indent, if it applies to this paragraph.
+    indent: Option<Length>,
+    spacing: Length,
+}
+
+impl ParStyles {
+    fn declare(&self, declarations: &mut Declarations) {
+        if self.justify {
+            declarations.set("text-align", "justify");
+        }
+
+        if let Some(indent) = self.indent
+            && !indent.is_zero()
+        {
+            declarations.set("text-indent", eco_format!("{}", css::length(indent)));
+        }
+
+        // Browsers already separate paragraphs by about the default spacing,
+        // so we only override it when it was changed.
+        if self.spacing != StyleChain::default().get(ParElem::spacing) {
+            declarations
+                .set("margin-block", eco_format!("{}", css::length(self.spacing)));
+        }
+    }
+}
+
+/// The fill, stroke, inset, and spacing of a block.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct BlockStyles {
+    fill: Option<Paint>,
+    stroke: Sides<Option<FixedStroke>>,
+    inset: Sides<Option<Rel>>,
+    above: Smart<Spacing>,
+    below: Smart<Spacing>,
+}
+
+impl BlockStyles {
+    /// Records the styles of a block.
+    pub fn new(elem: &Packed<BlockElem>, styles: StyleChain) -> Self {
+        Self {
+            fill: elem.fill.get_cloned(styles),
+            stroke: elem
+                .stroke
+                .resolve(styles)
+                .unwrap_or_default()
+                .map(|side| side.map(Stroke::unwrap_or_default)),
+            inset: elem.inset.get(styles),
+            above: elem.above.get(styles),
+            below: elem.below.get(styles),
+        }
+    }
+
+    fn declare(&self, declarations: &mut Declarations) {
+        if let Some(Paint::Solid(color)) = self.fill {
+            declarations.set("background-color", eco_format!("{}", css::color(color)));
+        }
+
+        if self.stroke.iter().any(Option::is_some) {
+            let borders = self.stroke.as_ref().map(|side| match side {
+                Some(stroke) => {
+                    let style = if stroke.dash.is_some() { "dashed" } else { "solid" };
+                    let mut value = eco_format!("{}pt {style}", stroke.thickness.to_pt());
+                    if let Paint::Solid(color) = stroke.paint {
+                        write!(value, " {}", css::color(color)).unwrap();
+                    }
+                    value
+                }
+                None => "none".into(),
+            });
+            if borders.is_uniform() {
+                declarations.set("border", borders.top);
+            } else {
+                declarations.set("border-top", borders.top);
+                declarations.set("border-right", borders.right);
+                declarations.set("border-bottom", borders.bottom);
+                declarations.set("border-left", borders.left);
+            }
+        }
+
+        if self.inset.iter().any(Option::is_some) {
+            let padding = self
+                .inset
+                .map(|side| eco_format!("{}", css::rel(side.unwrap_or_default())));
+            declarations.set("padding", shorthand(padding));
+        }
+
+        for (property, spacing) in
+            [("margin-block-start", self.above), ("margin-block-end", self.below)]
+        {
+            // Fractional spacing has no CSS equivalent and automatic spacing is
+            // determined by the surrounding paragraphs.
+            if let Smart::Custom(Spacing::Rel(rel)) = spacing {
+                declarations.set(property, eco_format!("{}", css::rel(rel)));
+            }
+        }
+    }
+}
+
+/// CSS declarations, as pairs of property and value.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+struct Declarations(EcoVec<(EcoString, EcoString)>);
+
+impl Declarations {
+    /// Sets a property, replacing a previous declaration of it.
+    fn set(&mut self, property: &str, value: impl Into<EcoString>) {
+        let value = value.into();
+        match self.0.iter().position(|(k, _)| k == property) {
+            Some(i) => self.0.make_mut()[i].1 = value,
+            None => self.0.push((property.into(), value)),
+        }
+    }
+
+    /// Finds the value of a property.
+    fn get(&self, property: &str) -> Option<&EcoString> {
+        self.0.iter().find(|(k, _)| k == property).map(|(_, v)| v)
+    }
+}
+
+/// Moves the declarations of all elements into a stylesheet in the `<head>`.
+///
+/// Unless `explicit` is set, the reader is assumed to use Typst's default
+/// text styles, so declarations matching them are omitted.
+pub fn embed(root: &mut HtmlElement, explicit: bool) {
+    let defaults = if explicit {
+        Declarations::default()
+    } else {
+        SheetStyles::new(StyleChain::default()).declarations()
+    };
+    let mut sheet = Sheet::default();
+    assign(root, &defaults, &mut sheet);
+    if sheet.rules.is_empty() {
+        return;
+    }
+
+    let Some(head) = root.children.make_mut().iter_mut().find_map(|node| match node {
+        HtmlNode::Element(element) if element.tag == tag::head => Some(element),
+        _ => None,
+    }) else {
+        return;
+    };
+
+    let style = HtmlElement::new(tag::style)
+        .with_children(eco_vec![HtmlNode::text(sheet.rules, Span::detached())]);
+    head.children.push(style.into());
+}
+
+/// Collects the font variants with which the text of an element and its
+/// descendants is displayed, per family.
+pub fn used_fonts(element: &HtmlElement, fonts: &mut Vec<(EcoString, FontVariant)>) {
+    collect_fonts(element, None, fonts);
+}
+
+/// Collects the used font variants, given the font and variant that the
+/// element inherits.
+fn collect_fonts<'a>(
+    element: &'a HtmlElement,
+    parent: Option<(&'a FontList, FontVariant)>,
+    fonts: &mut Vec<(EcoString, FontVariant)>,
+) {
+    let mut current = match &element.sheet.text {
+        Some(text) => Some((&text.font, text.variant)),
+        None => parent,
+    };
+
+    // Readers display some elements in bold or italics by default. A
+    // declaration only overrides that if it differs from the inherited one.
+    if let Some((_, variant)) = &mut current {
+        let inherited = parent.map(|(_, variant)| variant);
+        if (matches!(element.tag, tag::b | tag::strong | tag::th)
+            || tag::is_heading_content(element.tag))
+            && inherited.is_none_or(|inherited| inherited.weight == variant.weight)
+        {
+            variant.weight = variant.weight.max(FontWeight::BOLD);
+        }
+        if matches!(element.tag, tag::i | tag::em)
+            && inherited.is_none_or(|inherited| inherited.style == variant.style)
+        {
+            variant.style = FontStyle::Italic;
+        }
+    }
+
+    for node in &element.children {
+        match node {
+            HtmlNode::Text(..) => {
+                let Some((font, variant)) = current else { continue };
+                for family in font {
+                    if !fonts
+                        .iter()
+                        .any(|(name, v)| name == family.as_str() && *v == variant)
+                    {
+                        fonts.push((family.as_str().into(), variant));
+                    }
+                }
+            }
+            HtmlNode::Element(child) => collect_fonts(child, current, fonts),
+            _ => {}
+        }
+    }
+}
+
+/// The rules collected so far.
+#[derive(Default)]
+struct Sheet {
+    /// The rules, one per line.
+    rules: EcoString,
+    /// The class of each distinct rule body.
+    classes: FxHashMap<EcoString, EcoString>,
+}
+
+impl Sheet {
+    /// Adds a rule for the declarations, unless an identical one exists, and
+    /// returns its class.
+    ///
+    /// Classes are numbered in order of first use, so that distinct rules
+    /// never share a class.
+    fn add(&mut self, declarations: &Declarations) -> EcoString {
+        let mut body = EcoString::new();
+        for (property, value) in &declarations.0 {
+            write!(body, "{property}: {value}; ").unwrap();
+        }
+
+        let n = self.classes.len() + 1;
+        let rules = &mut self.rules;
+        self.classes
+            .entry(body)
+            .or_insert_with_key(|body| {
+                let class = eco_format!("typst-{n}");
+                if !rules.is_empty() {
+                    rules.push('\n');
+                }
+                write!(rules, ".{class} {{ {body}}}").unwrap();
+                class
+            })
+            .clone()
+    }
+}
+
+/// Replaces the declarations of an element and its descendants with classes.
+fn assign(element: &mut HtmlElement, inherited: &Declarations, sheet: &mut Sheet) {
+    let mut own = Declarations::default();
+    let mut passed = inherited.clone();
+    for (property, value) in std::mem::take(&mut element.sheet).declarations().0 {
+        if INHERITED.contains(&property.as_str()) {
+            if inherited.get(&property) == Some(&value) {
+                continue;
+            }
+            passed.set(&property, value.clone());
+        }
+
+        // Font sizes are relative so that they scale with the browser's
+        // default size.
+        let value = match (property.as_str(), inherited.get("font-size")) {
+            ("font-size", Some(parent)) => relative_size(&value, parent).unwrap_or(value),
+            _ => value,
+        };
+
+        own.set(&property, value);
+    }
+
+    if !own.0.is_empty() {
+        let class = sheet.add(&own);
+        match element.attrs.0.iter().position(|&(key, _)| key == attr::class) {
+            Some(i) => {
+                let value = &mut element.attrs.0.make_mut()[i].1;
+                value.push(' ');
+                value.push_str(&class);
+            }
+            None => element.attrs.push(attr::class, class),
+        }
+    }
+
+    for node in element.children.make_mut() {
+        if let HtmlNode::Element(child) = node {
+            assign(child, &passed, sheet);
+        }
+    }
+}
+
+/// Expresses a font size in points relative to the parent's size in points.
+fn relative_size(size: &str, parent: &str) -> Option<EcoString> {
+    let pt = |value: &str| value.strip_suffix("pt")?.parse::<f64>().ok();
+    let ratio = pt(size)? / pt(parent)?;
+    ratio
+        .is_finite()
+        .then(|| eco_format!("{}em", typst_utils::round_with_precision(ratio, 4)))
+}
+
+/// Combines the values for the four sides into a shorthand value.
+fn shorthand(sides: Sides<EcoString>) -> EcoString {
+    if sides.is_uniform() {
+        sides.top
+    } else {
+        eco_format!("{} {} {} {}", sides.top, sides.right, sides.bottom, sides.left)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typst_library::text::TextSize;
+    use typst_library::visualize::Color;
+
+    use super::*;
+
+    fn element(
+        tag: crate::HtmlTag,
+        sheet: SheetStyles,
+        children: Vec<HtmlNode>,
+    ) -> HtmlNode {
+        let mut element = HtmlElement::new(tag).with_children(children.into());
+        element.sheet = sheet;
+        element.into()
+    }
+
+    fn with_fill(color: Color) -> SheetStyles {
+        let fill = TextElem::fill.set(Paint::Solid(color)).wrap();
+        SheetStyles::new(StyleChain::default().chain(&fill))
+    }
+
+    /// Builds a document with the given body children, embeds a stylesheet,
+    /// and returns it with the rules.
+    fn embedded(children: Vec<HtmlNode>) -> (HtmlElement, String) {
+        let body = element(tag::body, SheetStyles::new(StyleChain::default()), children);
+        let head: HtmlNode = HtmlElement::new(tag::head).into();
+        let mut root = HtmlElement::new(tag::html).with_children(eco_vec![head, body]);
+        embed(&mut root, false);
+
+        let HtmlNode::Element(head) = &root.children[0] else { panic!() };
+        let rules = match head.children.first() {
+            Some(HtmlNode::Element(style)) => match &style.children[0] {
+                HtmlNode::Text(text, _) => text.to_string(),
+                _ => panic!(),
+            },
+            _ => String::new(),
+        };
+        (root, rules)
+    }
+
+    /// The classes of the body's children.
+    fn classes(root: &HtmlElement) -> Vec<Option<&str>> {
+        let HtmlNode::Element(body) = &root.children[1] else { panic!() };
+        body.children
+            .iter()
+            .map(|node| match node {
+                HtmlNode::Element(element) => {
+                    element.attrs.get(attr::class).map(EcoString::as_str)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stylesheet_default_styles() {
+        let default = SheetStyles::new(StyleChain::default());
+        let (root, rules) = embedded(vec![element(tag::p, default, vec![])]);
+        assert_eq!(rules, "");
+        assert_eq!(classes(&root), [None]);
+    }
+
+    #[test]
+    fn test_stylesheet_classes() {
+        let red = Color::RED;
+        let blue = Color::BLUE;
+        let (root, rules) = embedded(vec![
+            element(tag::p, with_fill(red), vec![]),
+            element(tag::p, with_fill(blue), vec![]),
+            element(tag::p, with_fill(red), vec![]),
+        ]);
+
+        // Identical rules share a class, distinct ones never do.
+        assert_eq!(classes(&root), [Some("typst-1"), Some("typst-2"), Some("typst-1")]);
+        assert_eq!(
+            rules,
+            format!(
+                ".typst-1 {{ color: {}; }}\n.typst-2 {{ color: {}; }}",
+                css::color(red),
+                css::color(blue),
+            )
+        );
+    }
+
+    #[test]
+    fn test_stylesheet_inherited() {
+        // The child has the same color as its parent, so only the parent
+        // declares it.
+        let child = element(tag::span, with_fill(Color::RED), vec![]);
+        let (root, _) =
+            embedded(vec![element(tag::div, with_fill(Color::RED), vec![child])]);
+        let HtmlNode::Element(body) = &root.children[1] else { panic!() };
+        let HtmlNode::Element(div) = &body.children[0] else { panic!() };
+        let HtmlNode::Element(span) = &div.children[0] else { panic!() };
+        assert!(div.attrs.get(attr::class).is_some());
+        assert!(span.attrs.get(attr::class).is_none());
+    }
+
+    #[test]
+    fn test_stylesheet_relative_size() {
+        let size = TextElem::size.set(TextSize(Abs::pt(22.0).into())).wrap();
+        let sheet = SheetStyles::new(StyleChain::default().chain(&size));
+        let (_, rules) = embedded(vec![element(tag::p, sheet, vec![])]);
+        assert_eq!(rules, ".typst-1 { font-size: 2em; }");
+    }
+
+    #[test]
+    fn test_stylesheet_paragraph() {
+        let justify = ParElem::justify.set(true).wrap();
+        let sheet = SheetStyles::new(StyleChain::default())
+            .with_par(StyleChain::default().chain(&justify), false);
+        let (_, rules) = embedded(vec![element(tag::p, sheet, vec![])]);
+        assert_eq!(rules, ".typst-1 { text-align: justify; }");
+    }
+
+    #[test]
+    fn test_used_fonts() {
+        let text = || HtmlNode::text("Text", Span::detached());
+        let default = || SheetStyles::new(StyleChain::default());
+        let italic = TextElem::style.set(FontStyle::Italic).wrap();
+        let root = HtmlElement::new(tag::body).with_children(eco_vec![
+            element(tag::p, default(), vec![text()]),
+            element(tag::strong, default(), vec![text()]),
+            element(tag::em, default(), vec![]),
+            element(
+                tag::span,
+                SheetStyles::new(StyleChain::default().chain(&italic)),
+                vec![text()],
+            ),
+        ]);
+
+        let mut fonts = vec![];
+        used_fonts(&root, &mut fonts);
+
+        // The empty emphasis displays no text in italics.
+        let variant = |style, weight| FontVariant::new(style, weight, Default::default());
+        let family = EcoString::from("libertinus serif");
+        assert_eq!(
+            fonts,
+            [
+                (family.clone(), variant(FontStyle::Normal, FontWeight::REGULAR)),
+                (family.clone(), variant(FontStyle::Normal, FontWeight::BOLD)),
+                (family, variant(FontStyle::Italic, FontWeight::REGULAR)),
+            ]
+        );
+    }
+}
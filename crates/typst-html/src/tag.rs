@@ -118,6 +118,30 @@ pub const var: HtmlTag = HtmlTag::constant("var");
 pub const video: HtmlTag = HtmlTag::constant("video");
 pub const wbr: HtmlTag = HtmlTag::constant("wbr");
 
+// MathML Core § 3 Presentation markup
+
+pub const math: HtmlTag = HtmlTag::constant("math");
+pub const mfrac: HtmlTag = HtmlTag::constant("mfrac");
+pub const mi: HtmlTag = HtmlTag::constant("mi");
+pub const mmultiscripts: HtmlTag = HtmlTag::constant("mmultiscripts");
+pub const mn: HtmlTag = HtmlTag::constant("mn");
+pub const mo: HtmlTag = HtmlTag::constant("mo");
+pub const mover: HtmlTag = HtmlTag::constant("mover");
+pub const mprescripts: HtmlTag = HtmlTag::constant("mprescripts");
+pub const mroot: HtmlTag = HtmlTag::constant("mroot");
+pub const mrow: HtmlTag = HtmlTag::constant("mrow");
+pub const mspace: HtmlTag = HtmlTag::constant("mspace");
+pub const msqrt: HtmlTag = HtmlTag::constant("msqrt");
+pub const msub: HtmlTag = HtmlTag::constant("msub");
+pub const msubsup: HtmlTag = HtmlTag::constant("msubsup");
+pub const msup: HtmlTag = HtmlTag::constant("msup");
+pub const mtable: HtmlTag = HtmlTag::constant("mtable");
+pub const mtd: HtmlTag = HtmlTag::constant("mtd");
+pub const mtext: HtmlTag = HtmlTag::constant("mtext");
+pub const mtr: HtmlTag = HtmlTag::constant("mtr");
+pub const munder: HtmlTag = HtmlTag::constant("munder");
+pub const munderover: HtmlTag = HtmlTag::constant("munderover");
+
 // HTML spec § 13.1.2 Elements
 
 /// Whether this is a void tag whose associated element may not have
@@ -221,6 +245,7 @@ pub fn is_flow_content(tag: HtmlTag) -> bool {
             | self::link
             | self::main
             | self::map
+            | self::math
             | self::mark
             | self::menu
             | self::meta
@@ -305,6 +330,7 @@ pub fn is_phrasing_content(tag: HtmlTag) -> bool {
             | self::link
             | self::map
             | self::mark
+            | self::math
             | self::meta
             | self::meter
             | self::noscript
@@ -342,6 +368,7 @@ pub fn is_embedded_content(tag: HtmlTag) -> bool {
             | self::canvas
             | self::embed
             | self::iframe
+            | self::math
             | self::img
             | self::object
             | self::picture
@@ -416,6 +443,7 @@ pub fn is_palpable_content(tag: HtmlTag) -> bool {
             | self::main
             | self::map
             | self::mark
+            | self::math
             | self::menu
             | self::meter
             | self::nav
@@ -525,6 +553,7 @@ pub fn is_inline_by_default(tag: HtmlTag) -> bool {
             | self::i
             | self::em
             | self::mark
+            | self::math
             | self::kbd
             | self::rp
             | self::q
@@ -5,6 +5,8 @@ mod complete;
 mod definition;
 mod jump;
 mod matchers;
+mod references;
+mod rename;
 mod tooltip;
 mod utils;
 
@@ -13,6 +15,8 @@ pub use self::complete::{Completion, CompletionKind, autocomplete};
 pub use self::definition::{Definition, definition};
 pub use self::jump::{Jump, jump_from_click, jump_from_click_in_frame, jump_from_cursor};
 pub use self::matchers::{DerefTarget, NamedItem, deref_target, named_items};
+pub use self::references::references;
+pub use self::rename::{TextEdit, rename};
 pub use self::tooltip::{Tooltip, tooltip};
 
 use ecow::EcoString;
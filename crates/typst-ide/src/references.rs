@@ -0,0 +1,447 @@
+use std::ops::Range;
+
+use ecow::EcoString;
+use rustc_hash::FxHashSet;
+use typst::syntax::ast::AstNode;
+use typst::syntax::{FileId, LinkedNode, Side, Source, Span, SyntaxKind, ast};
+
+use crate::{IdeWorld, analyze_import, named_items};
+
+/// Find all references to the item under the cursor.
+///
+/// The item can be a `let` binding, a function parameter, an imported item, or
+/// a label. The references include the item's declaration and are searched in
+/// all source files of the project, that is, the given source, the world's main
+/// file, and the world's [files](IdeWorld::files).
+///
+/// Returns an empty list if there is no such item at the cursor.
+pub fn references(world: &dyn IdeWorld, source: &Source, cursor: usize) -> Vec<Span> {
+    let Some(target) = target(world, source, cursor) else {
+        return vec![];
+    };
+
+    occurrences(world, &sources(world, source), &target)
+        .into_iter()
+        .map(|occurrence| occurrence.span)
+        .collect()
+}
+
+/// An item whose references can be searched.
+pub(crate) enum Target {
+    /// A binding with its original name and the span of its declaration.
+    Binding(EcoString, Span),
+    /// A label with the given name.
+    Label(EcoString),
+}
+
+/// A reference to a [`Target`] in a source file.
+pub(crate) struct Occurrence {
+    /// The file that contains the reference.
+    pub id: FileId,
+    /// The span of the referencing node.
+    pub span: Span,
+    /// The byte range of just the name in the file.
+    pub range: Range<usize>,
+    /// The name as written at the reference. Differs from the target's name
+    /// for an `import .. as` alias and its uses.
+    pub name: EcoString,
+    /// How the name is referenced.
+    pub role: Role,
+}
+
+/// How a name is referenced.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum Role {
+    /// The name is bound, e.g. by a `let` binding, a parameter, or an import.
+    Declaration,
+    /// The name is part of an import path without being bound by it.
+    Path,
+    /// The name is used.
+    Use,
+}
+
+/// Determine the item at the cursor.
+pub(crate) fn target(
+    world: &dyn IdeWorld,
+    source: &Source,
+    cursor: usize,
+) -> Option<Target> {
+    let root = LinkedNode::new(source.root());
+    [Side::Before, Side::After].into_iter().find_map(|side| {
+        let leaf = root.leaf_at(cursor, side)?;
+        match leaf.kind() {
+            SyntaxKind::Label => {
+                Some(Target::Label(leaf.cast::<ast::Label>()?.get().into()))
+            }
+            SyntaxKind::RefMarker => {
+                Some(Target::Label(leaf.text().trim_start_matches('@').into()))
+            }
+            SyntaxKind::Ident | SyntaxKind::MathIdent => {
+                let decl = declaration(world, &leaf)?;
+                let decl_source = world.source(decl.id()?).ok()?;
+                let name = LinkedNode::new(decl_source.root()).find(decl)?.text().clone();
+                Some(Target::Binding(name, decl))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Collect all source files that references are searched in.
+pub(crate) fn sources(world: &dyn IdeWorld, source: &Source) -> Vec<Source> {
+    let mut seen = FxHashSet::default();
+    seen.insert(source.id());
+
+    let mut sources = vec![source.clone()];
+    for id in std::iter::once(world.main()).chain(world.files()) {
+        let is_typ =
+            id.vpath().as_rootless_path().extension().is_some_and(|e| e == "typ");
+        if id.package().is_none()
+            && is_typ
+            && seen.insert(id)
+            && let Ok(source) = world.source(id)
+        {
+            sources.push(source);
+        }
+    }
+
+    sources
+}
+
+/// Find all references to the target in the given sources.
+pub(crate) fn occurrences(
+    world: &dyn IdeWorld,
+    sources: &[Source],
+    target: &Target,
+) -> Vec<Occurrence> {
+    let mut output = vec![];
+    for source in sources {
+        let root = LinkedNode::new(source.root());
+        match target {
+            Target::Label(name) => collect_labels(source.id(), &root, name, &mut output),
+            Target::Binding(name, decl) => {
+                // Uses of `import .. as` aliases refer to the same binding, but
+                // under a different name.
+                let mut names = FxHashSet::default();
+                names.insert(name.clone());
+                collect_aliases(world, &root, *decl, &mut names);
+                collect_bindings(world, source.id(), &root, *decl, &names, &mut output);
+            }
+        }
+    }
+    output
+}
+
+/// Find the references to a label in a syntax tree.
+fn collect_labels(
+    id: FileId,
+    node: &LinkedNode,
+    name: &str,
+    output: &mut Vec<Occurrence>,
+) {
+    if let Some(label) = node.cast::<ast::Label>()
+        && label.get() == name
+    {
+        let range = node.range();
+        output.push(Occurrence {
+            id,
+            span: node.span(),
+            range: range.start + 1..range.end - 1,
+            name: name.into(),
+            role: Role::Declaration,
+        });
+    } else if let Some(reference) = node.cast::<ast::Ref>()
+        && reference.target() == name
+        && let Some(marker) =
+            node.children().find(|child| child.kind() == SyntaxKind::RefMarker)
+    {
+        let range = marker.range();
+        output.push(Occurrence {
+            id,
+            span: node.span(),
+            range: range.start + 1..range.end,
+            name: name.into(),
+            role: Role::Use,
+        });
+    }
+
+    for child in node.children() {
+        collect_labels(id, &child, name, output);
+    }
+}
+
+/// Find the names that a binding is imported under with `import .. as`.
+fn collect_aliases(
+    world: &dyn IdeWorld,
+    node: &LinkedNode,
+    decl: Span,
+    names: &mut FxHashSet<EcoString>,
+) {
+    if let Some(item) = node.cast::<ast::RenamedImportItem>()
+        && let Some(path) = node.find(item.path().span())
+        && let Some(last) = path.find(item.original_name().span())
+        && imported(world, &path, &last) == Some(decl)
+    {
+        names.insert(item.new_name().get().clone());
+    }
+
+    for child in node.children() {
+        collect_aliases(world, &child, decl, names);
+    }
+}
+
+/// Find the references to a binding in a syntax tree.
+fn collect_bindings(
+    world: &dyn IdeWorld,
+    id: FileId,
+    node: &LinkedNode,
+    decl: Span,
+    names: &FxHashSet<EcoString>,
+    output: &mut Vec<Occurrence>,
+) {
+    if matches!(node.kind(), SyntaxKind::Ident | SyntaxKind::MathIdent)
+        && names.contains(node.text())
+        && let Some(role) = role(node)
+        && declaration(world, node) == Some(decl)
+    {
+        output.push(Occurrence {
+            id,
+            span: node.span(),
+            range: node.range(),
+            name: node.text().clone(),
+            role,
+        });
+    }
+
+    for child in node.children() {
+        collect_bindings(world, id, &child, decl, names, output);
+    }
+}
+
+/// Find the declaration of the binding an identifier refers to.
+pub(crate) fn declaration(world: &dyn IdeWorld, node: &LinkedNode) -> Option<Span> {
+    let role = role(node)?;
+    let parent = node.parent()?;
+
+    // Import paths and the names bound by them refer to the binding in the
+    // imported module.
+    match parent.kind() {
+        SyntaxKind::ImportItemPath => return imported(world, parent, node),
+        SyntaxKind::RenamedImportItem => {
+            let item = parent.cast::<ast::RenamedImportItem>()?;
+            let path = parent.find(item.path().span())?;
+            let last = path.find(item.original_name().span())?;
+            return imported(world, &path, &last);
+        }
+        _ => {}
+    }
+
+    if role == Role::Declaration {
+        return Some(node.span());
+    }
+
+    let name = node.text();
+    named_items(world, node.clone(), |item| (item.name() == name).then(|| item.span()))
+}
+
+/// Determine how an identifier references a name, if it does at all.
+///
+/// Field names and the keys of named arguments and dictionaries don't refer
+/// to bindings.
+pub(crate) fn role(node: &LinkedNode) -> Option<Role> {
+    if !matches!(node.kind(), SyntaxKind::Ident | SyntaxKind::MathIdent) {
+        return None;
+    }
+
+    let parent = node.parent()?;
+    match parent.kind() {
+        SyntaxKind::ImportItemPath => {
+            // Only the last segment of a path is bound, and only if the item
+            // isn't renamed.
+            let renamed = parent.parent()?.kind() == SyntaxKind::RenamedImportItem;
+            let last = node.next_sibling().is_none();
+            Some(if last && !renamed { Role::Declaration } else { Role::Path })
+        }
+        SyntaxKind::RenamedImportItem => Some(Role::Declaration),
+        _ if binds(node) => Some(Role::Declaration),
+        SyntaxKind::FieldAccess if node.prev_sibling().is_some() => None,
+        SyntaxKind::Named if node.prev_sibling().is_none() => None,
+        _ => Some(Role::Use),
+    }
+}
+
+/// Whether an identifier introduces a new binding.
+fn binds(node: &LinkedNode) -> bool {
+    let span = node.span();
+    let mut ancestor = node.parent();
+    while let Some(parent) = ancestor {
+        let idents = if let Some(binding) = parent.cast::<ast::LetBinding>() {
+            binding.kind().bindings()
+        } else if let Some(closure) = parent.cast::<ast::Closure>() {
+            closure.name().into_iter().chain(params(closure)).collect()
+        } else if let Some(for_loop) = parent.cast::<ast::ForLoop>() {
+            for_loop.pattern().bindings()
+        } else if let Some(import) = parent.cast::<ast::ModuleImport>() {
+            import.new_name().into_iter().collect()
+        } else {
+            vec![]
+        };
+
+        if idents.iter().any(|ident| ident.span() == span) {
+            return true;
+        }
+
+        ancestor = parent.parent();
+    }
+    false
+}
+
+/// The identifiers bound by a closure's parameters.
+pub(crate) fn params<'a>(closure: ast::Closure<'a>) -> Vec<ast::Ident<'a>> {
+    closure
+        .params()
+        .children()
+        .flat_map(|param| match param {
+            ast::Param::Pos(pattern) => pattern.bindings(),
+            ast::Param::Named(named) => vec![named.name()],
+            ast::Param::Spread(spread) => spread.sink_ident().into_iter().collect(),
+        })
+        .collect()
+}
+
+/// Resolve a segment of an import path to the declaration of the binding it
+/// refers to in the imported module.
+fn imported(
+    world: &dyn IdeWorld,
+    path: &LinkedNode,
+    segment: &LinkedNode,
+) -> Option<Span> {
+    let mut import = path.clone();
+    while import.kind() != SyntaxKind::ModuleImport {
+        import = import.parent()?.clone();
+    }
+
+    let source = import.find(import.cast::<ast::ModuleImport>()?.source().span())?;
+    let module = analyze_import(world, &source)?;
+    let mut scope = module.scope();
+    for child in path.children() {
+        let Some(ident) = child.cast::<ast::Ident>() else { continue };
+        let binding = scope?.get(ident.get())?;
+        if child.span() == segment.span() {
+            return Some(binding.span());
+        }
+        scope = binding.read().scope();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+    use std::ops::Range;
+
+    use typst::WorldExt;
+
+    use super::references;
+    use crate::tests::{FilePos, TestWorld, WorldLike};
+
+    type Response = Vec<(String, Range<usize>)>;
+
+    trait ResponseExt {
+        fn must_be(&self, expected: &[(&str, Range<usize>)]) -> &Self;
+    }
+
+    impl ResponseExt for Response {
+        #[track_caller]
+        fn must_be(&self, expected: &[(&str, Range<usize>)]) -> &Self {
+            let expected: Vec<_> = expected
+                .iter()
+                .map(|(path, range)| (path.to_string(), range.clone()))
+                .collect();
+            assert_eq!(*self, expected);
+            self
+        }
+    }
+
+    #[track_caller]
+    fn test(world: impl WorldLike, pos: impl FilePos) -> Response {
+        let world = world.acquire();
+        let world = world.borrow();
+        let (source, cursor) = pos.resolve(world);
+        references(world, &source, cursor)
+            .into_iter()
+            .map(|span| {
+                let path = span.id().unwrap().vpath().as_rootless_path();
+                (path.to_string_lossy().into(), world.range(span).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_references_let() {
+        test("#let x = 1; #x + #x", -2).must_be(&[
+            ("main.typ", 5..6),
+            ("main.typ", 13..14),
+            ("main.typ", 18..19),
+        ]);
+        test("#let x = 1; #x + #x", 5).must_be(&[
+            ("main.typ", 5..6),
+            ("main.typ", 13..14),
+            ("main.typ", 18..19),
+        ]);
+    }
+
+    #[test]
+    fn test_references_shadowed() {
+        test("#let x = 1; #x; #let x = 2; #x", 5)
+            .must_be(&[("main.typ", 5..6), ("main.typ", 13..14)]);
+    }
+
+    #[test]
+    fn test_references_param() {
+        test("#let f(a) = a + 1; #let a = 2; #a", 7)
+            .must_be(&[("main.typ", 7..8), ("main.typ", 12..13)]);
+    }
+
+    #[test]
+    fn test_references_cross_file() {
+        let world = TestWorld::new("#import \"other.typ\": x; #x")
+            .with_source("other.typ", "#let x = 1; #x");
+        let expected = [
+            ("main.typ", 21..22),
+            ("main.typ", 25..26),
+            ("other.typ", 5..6),
+            ("other.typ", 13..14),
+        ];
+        test(&world, -2).must_be(&expected);
+        test(&world, ("other.typ", 5)).must_be(&[
+            ("other.typ", 5..6),
+            ("other.typ", 13..14),
+            ("main.typ", 21..22),
+            ("main.typ", 25..26),
+        ]);
+    }
+
+    #[test]
+    fn test_references_alias() {
+        let world = TestWorld::new("#import \"other.typ\": x as y; #y")
+            .with_source("other.typ", "#let x = 1");
+        test(&world, -2).must_be(&[
+            ("main.typ", 21..22),
+            ("main.typ", 26..27),
+            ("main.typ", 30..31),
+            ("other.typ", 5..6),
+        ]);
+    }
+
+    #[test]
+    fn test_references_label() {
+        test("= A <a>\n@a #ref(<a>) @b", 2).must_be(&[]);
+        test("= A <a>\n@a #ref(<a>) @b", 5).must_be(&[
+            ("main.typ", 4..7),
+            ("main.typ", 8..10),
+            ("main.typ", 16..19),
+        ]);
+    }
+}
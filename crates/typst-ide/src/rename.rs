@@ -0,0 +1,272 @@
+use std::ops::Range;
+
+use ecow::{EcoString, eco_format};
+use typst::diag::{StrResult, bail};
+use typst::syntax::{
+    FileId, LinkedNode, Source, Span, SyntaxKind, is_ident, is_valid_label_literal_id,
+};
+
+use crate::references::{Role, Target, declaration, occurrences, role, sources, target};
+use crate::{IdeWorld, named_items};
+
+/// A change to a source file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TextEdit {
+    /// The file to change.
+    pub id: FileId,
+    /// The byte range in the file to replace.
+    pub range: Range<usize>,
+    /// The text to replace the range with.
+    pub text: EcoString,
+}
+
+/// Rename the item under the cursor and all its [references](crate::references).
+///
+/// Renaming a binding also renames the items that import it. Aliases created
+/// with `import .. as` keep their name, but can be renamed themselves. Renaming
+/// a label also renames the references to it.
+///
+/// Fails if the new name is invalid or if the rename would change what some
+/// name refers to, i.e. if the renamed binding would shadow another binding of
+/// the same name or be shadowed by it.
+pub fn rename(
+    world: &dyn IdeWorld,
+    source: &Source,
+    cursor: usize,
+    new_name: &str,
+) -> StrResult<Vec<TextEdit>> {
+    let Some(target) = target(world, source, cursor) else {
+        bail!("there is no binding or label to rename here");
+    };
+
+    let sources = sources(world, source);
+    let occurrences = occurrences(world, &sources, &target);
+
+    // The name at the cursor differs from the target's name if the cursor is
+    // at an alias. Then, only the alias is renamed.
+    let old_name = occurrences
+        .iter()
+        .find(|occurrence| {
+            occurrence.id == source.id()
+                && occurrence.range.start <= cursor
+                && cursor <= occurrence.range.end
+        })
+        .map(|occurrence| occurrence.name.clone());
+
+    let old_name = match &target {
+        Target::Label(name) => {
+            if !is_valid_label_literal_id(new_name) {
+                bail!("`{new_name}` is not a valid label name");
+            }
+            name.clone()
+        }
+        Target::Binding(name, decl) => {
+            if !is_ident(new_name) {
+                bail!("`{new_name}` is not a valid identifier");
+            }
+            let old_name = old_name.unwrap_or_else(|| name.clone());
+            if old_name == *name {
+                check_declaration(world, *decl)?;
+            }
+            check_shadowing(world, &sources, &old_name, *decl, new_name)?;
+            old_name
+        }
+    };
+
+    Ok(occurrences
+        .into_iter()
+        .filter(|occurrence| occurrence.name == old_name)
+        .map(|occurrence| TextEdit {
+            id: occurrence.id,
+            range: occurrence.range,
+            text: new_name.into(),
+        })
+        .collect())
+}
+
+/// Check that the declaration of a binding can be renamed.
+fn check_declaration(world: &dyn IdeWorld, decl: Span) -> StrResult<()> {
+    let Some(id) = decl.id() else { bail!("cannot rename this binding") };
+    if id.package().is_some() {
+        bail!("cannot rename bindings defined in a package");
+    }
+
+    // Renaming a named parameter would also require renaming the named
+    // arguments of all calls, which we can't find reliably.
+    let source = world.source(id).map_err(|err| err.to_string())?;
+    let root = LinkedNode::new(source.root());
+    if let Some(node) = root.find(decl)
+        && node.parent_kind() == Some(SyntaxKind::Named)
+    {
+        bail!("cannot rename named parameters");
+    }
+
+    Ok(())
+}
+
+/// Check that renaming a binding doesn't change what any name refers to.
+fn check_shadowing(
+    world: &dyn IdeWorld,
+    sources: &[Source],
+    old_name: &str,
+    decl: Span,
+    new_name: &str,
+) -> StrResult<()> {
+    for source in sources {
+        let root = LinkedNode::new(source.root());
+        if let Some(message) = find_conflict(world, &root, old_name, decl, new_name) {
+            bail!("{message}");
+        }
+    }
+    Ok(())
+}
+
+/// Find a use of either name whose binding would change through the rename.
+fn find_conflict(
+    world: &dyn IdeWorld,
+    node: &LinkedNode,
+    old_name: &str,
+    decl: Span,
+    new_name: &str,
+) -> Option<EcoString> {
+    let name = node.text();
+    if (name == old_name || name == new_name) && role(node) == Some(Role::Use) {
+        // Find which of the two bindings is closer to the use.
+        let closest = named_items(world, node.clone(), |item| {
+            if item.name() == old_name && item.span() == decl {
+                Some(true)
+            } else if item.name() == new_name {
+                Some(false)
+            } else {
+                None
+            }
+        });
+
+        let renamed = name == old_name && declaration(world, node) == Some(decl);
+        if renamed && closest == Some(false) {
+            return Some(eco_format!(
+                "renaming `{old_name}` to `{new_name}` would make a reference \
+                 refer to another `{new_name}`",
+            ));
+        } else if name == new_name && closest == Some(true) {
+            return Some(eco_format!(
+                "renaming `{old_name}` to `{new_name}` would shadow an \
+                 existing `{new_name}`",
+            ));
+        }
+    }
+
+    node.children()
+        .find_map(|child| find_conflict(world, &child, old_name, decl, new_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+    use std::ops::Range;
+
+    use typst::diag::StrResult;
+
+    use super::{TextEdit, rename};
+    use crate::tests::{FilePos, TestWorld, WorldLike};
+
+    type Response = StrResult<Vec<TextEdit>>;
+
+    trait ResponseExt {
+        fn must_edit(&self, expected: &[(&str, Range<usize>)]) -> &Self;
+        fn must_fail(&self, message: &str) -> &Self;
+    }
+
+    impl ResponseExt for Response {
+        #[track_caller]
+        fn must_edit(&self, expected: &[(&str, Range<usize>)]) -> &Self {
+            let edits = self.as_ref().expect("rename failed");
+            let ranges: Vec<_> = edits
+                .iter()
+                .map(|edit| {
+                    let path = edit.id.vpath().as_rootless_path();
+                    (path.to_string_lossy().into_owned(), edit.range.clone())
+                })
+                .collect();
+            let expected: Vec<_> = expected
+                .iter()
+                .map(|(path, range)| (path.to_string(), range.clone()))
+                .collect();
+            assert_eq!(ranges, expected);
+            self
+        }
+
+        #[track_caller]
+        fn must_fail(&self, message: &str) -> &Self {
+            match self {
+                Err(error) => assert_eq!(error, message),
+                Ok(_) => panic!("expected rename to fail"),
+            }
+            self
+        }
+    }
+
+    #[track_caller]
+    fn test(world: impl WorldLike, pos: impl FilePos, new_name: &str) -> Response {
+        let world = world.acquire();
+        let world = world.borrow();
+        let (source, cursor) = pos.resolve(world);
+        rename(world, &source, cursor, new_name)
+    }
+
+    #[test]
+    fn test_rename_let() {
+        test("#let x = 1; #x + #x", -2, "y").must_edit(&[
+            ("main.typ", 5..6),
+            ("main.typ", 13..14),
+            ("main.typ", 18..19),
+        ]);
+    }
+
+    #[test]
+    fn test_rename_cross_file() {
+        let world = TestWorld::new("#import \"other.typ\": x; #x")
+            .with_source("other.typ", "#let x = 1");
+        test(&world, ("other.typ", 5), "y").must_edit(&[
+            ("other.typ", 5..6),
+            ("main.typ", 21..22),
+            ("main.typ", 25..26),
+        ]);
+    }
+
+    #[test]
+    fn test_rename_alias() {
+        let world = TestWorld::new("#import \"other.typ\": x as y; #y")
+            .with_source("other.typ", "#let x = 1");
+        test(&world, -2, "z").must_edit(&[("main.typ", 26..27), ("main.typ", 30..31)]);
+        test(&world, ("other.typ", 5), "z")
+            .must_edit(&[("other.typ", 5..6), ("main.typ", 21..22)]);
+    }
+
+    #[test]
+    fn test_rename_label() {
+        test("= A <a>\n@a #ref(<a>)", 5, "b").must_edit(&[
+            ("main.typ", 5..6),
+            ("main.typ", 9..10),
+            ("main.typ", 17..18),
+        ]);
+        test("= A <a>\n@a", 5, "b c").must_fail("`b c` is not a valid label name");
+    }
+
+    #[test]
+    fn test_rename_shadowing() {
+        test("#let x = 1; #let y = 2; #x", 5, "y")
+            .must_fail("renaming `x` to `y` would make a reference refer to another `y`");
+        test("#let y = 1; #let x = 2; #(x + y)", 17, "y")
+            .must_fail("renaming `x` to `y` would shadow an existing `y`");
+        test("#let x = 1; #x; #let y = 2; #y", 5, "y")
+            .must_edit(&[("main.typ", 5..6), ("main.typ", 13..14)]);
+    }
+
+    #[test]
+    fn test_rename_invalid() {
+        test("#let x = 1", 5, "1x").must_fail("`1x` is not a valid identifier");
+        test("#text", 1, "y").must_fail("there is no binding or label to rename here");
+        test("#let f(a: 1) = a", 7, "b").must_fail("cannot rename named parameters");
+    }
+}
@@ -3,12 +3,16 @@
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard};
 
 use ecow::eco_format;
 use once_cell::sync::OnceCell;
 use serde::Deserialize;
 use typst_library::diag::{PackageError, PackageResult, StrResult, bail};
-use typst_syntax::package::{PackageSpec, PackageVersion, VersionlessPackageSpec};
+use typst_syntax::package::{
+    Dependencies, PackageLock, PackageSpec, PackageVersion, VersionBound,
+    VersionlessPackageSpec,
+};
 
 use crate::download::{Downloader, Progress};
 
@@ -47,10 +51,20 @@ pub struct PackageStorage {
     package_cache_path: Option<PathBuf>,
     /// The path at which local packages are stored.
     package_path: Option<PathBuf>,
+    /// The path of a project-local directory with vendored packages, which
+    /// takes precedence over all other locations.
+    vendor_path: Option<PathBuf>,
+    /// Whether to fail instead of downloading packages or the index.
+    frozen: bool,
     /// The downloader used for fetching the index and packages.
     downloader: Downloader,
     /// The cached index of the default namespace.
     index: OnceCell<Vec<serde_json::Value>>,
+    /// Version requirements for packages imported without a version.
+    dependencies: Dependencies,
+    /// The versions packages imported without a version resolve to. Extended
+    /// whenever a package is resolved that is not yet locked.
+    lock: RwLock<PackageLock>,
 }
 
 impl PackageStorage {
@@ -76,11 +90,50 @@ impl PackageStorage {
         Self {
             package_cache_path: package_cache_path.or_else(default_package_cache_path),
             package_path: package_path.or_else(default_package_path),
+            vendor_path: None,
+            frozen: false,
             downloader,
             index,
+            dependencies: Dependencies::new(),
+            lock: RwLock::new(PackageLock::default()),
         }
     }
 
+    /// Configures a directory with vendored packages that is consulted before
+    /// the package and cache directories.
+    pub fn with_vendor_path(mut self, vendor_path: PathBuf) -> Self {
+        self.vendor_path = Some(vendor_path);
+        self
+    }
+
+    /// Configures whether the storage may access the network. A frozen storage
+    /// fails instead of downloading and only resolves packages imported
+    /// without a version if they are locked.
+    pub fn with_frozen(mut self, frozen: bool) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
+    /// Configures the version requirements and the lockfile used to resolve
+    /// packages that are imported without a version.
+    pub fn with_lock(mut self, dependencies: Dependencies, lock: PackageLock) -> Self {
+        self.dependencies = dependencies;
+        self.lock = RwLock::new(lock);
+        self
+    }
+
+    /// Returns the version requirements for packages imported without a
+    /// version.
+    pub fn dependencies(&self) -> &Dependencies {
+        &self.dependencies
+    }
+
+    /// Returns the current lockfile, including all versions that were resolved
+    /// since it was configured.
+    pub fn lock(&self) -> PackageLock {
+        self.read_lock().clone()
+    }
+
     /// Returns the path at which non-local packages should be stored when
     /// downloaded.
     pub fn package_cache_path(&self) -> Option<&Path> {
@@ -92,6 +145,11 @@ impl PackageStorage {
         self.package_path.as_deref()
     }
 
+    /// Returns the path of the directory with vendored packages.
+    pub fn vendor_path(&self) -> Option<&Path> {
+        self.vendor_path.as_deref()
+    }
+
     /// Makes a package available on-disk and returns the path at which it is
     /// located (will be either in the cache or package directory).
     pub fn prepare_package(
@@ -101,6 +159,13 @@ impl PackageStorage {
     ) -> PackageResult<PathBuf> {
         let subdir = format!("{}/{}/{}", spec.namespace, spec.name, spec.version);
 
+        if let Some(vendor_dir) = &self.vendor_path {
+            let dir = vendor_dir.join(&subdir);
+            if dir.exists() {
+                return Ok(dir);
+            }
+        }
+
         if let Some(packages_dir) = &self.package_path {
             let dir = packages_dir.join(&subdir);
             if dir.exists() {
@@ -116,6 +181,11 @@ impl PackageStorage {
 
             // Download from network if it doesn't exist yet.
             if spec.namespace == DEFAULT_NAMESPACE {
+                if self.frozen {
+                    return Err(PackageError::NetworkFailed(Some(
+                        "downloads are disabled in frozen mode".into(),
+                    )));
+                }
                 self.download_package(spec, cache_dir, progress)?;
                 if dir.exists() {
                     return Ok(dir);
@@ -126,35 +196,149 @@ impl PackageStorage {
         Err(PackageError::NotFound(spec.clone()))
     }
 
+    /// Resolves a package that was imported without a version.
+    ///
+    /// Uses the locked version if it still satisfies the version requirement
+    /// from the dependencies. Otherwise, determines the latest version that
+    /// satisfies it and locks that one.
+    pub fn resolve_version(
+        &self,
+        spec: &VersionlessPackageSpec,
+    ) -> StrResult<PackageVersion> {
+        let bound = self.dependencies.get(spec);
+        if let Some(version) = self.read_lock().get(spec)
+            && bound.is_none_or(|bound| version.matches_eq(bound))
+        {
+            return Ok(version);
+        }
+
+        let Some(bound) = bound else {
+            bail!("package {spec} is not listed in the dependencies");
+        };
+
+        self.lock_matching_version(spec, bound)
+    }
+
+    /// Resolves a package that another package imported without a version,
+    /// using the dependencies from that package's manifest.
+    ///
+    /// Uses the locked version if it satisfies the version requirement.
+    /// Otherwise, determines the latest version that satisfies it. That one
+    /// is only locked if no version is locked yet, as the project or another
+    /// package relies on the locked one.
+    pub fn resolve_dependency_version(
+        &self,
+        spec: &VersionlessPackageSpec,
+        package: &PackageSpec,
+        dependencies: &Dependencies,
+    ) -> StrResult<PackageVersion> {
+        let Some(bound) = dependencies.get(spec) else {
+            bail!("package {spec} is not listed in the dependencies of {package}");
+        };
+
+        let locked = self.read_lock().get(spec);
+        match locked {
+            Some(version) if version.matches_eq(bound) => Ok(version),
+            Some(_) if !self.frozen => self.determine_matching_version(spec, bound),
+            _ => self.lock_matching_version(spec, bound),
+        }
+    }
+
+    /// Determines the latest version of a package that satisfies the bound
+    /// and locks it.
+    fn lock_matching_version(
+        &self,
+        spec: &VersionlessPackageSpec,
+        bound: &VersionBound,
+    ) -> StrResult<PackageVersion> {
+        if self.frozen {
+            bail!("package {spec} is not locked to a version matching {bound}");
+        }
+
+        let version = self.determine_matching_version(spec, bound)?;
+        self.lock
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(spec.clone().at(version));
+        Ok(version)
+    }
+
+    /// Provides read access to the lock.
+    ///
+    /// The lock is only ever replaced or extended as a whole, so it is
+    /// consistent even if a thread panicked while holding it.
+    fn read_lock(&self) -> RwLockReadGuard<'_, PackageLock> {
+        self.lock.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
     /// Tries to determine the latest version of a package.
     pub fn determine_latest_version(
         &self,
         spec: &VersionlessPackageSpec,
     ) -> StrResult<PackageVersion> {
-        if spec.namespace == DEFAULT_NAMESPACE {
+        self.find_latest_version(spec, |_| true)?.ok_or_else(|| {
+            if spec.namespace == DEFAULT_NAMESPACE {
+                eco_format!("failed to find package {spec}")
+            } else {
+                eco_format!("please specify the desired version")
+            }
+        })
+    }
+
+    /// Tries to determine the latest version of a package that matches the
+    /// given bound.
+    pub fn determine_matching_version(
+        &self,
+        spec: &VersionlessPackageSpec,
+        bound: &VersionBound,
+    ) -> StrResult<PackageVersion> {
+        self.find_latest_version(spec, |version| version.matches_eq(bound))?
+            .ok_or_else(|| {
+                eco_format!("failed to find a version of {spec} matching {bound}")
+            })
+    }
+
+    /// Finds the latest available version of a package that passes the
+    /// filter.
+    fn find_latest_version(
+        &self,
+        spec: &VersionlessPackageSpec,
+        filter: impl Fn(&PackageVersion) -> bool,
+    ) -> StrResult<Option<PackageVersion>> {
+        if spec.namespace == DEFAULT_NAMESPACE && !self.frozen {
             // For `DEFAULT_NAMESPACE`, download the package index and find the latest
             // version.
-            self.download_index()?
+            Ok(self
+                .download_index()?
                 .iter()
                 .filter_map(|value| MinimalPackageInfo::deserialize(value).ok())
                 .filter(|package| package.name == spec.name)
                 .map(|package| package.version)
-                .max()
-                .ok_or_else(|| eco_format!("failed to find package {spec}"))
+                .filter(filter)
+                .max())
         } else {
             // For other namespaces, search locally. We only search in the data
             // directory and not the cache directory, because the latter is not
-            // intended for storage of local packages.
+            // intended for storage of local packages. In frozen mode, the
+            // cache is searched for the default namespace instead of the
+            // index.
+            let cache_path = self
+                .package_cache_path
+                .as_ref()
+                .filter(|_| spec.namespace == DEFAULT_NAMESPACE);
             let subdir = format!("{}/{}", spec.namespace, spec.name);
-            self.package_path
+            Ok(self
+                .vendor_path
                 .iter()
+                .chain(&self.package_path)
+                .chain(cache_path)
                 .flat_map(|dir| std::fs::read_dir(dir.join(&subdir)).ok())
                 .flatten()
                 .filter_map(|entry| entry.ok())
                 .map(|entry| entry.path())
                 .filter_map(|path| path.file_name()?.to_string_lossy().parse().ok())
-                .max()
-                .ok_or_else(|| eco_format!("please specify the desired version"))
+                .filter(filter)
+                .max())
         }
     }
 
@@ -335,4 +519,82 @@ mod tests {
             Err("failed to find package @preview/unequivocal-ams".into())
         )
     }
+
+    #[test]
+    fn resolve_locked_version() {
+        let index = ["0.1.0", "0.1.2", "0.2.0"]
+            .map(|version| {
+                serde_json::json!({
+                    "name": "charged-ieee",
+                    "version": version,
+                    "entrypoint": "lib.typ",
+                })
+            })
+            .to_vec();
+
+        let spec: VersionlessPackageSpec = "@preview/charged-ieee".parse().unwrap();
+        let v0_1_0 = PackageVersion { major: 0, minor: 1, patch: 0 };
+        let v0_1_2 = PackageVersion { major: 0, minor: 1, patch: 2 };
+
+        let mut lock = PackageLock::default();
+        lock.insert(spec.clone().at(v0_1_0));
+        let storage = |bound: &str| {
+            PackageStorage::with_index(
+                None,
+                None,
+                Downloader::new("typst/test"),
+                OnceCell::with_value(index.clone()),
+            )
+            .with_lock(
+                Dependencies::from([(spec.clone(), bound.parse().unwrap())]),
+                lock.clone(),
+            )
+        };
+
+        // The locked version still matches.
+        let storage_0_1 = storage("0.1");
+        assert_eq!(storage_0_1.resolve_version(&spec), Ok(v0_1_0));
+
+        // In frozen mode, the lock can't be updated.
+        assert_eq!(
+            storage("0.2").with_frozen(true).resolve_version(&spec),
+            Err("package @preview/charged-ieee is not locked to a version matching 0.2"
+                .into())
+        );
+
+        // The requirement was bumped, so the lock is updated.
+        let storage_0_2 = storage("0.2");
+        let v0_2_0 = PackageVersion { major: 0, minor: 2, patch: 0 };
+        assert_eq!(storage_0_2.resolve_version(&spec), Ok(v0_2_0));
+        assert_eq!(storage_0_2.lock().get(&spec), Some(v0_2_0));
+
+        assert_eq!(
+            storage("0.1").determine_matching_version(&spec, &"0.1".parse().unwrap()),
+            Ok(v0_1_2)
+        );
+        assert_eq!(
+            storage("0.3").resolve_version(&spec),
+            Err("failed to find a version of @preview/charged-ieee matching 0.3".into())
+        );
+
+        // A package's own dependencies don't replace the project's lock.
+        let package: PackageSpec = "@preview/ieee-extras:1.0.0".parse().unwrap();
+        let dependencies =
+            |bound: &str| Dependencies::from([(spec.clone(), bound.parse().unwrap())]);
+        assert_eq!(
+            storage_0_1.resolve_dependency_version(&spec, &package, &dependencies("0")),
+            Ok(v0_1_0)
+        );
+        assert_eq!(
+            storage_0_1.resolve_dependency_version(&spec, &package, &dependencies("0.2")),
+            Ok(v0_2_0)
+        );
+        assert_eq!(storage_0_1.lock().get(&spec), Some(v0_1_0));
+        assert_eq!(
+            storage_0_1.resolve_dependency_version(&spec, &package, &Dependencies::new()),
+            Err("package @preview/charged-ieee is not listed in the dependencies of \
+                 @preview/ieee-extras:1.0.0"
+                .into())
+        );
+    }
 }
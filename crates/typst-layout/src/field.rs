@@ -0,0 +1,52 @@
+use typst_library::diag::{At, SourceResult};
+use typst_library::engine::Engine;
+use typst_library::foundations::{Packed, StyleChain};
+use typst_library::introspection::Locator;
+use typst_library::layout::{Frame, FrameItem, Point, Region};
+use typst_library::pdf::{FieldWidget, FieldWidgetElem};
+use typst_library::text::{TextElem, families, variant};
+use typst_library::visualize::{Color, Paint};
+
+/// Layout a widget of a form field.
+///
+/// The widget fills the region. The font in which the field's value is shown
+/// is resolved here, as PDF export has no access to the field's styles.
+#[typst_macros::time(span = elem.span())]
+pub fn layout_field_widget(
+    elem: &Packed<FieldWidgetElem>,
+    engine: &mut Engine,
+    _: Locator,
+    styles: StyleChain,
+    region: Region,
+) -> SourceResult<Frame> {
+    let world = engine.world;
+    let variant = variant(styles);
+    let font = families(styles)
+        .find_map(|family| {
+            world
+                .book()
+                .select(family.as_str(), variant)
+                .and_then(|id| world.font(id))
+                .filter(|_| family.covers().is_none())
+        })
+        .ok_or("no font could be found")
+        .at(elem.span())?;
+
+    let color = match styles.get_ref(TextElem::fill) {
+        Paint::Solid(color) => *color,
+        Paint::Gradient(_) | Paint::Tiling(_) => Color::BLACK,
+    };
+
+    let variations = styles.get_cloned(TextElem::variations);
+    let widget = FieldWidget {
+        field: elem.field,
+        index: elem.index,
+        font: font.instance(variant, &variations),
+        size: styles.resolve(TextElem::size),
+        color,
+    };
+
+    let mut frame = Frame::soft(region.size);
+    frame.push(Point::zero(), FrameItem::Widget(widget, region.size));
+    Ok(frame)
+}
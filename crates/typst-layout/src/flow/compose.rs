@@ -2,23 +2,28 @@ use std::num::NonZeroUsize;
 
 use typst_library::diag::SourceResult;
 use typst_library::engine::Engine;
-use typst_library::foundations::{Content, NativeElement, Packed, Resolve, Smart};
+use typst_library::foundations::{
+    Content, NativeElement, Packed, Resolve, Smart, Str, StyleChain,
+};
 use typst_library::introspection::{
     Counter, CounterDisplayElem, CounterState, CounterUpdate, Location, Locator,
-    SplitLocator, Tag,
+    PageIntrospection, SplitLocator, Tag,
 };
 use typst_library::layout::{
     Abs, Axes, Dir, FixedAlignment, Fragment, Frame, FrameItem, FrameParent, Inherit,
     OuterHAlignment, PlacementScope, Point, Region, Regions, Rel, Size,
 };
 use typst_library::model::{
-    FootnoteElem, FootnoteEntry, LineNumberingScope, Numbering, ParLineMarker,
+    FootnoteElem, FootnoteEntry, LineNumberingScope, MarginNoteElem, Numbering,
+    ParLineMarker,
 };
+use typst_library::pdf::ArtifactKind;
 use typst_syntax::Span;
 use typst_utils::{NonZeroExt, Numeric};
 
 use super::{
-    Config, FlowMode, FlowResult, LineNumberConfig, PlacedChild, Stop, Work, distribute,
+    Config, FlowMode, FlowResult, LineNumberConfig, MarginNoteConfig, PlacedChild, Stop,
+    Work, distribute,
 };
 
 /// Composes the contents of a single page/region. A region can have multiple
@@ -72,7 +77,7 @@ pub struct Composer<'a, 'b, 'x, 'y> {
     // footnotes otherwise). For floats, we revisit them anyway, so it's okay to
     // use `work.floats` directly. This is not super clean; probably there's a
     // better way.
-    footnote_spill: Option<std::vec::IntoIter<Frame>>,
+    footnote_spill: Option<(Packed<FootnoteElem>, std::vec::IntoIter<Frame>)>,
     footnote_queue: Vec<Packed<FootnoteElem>>,
 }
 
@@ -101,7 +106,21 @@ impl<'a, 'b> Composer<'a, 'b, '_, '_> {
         };
         drop(checkpoint);
 
-        Ok(self.page_insertions.finalize(self.work, self.config, output))
+        let mut output = self.page_insertions.finalize(self.work, self.config, output);
+
+        // Lay out per-page margin notes.
+        if let Some(note_config) = &self.config.margin_notes {
+            layout_margin_notes(
+                self.engine,
+                self.work,
+                self.config,
+                note_config,
+                regions,
+                &mut output,
+            )?;
+        }
+
+        Ok(output)
     }
 
     /// Lay out the inner contents of a container/page.
@@ -419,9 +438,9 @@ impl<'a, 'b> Composer<'a, 'b, '_, '_> {
         flow_need: Abs,
         migratable: bool,
     ) -> FlowResult<()> {
-        // Ignore reference footnotes and already processed ones.
+        // Ignore reference footnotes, endnotes, and already processed ones.
         let loc = elem.location().unwrap();
-        if elem.is_ref() || self.skipped(loc) {
+        if elem.is_ref() || elem.is_endnote() || self.skipped(loc) {
             return Ok(());
         }
 
@@ -433,13 +452,18 @@ impl<'a, 'b> Composer<'a, 'b, '_, '_> {
             return Ok(());
         }
 
-        // If there weren't any footnotes so far, account for the footnote
-        // separator.
+        // If there weren't any footnotes of this series so far, account for
+        // the series' separator.
+        let series = elem.series.get_cloned(StyleChain::default());
         let mut separator = None;
         let mut separator_need = Abs::zero();
-        if area.footnotes.is_empty() {
-            let frame =
-                layout_footnote_separator(self.engine, self.config, regions.base())?;
+        if !area.has_footnotes(&series) {
+            let frame = layout_footnote_separator(
+                self.engine,
+                self.config,
+                &elem,
+                regions.base(),
+            )?;
             separator_need += self.config.footnote.clearance + frame.height();
             separator = Some(frame);
         }
@@ -502,18 +526,18 @@ impl<'a, 'b> Composer<'a, 'b, '_, '_> {
 
         // Save the separator.
         if let Some(frame) = separator {
-            area.push_footnote_separator(self.config, frame);
+            area.push_footnote_separator(self.config, series.clone(), frame);
             regions.size.y -= separator_need;
         }
 
         // Save the footnote's frame.
-        area.push_footnote(self.config, first);
+        area.push_footnote(self.config, &series, first);
         area.skips.push(loc);
         regions.size.y -= note_need;
 
         // Save the spill.
         if !iter.as_slice().is_empty() {
-            self.footnote_spill = Some(iter);
+            self.footnote_spill = Some((elem, iter));
         }
 
         // Lay out nested footnotes.
@@ -541,22 +565,23 @@ impl<'a, 'b> Composer<'a, 'b, '_, '_> {
     /// Handles spillover from a footnote.
     fn footnote_spill(
         &mut self,
-        mut iter: std::vec::IntoIter<Frame>,
+        (elem, mut iter): (Packed<FootnoteElem>, std::vec::IntoIter<Frame>),
         base: Size,
     ) -> SourceResult<()> {
         let area = &mut self.column_insertions;
 
         // Create and save the separator.
-        let separator = layout_footnote_separator(self.engine, self.config, base)?;
-        area.push_footnote_separator(self.config, separator);
+        let series = elem.series.get_cloned(StyleChain::default());
+        let separator = layout_footnote_separator(self.engine, self.config, &elem, base)?;
+        area.push_footnote_separator(self.config, series.clone(), separator);
 
         // Save the footnote's frame.
         let frame = iter.next().unwrap();
-        area.push_footnote(self.config, frame);
+        area.push_footnote(self.config, &series, frame);
 
         // Save the spill.
         if !iter.as_slice().is_empty() {
-            self.footnote_spill = Some(iter);
+            self.footnote_spill = Some((elem, iter));
         }
 
         Ok(())
@@ -576,15 +601,21 @@ impl<'a, 'b> Composer<'a, 'b, '_, '_> {
     }
 }
 
-/// Lay out the footnote separator, typically a line.
+/// Lay out the separator for the footnote's series, typically a line.
 fn layout_footnote_separator(
     engine: &mut Engine,
     config: &Config,
+    elem: &Packed<FootnoteElem>,
     base: Size,
 ) -> SourceResult<Frame> {
+    let separator = match elem.separator.get_cloned(StyleChain::default()) {
+        Smart::Custom(separator) => separator.artifact(ArtifactKind::Other),
+        Smart::Auto => config.footnote.separator.clone(),
+    };
+
     crate::layout_frame(
         engine,
-        &config.footnote.separator,
+        &separator,
         Locator::root(),
         config.shared,
         Region::new(base, Axes::new(config.footnote.expand, false)),
@@ -624,8 +655,7 @@ fn layout_footnote(
 struct Insertions<'a, 'b> {
     top_floats: Vec<(&'b PlacedChild<'a>, Frame)>,
     bottom_floats: Vec<(&'b PlacedChild<'a>, Frame)>,
-    footnotes: Vec<Frame>,
-    footnote_separator: Option<Frame>,
+    footnotes: Vec<FootnoteGroup>,
     top_size: Abs,
     bottom_size: Abs,
     width: Abs,
@@ -654,18 +684,33 @@ impl<'a, 'b> Insertions<'a, 'b> {
         }
     }
 
-    /// Add a footnote to the bottom area.
-    fn push_footnote(&mut self, config: &Config, frame: Frame) {
+    /// Whether the bottom area already has footnotes of the given series.
+    fn has_footnotes(&self, series: &Option<Str>) -> bool {
+        self.footnotes.iter().any(|group| group.series == *series)
+    }
+
+    /// Add a footnote to the bottom area, below the previous footnotes of its
+    /// series. The series' separator must already have been added.
+    fn push_footnote(&mut self, config: &Config, series: &Option<Str>, frame: Frame) {
         self.width.set_max(frame.width());
         self.bottom_size += config.footnote.gap + frame.height();
-        self.footnotes.push(frame);
+        if let Some(group) = self.footnotes.iter_mut().find(|g| g.series == *series) {
+            group.entries.push(frame);
+        }
     }
 
-    /// Add a footnote separator to the bottom area.
-    fn push_footnote_separator(&mut self, config: &Config, frame: Frame) {
+    /// Add a footnote separator to the bottom area, which starts the footnotes
+    /// of a series.
+    fn push_footnote_separator(
+        &mut self,
+        config: &Config,
+        series: Option<Str>,
+        frame: Frame,
+    ) {
         self.width.set_max(frame.width());
         self.bottom_size += config.footnote.clearance + frame.height();
-        self.footnote_separator = Some(frame);
+        self.footnotes
+            .push(FootnoteGroup { series, separator: frame, entries: vec![] });
     }
 
     /// The combined height of the top and bottom area (including clearances).
@@ -682,7 +727,6 @@ impl<'a, 'b> Insertions<'a, 'b> {
 
         if self.top_floats.is_empty()
             && self.bottom_floats.is_empty()
-            && self.footnote_separator.is_none()
             && self.footnotes.is_empty()
         {
             return inner;
@@ -721,24 +765,34 @@ impl<'a, 'b> Insertions<'a, 'b> {
             output.push_frame(Point::new(x, y) + delta, frame);
         }
 
-        if let Some(frame) = self.footnote_separator {
+        for group in self.footnotes {
             offset_bottom += config.footnote.clearance;
             let y = offset_bottom;
-            offset_bottom += frame.height();
-            output.push_frame(Point::with_y(y), frame);
-        }
-
-        for frame in self.footnotes {
-            offset_bottom += config.footnote.gap;
-            let y = offset_bottom;
-            offset_bottom += frame.height();
-            output.push_frame(Point::with_y(y), frame);
+            offset_bottom += group.separator.height();
+            output.push_frame(Point::with_y(y), group.separator);
+
+            for frame in group.entries {
+                offset_bottom += config.footnote.gap;
+                let y = offset_bottom;
+                offset_bottom += frame.height();
+                output.push_frame(Point::with_y(y), frame);
+            }
         }
 
         output
     }
 }
 
+/// The footnotes of one series in an insertion area.
+struct FootnoteGroup {
+    /// The series of the footnotes.
+    series: Option<Str>,
+    /// The separator above the footnotes.
+    separator: Frame,
+    /// The footnotes' frames.
+    entries: Vec<Frame>,
+}
+
 /// Lay out the given collected lines' line numbers to an output frame.
 ///
 /// The numbers are placed either on the left margin (left border of the frame)
@@ -905,6 +959,98 @@ fn layout_line_number(
     Ok(frame)
 }
 
+/// Lay out the margin notes of a page into its outside margin.
+///
+/// Notes queued on the previous page come first, followed by the notes whose
+/// anchors are in the output frame, each at the height of its anchor. If a
+/// note would overlap with the one above it, it is pushed down. Notes that
+/// don't fit onto the page anymore are queued for the next page.
+fn layout_margin_notes(
+    engine: &mut Engine,
+    work: &mut Work,
+    config: &Config,
+    note_config: &MarginNoteConfig,
+    regions: Regions,
+    output: &mut Frame,
+) -> SourceResult<()> {
+    let queued = std::mem::take(&mut work.margin_notes);
+    let mut notes = find_in_frame::<MarginNoteElem>(output);
+    if queued.is_empty() && notes.is_empty() {
+        return Ok(());
+    }
+
+    // With multiple columns, the anchors aren't necessarily sorted by height.
+    notes.sort_by_key(|&(y, _)| y);
+
+    // Determine which margin is the outside one. If no note is anchored on
+    // this page, it only hosts notes queued on the previous page.
+    let page = match notes.first() {
+        Some((_, note)) => {
+            engine.introspect(PageIntrospection(note.location().unwrap(), note.span()))
+        }
+        None => work.margin_page,
+    };
+
+    let clearance = note_config.clearance;
+    let width = (note_config.width - 2.0 * clearance).max(Abs::zero());
+    let x = if note_config.right(page) {
+        output.width() + clearance
+    } else {
+        -clearance - width
+    };
+
+    // The position below which the next note can start.
+    let mut top = Abs::zero();
+    let mut empty = true;
+
+    let mut iter = queued.into_iter().map(|note| (Abs::zero(), note)).chain(notes);
+    while let Some((anchor, note)) = iter.next() {
+        let frame = layout_margin_note(engine, config, &note, width)?;
+        let y = anchor.max(top);
+        let bottom = y + frame.height();
+
+        // If the note doesn't fit, we move it and all following ones to the
+        // next page, where queued notes start at the top. If the note is
+        // already at the top of an empty margin, moving it wouldn't help, so
+        // we place it anyway. Pages that fit their content grow instead.
+        if regions.expand.y && !output.height().fits(bottom) && !(empty && y.is_zero()) {
+            work.margin_notes.push(note);
+            work.margin_notes.extend(iter.by_ref().map(|(_, note)| note));
+            work.margin_page = page.saturating_add(1);
+            break;
+        }
+
+        if !regions.expand.y {
+            output.size_mut().y.set_max(bottom);
+        }
+
+        output.push_frame(Point::new(x, y), frame);
+        top = bottom + note_config.gap;
+        empty = false;
+    }
+
+    Ok(())
+}
+
+/// Lay out a single margin note with the given width.
+fn layout_margin_note(
+    engine: &mut Engine,
+    config: &Config,
+    note: &Packed<MarginNoteElem>,
+    width: Abs,
+) -> SourceResult<Frame> {
+    let loc = note.location().unwrap();
+    let mut frame = crate::layout_frame(
+        engine,
+        &note.body,
+        Locator::synthesize(loc),
+        config.shared,
+        Region::new(Size::new(width, Abs::inf()), Axes::new(true, false)),
+    )?;
+    frame.set_parent(FrameParent::new(loc, Inherit::No));
+    Ok(frame)
+}
+
 /// Collect all matching elements and their vertical positions in the frame.
 ///
 /// On each subframe we encounter, we add that subframe's position to `prev_y`,
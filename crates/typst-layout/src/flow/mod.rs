@@ -22,10 +22,12 @@ use typst_library::introspection::{
     Introspector, Location, Locator, LocatorLink, SplitLocator, Tag,
 };
 use typst_library::layout::{
-    Abs, ColumnsElem, Dir, Em, Fragment, Frame, PageElem, PlacementScope, Region,
-    Regions, Rel, Size,
+    Abs, Binding, ColumnsElem, Dir, Em, Fragment, Frame, PageElem, PlacementScope,
+    Region, Regions, Rel, Size,
+};
+use typst_library::model::{
+    FootnoteElem, FootnoteEntry, LineNumberingScope, MarginNoteElem, ParLine,
 };
-use typst_library::model::{FootnoteElem, FootnoteEntry, LineNumberingScope, ParLine};
 use typst_library::pdf::ArtifactKind;
 use typst_library::routines::{Arenas, FragmentKind, Pair, RealizationKind, Routines};
 use typst_library::text::TextElem;
@@ -37,6 +39,7 @@ use self::collect::{
 };
 use self::compose::{Composer, compose};
 use self::distribute::distribute;
+use crate::pages::{page_binding, page_geometry};
 
 /// Lays out content into a single region, producing a single frame.
 pub fn layout_frame(
@@ -169,7 +172,7 @@ fn layout_fragment_impl(
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum FlowMode {
     /// A root flow with block-level elements. Like `FlowMode::Block`, but can
-    /// additionally host footnotes and line numbers.
+    /// additionally host footnotes, line numbers, and margin notes.
     Root,
     /// A flow whose children are block-level elements.
     Block,
@@ -282,6 +285,22 @@ fn configuration<'x>(
                 )
             },
         }),
+        margin_notes: (mode == FlowMode::Root).then(|| {
+            let (_, margin, two_sided) = page_geometry(shared);
+            let binding = page_binding(shared);
+            MarginNoteConfig {
+                // For two-sided pages, the right margin is the outside one.
+                width: if two_sided || binding == Binding::Left {
+                    margin.right
+                } else {
+                    margin.left
+                },
+                two_sided,
+                binding,
+                clearance: shared.resolve(MarginNoteElem::clearance),
+                gap: shared.resolve(MarginNoteElem::gap),
+            }
+        }),
     }
 }
 
@@ -301,9 +320,13 @@ struct Work<'a, 'b> {
     /// Queued footnotes that didn't fit in previous regions.
     footnotes: EcoVec<Packed<FootnoteElem>>,
     /// Spilled frames of a footnote that didn't fully fit. Similar to `spill`.
-    footnote_spill: Option<std::vec::IntoIter<Frame>>,
+    footnote_spill: Option<(Packed<FootnoteElem>, std::vec::IntoIter<Frame>)>,
     /// Queued tags that will be attached to the next frame.
     tags: EcoVec<&'a Tag>,
+    /// Queued margin notes that didn't fit in previous regions.
+    margin_notes: EcoVec<Packed<MarginNoteElem>>,
+    /// The page number of the region that queued margin notes move to.
+    margin_page: NonZeroUsize,
     /// Identifies floats and footnotes that can be skipped if visited because
     /// they were already handled and incorporated as column or page level
     /// insertions.
@@ -320,6 +343,8 @@ impl<'a, 'b> Work<'a, 'b> {
             footnotes: EcoVec::new(),
             footnote_spill: None,
             tags: EcoVec::new(),
+            margin_notes: EcoVec::new(),
+            margin_page: NonZeroUsize::ONE,
             skips: Rc::new(FxHashSet::default()),
         }
     }
@@ -341,6 +366,7 @@ impl<'a, 'b> Work<'a, 'b> {
             && self.floats.is_empty()
             && self.footnote_spill.is_none()
             && self.footnotes.is_empty()
+            && self.margin_notes.is_empty()
     }
 
     /// Add skipped floats and footnotes from the insertion areas to the skip
@@ -354,8 +380,8 @@ impl<'a, 'b> Work<'a, 'b> {
 
 /// Shared configuration for the whole flow.
 struct Config<'x> {
-    /// Whether this is the root flow, which can host footnotes and line
-    /// numbers.
+    /// Whether this is the root flow, which can host footnotes, line numbers,
+    /// and margin notes.
     mode: FlowMode,
     /// The styles shared by the whole flow. This is used for footnotes and line
     /// numbers.
@@ -366,6 +392,8 @@ struct Config<'x> {
     footnote: FootnoteConfig,
     /// Settings for line numbers.
     line_numbers: Option<LineNumberConfig>,
+    /// Settings for margin notes.
+    margin_notes: Option<MarginNoteConfig>,
 }
 
 /// Configuration of footnotes.
@@ -411,6 +439,33 @@ struct LineNumberConfig {
     default_clearance: Abs,
 }
 
+/// Configuration of margin notes.
+struct MarginNoteConfig {
+    /// The width of the outside margin.
+    width: Abs,
+    /// Whether the outside margin alternates between left and right.
+    two_sided: bool,
+    /// The side on which the pages are bound.
+    binding: Binding,
+    /// The space between the text and the notes and between the notes and the
+    /// page's edge.
+    clearance: Abs,
+    /// The minimum gap between notes.
+    gap: Abs,
+}
+
+impl MarginNoteConfig {
+    /// Whether the outside margin of the page with the given number is on the
+    /// right.
+    fn right(&self, page: NonZeroUsize) -> bool {
+        if self.two_sided {
+            !self.binding.swap(page)
+        } else {
+            self.binding == Binding::Left
+        }
+    }
+}
+
 /// The result type for flow layout.
 ///
 /// The `Err(_)` variant incorporate control flow events for finishing and
@@ -214,7 +214,7 @@ pub fn collect<'a>(
                     InlineItem::Frame(mut frame) => {
                         frame.modify(&FrameModifiers::get_in(styles));
                         apply_shift(&engine.world, &mut frame, styles);
-                        collector.push_item(Item::Frame(frame));
+                        collector.push_frame(frame, config);
                     }
                 }
             }
@@ -229,7 +229,7 @@ pub fn collect<'a>(
                     layout_box(elem, engine, loc, styles, region)
                 })?;
                 apply_shift(&engine.world, &mut frame, styles);
-                collector.push_item(Item::Frame(frame));
+                collector.push_frame(frame, config);
             }
         } else if let Some(elem) = child.to_packed::<TagElem>() {
             collector.push_item(Item::Tag(&elem.tag));
@@ -289,6 +289,12 @@ impl<'a> Collector<'a> {
         self.segments.push(Segment::Text(segment_len, styles));
     }
 
+    /// Push laid-out inline content, which stays upright in vertical text.
+    fn push_frame(&mut self, frame: Frame, config: &Config) {
+        let frame = if config.is_vertical() { upright(frame) } else { frame };
+        self.push_item(Item::Frame(frame));
+    }
+
     fn push_item(&mut self, item: Item<'a>) {
         match (self.segments.last_mut(), &item) {
             // Merge adjacent weak spacing by taking the maximum.
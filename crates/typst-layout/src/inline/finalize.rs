@@ -13,6 +13,10 @@ pub fn finalize(
     expand: bool,
     locator: &mut SplitLocator<'_>,
 ) -> SourceResult<Fragment> {
+    if p.config.is_vertical() {
+        return finalize_vertical(engine, p, lines, region, expand, locator);
+    }
+
     // Determine the resulting width: Full width of the region if we should
     // expand or there's fractional spacing, fit-to-width otherwise.
     let width = if !region.x.is_finite()
@@ -4,7 +4,7 @@ use std::ops::{Deref, DerefMut};
 use typst_library::engine::Engine;
 use typst_library::foundations::Resolve;
 use typst_library::introspection::{SplitLocator, Tag, TagFlags};
-use typst_library::layout::{Abs, Dir, Em, Fr, Frame, FrameItem, Point};
+use typst_library::layout::{Abs, Axis, Dir, Em, Fr, Frame, FrameItem, Point};
 use typst_library::model::ParLineMarker;
 use typst_library::text::{Lang, TextElem, variant};
 use typst_utils::Numeric;
@@ -491,10 +491,10 @@ pub fn commit(
     let mut remaining = width - line.width - p.config.hanging_indent;
     let mut offset = Abs::zero();
 
-    // We always build the line from left to right. In an LTR paragraph, we must
-    // thus add the hanging indent to the offset. In an RTL paragraph, the
-    // hanging indent arises naturally due to the line width.
-    if p.config.dir == Dir::LTR {
+    // We always build the line from left to right. In an LTR or vertical
+    // paragraph, we must thus add the hanging indent to the offset. In an RTL
+    // paragraph, the hanging indent arises naturally due to the line width.
+    if p.config.dir.is_positive() {
         offset += p.config.hanging_indent;
     }
 
@@ -583,12 +583,15 @@ pub fn commit(
                 }
             }
             Item::Text(shaped) => {
-                let frame = shaped.build(
+                let mut frame = shaped.build(
                     engine,
                     &p.spans,
                     justification_ratio,
                     extra_justification,
                 );
+                if p.config.is_vertical() && shaped.dir.axis() == Axis::X {
+                    center_rotated(&mut frame, shaped);
+                }
                 push(&mut offset, frame, idx);
             }
             Item::Frame(frame) => {
@@ -7,6 +7,7 @@ mod line;
 mod linebreak;
 mod prepare;
 mod shaping;
+mod vertical;
 
 pub use self::box_::layout_box;
 pub use self::shaping::{SharedShapingContext, create_shape_plan, get_font_and_covers};
@@ -17,14 +18,14 @@ use typst_library::diag::SourceResult;
 use typst_library::engine::{Engine, Route, Sink, Traced};
 use typst_library::foundations::{Packed, Smart, StyleChain};
 use typst_library::introspection::{Introspector, Locator, LocatorLink, SplitLocator};
-use typst_library::layout::{Abs, AlignElem, Dir, FixedAlignment, Fragment, Size};
+use typst_library::layout::{Abs, AlignElem, Axis, Dir, FixedAlignment, Fragment, Size};
 use typst_library::model::{
     EnumElem, FirstLineIndent, JustificationLimits, Linebreaks, ListElem, ParElem,
     ParLine, ParLineMarker, TermsElem,
 };
 use typst_library::routines::{Arenas, Pair, RealizationKind, Routines};
 use typst_library::text::{Costs, Lang, TextElem};
-use typst_utils::{Numeric, Protected, SliceExt};
+use typst_utils::{Get, Numeric, Protected, SliceExt};
 
 use self::collect::{Item, Segment, SpanMapper, collect};
 use self::deco::decorate;
@@ -36,6 +37,9 @@ use self::shaping::{
     BEGIN_PUNCT_PAT, END_PUNCT_PAT, ShapedGlyph, ShapedText, cjk_punct_style,
     is_of_cj_script, shape_range,
 };
+use self::vertical::{
+    center_rotated, combine, finalize_vertical, is_combined, is_upright, upright,
+};
 
 /// Range of a substring of text.
 type Range = std::ops::Range<usize>;
@@ -118,6 +122,7 @@ fn layout_par_impl(
             linebreaks: elem.linebreaks.get(styles),
             first_line_indent: elem.first_line_indent.get(styles),
             hanging_indent: elem.hanging_indent.resolve(styles),
+            leading: elem.leading.resolve(styles),
         },
     )
 }
@@ -144,6 +149,7 @@ pub fn layout_inline<'a>(
             linebreaks: shared.get(ParElem::linebreaks),
             first_line_indent: shared.get(ParElem::first_line_indent),
             hanging_indent: shared.resolve(ParElem::hanging_indent),
+            leading: shared.resolve(ParElem::leading),
         },
     )
 }
@@ -170,8 +176,9 @@ fn layout_inline_impl<'a>(
     // proceed to line breaking.
     let p = prepare(engine, &config, &text, segments, spans)?;
 
-    // Break the text into lines.
-    let lines = linebreak(engine, &p, region.x - config.hanging_indent);
+    // Break the text into lines. In vertical text, lines run along the y-axis.
+    let lines =
+        linebreak(engine, &p, region.get(config.dir.axis()) - config.hanging_indent);
 
     // Turn the selected lines into frames.
     finalize(engine, &p, &lines, region, expand, locator)
@@ -217,17 +224,21 @@ fn configuration(
         } else {
             Abs::zero()
         },
-        numbering_marker: shared.get_cloned(ParLine::numbering).map(|numbering| {
-            Packed::new(ParLineMarker::new(
-                numbering,
-                shared.get(ParLine::number_align),
-                shared.get(ParLine::number_margin),
-                // Delay resolving the number clearance until line numbers are
-                // laid out to avoid inconsistent spacing depending on varying
-                // font size.
-                shared.get(ParLine::number_clearance),
-            ))
-        }),
+        numbering_marker: shared
+            .get_cloned(ParLine::numbering)
+            // Line numbers are not supported for the columns of vertical text.
+            .filter(|_| dir.axis() == Axis::X)
+            .map(|numbering| {
+                Packed::new(ParLineMarker::new(
+                    numbering,
+                    shared.get(ParLine::number_align),
+                    shared.get(ParLine::number_margin),
+                    // Delay resolving the number clearance until line numbers
+                    // are laid out to avoid inconsistent spacing depending on
+                    // varying font size.
+                    shared.get(ParLine::number_clearance),
+                ))
+            }),
         align: shared.get(AlignElem::alignment).fix(dir).x,
         font_size,
         dir,
@@ -237,6 +248,7 @@ fn configuration(
         fallback: shared.get(TextElem::fallback),
         cjk_latin_spacing: shared.get(TextElem::cjk_latin_spacing).is_auto(),
         costs: shared.get(TextElem::costs),
+        leading: base.leading,
     }
 }
 
@@ -260,6 +272,7 @@ struct ConfigBase {
     linebreaks: Smart<Linebreaks>,
     first_line_indent: FirstLineIndent,
     hanging_indent: Abs,
+    leading: Abs,
 }
 
 /// Shared configuration for the whole inline layout.
@@ -294,6 +307,16 @@ struct Config {
     cjk_latin_spacing: bool,
     /// Costs for various layout decisions.
     costs: Costs,
+    /// The spacing between lines. Only used for the columns of vertical text,
+    /// horizontal lines are spaced by the flow.
+    leading: Abs,
+}
+
+impl Config {
+    /// Whether the lines run vertically.
+    fn is_vertical(&self) -> bool {
+        self.dir.axis() == Axis::Y
+    }
 }
 
 /// Get a style property, but only if it is the same for all of the children.
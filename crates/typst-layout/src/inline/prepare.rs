@@ -1,5 +1,5 @@
 use either::Either;
-use typst_library::layout::{Dir, Em};
+use typst_library::layout::{Abs, Axis, Dir, Em};
 use typst_library::text::TextElem;
 use unicode_bidi::{BidiInfo, Level as BidiLevel};
 
@@ -92,7 +92,15 @@ pub fn prepare<'a>(
 
         match segment {
             Segment::Text(_, styles) => {
-                shape_range(&mut items, engine, text, &bidi, range, styles);
+                shape_range(
+                    &mut items,
+                    engine,
+                    text,
+                    &bidi,
+                    range,
+                    styles,
+                    config.is_vertical(),
+                );
             }
             Segment::Item(item) => items.push((range, item)),
         }
@@ -100,6 +108,20 @@ pub fn prepare<'a>(
         cursor = end;
     }
 
+    // In vertical text, set short runs of digits upright within a single em.
+    if config.is_vertical() {
+        for (_, item) in &mut items {
+            if let Item::Text(shaped) = item
+                && shaped.dir.axis() == Axis::X
+                && is_combined(shaped.text)
+            {
+                let frame = shaped.build(engine, &spans, 0.0, Abs::zero());
+                let em = shaped.styles.resolve(TextElem::size);
+                *item = Item::Frame(combine(frame, em));
+            }
+        }
+    }
+
     // Build the mapping from byte to item indices.
     let mut indices = Vec::with_capacity(text.len());
     for (i, (range, _)) in items.iter().enumerate() {
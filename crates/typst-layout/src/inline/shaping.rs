@@ -11,17 +11,18 @@ use ttf_parser::gsub::SubstitutionSubtable;
 use typst_library::World;
 use typst_library::engine::Engine;
 use typst_library::foundations::{Regex, Smart, StyleChain};
-use typst_library::layout::{Abs, Dir, Em, Frame, FrameItem, Point, Rel, Size};
+use typst_library::layout::{Abs, Axis, Dir, Em, Frame, FrameItem, Point, Rel, Size};
 use typst_library::model::{JustificationLimits, ParElem};
 use typst_library::text::{
-    Font, FontFamily, FontVariant, Glyph, Lang, Region, ShiftSettings, TextEdgeBounds,
-    TextElem, TextItem, families, features, is_default_ignorable, language, variant,
+    Font, FontFamily, FontVariant, FontVariations, Glyph, Lang, Region, ShiftSettings,
+    TextEdgeBounds, TextElem, TextItem, families, features, is_default_ignorable,
+    language, variant,
 };
 use typst_utils::SliceExt;
 use unicode_bidi::{BidiInfo, Level as BidiLevel};
 use unicode_script::{Script, UnicodeScript};
 
-use super::{Item, Range, SpanMapper, decorate};
+use super::{Item, Range, SpanMapper, decorate, is_upright, upright};
 use crate::modifiers::FrameModifyText;
 
 const SHY: char = '\u{ad}';
@@ -135,10 +136,19 @@ pub struct ShapedGlyph {
     /// The glyph's index in the font.
     pub glyph_id: u16,
     /// The advance width of the glyph.
+    ///
+    /// For upright glyphs in vertical text, this is the advance along the
+    /// line, i.e. the advance height.
     pub x_advance: Em,
     /// The horizontal offset of the glyph.
+    ///
+    /// For upright glyphs in vertical text, this is the downwards offset along
+    /// the line.
     pub x_offset: Em,
     /// The vertical offset of the glyph.
+    ///
+    /// For upright glyphs in vertical text, this is the rightwards offset
+    /// across the line.
     pub y_offset: Em,
     /// The font size for the glyph.
     pub size: Abs,
@@ -314,6 +324,21 @@ impl ShapedGlyph {
         self.x_advance -= amount;
         self.adjustability.shrinkability.1 -= amount;
     }
+
+    /// The extra advance the glyph gets on its left and right side for the
+    /// given justification.
+    fn justification(&self, ratio: f64, extra: Abs) -> (Em, Em) {
+        let adjustability =
+            if ratio < 0.0 { self.shrinkability() } else { self.stretchability() };
+
+        let left = adjustability.0 * ratio;
+        let mut right = adjustability.1 * ratio;
+        if self.is_justifiable() {
+            right += Em::from_abs(extra, self.size);
+        }
+
+        (left, right)
+    }
 }
 
 impl<'a> ShapedText<'a> {
@@ -328,6 +353,10 @@ impl<'a> ShapedText<'a> {
         justification_ratio: f64,
         extra_justification: Abs,
     ) -> Frame {
+        if self.dir.axis() == Axis::Y {
+            return self.build_upright(spans, justification_ratio, extra_justification);
+        }
+
         let (top, bottom) = self.measure(engine);
         let size = Size::new(self.width(), top + bottom);
 
@@ -365,24 +394,8 @@ impl<'a> ShapedText<'a> {
                     let kept = self.glyphs.kept.contains(&i);
 
                     let (x_advance, x_offset) = if kept {
-                        let adjustability_left = if justification_ratio < 0.0 {
-                            shaped.shrinkability().0
-                        } else {
-                            shaped.stretchability().0
-                        };
-                        let adjustability_right = if justification_ratio < 0.0 {
-                            shaped.shrinkability().1
-                        } else {
-                            shaped.stretchability().1
-                        };
-
-                        let justification_left = adjustability_left * justification_ratio;
-                        let mut justification_right =
-                            adjustability_right * justification_ratio;
-                        if shaped.is_justifiable() {
-                            justification_right +=
-                                Em::from_abs(extra_justification, glyph_size)
-                        }
+                        let (justification_left, justification_right) = shaped
+                            .justification(justification_ratio, extra_justification);
 
                         frame.size_mut().x += justification_left.at(glyph_size)
                             + justification_right.at(glyph_size);
@@ -462,6 +475,86 @@ impl<'a> ShapedText<'a> {
         frame
     }
 
+    /// Build the frame of an upright run in vertical text.
+    ///
+    /// The glyphs are set from top to bottom in a frame that is one em wide
+    /// and then turned into the rotated coordinate system of the line.
+    fn build_upright(
+        &self,
+        spans: &SpanMapper,
+        justification_ratio: f64,
+        extra_justification: Abs,
+    ) -> Frame {
+        let size = self.styles.resolve(TextElem::size);
+        let fill = self.styles.get_ref(TextElem::fill);
+        let stroke = self.styles.resolve(TextElem::stroke);
+        let span_offset = self.styles.get(TextElem::span_offset);
+
+        let mut offset = Abs::zero();
+        let mut frame = Frame::soft(Size::with_x(size));
+
+        let mut i = 0;
+        for ((font, glyph_size), group) in
+            self.glyphs.all().group_by_key(|g| (g.font.clone(), g.size))
+        {
+            let mut range = group[0].range.clone();
+            for glyph in group {
+                range.start = range.start.min(glyph.range.start);
+                range.end = range.end.max(glyph.range.end);
+            }
+
+            let glyphs: Vec<Glyph> = group
+                .iter()
+                .map(|shaped: &ShapedGlyph| {
+                    let kept = self.glyphs.kept.contains(&i);
+                    let (advance, inline_offset) = if kept {
+                        let (left, right) = shaped
+                            .justification(justification_ratio, extra_justification);
+                        (shaped.x_advance + left + right, shaped.x_offset + left)
+                    } else {
+                        (Em::zero(), Em::zero())
+                    };
+                    i += 1;
+
+                    let mut span = spans.span_at(shaped.range.start);
+                    span.1 = span.1.saturating_add(span_offset.saturating_as());
+
+                    // The text item's origin is on the center line at the top
+                    // of the glyphs and its advances and offsets are Y-up.
+                    Glyph {
+                        id: shaped.glyph_id,
+                        x_advance: Em::zero(),
+                        x_offset: shaped.y_offset,
+                        y_advance: -advance,
+                        y_offset: -inline_offset,
+                        range: (shaped.range.start - range.start).saturating_as()
+                            ..(shaped.range.end - range.start).saturating_as(),
+                        span,
+                    }
+                })
+                .collect();
+
+            let item = TextItem {
+                font,
+                size: glyph_size,
+                lang: self.lang,
+                region: self.region,
+                fill: fill.clone(),
+                stroke: stroke.clone().map(|s| s.unwrap_or_default()),
+                text: self.text[range.start - self.base..range.end - self.base].into(),
+                glyphs,
+            };
+
+            let height = -item.height();
+            frame.push(Point::new(size / 2.0, offset), FrameItem::Text(item));
+            offset += height;
+        }
+
+        frame.size_mut().y = offset;
+        frame.modify_text(self.styles);
+        upright(frame)
+    }
+
     /// Computes the width of a run of glyphs relative to the font size,
     /// accounting for their individual scaling factors and other font metrics.
     pub fn width(&self) -> Abs {
@@ -488,11 +581,13 @@ impl<'a> ShapedText<'a> {
             // When there are no glyphs, we just use the vertical metrics of the
             // first available font.
             let world = engine.world;
+            let variations = self.styles.get_cloned(TextElem::variations);
             for family in families(self.styles) {
                 if let Some(font) = world
                     .book()
                     .select(family.as_str(), self.variant)
                     .and_then(|id| world.font(id))
+                    .map(|font| font.instance(self.variant, &variations))
                 {
                     expand(&font, TextEdgeBounds::Zero);
                     break;
@@ -599,8 +694,9 @@ impl<'a> ShapedText<'a> {
             .chain(fallback_func.iter().map(|f| f()))
             .flatten();
 
+        let variations = base.styles.get_cloned(TextElem::variations);
         chain.find_map(|id| {
-            let font = world.font(id)?;
+            let font = world.font(id)?.instance(base.variant, &variations);
             let ttf = font.ttf();
             let glyph_id = ttf.glyph_index('-')?;
             let x_advance = font.to_em(ttf.glyph_hor_advance(glyph_id)?);
@@ -714,6 +810,10 @@ impl Debug for ShapedText<'_> {
 
 /// Group a range of text by BiDi level and script, shape the runs and generate
 /// items for them.
+///
+/// In vertical text, the runs are additionally grouped by whether their
+/// characters stay upright. Upright runs are shaped from top to bottom while
+/// all others are shaped horizontally and end up rotated.
 pub fn shape_range<'a>(
     items: &mut Vec<(Range, Item<'a>)>,
     engine: &Engine,
@@ -721,12 +821,19 @@ pub fn shape_range<'a>(
     bidi: &BidiInfo<'a>,
     range: Range,
     styles: StyleChain<'a>,
+    vertical: bool,
 ) {
     let script = styles.get(TextElem::script);
     let lang = styles.get(TextElem::lang);
     let region = styles.get(TextElem::region);
-    let mut process = |range: Range, level: BidiLevel| {
-        let dir = if level.is_ltr() { Dir::LTR } else { Dir::RTL };
+    let mut process = |range: Range, level: BidiLevel, upright: bool| {
+        let dir = if upright {
+            Dir::TTB
+        } else if level.is_ltr() {
+            Dir::LTR
+        } else {
+            Dir::RTL
+        };
         let shaped =
             shape(engine, range.start, &text[range.clone()], styles, dir, lang, region);
         items.push((range, Item::Text(shaped)));
@@ -734,6 +841,7 @@ pub fn shape_range<'a>(
 
     let mut prev_level = BidiLevel::ltr();
     let mut prev_script = Script::Unknown;
+    let mut prev_upright = false;
     let mut cursor = range.start;
 
     // Group by embedding level and script.  If the text's script is explicitly
@@ -752,19 +860,30 @@ pub fn shape_range<'a>(
             Smart::Custom(_) => Script::Unknown,
         };
 
-        if level != prev_level || !is_compatible(curr_script, prev_script) {
+        // Combining marks and variation selectors stay with their base.
+        let curr_upright = vertical
+            && text[i..].chars().next().is_some_and(|c| match c.script() {
+                Script::Inherited => prev_upright,
+                _ => is_upright(c),
+            });
+
+        if level != prev_level
+            || curr_upright != prev_upright
+            || !is_compatible(curr_script, prev_script)
+        {
             if cursor < i {
-                process(cursor..i, prev_level);
+                process(cursor..i, prev_level, prev_upright);
             }
             cursor = i;
             prev_level = level;
             prev_script = curr_script;
+            prev_upright = curr_upright;
         } else if is_generic_script(prev_script) {
             prev_script = curr_script;
         }
     }
 
-    process(cursor..range.end, prev_level);
+    process(cursor..range.end, prev_level, prev_upright);
 }
 
 /// Whether this is not a specific script.
@@ -797,6 +916,7 @@ fn shape<'a>(
         used: vec![],
         styles,
         variant: variant(styles),
+        variations: styles.get_cloned(TextElem::variations),
         features: features(styles),
         fallback: styles.get(TextElem::fallback),
         dir,
@@ -835,6 +955,7 @@ struct ShapingContext<'a> {
     styles: StyleChain<'a>,
     size: Abs,
     variant: FontVariant,
+    variations: FontVariations,
     features: Vec<rustybuzz::Feature>,
     fallback: bool,
     dir: Dir,
@@ -854,6 +975,8 @@ pub trait SharedShapingContext<'a> {
 
     fn variant(&self) -> FontVariant;
 
+    fn variations(&self) -> &FontVariations;
+
     fn fallback(&self) -> bool;
 }
 
@@ -874,6 +997,10 @@ impl<'a> SharedShapingContext<'a> for ShapingContext<'a> {
         self.variant
     }
 
+    fn variations(&self) -> &FontVariations {
+        &self.variations
+    }
+
     fn fallback(&self) -> bool {
         self.fallback
     }
@@ -898,6 +1025,7 @@ where
         selection = book
             .select(family.as_str(), ctx.variant())
             .and_then(|id| world.font(id))
+            .map(|font| font.instance(ctx.variant(), ctx.variations()))
             .filter(|font| !ctx.used().contains(font));
         if selection.is_some() {
             covers = family.covers();
@@ -911,6 +1039,7 @@ where
         selection = book
             .select_fallback(first, ctx.variant(), text)
             .and_then(|id| world.font(id))
+            .map(|font| font.instance(ctx.variant(), ctx.variations()))
             .filter(|font| !ctx.used().contains(font));
     }
 
@@ -965,7 +1094,8 @@ fn shape_segment<'a>(
     buffer.set_direction(match ctx.dir {
         Dir::LTR => rustybuzz::Direction::LeftToRight,
         Dir::RTL => rustybuzz::Direction::RightToLeft,
-        _ => unimplemented!("vertical text layout"),
+        Dir::TTB => rustybuzz::Direction::TopToBottom,
+        Dir::BTT => rustybuzz::Direction::BottomToTop,
     });
     buffer.guess_segment_properties();
 
@@ -1006,6 +1136,7 @@ fn shape_segment<'a>(
     let infos = buffer.glyph_infos();
     let pos = buffer.glyph_positions();
     let ltr = ctx.dir.is_positive();
+    let vertical = ctx.dir.axis() == Axis::Y;
 
     // Whether the character at the given offset is covered by the coverage.
     let is_covered = |offset| {
@@ -1065,14 +1196,29 @@ fn shape_segment<'a>(
 
             let c = text[cluster..].chars().next().unwrap();
             let script = c.script();
-            let x_advance = font.to_em(pos[i].x_advance);
+            let (x_advance, x_offset, y_offset) = if vertical {
+                // Vertical positions are Y-up and relative to the top center
+                // of the glyph. We store the ones along the line in the `x`
+                // fields (see `ShapedGlyph`).
+                (
+                    font.to_em(-pos[i].y_advance),
+                    font.to_em(-pos[i].y_offset),
+                    font.to_em(pos[i].x_offset),
+                )
+            } else {
+                // TODO: Don't ignore y_advance.
+                (
+                    font.to_em(pos[i].x_advance),
+                    font.to_em(pos[i].x_offset) + script_compensation,
+                    font.to_em(pos[i].y_offset) + script_shift,
+                )
+            };
             ctx.glyphs.push(ShapedGlyph {
                 font: font.clone(),
                 glyph_id: info.glyph_id as u16,
-                // TODO: Don't ignore y_advance.
                 x_advance,
-                x_offset: font.to_em(pos[i].x_offset) + script_compensation,
-                y_offset: font.to_em(pos[i].y_offset) + script_shift,
+                x_offset,
+                y_offset,
                 size: scale.at(ctx.size),
                 adjustability: Adjustability::default(),
                 range: start..end,
@@ -1213,7 +1359,14 @@ pub fn create_shape_plan(
 
 /// Shape the text with tofus from the given font.
 fn shape_tofus(ctx: &mut ShapingContext, base: usize, text: &str, font: Font) {
-    let x_advance = font.x_advance(0).unwrap_or_default();
+    let width = font.x_advance(0).unwrap_or_default();
+    let (x_advance, x_offset, y_offset) = if ctx.dir.axis() == Axis::Y {
+        // Hang the glyph from the top of the line and center it across.
+        let height = font.y_advance(0).unwrap_or(Em::one());
+        (height, font.metrics().ascender, -width / 2.0)
+    } else {
+        (width, Em::zero(), Em::zero())
+    };
     let add_glyph = |(cluster, c): (usize, char)| {
         let start = base + cluster;
         let end = start + c.len_utf8();
@@ -1222,8 +1375,8 @@ fn shape_tofus(ctx: &mut ShapingContext, base: usize, text: &str, font: Font) {
             font: font.clone(),
             glyph_id: 0,
             x_advance,
-            x_offset: Em::zero(),
-            y_offset: Em::zero(),
+            x_offset,
+            y_offset,
             size: ctx.size,
             adjustability: Adjustability::default(),
             range: start..end,
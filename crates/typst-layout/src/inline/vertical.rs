@@ -0,0 +1,183 @@
+use typst_library::layout::{Frame, FrameItem, GroupItem, Point, Ratio, Transform};
+
+use super::*;
+
+/// A quarter turn clockwise. Maps the inline axis of a line onto the physical
+/// y-axis and the line's top onto its right side.
+const CLOCKWISE: Transform = Transform {
+    sx: Ratio::zero(),
+    ky: Ratio::one(),
+    kx: Ratio::new(-1.0),
+    sy: Ratio::zero(),
+    tx: Abs::zero(),
+    ty: Abs::zero(),
+};
+
+/// A quarter turn counter-clockwise. Undoes [`CLOCKWISE`].
+const COUNTER_CLOCKWISE: Transform = Transform {
+    sx: Ratio::zero(),
+    ky: Ratio::new(-1.0),
+    kx: Ratio::one(),
+    sy: Ratio::zero(),
+    tx: Abs::zero(),
+    ty: Abs::zero(),
+};
+
+/// Turns the selected lines of vertical text into frames.
+///
+/// Lines are laid out just like horizontal ones and then rotated into
+/// columns. The columns progress from right to left and are distributed into
+/// as many frames as needed to fit into the region's width.
+pub fn finalize_vertical(
+    engine: &mut Engine,
+    p: &Preparation,
+    lines: &[Line],
+    region: Size,
+    expand: bool,
+    locator: &mut SplitLocator<'_>,
+) -> SourceResult<Fragment> {
+    // Determine the length of the columns: Full height of the region if it is
+    // finite, fit-to-height otherwise.
+    let length = if region.y.is_finite() {
+        region.y
+    } else {
+        p.config.hanging_indent
+            + lines.iter().map(|line| line.width).max().unwrap_or_default()
+    };
+
+    let leading = p.config.leading;
+    let mut frames = vec![];
+    let mut columns = vec![];
+    let mut width = Abs::zero();
+
+    for line in lines {
+        let column = commit(engine, p, line, length, region.x, locator)?;
+        if !columns.is_empty() {
+            if region.x.fits(width + leading + column.height()) {
+                width += leading;
+            } else {
+                let full = std::mem::take(&mut columns);
+                frames.push(stack(full, width, region, expand, leading, length));
+                width = Abs::zero();
+            }
+        }
+
+        width += column.height();
+        columns.push(column);
+    }
+
+    if !columns.is_empty() {
+        frames.push(stack(columns, width, region, expand, leading, length));
+    }
+
+    Ok(Fragment::frames(frames))
+}
+
+/// Stacks columns from right to left into one frame.
+fn stack(
+    columns: Vec<Frame>,
+    width: Abs,
+    region: Size,
+    expand: bool,
+    leading: Abs,
+    length: Abs,
+) -> Frame {
+    let width = if expand && region.x.is_finite() { region.x } else { width };
+    let mut output = Frame::soft(Size::new(width, length));
+
+    let mut x = width;
+    for column in columns {
+        let thickness = column.height();
+        output.push(
+            Point::with_x(x),
+            FrameItem::Group(GroupItem {
+                transform: CLOCKWISE,
+                ..GroupItem::new(column)
+            }),
+        );
+        x -= thickness + leading;
+    }
+
+    output
+}
+
+/// Turns the frame of content that stays upright in vertical text into a
+/// frame in the rotated coordinate system of a line.
+///
+/// The resulting frame's baseline is the content's vertical center line.
+pub fn upright(frame: Frame) -> Frame {
+    let size = frame.size();
+    let mut output = Frame::soft(Size::new(size.y, size.x));
+    output.set_baseline(size.x / 2.0);
+    output.push(
+        Point::with_y(size.x),
+        FrameItem::Group(GroupItem {
+            transform: COUNTER_CLOCKWISE,
+            ..GroupItem::new(frame)
+        }),
+    );
+    output
+}
+
+/// Sets a short run of horizontal text upright within a single em of a
+/// vertical line (tate-chu-yoko).
+pub fn combine(frame: Frame, em: Abs) -> Frame {
+    let mut output = Frame::soft(Size::splat(em));
+    let pos = Point::new((em - frame.width()) / 2.0, (em - frame.height()) / 2.0);
+    output.push_frame(pos, frame);
+    upright(output)
+}
+
+/// Whether a run of horizontal text in vertical text is combined into a
+/// single upright em instead of being rotated.
+pub fn is_combined(text: &str) -> bool {
+    (1..=2).contains(&text.len()) && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Moves the baseline of a rotated run of horizontal text onto its central
+/// line, so that it is centered on the line like the upright glyphs around it.
+pub fn center_rotated(frame: &mut Frame, shaped: &ShapedText) {
+    let Some(glyph) = shaped.glyphs.first() else { return };
+    let metrics = glyph.font.metrics();
+    let central = ((metrics.ascender + metrics.descender) / 2.0).at(glyph.size);
+    frame.set_baseline(frame.baseline() - central);
+}
+
+/// Whether a character stays upright in vertical text.
+///
+/// This approximates the `U`, `Tu`, and `Tr` classes of the Unicode
+/// Vertical_Orientation property (UAX #50). Characters of the latter two
+/// classes are replaced with their vertical forms by the font's `vert`
+/// feature during shaping. All other characters are rotated.
+pub fn is_upright(c: char) -> bool {
+    matches!(
+        c,
+        '§' | '©'
+            | '®'
+            | '±'
+            | '¼'..='¾'
+            | '×'
+            | '÷'
+            | '\u{1100}'..='\u{11FF}'
+            | '‖'
+            | '†'..='‡'
+            | '‥'..='…'
+            | '‰'..='‱'
+            | '※'..='‼'
+            | '⁂'
+            | '⁇'..='⁉'
+            | '⁑'
+            | '\u{2150}'..='\u{218F}'
+            | '\u{2460}'..='\u{24FF}'
+            | '\u{25A0}'..='\u{27BF}'
+            | '\u{2E80}'..='\u{A4CF}'
+            | '\u{A960}'..='\u{A97F}'
+            | '\u{AC00}'..='\u{D7FF}'
+            | '\u{E000}'..='\u{FAFF}'
+            | '\u{FE10}'..='\u{FE1F}'
+            | '\u{FE30}'..='\u{FE6F}'
+            | '\u{FF00}'..='\u{FFE7}'
+            | '\u{1F000}'..='\u{1FAFF}'
+            | '\u{20000}'..='\u{3FFFD}'
+    )
+}
@@ -1,5 +1,6 @@
 //! Typst's layout engine.
 
+mod field;
 mod flow;
 mod grid;
 mod image;
@@ -329,6 +329,7 @@ impl GlyphFragment {
         let (font, glyphs) = shape(
             world,
             variant(styles),
+            styles.get_cloned(TextElem::variations),
             features(styles),
             language(styles),
             styles.get(TextElem::fallback),
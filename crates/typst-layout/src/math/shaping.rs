@@ -6,7 +6,9 @@ use ecow::EcoString;
 use rustybuzz::{BufferFlags, UnicodeBuffer};
 use typst_library::World;
 use typst_library::layout::{Abs, Em};
-use typst_library::text::{Font, FontFamily, FontVariant, Glyph, Lang, Region, TextItem};
+use typst_library::text::{
+    Font, FontFamily, FontVariant, FontVariations, Glyph, Lang, Region, TextItem,
+};
 use typst_library::visualize::{FixedStroke, Paint};
 use typst_syntax::Span;
 
@@ -140,6 +142,7 @@ pub struct ShapedGlyph {
 pub fn shape(
     world: Tracked<dyn World + '_>,
     variant: FontVariant,
+    variations: FontVariations,
     features: Vec<rustybuzz::Feature>,
     language: rustybuzz::Language,
     fallback: bool,
@@ -150,6 +153,7 @@ pub fn shape(
         world,
         used: vec![],
         variant,
+        variations,
         features,
         language,
         fallback,
@@ -167,6 +171,7 @@ struct ShapingContext<'a> {
     world: Tracked<'a, dyn World + 'a>,
     used: Vec<Font>,
     variant: FontVariant,
+    variations: FontVariations,
     features: Vec<rustybuzz::Feature>,
     language: rustybuzz::Language,
     fallback: bool,
@@ -191,6 +196,10 @@ impl<'a> SharedShapingContext<'a> for ShapingContext<'a> {
         self.variant
     }
 
+    fn variations(&self) -> &FontVariations {
+        &self.variations
+    }
+
     fn fallback(&self) -> bool {
         self.fallback
     }
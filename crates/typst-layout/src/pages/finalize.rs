@@ -22,8 +22,11 @@ pub fn finalize(
         background,
         foreground,
         fill,
+        bleed,
         numbering,
         supplement,
+        transition,
+        duration,
     }: LayoutedPage,
 ) -> SourceResult<Page> {
     // If two sided, left becomes inside and right becomes outside.
@@ -45,7 +48,8 @@ pub fn finalize(
     // important as it affects the relative ordering of introspectable elements
     // and thus how counters resolve.
     if let Some(background) = background {
-        frame.push_frame(Point::zero(), background);
+        // The background also covers the bleed.
+        frame.push_frame(Point::new(-bleed.left, -bleed.top), background);
     }
     if let Some(header) = header {
         frame.push_frame(Point::with_x(margin.left), header);
@@ -70,5 +74,14 @@ pub fn finalize(
     let number = counter.logical();
     counter.step();
 
-    Ok(Page { frame, fill, numbering, supplement, number })
+    Ok(Page {
+        frame,
+        fill,
+        bleed,
+        numbering,
+        supplement,
+        number,
+        transition,
+        duration,
+    })
 }
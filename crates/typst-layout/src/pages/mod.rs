@@ -4,6 +4,8 @@ mod collect;
 mod finalize;
 mod run;
 
+pub(crate) use self::run::{page_binding, page_geometry};
+
 use std::num::NonZeroUsize;
 
 use comemo::{Tracked, TrackedMut};
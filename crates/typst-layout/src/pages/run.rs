@@ -3,7 +3,7 @@ use typst_library::World;
 use typst_library::diag::SourceResult;
 use typst_library::engine::{Engine, Route, Sink, Traced};
 use typst_library::foundations::{
-    Content, NativeElement, Resolve, Smart, StyleChain, Styles,
+    Content, Duration, NativeElement, Resolve, Smart, StyleChain, Styles,
 };
 use typst_library::introspection::{
     Counter, CounterDisplayElem, CounterKey, Introspector, Locator, LocatorLink,
@@ -14,7 +14,7 @@ use typst_library::layout::{
     VAlignment,
 };
 use typst_library::model::Numbering;
-use typst_library::pdf::ArtifactKind;
+use typst_library::pdf::{ArtifactKind, PageTransition};
 use typst_library::routines::{Pair, Routines};
 use typst_library::text::{LocalName, TextElem};
 use typst_library::visualize::Paint;
@@ -36,8 +36,11 @@ pub struct LayoutedPage {
     pub background: Option<Frame>,
     pub foreground: Option<Frame>,
     pub fill: Smart<Option<Paint>>,
+    pub bleed: Sides<Abs>,
     pub numbering: Option<Numbering>,
     pub supplement: Content,
+    pub transition: Option<PageTransition>,
+    pub duration: Option<Duration>,
 }
 
 /// Layout a single page suitable  for parity adjustment.
@@ -101,31 +104,11 @@ fn layout_page_run_impl(
     let styles = Styles::root(children, initial);
     let styles = StyleChain::new(&styles);
 
-    // When one of the lengths is infinite the page fits its content along
-    // that axis.
-    let width = styles.resolve(PageElem::width).unwrap_or(Abs::inf());
-    let height = styles.resolve(PageElem::height).unwrap_or(Abs::inf());
-    let mut size = Size::new(width, height);
-    if styles.get(PageElem::flipped) {
-        std::mem::swap(&mut size.x, &mut size.y);
-    }
-
-    let mut min = width.min(height);
-    if !min.is_finite() {
-        min = Paper::A4.width();
-    }
-
-    // Determine the margins.
-    let default = Rel::<Length>::from((2.5 / 21.0) * min);
-    let margin = styles.get(PageElem::margin);
-    let two_sided = margin.two_sided.unwrap_or(false);
-    let margin = margin
-        .sides
-        .map(|side| side.and_then(Smart::custom).unwrap_or(default))
-        .resolve(styles)
-        .relative_to(size);
+    // Determine the size and the margins.
+    let (size, margin, two_sided) = page_geometry(styles);
 
     let fill = styles.get_cloned(PageElem::fill);
+    let bleed = styles.resolve(PageElem::bleed).map(Option::unwrap_or_default);
     let foreground = styles.get_ref(PageElem::foreground);
     let background = styles.get_ref(PageElem::background);
     let header_ascent = styles.resolve(PageElem::header_ascent).relative_to(margin.top);
@@ -137,12 +120,9 @@ fn layout_page_run_impl(
         Smart::Custom(content) => content.unwrap_or_default(),
     };
     let number_align = styles.get(PageElem::number_align);
-    let binding = styles.get(PageElem::binding).unwrap_or_else(|| {
-        match styles.resolve(TextElem::dir) {
-            Dir::LTR => Binding::Left,
-            _ => Binding::Right,
-        }
-    });
+    let transition = styles.get(PageElem::transition);
+    let duration = styles.get(PageElem::duration);
+    let binding = page_binding(styles);
 
     // Construct the numbering (for header or footer).
     let numbering_marginal = numbering.as_ref().map(|numbering| {
@@ -213,15 +193,19 @@ fn layout_page_run_impl(
         let header_size = Size::new(inner.width(), margin.top - header_ascent);
         let footer_size = Size::new(inner.width(), margin.bottom - footer_descent);
         let full_size = inner.size() + margin.sum_by_axis();
+        let bleed_size = full_size + bleed.sum_by_axis();
         let mid = HAlignment::Center + VAlignment::Horizon;
         layouted.push(LayoutedPage {
             inner,
             fill: fill.clone(),
+            bleed,
             numbering: numbering.clone(),
             supplement: supplement.clone(),
+            transition,
+            duration,
             header: layout_marginal(&header, header_size, Alignment::BOTTOM)?,
             footer: layout_marginal(&footer, footer_size, Alignment::TOP)?,
-            background: layout_marginal(&background, full_size, mid)?,
+            background: layout_marginal(&background, bleed_size, mid)?,
             foreground: layout_marginal(foreground, full_size, mid)?,
             margin,
             binding,
@@ -231,3 +215,43 @@ fn layout_page_run_impl(
 
     Ok(layouted)
 }
+
+/// Determines the size of the pages in a run, their margins, and whether the
+/// margins are two-sided.
+///
+/// When one of the lengths is infinite the page fits its content along that
+/// axis.
+pub(crate) fn page_geometry(styles: StyleChain) -> (Size, Sides<Abs>, bool) {
+    let width = styles.resolve(PageElem::width).unwrap_or(Abs::inf());
+    let height = styles.resolve(PageElem::height).unwrap_or(Abs::inf());
+    let mut size = Size::new(width, height);
+    if styles.get(PageElem::flipped) {
+        std::mem::swap(&mut size.x, &mut size.y);
+    }
+
+    let mut min = width.min(height);
+    if !min.is_finite() {
+        min = Paper::A4.width();
+    }
+
+    let default = Rel::<Length>::from((2.5 / 21.0) * min);
+    let margin = styles.get(PageElem::margin);
+    let two_sided = margin.two_sided.unwrap_or(false);
+    let margin = margin
+        .sides
+        .map(|side| side.and_then(Smart::custom).unwrap_or(default))
+        .resolve(styles)
+        .relative_to(size);
+
+    (size, margin, two_sided)
+}
+
+/// Determines on which side the pages in a run are bound.
+pub(crate) fn page_binding(styles: StyleChain) -> Binding {
+    styles
+        .get(PageElem::binding)
+        .unwrap_or_else(|| match styles.resolve(TextElem::dir) {
+            Dir::LTR => Binding::Left,
+            _ => Binding::Right,
+        })
+}
@@ -8,21 +8,25 @@ use typst_library::foundations::{
 };
 use typst_library::introspection::{Counter, Locator, LocatorLink};
 use typst_library::layout::{
-    Abs, AlignElem, Alignment, Axes, BlockBody, BlockElem, ColumnsElem, Em,
-    FixedAlignment, GridCell, GridChild, GridElem, GridItem, HAlignment, HElem, HideElem,
-    InlineElem, LayoutElem, Length, MoveElem, OuterVAlignment, PadElem, PageElem,
-    PlaceElem, PlacementScope, Region, Rel, RepeatElem, RotateElem, ScaleElem, Sides,
-    Size, Sizing, SkewElem, Spacing, StackChild, StackElem, TrackSizings, VElem,
+    Abs, AlignElem, Alignment, Axes, BlockBody, BlockElem, BoxElem, ColumnsElem, Corners,
+    Em, FixedAlignment, GridCell, GridChild, GridElem, GridItem, HAlignment, HElem,
+    HideElem, InlineElem, LayoutElem, Length, MoveElem, OuterVAlignment, PadElem,
+    PageElem, PlaceElem, PlacementScope, Ratio, Region, Rel, RepeatElem, RotateElem,
+    ScaleElem, Sides, Size, Sizing, SkewElem, Spacing, StackChild, StackElem,
+    TrackSizings, VElem,
 };
 use typst_library::math::EquationElem;
 use typst_library::model::{
     Attribution, BibliographyElem, CiteElem, CiteGroup, CslIndentElem, CslLightElem,
-    Destination, DirectLinkElem, EmphElem, EnumElem, FigureCaption, FigureElem,
-    FootnoteElem, FootnoteEntry, HeadingElem, LinkElem, LinkMarker, ListElem,
-    OutlineElem, OutlineEntry, ParElem, ParbreakElem, QuoteElem, RefElem, StrongElem,
-    TableCell, TableElem, TermsElem, TitleElem, Works,
+    Destination, DirectLinkElem, EmphElem, EndnotesElem, EnumElem, FigureCaption,
+    FigureElem, FootnoteElem, FootnoteEntry, HeadingElem, LinkElem, LinkMarker, ListElem,
+    MarginNoteElem, OutlineElem, OutlineEntry, ParElem, ParbreakElem, QuoteElem, RefElem,
+    StrongElem, TableCell, TableElem, TermsElem, TitleElem, Works,
+};
+use typst_library::pdf::{
+    ArtifactElem, ArtifactKind, AttachElem, FieldElem, FieldKind, FieldValue,
+    FieldWidgetElem, PdfMarkerTag,
 };
-use typst_library::pdf::{ArtifactElem, ArtifactKind, AttachElem, PdfMarkerTag};
 use typst_library::text::{
     DecoLine, Decoration, HighlightElem, ItalicToggle, LinebreakElem, LocalName,
     OverlineElem, RawElem, RawLine, ScriptKind, ShiftSettings, Smallcaps, SmallcapsElem,
@@ -55,6 +59,8 @@ pub fn register(rules: &mut NativeRuleMap) {
     rules.register(Paged, QUOTE_RULE);
     rules.register(Paged, FOOTNOTE_RULE);
     rules.register(Paged, FOOTNOTE_ENTRY_RULE);
+    rules.register(Paged, ENDNOTES_RULE);
+    rules.register(Paged, MARGIN_NOTE_RULE);
     rules.register(Paged, OUTLINE_RULE);
     rules.register(Paged, OUTLINE_ENTRY_RULE);
     rules.register(Paged, REF_RULE);
@@ -108,6 +114,7 @@ pub fn register(rules: &mut NativeRuleMap) {
     // PDF.
     rules.register(Paged, ATTACH_RULE);
     rules.register(Paged, ARTIFACT_RULE);
+    rules.register(Paged, FIELD_RULE);
     rules.register(Paged, PDF_MARKER_TAG_RULE);
 }
 
@@ -413,6 +420,24 @@ const FOOTNOTE_ENTRY_RULE: ShowFn<FootnoteEntry> = |elem, engine, styles| {
     ]))
 };
 
+const ENDNOTES_RULE: ShowFn<EndnotesElem> = |elem, engine, styles| {
+    let span = elem.span();
+    let mut seq = vec![];
+    seq.extend(elem.realize_title(styles));
+    for note in elem.notes(engine, styles) {
+        let loc = note.location().unwrap();
+        // The entry gets the same derived location as it would at the bottom
+        // of the page so that the note's marker links to it.
+        seq.push(FootnoteEntry::new(note).pack().spanned(span).located(loc.variant(1)));
+        seq.push(ParbreakElem::shared().clone());
+    }
+    Ok(Content::sequence(seq))
+};
+
+// The note itself is placed into the margin by flow layout. In the text, it
+// only leaves behind its tags.
+const MARGIN_NOTE_RULE: ShowFn<MarginNoteElem> = |_, _, _| Ok(Content::empty());
+
 const OUTLINE_RULE: ShowFn<OutlineElem> = |elem, engine, styles| {
     let title = elem.realize_title(styles);
     let entries = elem.realize_flat(engine, styles)?;
@@ -834,4 +859,83 @@ const ATTACH_RULE: ShowFn<AttachElem> = |_, _, _| Ok(Content::empty());
 
 const ARTIFACT_RULE: ShowFn<ArtifactElem> = |elem, _, _| Ok(elem.body.clone());
 
+const FIELD_RULE: ShowFn<FieldElem> = |elem, _, styles| {
+    let span = elem.span();
+    let kind = elem.kind.get(styles);
+    let options = elem.options.get_ref(styles);
+    let what = match kind {
+        FieldKind::Text => "text field",
+        FieldKind::Checkbox => "checkbox",
+        FieldKind::Radio => "radio group",
+        FieldKind::Dropdown => "dropdown",
+        FieldKind::Signature => "signature field",
+    };
+
+    match (kind, elem.value.get_ref(styles)) {
+        (_, None) => {}
+        (FieldKind::Checkbox, Some(FieldValue::Bool(_))) => {}
+        (FieldKind::Text, Some(FieldValue::Str(_))) => {}
+        (FieldKind::Radio | FieldKind::Dropdown, Some(FieldValue::Str(value))) => {
+            if !options.contains(value) {
+                bail!(span, "the value of a {what} must be one of its options");
+            }
+        }
+        (FieldKind::Signature, Some(_)) => {
+            bail!(span, "signature fields cannot have a value")
+        }
+        (FieldKind::Checkbox, Some(_)) => {
+            bail!(span, "expected boolean as the value of a checkbox")
+        }
+        (_, Some(_)) => bail!(span, "expected string as the value of a {what}"),
+    }
+
+    if matches!(kind, FieldKind::Radio | FieldKind::Dropdown) && options.is_empty() {
+        bail!(span, "a {what} must have at least one option");
+    }
+
+    let (width, height, baseline) = match kind {
+        FieldKind::Text | FieldKind::Dropdown => (10.0, 1.4, 0.2),
+        FieldKind::Checkbox | FieldKind::Radio => (0.8, 0.8, 0.0),
+        FieldKind::Signature => (14.0, 3.0, 0.0),
+    };
+    let width = elem.width.get(styles).unwrap_or(Em::new(width).into());
+    let height = elem.height.get(styles).unwrap_or(Em::new(height).into());
+    let radius = (kind == FieldKind::Radio).then(|| Ratio::new(0.5).into());
+    let location = elem.location().unwrap();
+
+    let widget = |index: usize| {
+        let body = BlockElem::single_layouter(
+            Packed::new(FieldWidgetElem::new(location, index)).spanned(span),
+            crate::field::layout_field_widget,
+        )
+        .with_height(Sizing::Rel(Rel::one()));
+        BoxElem::new()
+            .with_body(Some(body.pack().spanned(span)))
+            .with_width(Sizing::Rel(width))
+            .with_height(Smart::Custom(height))
+            .with_baseline(Ratio::new(baseline).into())
+            .with_fill(elem.fill.get_cloned(styles))
+            .with_stroke(Sides::splat(Some(elem.stroke.get_cloned(styles))))
+            .with_radius(Corners::splat(radius))
+            .pack()
+            .spanned(span)
+    };
+
+    if kind != FieldKind::Radio {
+        return Ok(widget(0));
+    }
+
+    // A radio group shows one button per option, followed by its text.
+    let mut seq = vec![];
+    for (i, option) in options.iter().enumerate() {
+        if i > 0 {
+            seq.push(HElem::new(Em::new(1.0).into()).pack());
+        }
+        seq.push(widget(i));
+        seq.push(HElem::new(Em::new(0.3).into()).pack());
+        seq.push(TextElem::packed(option.clone()));
+    }
+    Ok(Content::sequence(seq).spanned(span))
+};
+
 const PDF_MARKER_TAG_RULE: ShowFn<PdfMarkerTag> = |elem, _, _| Ok(elem.body.clone());
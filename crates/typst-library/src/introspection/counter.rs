@@ -564,6 +564,19 @@ cast! {
 pub trait Count {
     /// Get the counter update for this element.
     fn update(&self) -> Option<CounterUpdate>;
+
+    /// Whether the given counter, whose selector matches this element, should
+    /// count it.
+    ///
+    /// This is for elements that pick their counter based on their fields,
+    /// like footnotes of a series: `counter(footnote)` matches all footnotes,
+    /// but should only count those without a series. The counter's key is
+    /// the selector the user wrote, so we can't narrow it in
+    /// [`Counter::select`] without knowing every element's rules. Instead, the
+    /// element decides here.
+    fn is_counted_by(&self, _: &Counter) -> bool {
+        true
+    }
 }
 
 /// Counts through elements with different levels.
@@ -902,6 +915,7 @@ fn sequence_impl(
         }
 
         if let Some(update) = match elem.with::<dyn Count>() {
+            Some(countable) if !countable.is_counted_by(counter) => None,
             Some(countable) => countable.update(),
             None => Some(CounterUpdate::Step(NonZeroUsize::ONE)),
         } {
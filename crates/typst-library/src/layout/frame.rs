@@ -11,6 +11,7 @@ use crate::foundations::{Dict, Label, Value, cast, dict};
 use crate::introspection::{Location, Tag};
 use crate::layout::{Abs, Axes, FixedAlignment, Length, Point, Size, Transform};
 use crate::model::Destination;
+use crate::pdf::FieldWidget;
 use crate::text::TextItem;
 use crate::visualize::{Color, Curve, FixedStroke, Geometry, Image, Paint, Shape};
 
@@ -474,6 +475,8 @@ pub enum FrameItem {
     Image(Image, Size, Span),
     /// An internal or external link to a destination.
     Link(Destination, Size),
+    /// A widget of an interactive form field and its size.
+    Widget(FieldWidget, Size),
     /// An introspectable element that produced something within this frame.
     Tag(Tag),
 }
@@ -486,6 +489,7 @@ impl Debug for FrameItem {
             Self::Shape(shape, _) => write!(f, "{shape:?}"),
             Self::Image(image, _, _) => write!(f, "{image:?}"),
             Self::Link(dest, _) => write!(f, "Link({dest:?})"),
+            Self::Widget(widget, _) => write!(f, "Widget({widget:?})"),
             Self::Tag(tag) => write!(f, "{tag:?}"),
         }
     }
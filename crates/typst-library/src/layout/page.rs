@@ -7,8 +7,8 @@ use typst_utils::{NonZeroExt, Scalar, singleton};
 use crate::diag::{SourceResult, bail};
 use crate::engine::Engine;
 use crate::foundations::{
-    Args, AutoValue, Cast, Construct, Content, Dict, Fold, NativeElement, Set, Smart,
-    Value, cast, elem,
+    Args, AutoValue, Cast, Construct, Content, Dict, Duration, Fold, NativeElement, Set,
+    Smart, Value, cast, elem,
 };
 use crate::introspection::Introspector;
 use crate::layout::{
@@ -16,6 +16,7 @@ use crate::layout::{
     Sides, SpecificAlignment,
 };
 use crate::model::{DocumentInfo, Numbering};
+use crate::pdf::PageTransition;
 use crate::text::LocalName;
 use crate::visualize::{Color, Paint};
 
@@ -217,6 +218,31 @@ pub struct PageElem {
     #[ghost]
     pub fill: Smart<Option<Paint>>,
 
+    /// How far the page's fill and background extend beyond its edges.
+    ///
+    /// Printers cannot print up to the edge of a sheet. To get colors and
+    /// images that reach the edge of a page anyway, pages are printed on
+    /// larger sheets and then cut to size. The bleed is the area beyond the
+    /// page's edges that is cut off. It ensures that no white stripe remains if
+    /// the cut is slightly off. Print shops typically ask for a bleed of 3mm.
+    ///
+    /// The page's [fill]($page.fill) and [background]($page.background) cover
+    /// the bleed. Content that is [placed]($place) beyond the page's edges
+    /// extends into it. You can pass a single length for all sides or a
+    /// dictionary with the keys `left`, `top`, `right`, `bottom`, `x`, `y`,
+    /// and `rest`.
+    ///
+    /// The bleed is only part of PDF exports, which record the page's final
+    /// size in its trim box. Other formats show the page as it looks after
+    /// cutting.
+    ///
+    /// ```typ
+    /// #set page(bleed: 3mm, fill: navy)
+    /// ```
+    #[fold]
+    #[ghost]
+    pub bleed: Sides<Option<Length>>,
+
     /// How to number the pages. You can refer to the Page Setup Guide for
     /// [customizing page numbers]($guides/page-setup/#page-numbers).
     ///
@@ -380,6 +406,33 @@ pub struct PageElem {
     #[ghost]
     pub foreground: Option<Content>,
 
+    /// The visual effect with which PDF viewers move to the page in a
+    /// presentation.
+    ///
+    /// Can be a style like `{"dissolve"}` or a dictionary with a `style`, a
+    /// `duration` for the effect, and a `direction` in which it moves, e.g.
+    /// `{(style: "push", duration: 0.5s, direction: rtl)}`. The available
+    /// styles are `{"split"}`, `{"blinds"}`, `{"box"}`, `{"wipe"}`,
+    /// `{"dissolve"}`, `{"glitter"}`, `{"replace"}`, `{"fly"}`, `{"push"}`,
+    /// `{"cover"}`, `{"uncover"}`, and `{"fade"}`.
+    ///
+    /// ```typ
+    /// #set page(transition: "fade")
+    /// ```
+    #[ghost]
+    pub transition: Option<PageTransition>,
+
+    /// How long PDF viewers show the page in a presentation before
+    /// automatically advancing to the next one.
+    ///
+    /// If this is `{none}` (default), the viewer waits for the reader.
+    ///
+    /// ```typ
+    /// #set page(duration: 10s)
+    /// ```
+    #[ghost]
+    pub duration: Option<Duration>,
+
     /// The contents of the page(s).
     ///
     /// Multiple pages will be created if the content does not fit on a single
@@ -507,6 +560,9 @@ pub struct Page {
     /// Exporters should access the resolved value of this property through
     /// `fill_or_transparent()` or `fill_or_white()`.
     pub fill: Smart<Option<Paint>>,
+    /// How far the fill and background extend beyond the frame on each side,
+    /// to be cut off after printing.
+    pub bleed: Sides<Abs>,
     /// The page's numbering.
     pub numbering: Option<Numbering>,
     /// The page's supplement.
@@ -514,6 +570,10 @@ pub struct Page {
     /// The logical page number (controlled by `counter(page)` and may thus not
     /// match the physical number).
     pub number: u64,
+    /// The effect with which viewers move to the page in a presentation.
+    pub transition: Option<PageTransition>,
+    /// How long viewers show the page before advancing to the next one.
+    pub duration: Option<Duration>,
 }
 
 impl Page {
@@ -29,10 +29,11 @@ pub mod visualize;
 use std::ops::{Deref, Range};
 
 use serde::{Deserialize, Serialize};
+use typst_syntax::package::{PackageSpec, PackageVersion, VersionlessPackageSpec};
 use typst_syntax::{FileId, Source, Span};
 use typst_utils::{LazyHash, SmallBitSet};
 
-use crate::diag::FileResult;
+use crate::diag::{FileResult, HintedStrResult, bail};
 use crate::foundations::{Array, Binding, Bytes, Datetime, Dict, Module, Scope, Styles};
 use crate::layout::{Alignment, Dir};
 use crate::routines::Routines;
@@ -84,6 +85,22 @@ pub trait World: Send + Sync {
     /// If this function returns `None`, Typst's `datetime` function will
     /// return an error.
     fn today(&self, offset: Option<i64>) -> Option<Datetime>;
+
+    /// Determine the version a package imported without a version should
+    /// resolve to.
+    ///
+    /// If the import is within a package, `within` is that package and the
+    /// version should satisfy its manifest's dependencies instead of the
+    /// project's. Typically backed by a lockfile. If this function returns an
+    /// error, the import fails with it.
+    fn package_version(
+        &self,
+        spec: &VersionlessPackageSpec,
+        within: Option<&PackageSpec>,
+    ) -> HintedStrResult<PackageVersion> {
+        let _ = (spec, within);
+        bail!("package specification is missing version")
+    }
 }
 
 macro_rules! world_impl {
@@ -116,6 +133,14 @@ macro_rules! world_impl {
             fn today(&self, offset: Option<i64>) -> Option<Datetime> {
                 self.deref().today(offset)
             }
+
+            fn package_version(
+                &self,
+                spec: &VersionlessPackageSpec,
+                within: Option<&PackageSpec>,
+            ) -> HintedStrResult<PackageVersion> {
+                self.deref().package_version(spec, within)
+            }
         }
     };
 }
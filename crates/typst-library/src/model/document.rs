@@ -1,11 +1,15 @@
 use ecow::EcoString;
 
-use crate::diag::{HintedStrResult, SourceResult, bail};
+use typst_syntax::Spanned;
+
+use crate::diag::{At, HintedStrResult, SourceResult, bail};
 use crate::engine::Engine;
 use crate::foundations::{
-    Args, Array, Construct, Content, Datetime, OneOrMultiple, Smart, StyleChain, Styles,
-    Value, cast, elem,
+    Args, Array, Construct, Content, Datetime, Derived, OneOrMultiple, Smart, StyleChain,
+    Styles, Value, cast, elem,
 };
+use crate::loading::{DataSource, Load};
+use crate::pdf::{OutputIntent, PageLayout, PageMode, ViewerPreferences};
 use crate::text::{Locale, TextElem};
 
 /// The root element of a document and its metadata.
@@ -63,6 +67,65 @@ pub struct DocumentElem {
     /// something other than `{auto}`.
     #[ghost]
     pub date: Smart<Option<Datetime>>,
+
+    /// How PDF viewers arrange the pages when opening the document.
+    ///
+    /// If this is `{none}` (default), the viewer decides. Books typically
+    /// use `{"two-page-right"}`, which shows facing pages side by side.
+    ///
+    /// ```typ
+    /// #set document(page-layout: "two-page-right")
+    /// ```
+    #[ghost]
+    pub page_layout: Option<PageLayout>,
+
+    /// Which panel PDF viewers show when opening the document.
+    ///
+    /// Slide decks can use `{"full-screen"}` to start the presentation
+    /// right away.
+    ///
+    /// ```typ
+    /// #set document(page-mode: "full-screen")
+    /// ```
+    #[ghost]
+    pub page_mode: Option<PageMode>,
+
+    /// Settings for the window of PDF viewers, given as a dictionary with any
+    /// of the keys `hide-toolbar`, `hide-menubar`, `hide-window-ui`,
+    /// `fit-window`, `center-window`, and `display-title`.
+    ///
+    /// ```typ
+    /// #set document(viewer: (hide-toolbar: true, display-title: true))
+    /// ```
+    #[ghost]
+    pub viewer: Option<ViewerPreferences>,
+
+    /// An ICC profile that describes the printing condition the document is
+    /// prepared for, given as a [path]($syntax/#paths) or raw bytes.
+    ///
+    /// Print shops usually provide such a profile for their press and paper.
+    /// It is embedded as the output intent of exported PDFs, which tells
+    /// viewers and printers how the document's CMYK and
+    /// [spot colors]($color.spot) are meant to look. The profile must describe
+    /// an output device with a gray, RGB, or CMYK color space.
+    ///
+    /// This cannot be used together with PDF/A or PDF/UA, as the output intent
+    /// is added after the document was checked against the standard.
+    ///
+    /// ```typ
+    /// #set document(output-intent: "coated-fogra39.icc")
+    /// ```
+    #[ghost]
+    #[parse(match args.named::<Spanned<Option<DataSource>>>("output-intent")? {
+        Some(Spanned { v: Some(source), span }) => {
+            let loaded = Spanned::new(&source, span).load(engine.world)?;
+            let intent = OutputIntent::new(loaded.data).at(span)?;
+            Some(Some(Derived::new(source, intent)))
+        }
+        Some(Spanned { v: None, .. }) => Some(None),
+        None => None,
+    })]
+    pub output_intent: Option<Derived<DataSource, OutputIntent>>,
 }
 
 impl Construct for DocumentElem {
@@ -106,6 +169,14 @@ pub struct DocumentInfo {
     pub keywords: Vec<EcoString>,
     /// The document's creation date.
     pub date: Smart<Option<Datetime>>,
+    /// How PDF viewers should arrange the pages.
+    pub page_layout: Option<PageLayout>,
+    /// Which panel PDF viewers should show when opening the document.
+    pub page_mode: Option<PageMode>,
+    /// Settings for the window of PDF viewers.
+    pub viewer: Option<ViewerPreferences>,
+    /// The printing condition the document is prepared for.
+    pub output_intent: Option<OutputIntent>,
     /// The document's language, set from the first top-level set rule, e.g.
     ///
     /// ```typc
@@ -141,6 +212,20 @@ impl DocumentInfo {
         if styles.has(DocumentElem::date) {
             self.date = chain.get(DocumentElem::date);
         }
+        if styles.has(DocumentElem::page_layout) {
+            self.page_layout = chain.get(DocumentElem::page_layout);
+        }
+        if styles.has(DocumentElem::page_mode) {
+            self.page_mode = chain.get(DocumentElem::page_mode);
+        }
+        if styles.has(DocumentElem::viewer) {
+            self.viewer = chain.get(DocumentElem::viewer);
+        }
+        if styles.has(DocumentElem::output_intent) {
+            self.output_intent = chain
+                .get_cloned(DocumentElem::output_intent)
+                .map(|intent| intent.derived);
+        }
     }
 
     /// Populate this document info with locale details from the given styles.
@@ -1,20 +1,23 @@
 use std::num::NonZeroUsize;
 use std::str::FromStr;
 
-use ecow::{EcoString, eco_format};
+use ecow::{EcoString, eco_format, eco_vec};
 use typst_utils::NonZeroExt;
 
-use crate::diag::{At, SourceResult, StrResult, bail};
+use crate::diag::{At, SourceResult, StrResult, bail, warning};
 use crate::engine::Engine;
 use crate::foundations::{
-    Content, Label, NativeElement, Packed, ShowSet, Smart, StyleChain, Styles, cast,
-    elem, scope,
+    Cast, Content, Label, NativeElement, Packed, Selector, ShowSet, Smart, Str,
+    StyleChain, Styles, cast, elem, scope, select_where,
 };
 use crate::introspection::{
-    Count, Counter, CounterUpdate, Locatable, Location, QueryLabelIntrospection, Tagged,
+    Count, Counter, CounterKey, CounterUpdate, Locatable, Location, QueryIntrospection,
+    QueryLabelIntrospection, Tagged,
 };
 use crate::layout::{Abs, Em, Length, Ratio};
-use crate::model::{Destination, DirectLinkElem, Numbering, NumberingPattern, ParElem};
+use crate::model::{
+    Destination, DirectLinkElem, HeadingElem, Numbering, NumberingPattern, ParElem,
+};
 use crate::text::{LocalName, SuperElem, TextElem, TextSize};
 use crate::visualize::{LineElem, Stroke};
 
@@ -50,6 +53,27 @@ use crate::visualize::{LineElem, Stroke};
 /// And the online app. #footnote(<fn>)
 /// ```
 ///
+/// # Series and endnotes
+/// Footnotes can be split into multiple independent [series]($footnote.series),
+/// such as the critical apparatus of an edition next to regular notes. Each
+/// series is numbered separately and gets its own block at the bottom of the
+/// page. Notes of [kind]($footnote.kind) `{"endnote"}` are not shown at the
+/// bottom of the page. Instead, an [`endnotes`] element lists them where it is
+/// placed, for example at the end of a chapter or of the document.
+///
+/// ```example
+/// >>> #set page(height: 160pt)
+/// #let variant = footnote.with(series: "variants")
+/// #show footnote.where(series: "variants"): set footnote(
+///   numbering: "a",
+///   separator: line(length: 100%, stroke: 0.5pt),
+/// )
+///
+/// Call me Ishmael.#footnote[A famous opening.]
+/// Some years ago#variant[_Once_ in some copies.]
+/// --- never mind how long.
+/// ```
+///
 /// _Note:_ Set and show rules in the scope where `footnote` is called may not
 /// apply to the footnote's content. See [here][issue] for more information.
 ///
@@ -79,6 +103,36 @@ pub struct FootnoteElem {
     #[default(Numbering::Pattern(NumberingPattern::from_str("1").unwrap()))]
     pub numbering: Numbering,
 
+    /// The series this footnote belongs to.
+    ///
+    /// Each series has its own [counter]($counter), which is
+    /// `{counter(footnote.where(series: "name"))}` for a series called
+    /// `{"name"}`. Footnotes without a series are counted by
+    /// `{counter(footnote)}`. At the bottom of the page, the footnotes of each
+    /// series are listed separately, in the order in which the series first
+    /// appear on the page. To give a series its own numbering and separator,
+    /// use a show-set rule like `{show footnote.where(series: "name"): set
+    /// footnote(numbering: "a")}`.
+    pub series: Option<Str>,
+
+    /// Where the footnote's entry is shown.
+    ///
+    /// ```example
+    /// Endnotes#footnote(kind: "endnote")[Like this one.]
+    /// are listed separately.
+    ///
+    /// #endnotes(title: none)
+    /// ```
+    pub kind: FootnoteKind,
+
+    /// The separator between the document body and the footnotes of this
+    /// note's series.
+    ///
+    /// When set to `{auto}`, the separator of [`footnote.entry`] is used. The
+    /// separator of the first footnote of a series on a page is used for the
+    /// whole series on that page.
+    pub separator: Smart<Content>,
+
     /// The content to put into the footnote. Can also be the label of another
     /// footnote this one should point to.
     #[required]
@@ -125,6 +179,23 @@ impl FootnoteElem {
         matches!(self.body, FootnoteBody::Reference(_))
     }
 
+    /// Tests if this footnote is listed by an [`EndnotesElem`] instead of at
+    /// the bottom of the page.
+    pub fn is_endnote(&self) -> bool {
+        self.kind.get(StyleChain::default()) == FootnoteKind::Endnote
+    }
+
+    /// The counter of this footnote's series.
+    pub fn counter(&self) -> Counter {
+        match self.series.get_cloned(StyleChain::default()) {
+            Some(series) => Counter::new(CounterKey::Selector(select_where!(
+                FootnoteElem,
+                series => Some(series),
+            ))),
+            None => Counter::of(FootnoteElem::ELEM),
+        }
+    }
+
     /// Returns the content of the body of this footnote if it is not a ref.
     pub fn body_content(&self) -> Option<&Content> {
         match &self.body {
@@ -141,15 +212,46 @@ impl Packed<FootnoteElem> {
         engine: &mut Engine,
         styles: StyleChain,
     ) -> SourceResult<(Destination, Content)> {
-        let loc = self.declaration_location(engine).at(self.span())?;
+        let declaration = self.declaration(engine).at(self.span())?;
+        if self.is_endnote() && !self.is_ref() {
+            self.check_listed(engine);
+        }
+
+        let loc = declaration.location().unwrap();
         let numbering = self.numbering.get_ref(styles);
-        let counter = Counter::of(FootnoteElem::ELEM);
+        let counter = declaration.counter();
         let num = counter.display_at(engine, loc, styles, numbering, self.span())?;
         Ok((Destination::Location(loc.variant(1)), num))
     }
 
+    /// Warns if no `endnotes` element of this endnote's series follows it, in
+    /// which case its entry is not shown anywhere.
+    fn check_listed(&self, engine: &mut Engine) {
+        let loc = self.location().unwrap();
+        let series = self.series.get_cloned(StyleChain::default());
+        let selector = Selector::Or(eco_vec![
+            Selector::Location(loc),
+            select_where!(EndnotesElem, series => series),
+        ]);
+
+        let elems = engine.introspect(QueryIntrospection(selector, self.span()));
+        if elems.last().and_then(Content::location) == Some(loc) {
+            engine.sink.warn(warning!(
+                self.span(),
+                "endnote is not listed by any `endnotes` element";
+                hint: "place an `endnotes` element of the same series after it";
+            ));
+        }
+    }
+
     /// Returns the location of the definition of this footnote.
     pub fn declaration_location(&self, engine: &mut Engine) -> StrResult<Location> {
+        Ok(self.declaration(engine)?.location().unwrap())
+    }
+
+    /// Returns the footnote that defines this footnote's content. This is the
+    /// footnote itself unless it references another one.
+    pub fn declaration(&self, engine: &mut Engine) -> StrResult<Packed<FootnoteElem>> {
         match self.body {
             FootnoteBody::Reference(label) => {
                 let element =
@@ -160,9 +262,9 @@ impl Packed<FootnoteElem> {
                 if self.location() == footnote.location() {
                     bail!("footnote cannot reference itself");
                 }
-                footnote.declaration_location(engine)
+                footnote.declaration(engine)
             }
-            _ => Ok(self.location().unwrap()),
+            _ => Ok(self.clone()),
         }
     }
 }
@@ -171,6 +273,23 @@ impl Count for Packed<FootnoteElem> {
     fn update(&self) -> Option<CounterUpdate> {
         (!self.is_ref()).then(|| CounterUpdate::Step(NonZeroUsize::ONE))
     }
+
+    fn is_counted_by(&self, counter: &Counter) -> bool {
+        // Footnotes of a series have their own counter, so `counter(footnote)`
+        // only counts those without a series.
+        self.series.get_ref(StyleChain::default()).is_none()
+            || *counter != Counter::of(FootnoteElem::ELEM)
+    }
+}
+
+/// Where the entry of a footnote is shown.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum FootnoteKind {
+    /// At the bottom of the page that contains the footnote.
+    #[default]
+    Footnote,
+    /// Where the next [`endnotes`] element of the footnote's series is placed.
+    Endnote,
 }
 
 /// The body of a footnote can be either some content or a label referencing
@@ -299,7 +418,7 @@ impl Packed<FootnoteEntry> {
         let span = self.span();
         let default = StyleChain::default();
         let numbering = self.note.numbering.get_ref(default);
-        let counter = Counter::of(FootnoteElem::ELEM);
+        let counter = self.note.counter();
         let Some(loc) = self.note.location() else {
             bail!(
                 self.span(), "footnote entry must have a location";
@@ -331,6 +450,110 @@ cast! {
     v: Content => v.unpack::<Self>().unwrap_or_else(Self::with_content)
 }
 
+/// A list of endnotes.
+///
+/// Lists the footnotes of [kind]($footnote.kind) `{"endnote"}` that appear
+/// between the previous `endnotes` element of the same series (or the start of
+/// the document) and this one. Place it at the end of each chapter to get
+/// per-chapter notes or once at the end of the document to collect all of
+/// them. Endnotes that aren't followed by an `endnotes` element of their series
+/// are not shown, and a warning is emitted for them.
+///
+/// The entries are [footnote entries]($footnote.entry), so they can be
+/// customized in the same way as the entries at the bottom of the page.
+///
+/// # Example
+/// ```example
+/// #set footnote(kind: "endnote")
+///
+/// = Chapter
+/// Endnotes are marked like
+/// footnotes.#footnote[First note.]
+/// But their entries are
+/// listed elsewhere.#footnote[Second note.]
+///
+/// #endnotes()
+/// ```
+#[elem(Locatable, ShowSet)]
+pub struct EndnotesElem {
+    /// The series whose endnotes are listed. See the
+    /// [`series`]($footnote.series) parameter of footnotes.
+    pub series: Option<Str>,
+
+    /// The title of the endnotes.
+    ///
+    /// - When set to `{auto}`, an appropriate title for the
+    ///   [text language]($text.lang) will be used. This is the default.
+    /// - When set to `{none}`, the endnotes will not have a title.
+    /// - A custom title can be set by passing content.
+    ///
+    /// The title is an unnumbered heading. You can force it to be numbered
+    /// with a show-set rule: `{show endnotes: set heading(numbering: "1.")}`
+    pub title: Smart<Option<Content>>,
+}
+
+impl Packed<EndnotesElem> {
+    /// Produces the heading for the endnotes, if any.
+    pub fn realize_title(&self, styles: StyleChain) -> Option<Content> {
+        self.title
+            .get_cloned(styles)
+            .unwrap_or_else(|| {
+                Some(TextElem::packed(Packed::<EndnotesElem>::local_name_in(styles)))
+            })
+            .map(|title| {
+                HeadingElem::new(title)
+                    .with_depth(NonZeroUsize::ONE)
+                    .pack()
+                    .spanned(self.span())
+            })
+    }
+
+    /// Returns the endnotes listed by this element, in document order.
+    pub fn notes(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+    ) -> Vec<Packed<FootnoteElem>> {
+        let series = self.series.get_cloned(styles);
+        let selector = Selector::Or(eco_vec![
+            select_where!(FootnoteElem, series => series.clone()),
+            select_where!(EndnotesElem, series => series),
+        ]);
+
+        let loc = self.location().unwrap();
+        let mut notes = vec![];
+        for elem in engine.introspect(QueryIntrospection(selector, self.span())) {
+            if elem.location() == Some(loc) {
+                break;
+            }
+
+            match elem.to_packed::<FootnoteElem>() {
+                Some(note) if note.is_endnote() && !note.is_ref() => {
+                    notes.push(note.clone())
+                }
+                Some(_) => {}
+                // A previous list of endnotes of the same series already
+                // contains the notes so far.
+                None => notes.clear(),
+            }
+        }
+
+        notes
+    }
+}
+
+impl ShowSet for Packed<EndnotesElem> {
+    fn show_set(&self, _: StyleChain) -> Styles {
+        let mut out = Styles::new();
+        out.set(HeadingElem::numbering, None);
+        out
+    }
+}
+
+impl LocalName for Packed<EndnotesElem> {
+    const KEY: &'static str = "endnotes";
+}
+
 /// This is an empty element inserted by the HTML footnote rule to indicate the
 /// presence of the default footnote rule. It's only used by the error in
 /// `FootnoteContainer::unsupported_with_custom_dom` and could be removed if
@@ -0,0 +1,76 @@
+use crate::foundations::{Content, elem};
+use crate::introspection::{Locatable, Tagged};
+use crate::layout::{Em, Length};
+
+/// A note in the page margin.
+///
+/// Places remarks in the outside margin of the page, next to the line in which
+/// the note appears. This is a staple of Tufte-style and academic layouts,
+/// where notes should be read alongside the text instead of at the bottom of
+/// the page. Unlike a [footnote], a margin note does not insert a marker into
+/// the running text.
+///
+/// # Example
+/// ```example
+/// >>> #set page(width: 180pt, margin: (right: 80pt))
+/// Margin notes appear
+/// #margin-note[Right next to the text.]
+/// at the height of the line that contains them.
+/// ```
+///
+/// The outside margin is the one opposite to the page's
+/// [binding]($page.binding). For [two-sided]($page.margin) documents, the notes
+/// thus alternate between the right margin on odd pages and the left margin
+/// on even pages (or vice versa for right-bound documents). A note fills the
+/// margin apart from its [clearance]($margin-note.clearance) on both sides, so
+/// make sure to give the outside margin enough room.
+///
+/// When notes are close together, they are pushed down so that they don't
+/// overlap. Notes that don't fit onto the page anymore move to the top of the
+/// next page's margin.
+///
+/// ```example
+/// >>> #set page(width: 180pt, margin: (right: 80pt))
+/// #set margin-note(gap: 1em)
+///
+/// A first note
+/// #margin-note[First],
+/// and another one
+/// #margin-note[Second]
+/// in the same line.
+/// ```
+///
+/// Margin notes in the page's header, footer, background, and foreground are
+/// ignored. In HTML export, notes remain in the running text as `span`
+/// elements with the `margin-note` class, which can be styled with CSS.
+///
+/// _Note:_ Like with footnotes, set and show rules in the scope where
+/// `margin-note` is called may not apply to the note's content. Moreover, the
+/// clearance and gap must be uniform across each page run (a page run is a
+/// sequence of pages without an explicit pagebreak in between). For this
+/// reason, set rules for margin notes should be defined before any page
+/// content, typically at the very start of the document.
+#[elem(Locatable, Tagged)]
+pub struct MarginNoteElem {
+    /// The horizontal space between the text and the note and between the note
+    /// and the page's edge.
+    ///
+    /// ```example
+    /// >>> #set page(width: 180pt, margin: (right: 80pt))
+    /// #set margin-note(clearance: 4pt)
+    ///
+    /// Closer to the
+    /// #margin-note[Close by]
+    /// text.
+    /// ```
+    #[default(Em::new(1.0).into())]
+    pub clearance: Length,
+
+    /// The minimum vertical space between two notes.
+    #[default(Em::new(0.5).into())]
+    pub gap: Length,
+
+    /// The content to put into the margin.
+    #[required]
+    pub body: Content,
+}
@@ -11,6 +11,7 @@ mod footnote;
 mod heading;
 mod link;
 mod list;
+mod margin;
 #[path = "numbering.rs"]
 mod numbering_;
 mod outline;
@@ -32,6 +33,7 @@ pub use self::footnote::*;
 pub use self::heading::*;
 pub use self::link::*;
 pub use self::list::*;
+pub use self::margin::*;
 pub use self::numbering_::*;
 pub use self::outline::*;
 pub use self::par::*;
@@ -61,6 +63,8 @@ pub fn define(global: &mut Scope) {
     global.define_elem::<FigureElem>();
     global.define_elem::<QuoteElem>();
     global.define_elem::<FootnoteElem>();
+    global.define_elem::<EndnotesElem>();
+    global.define_elem::<MarginNoteElem>();
     global.define_elem::<OutlineElem>();
     global.define_elem::<RefElem>();
     global.define_elem::<CiteElem>();
@@ -0,0 +1,171 @@
+use ecow::EcoString;
+
+use crate::diag::{SourceResult, bail};
+use crate::engine::Engine;
+use crate::foundations::{Args, Cast, Construct, Content, IntoValue, Smart, cast, elem};
+use crate::introspection::{Locatable, Location};
+use crate::layout::{Abs, Length, Rel};
+use crate::text::Font;
+use crate::visualize::{Color, Paint, Stroke};
+
+/// A fillable field of an interactive PDF form.
+///
+/// Fields are laid out like [boxes]($box). When exporting to PDF, each field
+/// becomes a form field that readers can fill in, sign, and submit. In other
+/// export formats, only the field's outline is shown.
+///
+/// # Example
+/// ```example
+/// Name: #pdf.field("name", tooltip: "Full name", required: true) \
+/// Size: #pdf.field(
+///   "size",
+///   kind: "dropdown",
+///   options: ("S", "M", "L"),
+///   value: "M",
+/// ) \
+/// #pdf.field("newsletter", kind: "checkbox", tooltip: "Newsletter")
+/// Send me the newsletter
+///
+/// #pdf.field(
+///   "contact",
+///   kind: "radio",
+///   options: ("Email", "Phone"),
+///   value: "Email",
+/// )
+/// ```
+///
+/// # Accessibility
+/// Screen readers announce fields by their [`tooltip`]($pdf.field.tooltip),
+/// so you should always give one. If it is missing, the field's name is used
+/// instead. In tagged PDFs, each field is tagged as a form element at the
+/// position where it occurs in the document.
+///
+/// # Notes
+/// - Each field must have a unique name.
+/// - The value of text fields and dropdowns is shown in the current font,
+///   which is embedded in full so that any text can be entered. Only
+///   characters of the Windows-1252 code page can be entered, though.
+#[elem(Locatable)]
+pub struct FieldElem {
+    /// The name under which the field's value is stored and submitted.
+    #[required]
+    pub name: EcoString,
+
+    /// The kind of field.
+    pub kind: FieldKind,
+
+    /// The field's default value.
+    ///
+    /// - For text fields, this is the pre-filled text.
+    /// - For checkboxes, this is whether the box is checked.
+    /// - For radio groups and dropdowns, this is the selected option.
+    /// - Signature fields cannot have a default value.
+    pub value: Option<FieldValue>,
+
+    /// The options to choose from in a radio group or dropdown.
+    ///
+    /// A radio group shows one button per option, each followed by the
+    /// option's text.
+    pub options: Vec<EcoString>,
+
+    /// A short description of the field, which PDF readers show when
+    /// hovering over it and screen readers announce.
+    pub tooltip: Option<EcoString>,
+
+    /// Whether the field must be filled in before the form can be submitted.
+    pub required: bool,
+
+    /// The field's width.
+    ///
+    /// If this is `{auto}`, a width that fits the kind of field is used. For
+    /// radio groups, this is the width of each button.
+    pub width: Smart<Rel<Length>>,
+
+    /// The field's height.
+    ///
+    /// If this is `{auto}`, a height that fits the kind of field is used. For
+    /// radio groups, this is the height of each button.
+    pub height: Smart<Rel<Length>>,
+
+    /// The field's background color.
+    pub fill: Option<Paint>,
+
+    /// The field's border.
+    #[default(Some(Stroke::from_pair(Color::GRAY, Abs::pt(0.5).into())))]
+    pub stroke: Option<Stroke>,
+}
+
+/// The area of a form field that PDF readers let users fill in.
+///
+/// Radio groups have one widget per option, all other fields have a single
+/// one. It is laid out into a [`FrameItem::Widget`](crate::layout::FrameItem),
+/// which PDF export turns into a widget annotation.
+#[elem(Construct)]
+pub struct FieldWidgetElem {
+    /// The location of the field.
+    #[required]
+    #[internal]
+    pub field: Location,
+
+    /// Which of the field's widgets this is.
+    #[required]
+    #[internal]
+    pub index: usize,
+}
+
+impl Construct for FieldWidgetElem {
+    fn construct(_: &mut Engine, args: &mut Args) -> SourceResult<Content> {
+        bail!(args.span, "cannot be constructed manually");
+    }
+}
+
+/// A widget of a form field in a frame.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FieldWidget {
+    /// The location of the field.
+    pub field: Location,
+    /// Which of the field's widgets this is.
+    pub index: usize,
+    /// The font in which the field's value is shown.
+    pub font: Font,
+    /// The size of the font.
+    pub size: Abs,
+    /// The color of the text.
+    pub color: Color,
+}
+
+/// The kind of a form field.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum FieldKind {
+    /// A single-line text input.
+    #[default]
+    Text,
+    /// A box that can be checked and unchecked.
+    Checkbox,
+    /// A group of buttons of which exactly one can be selected.
+    Radio,
+    /// A list of options of which one can be selected.
+    Dropdown,
+    /// A placeholder for a digital signature.
+    Signature,
+}
+
+/// The default value of a form field.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum FieldValue {
+    /// Whether a checkbox is checked.
+    Bool(bool),
+    /// The text of a text field or the selected option of a radio group or
+    /// dropdown.
+    Str(EcoString),
+}
+
+cast! {
+    FieldValue,
+    self => match self {
+        Self::Bool(v) => v.into_value(),
+        Self::Str(v) => v.into_value(),
+    },
+    v: bool => Self::Bool(v),
+    v: EcoString => Self::Str(v),
+}
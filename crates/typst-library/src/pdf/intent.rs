@@ -0,0 +1,95 @@
+use crate::diag::{StrResult, bail};
+use crate::foundations::Bytes;
+
+/// An ICC profile that describes the printing condition a document is
+/// prepared for, such as a certain press and paper.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct OutputIntent {
+    data: Bytes,
+    components: u8,
+}
+
+impl OutputIntent {
+    /// Checks that the data is an ICC profile for a gray, RGB, or CMYK output
+    /// device.
+    pub fn new(data: Bytes) -> StrResult<Self> {
+        if data.len() < 128 || &data[36..40] != b"acsp" {
+            bail!("file is not a valid ICC profile");
+        }
+
+        // The header starts with the size of the profile.
+        let size = u32::from_be_bytes(data[..4].try_into().unwrap());
+        if size as usize > data.len() {
+            bail!("ICC profile is truncated");
+        }
+
+        if !matches!(&data[12..16], b"prtr" | b"mntr") {
+            bail!("ICC profile must describe an output device");
+        }
+
+        let components = match &data[16..20] {
+            b"GRAY" => 1,
+            b"RGB " => 3,
+            b"CMYK" => 4,
+            _ => bail!("ICC profile must use a gray, RGB, or CMYK color space"),
+        };
+
+        // Check that the tags are well-formed, so that readers can use the
+        // profile.
+        if qcms::Profile::new_from_slice(&data, false).is_none() {
+            bail!("ICC profile is malformed or uses unsupported features");
+        }
+
+        Ok(Self { data, components })
+    }
+
+    /// The raw ICC profile.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// The number of color components of the profile's color space.
+    pub fn components(&self) -> u8 {
+        self.components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The header of an ICC profile for a CMYK printer, without any tags.
+    fn header(size: u32) -> Vec<u8> {
+        let mut data = vec![0; 132];
+        data[..4].copy_from_slice(&size.to_be_bytes());
+        data[8] = 4;
+        data[12..16].copy_from_slice(b"prtr");
+        data[16..20].copy_from_slice(b"CMYK");
+        data[20..24].copy_from_slice(b"Lab ");
+        data[36..40].copy_from_slice(b"acsp");
+        data
+    }
+
+    #[track_caller]
+    fn test(data: Vec<u8>, message: &str) {
+        let error = OutputIntent::new(Bytes::new(data)).unwrap_err();
+        assert_eq!(error, message);
+    }
+
+    #[test]
+    fn test_output_intent_invalid() {
+        test(b"not a profile".to_vec(), "file is not a valid ICC profile");
+        test(header(4096), "ICC profile is truncated");
+
+        let mut data = header(132);
+        data[12..16].copy_from_slice(b"scnr");
+        test(data, "ICC profile must describe an output device");
+
+        let mut data = header(132);
+        data[16..20].copy_from_slice(b"XYZ ");
+        test(data, "ICC profile must use a gray, RGB, or CMYK color space");
+
+        // A profile without the tags that a printer profile requires.
+        test(header(132), "ICC profile is malformed or uses unsupported features");
+    }
+}
@@ -2,9 +2,15 @@
 
 mod accessibility;
 mod attach;
+mod field;
+mod intent;
+mod viewer;
 
 pub use self::accessibility::*;
 pub use self::attach::*;
+pub use self::field::*;
+pub use self::intent::*;
+pub use self::viewer::*;
 
 use crate::foundations::{Deprecation, Element, Module, Scope};
 use crate::{Feature, Features};
@@ -21,6 +27,7 @@ pub fn module(features: &Features) -> Module {
             .with_until("0.15.0"),
     );
     pdf.define_elem::<ArtifactElem>();
+    pdf.define_elem::<FieldElem>();
     if features.is_enabled(Feature::A11yExtras) {
         pdf.define_func::<table_summary>();
         pdf.define_func::<header_cell>();
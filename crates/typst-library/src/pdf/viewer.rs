@@ -0,0 +1,164 @@
+use crate::foundations::{Cast, Dict, Duration, IntoValue, Value, cast, dict};
+use crate::layout::Dir;
+
+/// How a PDF viewer arranges the pages when opening the document.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum PageLayout {
+    /// Shows one page at a time.
+    SinglePage,
+    /// Shows the pages in one continuous column.
+    OneColumn,
+    /// Shows the pages in two continuous columns, with odd-numbered pages on
+    /// the left.
+    TwoColumnLeft,
+    /// Shows the pages in two continuous columns, with odd-numbered pages on
+    /// the right.
+    TwoColumnRight,
+    /// Shows two pages at a time, with odd-numbered pages on the left.
+    TwoPageLeft,
+    /// Shows two pages at a time, with odd-numbered pages on the right. This
+    /// is the usual layout for books.
+    TwoPageRight,
+}
+
+/// Which panel a PDF viewer shows when opening the document.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum PageMode {
+    /// Shows the document outline.
+    Outline,
+    /// Shows page thumbnails.
+    Thumbnails,
+    /// Shows the document in full screen, without any menus or panels.
+    FullScreen,
+    /// Shows the attached files.
+    Attachments,
+}
+
+/// Settings that control the window of a PDF viewer.
+///
+/// Can be given as a dictionary with any of the keys `hide-toolbar`,
+/// `hide-menubar`, `hide-window-ui`, `fit-window`, `center-window`, and
+/// `display-title`, each mapping to a boolean.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ViewerPreferences {
+    /// Whether to hide the viewer's toolbars.
+    pub hide_toolbar: bool,
+    /// Whether to hide the viewer's menu bar.
+    pub hide_menubar: bool,
+    /// Whether to hide user interface elements such as scroll bars and
+    /// navigation controls, leaving only the page contents.
+    pub hide_window_ui: bool,
+    /// Whether to resize the window to fit the first page.
+    pub fit_window: bool,
+    /// Whether to position the window in the center of the screen.
+    pub center_window: bool,
+    /// Whether to show the document's title instead of its file name in the
+    /// window's title bar.
+    pub display_title: bool,
+}
+
+cast! {
+    ViewerPreferences,
+    self => Value::Dict(self.into()),
+    mut dict: Dict => {
+        let mut flag = |key: &str| -> crate::diag::HintedStrResult<bool> {
+            Ok(dict.take(key).ok().map(Value::cast).transpose()?.unwrap_or(false))
+        };
+        let preferences = Self {
+            hide_toolbar: flag("hide-toolbar")?,
+            hide_menubar: flag("hide-menubar")?,
+            hide_window_ui: flag("hide-window-ui")?,
+            fit_window: flag("fit-window")?,
+            center_window: flag("center-window")?,
+            display_title: flag("display-title")?,
+        };
+        dict.finish(&[
+            "hide-toolbar",
+            "hide-menubar",
+            "hide-window-ui",
+            "fit-window",
+            "center-window",
+            "display-title",
+        ])?;
+        preferences
+    },
+}
+
+impl From<ViewerPreferences> for Dict {
+    fn from(preferences: ViewerPreferences) -> Self {
+        dict! {
+            "hide-toolbar" => preferences.hide_toolbar,
+            "hide-menubar" => preferences.hide_menubar,
+            "hide-window-ui" => preferences.hide_window_ui,
+            "fit-window" => preferences.fit_window,
+            "center-window" => preferences.center_window,
+            "display-title" => preferences.display_title,
+        }
+    }
+}
+
+/// A visual effect with which a PDF viewer moves to a page in a presentation.
+///
+/// Can be given as just a style or as a dictionary with a `style`, a
+/// `duration` for the effect, and a `direction` in which it moves.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PageTransition {
+    /// The kind of effect.
+    pub style: TransitionStyle,
+    /// How long the effect takes. Viewers default to one second.
+    pub duration: Option<Duration>,
+    /// The direction in which the effect moves. Only applies to some styles.
+    pub direction: Option<Dir>,
+}
+
+cast! {
+    PageTransition,
+    self => Value::Dict(self.into()),
+    style: TransitionStyle => Self { style, duration: None, direction: None },
+    mut dict: Dict => {
+        let style = dict.take("style")?.cast()?;
+        let duration = dict.take("duration").ok().map(Value::cast).transpose()?;
+        let direction = dict.take("direction").ok().map(Value::cast).transpose()?;
+        dict.finish(&["style", "duration", "direction"])?;
+        Self { style, duration, direction }
+    },
+}
+
+impl From<PageTransition> for Dict {
+    fn from(transition: PageTransition) -> Self {
+        dict! {
+            "style" => transition.style,
+            "duration" => transition.duration.into_value(),
+            "direction" => transition.direction.into_value(),
+        }
+    }
+}
+
+/// The kind of visual effect of a page transition.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum TransitionStyle {
+    /// Two lines sweep across the screen, revealing the page.
+    Split,
+    /// Multiple lines sweep across the screen, like window blinds.
+    Blinds,
+    /// A rectangular box sweeps outward from the center.
+    Box,
+    /// A single line sweeps across the screen.
+    Wipe,
+    /// The old page dissolves gradually.
+    Dissolve,
+    /// Like dissolve, but the effect sweeps across the screen.
+    Glitter,
+    /// The new page simply replaces the old one.
+    Replace,
+    /// The new page flies in.
+    Fly,
+    /// The new page pushes the old one off the screen.
+    Push,
+    /// The new page slides onto the screen, covering the old one.
+    Cover,
+    /// The old page slides off the screen, uncovering the new one.
+    Uncover,
+    /// The new page gradually becomes visible through the old one.
+    Fade,
+}
@@ -7,8 +7,10 @@ use ttf_parser::{PlatformId, Tag, name_id};
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::exceptions::find_exception;
+use crate::layout::Ratio;
 use crate::text::{
-    Font, FontStretch, FontStyle, FontVariant, FontWeight, is_default_ignorable,
+    Font, FontStretch, FontStyle, FontVariant, FontVariantRange, FontWeight,
+    is_default_ignorable,
 };
 
 /// Metadata about a collection of fonts.
@@ -139,6 +141,9 @@ impl FontBook {
     ///   normal.
     /// - The absolute distance to the target stretch.
     /// - The absolute distance to the target weight.
+    ///
+    /// For variable fonts, the distances are measured to the closest stretch
+    /// and weight the font can be instanced at.
     fn find_best_variant(
         &self,
         like: Option<&FontInfo>,
@@ -150,6 +155,7 @@ impl FontBook {
 
         for id in ids {
             let current = &self.infos[id];
+            let (stretch, weight) = current.range.closest(current.variant, variant);
             let key = (
                 like.map(|like| {
                     (
@@ -162,8 +168,8 @@ impl FontBook {
                     )
                 }),
                 current.variant.style.distance(variant.style),
-                current.variant.stretch.distance(variant.stretch),
-                current.variant.weight.distance(variant.weight),
+                stretch.distance(variant.stretch),
+                weight.distance(variant.weight),
             );
 
             if best_key.is_none_or(|b| key < b) {
@@ -186,6 +192,10 @@ pub struct FontInfo {
     pub variant: FontVariant,
     /// Properties of the font.
     pub flags: FontFlags,
+    /// For variable fonts, the weights and stretches the font can be
+    /// instanced at.
+    #[serde(default)]
+    pub range: FontVariantRange,
     /// The unicode coverage of the font.
     pub coverage: Coverage,
 }
@@ -302,10 +312,31 @@ impl FontInfo {
             flags.insert(FontFlags::SERIF);
         }
 
+        // Determine which weights and stretches a variable font covers.
+        let mut range = FontVariantRange::default();
+        for axis in ttf.variation_axes() {
+            match &axis.tag.to_bytes() {
+                b"wght" => {
+                    range.weight = Some((
+                        FontWeight::from_number(axis.min_value as u16),
+                        FontWeight::from_number(axis.max_value as u16),
+                    ));
+                }
+                b"wdth" => {
+                    let stretch =
+                        |v: f32| FontStretch::from_ratio(Ratio::new(v as f64 / 100.0));
+                    range.stretch =
+                        Some((stretch(axis.min_value), stretch(axis.max_value)));
+                }
+                _ => {}
+            }
+        }
+
         Some(FontInfo {
             family,
             variant,
             flags,
+            range,
             coverage: Coverage::from_vec(codepoints),
         })
     }
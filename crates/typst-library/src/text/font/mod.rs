@@ -7,20 +7,23 @@ mod exceptions;
 mod variant;
 
 pub use self::book::{Coverage, FontBook, FontFlags, FontInfo};
-pub use self::variant::{FontStretch, FontStyle, FontVariant, FontWeight};
+pub use self::variant::{
+    FontStretch, FontStyle, FontVariant, FontVariantRange, FontWeight,
+};
 
 use std::cell::OnceCell;
 use std::fmt::{self, Debug, Formatter};
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, OnceLock};
 
-use ttf_parser::{GlyphId, name_id};
+use ttf_parser::{GlyphId, Tag, name_id};
 
 use self::book::find_name;
 use crate::foundations::{Bytes, Cast};
 use crate::layout::{Abs, Em, Frame};
 use crate::text::{
-    BottomEdge, DEFAULT_SUBSCRIPT_METRICS, DEFAULT_SUPERSCRIPT_METRICS, TopEdge,
+    BottomEdge, DEFAULT_SUBSCRIPT_METRICS, DEFAULT_SUPERSCRIPT_METRICS, FontVariations,
+    TopEdge,
 };
 
 /// An OpenType font.
@@ -41,6 +44,9 @@ struct FontInner {
     ttf: ttf_parser::Face<'static>,
     /// The underlying rustybuzz face.
     rusty: rustybuzz::Face<'static>,
+    /// The normalized variation coordinates of this instance of a variable
+    /// font. Empty for the default instance.
+    coords: Vec<i16>,
     // NOTE: `ttf` and `rusty` reference `data`, so it's important for `data`
     // to be dropped after them or they will be left dangling while they're
     // dropped. Fields are dropped in declaration order, so `data` needs to be
@@ -68,7 +74,101 @@ impl Font {
         let metrics = FontMetrics::from_ttf(&ttf);
         let info = FontInfo::from_ttf(&ttf)?;
 
-        Some(Self(Arc::new(FontInner { data, index, info, metrics, ttf, rusty })))
+        Some(Self(Arc::new(FontInner {
+            data,
+            index,
+            info,
+            metrics,
+            ttf,
+            rusty,
+            coords: vec![],
+        })))
+    }
+
+    /// Create an instance of this variable font at the given axis values.
+    ///
+    /// Axes that the font does not have are ignored and values outside of an
+    /// axis' range are clamped. Returns a clone of this font if it isn't
+    /// variable or if the values result in its default instance.
+    pub fn with_variations(&self, variations: &[(Tag, f32)]) -> Self {
+        if !self.info().flags.contains(FontFlags::VARIABLE) {
+            return self.clone();
+        }
+
+        // Floats aren't hashable, so we memoize over their bit patterns.
+        let variations: Vec<(Tag, u32)> = variations
+            .iter()
+            .map(|&(tag, value)| (tag, value.to_bits()))
+            .collect();
+        self.instantiate(&variations)
+    }
+
+    /// Create an instance of this variable font at the given axis values,
+    /// which are given as the bit patterns of `f32`s.
+    ///
+    /// Parsing a face is relatively expensive and instances are requested
+    /// for each shaped run, so this is memoized.
+    #[comemo::memoize]
+    fn instantiate(&self, variations: &[(Tag, u32)]) -> Self {
+        let data = &self.0.data;
+        let index = self.0.index;
+
+        // Safety: See `Font::new`.
+        let slice: &'static [u8] =
+            unsafe { std::slice::from_raw_parts(data.as_ptr(), data.len()) };
+
+        let Ok(mut ttf) = ttf_parser::Face::parse(slice, index) else {
+            return self.clone();
+        };
+
+        for &(tag, bits) in variations {
+            ttf.set_variation(tag, f32::from_bits(bits));
+        }
+
+        let coords: Vec<i16> =
+            ttf.variation_coordinates().iter().map(|coord| coord.get()).collect();
+        if coords.iter().all(|&coord| coord == 0) {
+            return self.clone();
+        }
+
+        // The cloned face retains the variation coordinates.
+        let rusty = rustybuzz::Face::from_face(ttf.clone());
+        let metrics = FontMetrics::from_ttf(&ttf);
+        Self(Arc::new(FontInner {
+            data: data.clone(),
+            index,
+            info: self.0.info.clone(),
+            metrics,
+            ttf,
+            rusty,
+            coords,
+        }))
+    }
+
+    /// Create the instance of this font that best matches the given variant
+    /// and explicit variations.
+    ///
+    /// The `wght`, `wdth`, and `ital` axes follow the variant's weight,
+    /// stretch, and style unless they are overridden by the explicit
+    /// variations.
+    #[comemo::memoize]
+    pub fn instance(&self, variant: FontVariant, variations: &FontVariations) -> Self {
+        if !self.info().flags.contains(FontFlags::VARIABLE) {
+            return self.clone();
+        }
+
+        let mut axes = vec![
+            (Tag::from_bytes(b"wght"), variant.weight.to_number() as f32),
+            (Tag::from_bytes(b"wdth"), variant.stretch.to_ratio().get() as f32 * 100.0),
+            (Tag::from_bytes(b"ital"), (variant.style == FontStyle::Italic) as u8 as f32),
+        ];
+        axes.extend(variations.iter().map(|(tag, value)| (tag, value as f32)));
+        self.with_variations(&axes)
+    }
+
+    /// Whether this is a non-default instance of a variable font.
+    pub fn is_instance(&self) -> bool {
+        !self.0.coords.is_empty()
     }
 
     /// Parse all fonts in the given data.
@@ -195,12 +295,17 @@ impl Hash for Font {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.data.hash(state);
         self.0.index.hash(state);
+        self.0.coords.hash(state);
     }
 }
 
 impl Debug for Font {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "Font({}, {:?})", self.info().family, self.info().variant)
+        write!(f, "Font({}, {:?}", self.info().family, self.info().variant)?;
+        if self.is_instance() {
+            write!(f, ", {:?}", self.0.coords)?;
+        }
+        f.write_str(")")
     }
 }
 
@@ -208,7 +313,9 @@ impl Eq for Font {}
 
 impl PartialEq for Font {
     fn eq(&self, other: &Self) -> bool {
-        self.0.data == other.0.data && self.0.index == other.0.index
+        self.0.data == other.0.data
+            && self.0.index == other.0.index
+            && self.0.coords == other.0.coords
     }
 }
 
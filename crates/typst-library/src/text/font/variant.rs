@@ -31,6 +31,36 @@ impl Debug for FontVariant {
     }
 }
 
+/// The weights and stretches a variable font can be instanced at.
+///
+/// For static fonts, both ranges are `None`.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Serialize, Deserialize)]
+pub struct FontVariantRange {
+    /// The minimum and maximum value of the font's `wght` axis.
+    pub weight: Option<(FontWeight, FontWeight)>,
+    /// The minimum and maximum value of the font's `wdth` axis.
+    pub stretch: Option<(FontStretch, FontStretch)>,
+}
+
+impl FontVariantRange {
+    /// The stretch and weight closest to the `target` that a font with this
+    /// range and the given default `variant` can be instanced at.
+    pub fn closest(
+        self,
+        variant: FontVariant,
+        target: FontVariant,
+    ) -> (FontStretch, FontWeight) {
+        let stretch = self
+            .stretch
+            .map_or(variant.stretch, |(min, max)| target.stretch.clamp(min, max));
+        let weight = self
+            .weight
+            .map_or(variant.weight, |(min, max)| target.weight.clamp(min, max));
+        (stretch, weight)
+    }
+}
+
 /// The style of a font.
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[derive(Cast, Serialize, Deserialize)]
@@ -308,6 +338,21 @@ mod tests {
         assert_eq!(d(10, 100), 90);
     }
 
+    #[test]
+    fn test_font_variant_range_closest() {
+        let variant = FontVariant::default();
+        let range = FontVariantRange {
+            weight: Some((FontWeight(300), FontWeight(700))),
+            stretch: None,
+        };
+        let closest = |weight| {
+            range.closest(variant, FontVariant { weight: FontWeight(weight), ..variant })
+        };
+        assert_eq!(closest(200), (FontStretch::NORMAL, FontWeight(300)));
+        assert_eq!(closest(450), (FontStretch::NORMAL, FontWeight(450)));
+        assert_eq!(closest(900), (FontStretch::NORMAL, FontWeight(700)));
+    }
+
     #[test]
     fn test_font_stretch_debug() {
         assert_eq!(FontStretch::EXPANDED.repr(), "125%")
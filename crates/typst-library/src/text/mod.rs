@@ -41,7 +41,7 @@ use rustybuzz::Feature;
 use smallvec::SmallVec;
 use ttf_parser::Tag;
 use typst_syntax::Spanned;
-use typst_utils::singleton;
+use typst_utils::{Scalar, singleton};
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::World;
@@ -52,7 +52,7 @@ use crate::foundations::{
     NoneValue, Packed, PlainText, Regex, Repr, Resolve, Scope, Set, Smart, Str,
     StyleChain, cast, dict, elem,
 };
-use crate::layout::{Abs, Axis, Dir, Em, Length, Ratio, Rel};
+use crate::layout::{Abs, Dir, Em, Length, Ratio, Rel};
 use crate::math::{EquationElem, MathSize};
 use crate::visualize::{Color, Paint, RelativeTo, Stroke};
 
@@ -213,7 +213,8 @@ pub struct TextElem {
     /// The desired thickness of the font's glyphs. Accepts an integer between
     /// `{100}` and `{900}` or one of the predefined weight names. When the
     /// desired weight is not available, Typst selects the font from the family
-    /// that is closest in weight.
+    /// that is closest in weight. For variable fonts with a weight (`wght`)
+    /// axis, the font is instanced at exactly the desired weight.
     ///
     /// If you want to strongly emphasize your text, you should do so using the
     /// [strong] function instead. This makes it easy to adapt the style later
@@ -234,7 +235,8 @@ pub struct TextElem {
     /// The desired width of the glyphs. Accepts a ratio between `{50%}` and
     /// `{200%}`. When the desired width is not available, Typst selects the
     /// font from the family that is closest in stretch. This will only stretch
-    /// the text if a condensed or expanded version of the font is available.
+    /// the text if a condensed or expanded version of the font is available or
+    /// if the font is a variable font with a width (`wdth`) axis.
     ///
     /// If you want to adjust the amount of space between characters instead of
     /// stretching the glyphs itself, use the [`tracking`]($text.tracking)
@@ -247,6 +249,27 @@ pub struct TextElem {
     #[ghost]
     pub stretch: FontStretch,
 
+    /// Axis values to apply to variable fonts.
+    ///
+    /// Variable fonts can be continuously adjusted along one or multiple
+    /// design axes, each identified by a four-letter tag. Common axes are
+    /// weight (`wght`), width (`wdth`), optical size (`opsz`), slant (`slnt`),
+    /// and italic (`ital`), but fonts may also define their own custom axes.
+    /// Values outside of an axis' range are clamped and axes that the font
+    /// does not have are ignored.
+    ///
+    /// The weight and width axes automatically follow the
+    /// [`weight`]($text.weight) and [`stretch`]($text.stretch) properties,
+    /// so you only need to set them here to override those.
+    ///
+    /// ```typ
+    /// #set text(font: "Roboto Flex")
+    /// #text(variations: (wght: 450, opsz: 12))[Fine-tuned]
+    /// ```
+    #[fold]
+    #[ghost]
+    pub variations: FontVariations,
+
     /// The size of the glyphs. This value forms the basis of the `em` unit:
     /// `{1em}` is equivalent to the font size.
     ///
@@ -483,6 +506,8 @@ pub struct TextElem {
     /// - `{auto}`: Automatically infer the direction from the `lang` property.
     /// - `{ltr}`: Layout text from left to right.
     /// - `{rtl}`: Layout text from right to left.
+    /// - `{ttb}`: Layout text from top to bottom in columns that progress from
+    ///   right to left.
     ///
     /// When writing in right-to-left scripts like Arabic or Hebrew, you should
     /// set the [text language]($text.lang) or direction. While individual runs
@@ -503,6 +528,18 @@ pub struct TextElem {
     /// #set text(dir: rtl)
     /// هذا عربي.
     /// ```
+    ///
+    /// In vertical text, Chinese and Japanese characters stay upright and use
+    /// their vertical forms, while runs of other scripts are rotated by 90
+    /// degrees. Runs of one or two digits are set horizontally within the
+    /// column (tate-chu-yoko). Inline boxes and equations stay upright.
+    ///
+    /// ```example
+    /// #set page(height: 120pt)
+    /// #set text(dir: ttb, lang: "ja")
+    /// 吾輩は猫である。名前はまだ無い。
+    /// 第12回 Typst 勉強会
+    /// ```
     #[ghost]
     pub dir: TextDir,
 
@@ -1150,8 +1187,8 @@ cast! {
     TextDir,
     self => self.0.into_value(),
     v: Smart<Dir> => {
-        if v.is_custom_and(|dir| dir.axis() == Axis::Y) {
-            bail!("text direction must be horizontal");
+        if v.is_custom_and(|dir| dir == Dir::BTT) {
+            bail!("text direction must be horizontal or top-to-bottom");
         }
         Self(v)
     },
@@ -1328,6 +1365,44 @@ impl Fold for FontFeatures {
     }
 }
 
+/// Axis values for variable fonts.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct FontVariations(pub Vec<(Tag, Scalar)>);
+
+impl FontVariations {
+    /// Iterate over the axis tags and values.
+    pub fn iter(&self) -> impl Iterator<Item = (Tag, f64)> + '_ {
+        self.0.iter().map(|&(tag, value)| (tag, value.get()))
+    }
+}
+
+cast! {
+    FontVariations,
+    self => self.0
+        .into_iter()
+        .map(|(tag, value)| {
+            let bytes = tag.to_bytes();
+            let key = std::str::from_utf8(&bytes).unwrap_or_default();
+            (key.into(), value.get().into_value())
+        })
+        .collect::<Dict>()
+        .into_value(),
+    values: Dict => Self(values
+        .into_iter()
+        .enumerate()
+        .map(|(i, (k, v))| Ok((
+            k.clone().into_value().cast::<Tag>().hint(tag_hint_helper(i, &k))?,
+            Scalar::new(v.cast::<f64>().hint(tag_hint_helper(i, &k))?),
+        )))
+        .collect::<HintedStrResult<_>>()?),
+}
+
+impl Fold for FontVariations {
+    fn fold(self, outer: Self) -> Self {
+        Self(self.0.fold(outer.0))
+    }
+}
+
 /// Collect the OpenType features to apply.
 pub fn features(styles: StyleChain) -> Vec<Feature> {
     let mut tags = vec![];
@@ -1519,26 +1594,12 @@ pub fn is_default_ignorable(c: char) -> bool {
 fn check_font_list(engine: &mut Engine, list: &Spanned<FontList>) {
     let book = engine.world.book();
     for family in &list.v {
-        match book.select_family(family.as_str()).next() {
-            Some(index) => {
-                if book
-                    .info(index)
-                    .is_some_and(|x| x.flags.contains(FontFlags::VARIABLE))
-                {
-                    engine.sink.warn(warning!(
-                        list.span,
-                        "variable fonts are not currently supported and may render \
-                         incorrectly";
-                        hint: "try installing a static version of \"{}\" instead",
-                            family.as_str();
-                    ))
-                }
-            }
-            None => engine.sink.warn(warning!(
+        if !book.contains_family(family.as_str()) {
+            engine.sink.warn(warning!(
                 list.span,
                 "unknown font family: {}",
                 family.as_str(),
-            )),
+            ));
         }
     }
 }
@@ -10,6 +10,7 @@ use palette::{
 };
 use qcms::Profile;
 use typst_syntax::{Span, Spanned};
+use typst_utils::PicoStr;
 
 use crate::diag::{At, SourceResult, StrResult, bail};
 use crate::foundations::{
@@ -208,6 +209,8 @@ pub enum Color {
     Hsl(Hsl),
     /// A 32-bit HSV color.
     Hsv(Hsv),
+    /// A named spot color.
+    Spot(Spot),
 }
 
 #[scope]
@@ -581,6 +584,55 @@ impl Color {
         })
     }
 
+    /// Create a spot color.
+    ///
+    /// Spot colors are printed with a dedicated, premixed ink instead of a mix
+    /// of the cyan, magenta, yellow, and key inks. This is how brand colors are
+    /// usually printed, as it reproduces them exactly. Spot colors are
+    /// identified by the name of their ink, which the print shop uses to pick
+    /// it. For example, colors from the Pantone Matching System are named like
+    /// `{"PANTONE 286 C"}`.
+    ///
+    /// In PDF export, spot colors are written as separations with the given
+    /// name, so that they end up on their own printing plate. Other export
+    /// formats and PDF viewers that don't know the ink show the fallback color
+    /// instead. It is converted to CMYK, so spot colors otherwise behave like
+    /// CMYK colors: Their [space]($color.space) is [`cmyk`]($color.cmyk) and
+    /// their [components]($color.components) are those of the tinted fallback
+    /// color. Lightening a spot color reduces its tint, while other
+    /// manipulations turn it into a CMYK color.
+    ///
+    /// Note that spot colors are not currently supported when PDF/A or PDF/UA
+    /// output is enabled. They also cannot be used in gradients, which use the fallback
+    /// color instead.
+    ///
+    /// ```example
+    /// #let blue = color.spot("PANTONE 286 C", cmyk(100%, 66%, 0%, 2%))
+    /// #square(fill: blue)
+    /// #square(fill: blue.lighten(50%))
+    /// ```
+    #[func]
+    pub fn spot(
+        /// The name of the ink.
+        name: Str,
+        /// The color that approximates the ink.
+        fallback: Color,
+        /// How much of the ink to apply.
+        #[named]
+        #[default(RatioComponent(Ratio::one()))]
+        tint: RatioComponent,
+    ) -> StrResult<Color> {
+        if name.is_empty() {
+            bail!("spot color name must not be empty");
+        }
+
+        Ok(Self::Spot(Spot {
+            name: PicoStr::intern(&name),
+            tint: tint.0.get() as f32,
+            fallback: fallback.to_cmyk(),
+        }))
+    }
+
     /// Create an HSL color.
     ///
     /// This color space is useful for specifying colors by hue, saturation and
@@ -774,6 +826,15 @@ impl Color {
                     Ratio::new(c.k.into())
                 ]
             }
+            Self::Spot(spot) => {
+                let c = spot.to_cmyk();
+                array![
+                    Ratio::new(c.c.into()),
+                    Ratio::new(c.m.into()),
+                    Ratio::new(c.y.into()),
+                    Ratio::new(c.k.into())
+                ]
+            }
             Self::Hsl(c) => {
                 array![
                     hue_angle(c.hue.into_degrees()),
@@ -792,7 +853,7 @@ impl Color {
             }
         };
         // Remove the alpha component if the corresponding argument was set.
-        if !alpha && !matches!(self, Self::Cmyk(_)) {
+        if !alpha && !matches!(self, Self::Cmyk(_) | Self::Spot(_)) {
             let _ = components.pop();
         }
         components
@@ -822,7 +883,7 @@ impl Color {
             Self::Oklch(_) => ColorSpace::Oklch,
             Self::LinearRgb(_) => ColorSpace::LinearRgb,
             Self::Rgb(_) => ColorSpace::Srgb,
-            Self::Cmyk(_) => ColorSpace::Cmyk,
+            Self::Cmyk(_) | Self::Spot(_) => ColorSpace::Cmyk,
             Self::Hsl(_) => ColorSpace::Hsl,
             Self::Hsv(_) => ColorSpace::Hsv,
         }
@@ -856,6 +917,7 @@ impl Color {
             Self::LinearRgb(c) => Self::LinearRgb(c.lighten(factor)),
             Self::Rgb(c) => Self::Rgb(c.lighten(factor)),
             Self::Cmyk(c) => Self::Cmyk(c.lighten(factor)),
+            Self::Spot(s) => Self::Spot(s.lighten(factor)),
             Self::Hsl(c) => Self::Hsl(c.lighten(factor)),
             Self::Hsv(c) => Self::Hsv(c.lighten(factor)),
         }
@@ -876,6 +938,7 @@ impl Color {
             Self::LinearRgb(c) => Self::LinearRgb(c.darken(factor)),
             Self::Rgb(c) => Self::Rgb(c.darken(factor)),
             Self::Cmyk(c) => Self::Cmyk(c.darken(factor)),
+            Self::Spot(s) => Self::Cmyk(s.to_cmyk().darken(factor)),
             Self::Hsl(c) => Self::Hsl(c.darken(factor)),
             Self::Hsv(c) => Self::Hsv(c.darken(factor)),
         }
@@ -901,7 +964,8 @@ impl Color {
             | Self::Oklch(_)
             | Self::LinearRgb(_)
             | Self::Rgb(_)
-            | Self::Cmyk(_) => {
+            | Self::Cmyk(_)
+            | Self::Spot(_) => {
                 Color::Hsv(self.to_hsv().saturate(f)).to_space(self.space())
             }
         })
@@ -927,7 +991,8 @@ impl Color {
             | Self::Oklch(_)
             | Self::LinearRgb(_)
             | Self::Rgb(_)
-            | Self::Cmyk(_) => {
+            | Self::Cmyk(_)
+            | Self::Spot(_) => {
                 Color::Hsv(self.to_hsv().desaturate(f)).to_space(self.space())
             }
         })
@@ -968,6 +1033,7 @@ impl Color {
                 Self::Rgb(Rgb::new(1.0 - c.red, 1.0 - c.green, 1.0 - c.blue, c.alpha))
             }
             Self::Cmyk(c) => Self::Cmyk(Cmyk::new(1.0 - c.c, 1.0 - c.m, 1.0 - c.y, c.k)),
+            Self::Spot(_) => unreachable!("spot colors are not a color space"),
             Self::Hsl(c) => Self::Hsl(Hsl::new(
                 RgbHue::from_degrees(c.hue.into_degrees() + 180.0),
                 c.saturation,
@@ -1198,7 +1264,7 @@ impl Color {
     /// Returns the alpha channel of the color, if it has one.
     pub fn alpha(&self) -> Option<f32> {
         match self {
-            Color::Cmyk(_) => None,
+            Color::Cmyk(_) | Color::Spot(_) => None,
             Color::Luma(c) => Some(c.alpha),
             Color::Oklab(c) => Some(c.alpha),
             Color::Oklch(c) => Some(c.alpha),
@@ -1212,7 +1278,7 @@ impl Color {
     /// Sets the alpha channel of the color, if it has one.
     pub fn with_alpha(mut self, alpha: f32) -> Self {
         match &mut self {
-            Color::Cmyk(_) => {}
+            Color::Cmyk(_) | Color::Spot(_) => {}
             Color::Luma(c) => c.alpha = alpha,
             Color::Oklab(c) => c.alpha = alpha,
             Color::Oklch(c) => c.alpha = alpha,
@@ -1245,6 +1311,7 @@ impl Color {
             Color::Rgb(c) => Color::Rgb(transform(c, scale)),
             Color::LinearRgb(c) => Color::LinearRgb(transform(c, scale)),
             Color::Cmyk(_) => bail!("CMYK does not have an alpha component"),
+            Color::Spot(_) => bail!("spot colors do not have an alpha component"),
             Color::Hsl(c) => Color::Hsl(transform(c, scale)),
             Color::Hsv(c) => Color::Hsv(transform(c, scale)),
         })
@@ -1261,6 +1328,10 @@ impl Color {
             Color::Rgb(c) => [c.red, c.green, c.blue, c.alpha],
             Color::LinearRgb(c) => [c.red, c.green, c.blue, c.alpha],
             Color::Cmyk(c) => [c.c, c.m, c.y, c.k],
+            Color::Spot(s) => {
+                let c = s.to_cmyk();
+                [c.c, c.m, c.y, c.k]
+            }
             Color::Hsl(c) => [
                 c.hue.into_degrees().rem_euclid(360.0),
                 c.saturation,
@@ -1299,6 +1370,7 @@ impl Color {
             Self::Rgb(c) => Luma::from_color(c),
             Self::LinearRgb(c) => Luma::from_color(c),
             Self::Cmyk(c) => Luma::from_color(c.to_rgba()),
+            Self::Spot(s) => Luma::from_color(s.to_cmyk().to_rgba()),
             Self::Hsl(c) => Luma::from_color(c),
             Self::Hsv(c) => Luma::from_color(c),
         }
@@ -1312,6 +1384,7 @@ impl Color {
             Self::Rgb(c) => Oklab::from_color(c),
             Self::LinearRgb(c) => Oklab::from_color(c),
             Self::Cmyk(c) => Oklab::from_color(c.to_rgba()),
+            Self::Spot(s) => Oklab::from_color(s.to_cmyk().to_rgba()),
             Self::Hsl(c) => Oklab::from_color(c),
             Self::Hsv(c) => Oklab::from_color(c),
         }
@@ -1325,6 +1398,7 @@ impl Color {
             Self::Rgb(c) => Oklch::from_color(c),
             Self::LinearRgb(c) => Oklch::from_color(c),
             Self::Cmyk(c) => Oklch::from_color(c.to_rgba()),
+            Self::Spot(s) => Oklch::from_color(s.to_cmyk().to_rgba()),
             Self::Hsl(c) => Oklch::from_color(c),
             Self::Hsv(c) => Oklch::from_color(c),
         }
@@ -1338,6 +1412,7 @@ impl Color {
             Self::Rgb(c) => c,
             Self::LinearRgb(c) => Rgb::from_linear(c),
             Self::Cmyk(c) => Rgb::from_color(c.to_rgba()),
+            Self::Spot(s) => Rgb::from_color(s.to_cmyk().to_rgba()),
             Self::Hsl(c) => Rgb::from_color(c),
             Self::Hsv(c) => Rgb::from_color(c),
         }
@@ -1351,6 +1426,7 @@ impl Color {
             Self::Rgb(c) => LinearRgb::from_color(c),
             Self::LinearRgb(c) => c,
             Self::Cmyk(c) => LinearRgb::from_color(c.to_rgba()),
+            Self::Spot(s) => LinearRgb::from_color(s.to_cmyk().to_rgba()),
             Self::Hsl(c) => Rgb::from_color(c).into_linear(),
             Self::Hsv(c) => Rgb::from_color(c).into_linear(),
         }
@@ -1364,6 +1440,7 @@ impl Color {
             Self::Rgb(c) => Cmyk::from_rgba(c),
             Self::LinearRgb(c) => Cmyk::from_rgba(Rgb::from_linear(c)),
             Self::Cmyk(c) => c,
+            Self::Spot(s) => s.to_cmyk(),
             Self::Hsl(c) => Cmyk::from_rgba(Rgb::from_color(c)),
             Self::Hsv(c) => Cmyk::from_rgba(Rgb::from_color(c)),
         }
@@ -1377,6 +1454,7 @@ impl Color {
             Self::Rgb(c) => Hsl::from_color(c),
             Self::LinearRgb(c) => Hsl::from_color(Rgb::from_linear(c)),
             Self::Cmyk(c) => Hsl::from_color(c.to_rgba()),
+            Self::Spot(s) => Hsl::from_color(s.to_cmyk().to_rgba()),
             Self::Hsl(c) => c,
             Self::Hsv(c) => Hsl::from_color(c),
         }
@@ -1390,6 +1468,7 @@ impl Color {
             Self::Rgb(c) => Hsv::from_color(c),
             Self::LinearRgb(c) => Hsv::from_color(Rgb::from_linear(c)),
             Self::Cmyk(c) => Hsv::from_color(c.to_rgba()),
+            Self::Spot(s) => Hsv::from_color(s.to_cmyk().to_rgba()),
             Self::Hsl(c) => Hsv::from_color(c),
             Self::Hsv(c) => c,
         }
@@ -1418,6 +1497,16 @@ impl Debug for Color {
                 write!(f, "LinearRgb({}, {}, {}, {})", v.red, v.green, v.blue, v.alpha)
             }
             Self::Cmyk(v) => write!(f, "Cmyk({}, {}, {}, {})", v.c, v.m, v.y, v.k),
+            Self::Spot(v) => write!(
+                f,
+                "Spot({:?}, {}, {}, {}, {}, {})",
+                v.name.resolve().as_str(),
+                v.tint,
+                v.fallback.c,
+                v.fallback.m,
+                v.fallback.y,
+                v.fallback.k
+            ),
             Self::Hsl(v) => write!(
                 f,
                 "Hsl({:?}, {}, {}, {})",
@@ -1480,6 +1569,16 @@ impl Repr for Color {
                     Ratio::new(c.k.into()).repr(),
                 )
             }
+            Self::Spot(s) => {
+                let name = s.name.resolve().as_str().repr();
+                let fallback = Self::Cmyk(s.fallback).repr();
+                if s.tint == 1.0 {
+                    eco_format!("color.spot({name}, {fallback})")
+                } else {
+                    let tint = Ratio::new(s.tint.into()).repr();
+                    eco_format!("color.spot({name}, {fallback}, tint: {tint})")
+                }
+            }
             Self::Oklab(c) => {
                 if c.alpha == 1.0 {
                     eco_format!(
@@ -1573,6 +1672,7 @@ impl PartialEq for Color {
             (Self::Oklch(a), Self::Oklch(b)) => a == b,
             (Self::LinearRgb(a), Self::LinearRgb(b)) => a == b,
             (Self::Cmyk(a), Self::Cmyk(b)) => a == b,
+            (Self::Spot(a), Self::Spot(b)) => a == b,
             (Self::Hsl(a), Self::Hsl(b)) => a == b,
             (Self::Hsv(a), Self::Hsv(b)) => a == b,
             _ => false,
@@ -1590,6 +1690,9 @@ impl Hash for Color {
         y.to_bits().hash(state);
         z.to_bits().hash(state);
         w.to_bits().hash(state);
+        if let Self::Spot(spot) = self {
+            spot.name.hash(state);
+        }
     }
 }
 
@@ -1755,6 +1858,33 @@ impl Cmyk {
     }
 }
 
+/// A named spot color.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Spot {
+    /// The name of the ink.
+    pub name: PicoStr,
+    /// How much of the ink to apply, between zero and one.
+    pub tint: f32,
+    /// The process color that approximates the ink at full strength.
+    pub fallback: Cmyk,
+}
+
+impl Spot {
+    /// The process color that approximates the tinted ink.
+    pub fn to_cmyk(self) -> Cmyk {
+        let tint = |u: f32| u * self.tint;
+        let Cmyk { c, m, y, k } = self.fallback;
+        Cmyk::new(tint(c), tint(m), tint(y), tint(k))
+    }
+
+    fn lighten(self, factor: f32) -> Self {
+        Self {
+            tint: (self.tint - self.tint * factor).clamp(0.0, 1.0),
+            ..self
+        }
+    }
+}
+
 /// A color with a weight.
 pub struct WeightedColor {
     color: Color,
@@ -22,26 +22,38 @@ use typst_library::layout::{
 };
 use typst_library::model::HeadingElem;
 use typst_library::text::Font;
-use typst_library::visualize::{Geometry, Paint};
+use typst_library::visualize::{Geometry, Paint, Spot};
 use typst_syntax::Span;
 
 use crate::PdfOptions;
 use crate::attach::attach_files;
+use crate::edit::Editor;
+use crate::encrypt::{apply_encryption, check_encryption};
+use crate::form::{PlacedWidget, apply_form_fields, check_form_fields, handle_widget};
 use crate::image::handle_image;
+use crate::intent::{apply_output_intent, check_output_intent};
 use crate::link::{LinkAnnotation, handle_link};
 use crate::metadata::build_metadata;
 use crate::outline::build_outline;
 use crate::page::PageLabelExt;
+use crate::prepress;
 use crate::shape::handle_shape;
+use crate::spot::{apply_spot_colors, check_spot_colors};
 use crate::tags::{self, GroupId, Tags};
 use crate::text::handle_text;
 use crate::util::{AbsExt, TransformExt, convert_path, display_font};
+use crate::viewer::{apply_viewer_settings, check_viewer_settings};
 
 #[typst_macros::time(name = "convert document")]
 pub fn convert(
     typst_document: &PagedDocument,
     options: &PdfOptions,
 ) -> SourceResult<Vec<u8>> {
+    check_encryption(options)?;
+    check_output_intent(typst_document, options)?;
+    check_form_fields(typst_document, options)?;
+    check_viewer_settings(typst_document, options)?;
+
     let settings = SerializeSettings {
         compress_content_streams: true,
         no_device_cs: true,
@@ -68,6 +80,7 @@ pub fn convert(
     );
 
     convert_pages(&mut gc, &mut document)?;
+    check_spot_colors(options, &gc.spots)?;
     attach_files(&gc, &mut document)?;
     let (doc_lang, tree) = tags::resolve(&mut gc)?;
 
@@ -75,7 +88,17 @@ pub fn convert(
     document.set_metadata(build_metadata(&gc, doc_lang));
     document.set_tag_tree(tree);
 
-    finish(document, gc, options.standards.config)
+    let spots = std::mem::take(&mut gc.spots);
+    let widgets = std::mem::take(&mut gc.widgets);
+    let pdf = finish(document, gc, options.standards.config)?;
+
+    let mut editor = Editor::new(pdf, options.is_validated());
+    apply_spot_colors(&mut editor, &spots)?;
+    apply_output_intent(&mut editor, typst_document)?;
+    apply_form_fields(&mut editor, typst_document, &widgets)?;
+    apply_viewer_settings(&mut editor, typst_document, options)?;
+    apply_encryption(&mut editor, options)?;
+    editor.finish()
 }
 
 fn convert_pages(gc: &mut GlobalContext, document: &mut Document) -> SourceResult<()> {
@@ -115,21 +138,23 @@ fn convert_pages(gc: &mut GlobalContext, document: &mut Document) -> SourceResul
             settings = settings.with_page_label(label);
         }
 
+        let settings = prepress::page_boxes(settings, typst_page, gc.options.marks)?;
         let mut page = document.start_page_with(settings);
         let mut surface = page.surface();
         let page_idx = gc.page_index_converter.pdf_page_index(i);
         let mut fc = FrameContext::new(page_idx, typst_page.frame.size());
 
         tags::page(gc, &mut surface, |gc, surface| {
-            handle_frame(
-                &mut fc,
-                &typst_page.frame,
-                typst_page.fill_or_transparent(),
-                surface,
-                gc,
-            )
+            let (frame, fill) = prepress::page_frame(typst_page);
+            handle_frame(&mut fc, &frame, fill, surface, gc)
         })?;
 
+        if let Some(marks) = prepress::marks_frame(typst_page, gc.options.marks) {
+            tags::marks(gc, &mut surface, |gc, surface| {
+                handle_frame(&mut fc, &marks, None, surface, gc)
+            })?;
+        }
+
         surface.finish();
 
         let link_annotations = fc.link_annotations.into_values().flatten();
@@ -214,6 +239,11 @@ impl FrameContext {
         self.states.last().unwrap()
     }
 
+    /// The size of the page or tiling that is converted.
+    pub(crate) fn root_size(&self) -> Size {
+        self.states[0].container_size()
+    }
+
     pub(crate) fn state_mut(&mut self) -> &mut State {
         self.states.last_mut().unwrap()
     }
@@ -257,6 +287,10 @@ pub(crate) struct GlobalContext<'a> {
     pub(crate) page_index_converter: PageIndexConverter,
     /// Tagged PDF context.
     pub(crate) tags: Tags,
+    /// The spot colors used in the document, in the order of their first use.
+    pub(crate) spots: Vec<Spot>,
+    /// The widgets of form fields, in the order in which they were drawn.
+    pub(crate) widgets: Vec<PlacedWidget>,
 }
 
 impl<'a> GlobalContext<'a> {
@@ -277,6 +311,8 @@ impl<'a> GlobalContext<'a> {
             image_spans: FxHashSet::default(),
             page_index_converter,
             tags,
+            spots: vec![],
+            widgets: vec![],
         }
     }
 }
@@ -312,6 +348,7 @@ pub(crate) fn handle_frame(
                 handle_image(gc, fc, image, *size, surface, *span)?
             }
             FrameItem::Link(dest, size) => handle_link(fc, gc, dest, *size)?,
+            FrameItem::Widget(widget, size) => handle_widget(fc, gc, widget, *size),
             FrameItem::Tag(Tag::Start(_, flags)) => {
                 if flags.tagged {
                     tags::handle_start(gc, surface);
@@ -0,0 +1,270 @@
+//! Edits of finished PDF files.
+//!
+//! Some features, like viewer settings and encryption, cannot be configured
+//! through krilla. To write them anyway, the file produced by krilla is loaded
+//! into an object graph, in which the affected objects are edited before the
+//! graph is serialized again. The file is only loaded once something is
+//! edited, so that all other exports are exactly what krilla wrote.
+//!
+//! krilla's validation does not see these edits, so they can't be proven to
+//! conform to a PDF/A or PDF/UA standard. Features that need them must thus be
+//! rejected before export when a standard is selected, like
+//! [`check_encryption`](crate::encrypt::check_encryption) does. The editor
+//! refuses to load a validated file as a safeguard.
+
+use lopdf::{Dictionary, Document, Object, ObjectId, StringFormat};
+use typst_library::diag::{At, ExpectInternal, SourceResult, bail};
+use typst_syntax::Span;
+
+use crate::PdfOptions;
+
+/// A finished PDF file that is being edited.
+pub struct Editor {
+    /// The file as written by krilla.
+    pdf: Vec<u8>,
+    /// The object graph of the file, once it was loaded.
+    document: Option<Document>,
+    /// Whether krilla validated the file against a standard.
+    validated: bool,
+}
+
+impl Editor {
+    /// Prepares the editing of a file that was possibly validated against a
+    /// standard.
+    pub fn new(pdf: Vec<u8>, validated: bool) -> Self {
+        Self { pdf, document: None, validated }
+    }
+
+    /// The file as written by krilla, without any edits.
+    pub fn original(&self) -> &[u8] {
+        &self.pdf
+    }
+
+    /// The object graph of the file, which is loaded on first use.
+    pub fn document(&mut self) -> SourceResult<&mut Document> {
+        if self.validated {
+            return None
+                .expect_internal("cannot edit a validated PDF")
+                .at(Span::detached());
+        }
+
+        if self.document.is_none() {
+            let document = Document::load_mem(&self.pdf)
+                .ok()
+                .expect_internal("failed to load exported PDF")
+                .at(Span::detached())?;
+            self.document = Some(document);
+        }
+        Ok(self.document.as_mut().unwrap())
+    }
+
+    /// Serializes the edited file.
+    ///
+    /// If nothing was edited, the original file is returned unchanged.
+    pub fn finish(self) -> SourceResult<Vec<u8>> {
+        let Some(mut document) = self.document else { return Ok(self.pdf) };
+        let mut pdf = Vec::with_capacity(self.pdf.len());
+        document
+            .save_to(&mut pdf)
+            .ok()
+            .expect_internal("failed to write edited PDF")
+            .at(Span::detached())?;
+        Ok(pdf)
+    }
+}
+
+/// Fails with the given message if the export is validated against a
+/// standard, as the validation would not see the edits a feature needs.
+pub fn check_unvalidated(
+    options: &PdfOptions,
+    span: Span,
+    message: &str,
+    remedy: &str,
+) -> SourceResult<()> {
+    if !options.is_validated() {
+        return Ok(());
+    }
+
+    let validator = options.standards.config.validator().as_str();
+    bail!(
+        span,
+        "{validator} error: {message}";
+        hint: "{remedy} or export without {validator}";
+    );
+}
+
+/// The document catalog.
+pub fn catalog(document: &mut Document) -> Option<&mut Dictionary> {
+    document.catalog_mut().ok()
+}
+
+/// Edits a dictionary in an entry of the catalog, which is created if it is
+/// missing.
+pub fn edit_catalog_entry(
+    document: &mut Document,
+    key: &str,
+    f: impl FnOnce(&mut Dictionary),
+) -> Option<()> {
+    let root = document.trailer.get(b"Root").ok()?.as_reference().ok()?;
+    edit_entry(document, root, key, f)
+}
+
+/// Edits a dictionary in an entry of a dictionary object, which is created if
+/// it is missing.
+pub fn edit_entry(
+    document: &mut Document,
+    id: ObjectId,
+    key: &str,
+    f: impl FnOnce(&mut Dictionary),
+) -> Option<()> {
+    let existing = dict(document, id)?.get(key.as_bytes()).ok().cloned();
+    let (mut entry, entry_id) = match existing {
+        Some(Object::Reference(entry_id)) => {
+            (dict(document, entry_id)?.clone(), Some(entry_id))
+        }
+        Some(Object::Dictionary(entry)) => (entry, None),
+        _ => (Dictionary::new(), None),
+    };
+
+    f(&mut entry);
+
+    match entry_id {
+        Some(entry_id) => document.set_object(entry_id, entry),
+        None => dict(document, id)?.set(key, entry),
+    }
+
+    Some(())
+}
+
+/// The object ids of all pages in the page tree, in order.
+pub fn pages(document: &Document) -> Vec<ObjectId> {
+    document.page_iter().collect()
+}
+
+/// A dictionary object.
+pub fn dict(document: &mut Document, id: ObjectId) -> Option<&mut Dictionary> {
+    document.get_dictionary_mut(id).ok()
+}
+
+/// Resolves an entry of a dictionary that may hold an indirect reference.
+pub fn get<'a>(
+    document: &'a Document,
+    dict: &'a Dictionary,
+    key: &str,
+) -> Option<&'a Object> {
+    document
+        .dereference(dict.get(key.as_bytes()).ok()?)
+        .ok()
+        .map(|(_, value)| value)
+}
+
+/// Encodes a text string, as a literal string if it is printable ASCII and
+/// as UTF-16 otherwise.
+pub fn text_string(text: &str) -> Object {
+    if text.bytes().all(|b| (0x20..0x7f).contains(&b)) {
+        Object::String(text.into(), StringFormat::Literal)
+    } else {
+        let mut encoded = vec![0xfe, 0xff];
+        for unit in text.encode_utf16() {
+            encoded.extend_from_slice(&unit.to_be_bytes());
+        }
+        Object::String(encoded, StringFormat::Hexadecimal)
+    }
+}
+
+/// Encodes a rectangle.
+pub fn rect(x1: f64, y1: f64, x2: f64, y2: f64) -> Object {
+    Object::Array([x1, y1, x2, y2].map(|v| Object::Real(v as f32)).to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use lopdf::dictionary;
+
+    use super::*;
+
+    /// Builds a minimal file with a catalog, a page tree, and one page.
+    fn sample() -> Vec<u8> {
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R /ViewerPreferences << /Direction /R2L >> >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] >>",
+        ];
+
+        let mut pdf = String::from("%PDF-1.7\n");
+        let mut offsets = vec![];
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            writeln!(pdf, "{} 0 obj\n{object}\nendobj", i + 1).unwrap();
+        }
+        let xref = pdf.len();
+        write!(pdf, "xref\n0 4\n0000000000 65535 f\r\n").unwrap();
+        for offset in offsets {
+            write!(pdf, "{offset:010} 00000 n\r\n").unwrap();
+        }
+        write!(
+            pdf,
+            "trailer\n<< /Size 4 /Root 1 0 R /ID [<01AB> <01AB>] >>\nstartxref\n{xref}\n%%EOF"
+        )
+        .unwrap();
+        pdf.into_bytes()
+    }
+
+    #[test]
+    fn test_editor_unchanged() {
+        let pdf = sample();
+        assert_eq!(Editor::new(pdf.clone(), false).finish().unwrap(), pdf);
+    }
+
+    #[test]
+    fn test_editor_validated() {
+        // A validated file is returned exactly as krilla wrote it.
+        let pdf = sample();
+        let mut editor = Editor::new(pdf.clone(), true);
+        assert!(editor.document().is_err());
+        assert_eq!(editor.finish().unwrap(), pdf);
+    }
+
+    #[test]
+    fn test_editor_edit() {
+        let mut editor = Editor::new(sample(), false);
+        let document = editor.document().unwrap();
+        let page = pages(document)[0];
+        let font = document.add_object(dictionary! { "Type" => "Font" });
+        dict(document, page).unwrap().set("Font", font);
+        dict(document, page).unwrap().remove(b"MediaBox");
+        catalog(document).unwrap().set("PageMode", "FullScreen");
+        edit_catalog_entry(document, "ViewerPreferences", |dict| {
+            dict.set("FitWindow", true);
+        })
+        .unwrap();
+
+        let pdf = editor.finish().unwrap();
+        assert!(pdf.starts_with(b"%PDF-1.7\n"));
+
+        let document = Document::load_mem(&pdf).unwrap();
+        assert_eq!(pages(&document), [(3, 0)]);
+        let catalog = document.catalog().unwrap();
+        assert_eq!(catalog.get(b"PageMode").unwrap().as_name().unwrap(), b"FullScreen");
+        let preferences = catalog.get(b"ViewerPreferences").unwrap().as_dict().unwrap();
+        assert_eq!(preferences.get(b"Direction").unwrap().as_name().unwrap(), b"R2L");
+        assert!(preferences.get(b"FitWindow").unwrap().as_bool().unwrap());
+
+        let page = document.get_dictionary((3, 0)).unwrap();
+        let font = get(&document, page, "Font").unwrap().as_dict().unwrap();
+        assert!(font.has_type(b"Font"));
+        assert!(!page.has(b"MediaBox"));
+        assert!(document.trailer.has(b"ID"));
+    }
+
+    #[test]
+    fn test_text_string() {
+        assert_eq!(text_string("a (b)"), Object::string_literal("a (b)"));
+        assert_eq!(
+            text_string("ä"),
+            Object::String(vec![0xfe, 0xff, 0x00, 0xe4], StringFormat::Hexadecimal)
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Encryption with passwords and permissions.
+//!
+//! krilla cannot encrypt documents, so we let lopdf encrypt all strings and
+//! streams of the finished document. We use the standard security handler with
+//! AES-256 (revision 6), as specified in PDF 2.0 and as an extension of
+//! PDF 1.7. The file encryption key is random.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use ecow::eco_format;
+use krilla::configure::PdfVersion;
+use lopdf::encryption::crypt_filters::{Aes256CryptFilter, CryptFilter};
+use lopdf::{
+    Document, EncryptionState, EncryptionVersion, Object, Permissions, StringFormat,
+    dictionary,
+};
+use typst_library::diag::{
+    At, ExpectInternal, HintedStrResult, SourceResult, StrResult, bail,
+};
+use typst_syntax::Span;
+
+use crate::edit::{self, Editor};
+use crate::{PdfEncryption, PdfOptions, PdfPermissions};
+
+/// Checks that encryption is compatible with the export target.
+pub fn check_encryption(options: &PdfOptions) -> SourceResult<()> {
+    if options.encryption.is_none() {
+        return Ok(());
+    }
+
+    edit::check_unvalidated(
+        options,
+        Span::detached(),
+        "the document cannot be encrypted",
+        "remove the passwords and permissions",
+    )?;
+
+    let version = options.standards.config.version();
+    if matches!(version, PdfVersion::Pdf14 | PdfVersion::Pdf15 | PdfVersion::Pdf16) {
+        bail!(
+            Span::detached(),
+            "encryption with AES-256 is not supported in {}", version.as_str();
+            hint: "export as PDF 1.7 or higher to encrypt the document";
+        );
+    }
+
+    Ok(())
+}
+
+/// Encrypts a finished PDF if the options ask for it.
+pub fn apply_encryption(editor: &mut Editor, options: &PdfOptions) -> SourceResult<()> {
+    let Some(encryption) = &options.encryption else { return Ok(()) };
+    let version = options.standards.config.version();
+    encrypt(editor.document()?, encryption, matches!(version, PdfVersion::Pdf20))
+        .at(Span::detached())
+}
+
+/// Encrypts all strings and streams and adds the encryption dictionary.
+fn encrypt(
+    document: &mut Document,
+    encryption: &PdfEncryption,
+    pdf20: bool,
+) -> HintedStrResult<()> {
+    // AES-256 is part of PDF 2.0, but only an extension of PDF 1.7.
+    if !pdf20 {
+        let extension = dictionary! { "BaseVersion" => "1.7", "ExtensionLevel" => 8 };
+        edit::edit_catalog_entry(document, "Extensions", |dict| {
+            dict.set("ADBE", extension);
+        })
+        .expect_internal("failed to encrypt document")?;
+    }
+
+    // Without an owner password, use a secret one so that the permissions
+    // cannot be lifted.
+    let owner_password = if encryption.owner_password.is_empty() {
+        hex(&random::<32>()?)
+    } else {
+        encryption.owner_password.clone()
+    };
+
+    let key = random::<32>()?;
+    let filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+    let version = EncryptionVersion::V5 {
+        encrypt_metadata: true,
+        crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), filter)]),
+        file_encryption_key: &key,
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        owner_password: &owner_password,
+        user_password: &encryption.user_password,
+        permissions: permissions(encryption.permissions),
+    };
+
+    EncryptionState::try_from(version)
+        .and_then(|state| document.encrypt(&state))
+        .map_err(|err| eco_format!("failed to encrypt document ({err})"))?;
+
+    if !document.trailer.has(b"ID") {
+        let id = Object::String(random::<16>()?.to_vec(), StringFormat::Hexadecimal);
+        document.trailer.set("ID", vec![id.clone(), id]);
+    }
+
+    Ok(())
+}
+
+/// Converts the permissions into the flags of the security handler.
+fn permissions(permissions: PdfPermissions) -> Permissions {
+    let flags = [
+        // Printing, also in high quality.
+        (
+            permissions.print,
+            Permissions::PRINTABLE | Permissions::PRINTABLE_IN_HIGH_QUALITY,
+        ),
+        // Modifying the contents, including assembling pages.
+        (permissions.modify, Permissions::MODIFIABLE | Permissions::ASSEMBLABLE),
+        (permissions.copy, Permissions::COPYABLE),
+        (permissions.annotate, Permissions::ANNOTABLE),
+        (permissions.fill_forms, Permissions::FILLABLE),
+        (permissions.accessibility, Permissions::COPYABLE_FOR_ACCESSIBILITY),
+    ];
+    flags
+        .into_iter()
+        .filter(|&(allowed, _)| allowed)
+        .fold(Permissions::empty(), |acc, (_, flags)| acc | flags)
+}
+
+/// Returns `N` bytes from the random number generator of the operating
+/// system.
+fn random<const N: usize>() -> StrResult<[u8; N]> {
+    let mut bytes = [0; N];
+    getrandom::fill(&mut bytes).map_err(|err| {
+        eco_format!("failed to generate random bytes for encryption ({err})")
+    })?;
+    Ok(bytes)
+}
+
+/// Encodes bytes as hexadecimal digits.
+fn hex(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    for b in bytes {
+        write!(encoded, "{b:02X}").unwrap();
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PdfStandard, PdfStandards};
+
+    /// Builds a minimal file with a catalog, a page tree, and a page with a
+    /// content stream.
+    fn sample() -> Vec<u8> {
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 4 0 R /T (Secret) >>",
+            "<< /Length 5 >>\nstream\nBT ET\nendstream",
+        ];
+
+        let mut pdf = b"%PDF-1.7\n%\x80\x80\x80\x80\n".to_vec();
+        let mut offsets = vec![];
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(
+                format!("{} 0 obj\n{object}\nendobj\n", i + 1).as_bytes(),
+            );
+        }
+        let xref = pdf.len();
+        let mut tail = String::from("xref\n0 5\n0000000000 65535 f\r\n");
+        for offset in offsets {
+            write!(tail, "{offset:010} 00000 n\r\n").unwrap();
+        }
+        write!(tail, "trailer\n<< /Size 5 /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF")
+            .unwrap();
+        pdf.extend_from_slice(tail.as_bytes());
+        pdf
+    }
+
+    /// Encrypts the sample file with the given user password.
+    fn encrypt_sample(user_password: &str) -> Vec<u8> {
+        let encryption = PdfEncryption {
+            user_password: user_password.into(),
+            owner_password: "owner".into(),
+            permissions: PdfPermissions { copy: false, ..Default::default() },
+        };
+
+        let mut editor = Editor::new(sample(), false);
+        encrypt(editor.document().unwrap(), &encryption, false).unwrap();
+        editor.finish().unwrap()
+    }
+
+    /// Checks that the sample's string and stream are readable again.
+    fn assert_decrypted(document: &Document) {
+        let page = document.get_dictionary((3, 0)).unwrap();
+        assert_eq!(page.get(b"T").unwrap().as_str().unwrap(), b"Secret");
+        let stream = document.get_object((4, 0)).unwrap().as_stream().unwrap();
+        assert_eq!(stream.content, b"BT ET");
+    }
+
+    #[test]
+    fn test_encrypt() {
+        let pdf = encrypt_sample("");
+        assert!(!pdf.windows(6).any(|w| w == b"Secret"));
+        assert!(!pdf.windows(5).any(|w| w == b"BT ET"));
+        assert_ne!(encrypt_sample(""), pdf);
+
+        // Without a user password, the file is decrypted when it is loaded.
+        let document = Document::load_mem(&pdf).unwrap();
+        assert_decrypted(&document);
+        assert!(document.catalog().unwrap().has(b"Extensions"));
+        assert!(document.trailer.has(b"ID"));
+    }
+
+    #[test]
+    fn test_encrypt_passwords() {
+        let pdf = encrypt_sample("user");
+        let document = Document::load_mem(&pdf).unwrap();
+        let id = document.trailer.get(b"Encrypt").unwrap().as_reference().unwrap();
+        let dict = document.get_dictionary(id).unwrap();
+        assert_eq!(dict.get(b"V").unwrap().as_i64().unwrap(), 5);
+        assert_eq!(dict.get(b"R").unwrap().as_i64().unwrap(), 6);
+        assert_eq!(dict.get(b"P").unwrap().as_i64().unwrap(), -20);
+
+        // lopdf only decrypts files without a user password when loading
+        // them, so we check the passwords like a reader would.
+        assert!(document.authenticate_user_password("user").is_ok());
+        assert!(document.authenticate_owner_password("owner").is_ok());
+        assert!(document.authenticate_owner_password("user").is_err());
+        assert!(document.authenticate_password("wrong").is_err());
+    }
+
+    #[test]
+    fn test_check_encryption_validated() {
+        // The encryption would not be validated, so it is refused.
+        let options = PdfOptions {
+            standards: PdfStandards::new(&[PdfStandard::Ua_1]).unwrap(),
+            encryption: Some(PdfEncryption {
+                user_password: String::new(),
+                owner_password: "owner".into(),
+                permissions: PdfPermissions::default(),
+            }),
+            ..Default::default()
+        };
+        let errors = check_encryption(&options).unwrap_err();
+        assert_eq!(errors[0].message, "PDF/UA-1 error: the document cannot be encrypted");
+    }
+
+    #[test]
+    fn test_permissions() {
+        let all = PdfPermissions::default();
+        assert_eq!(permissions(all), Permissions::all());
+        let none = PdfPermissions {
+            print: false,
+            copy: false,
+            modify: false,
+            annotate: false,
+            fill_forms: false,
+            accessibility: false,
+        };
+        assert_eq!(permissions(none), Permissions::empty());
+    }
+}
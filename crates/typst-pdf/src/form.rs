@@ -0,0 +1,857 @@
+//! Interactive form fields.
+//!
+//! Fields are laid out into [`FrameItem::Widget`]s, whose positions are
+//! collected during conversion. As krilla has no support for forms, the
+//! widget annotations, the fields, and the form dictionary are then edited
+//! into the finished document. In tagged documents, fields are tagged as
+//! structure elements with an ID (see [`tag_id`]), which are turned into
+//! `Form` elements that contain the widgets.
+//!
+//! [`FrameItem::Widget`]: typst_library::layout::FrameItem::Widget
+
+use std::fmt::Write;
+use std::io::Write as _;
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream, dictionary};
+use rustc_hash::{FxHashMap, FxHashSet};
+use smallvec::SmallVec;
+use typst_library::diag::{At, ExpectInternal, SourceResult, bail};
+use typst_library::foundations::{Content, NativeElement, Packed, StyleChain};
+use typst_library::introspection::Location;
+use typst_library::layout::{PagedDocument, Size};
+use typst_library::pdf::{FieldElem, FieldKind, FieldValue, FieldWidget};
+use typst_library::text::{Font, FontFlags, FontStyle};
+use typst_library::visualize::ColorSpace;
+use typst_syntax::Span;
+
+use crate::PdfOptions;
+use crate::convert::{FrameContext, GlobalContext};
+use crate::edit::{self, Editor};
+use crate::instance;
+use crate::link::bounding_box;
+use crate::util::AbsExt;
+
+/// Annotation flag: The annotation is printed.
+const PRINT: u32 = 1 << 2;
+/// Field flag: The field must have a value when the form is submitted.
+const REQUIRED: u32 = 1 << 1;
+/// Field flag: Exactly one radio button must be selected at all times.
+const NO_TOGGLE_TO_OFF: u32 = 1 << 14;
+/// Field flag: The button field is a radio group.
+const RADIO: u32 = 1 << 15;
+/// Field flag: The choice field is a dropdown instead of a list box.
+const COMBO: u32 = 1 << 17;
+
+/// The space between the border of a text field and its text, in points.
+const PADDING: f64 = 2.0;
+
+/// A widget together with its position in the document.
+pub struct PlacedWidget {
+    /// The widget.
+    pub widget: FieldWidget,
+    /// The index of the PDF page that the widget is on.
+    pub page: usize,
+    /// The widget's rectangle in PDF coordinates.
+    pub rect: [f64; 4],
+}
+
+/// Records the position of a widget.
+pub(crate) fn handle_widget(
+    fc: &FrameContext,
+    gc: &mut GlobalContext,
+    widget: &FieldWidget,
+    size: Size,
+) {
+    // Widgets in tilings or on pages that are not exported are dropped.
+    let Some(page) = fc.page_idx else { return };
+
+    // Krilla pages are drawn with a flipped y-axis, whose origin is at the
+    // top of the page's frame.
+    let rect = bounding_box(fc, size);
+    let height = f64::from(fc.root_size().y.to_f32().max(3.0));
+    gc.widgets.push(PlacedWidget {
+        widget: widget.clone(),
+        page,
+        rect: [
+            f64::from(rect.left()),
+            height - f64::from(rect.bottom()),
+            f64::from(rect.right()),
+            height - f64::from(rect.top()),
+        ],
+    });
+}
+
+/// The ID of the structure element that a field is tagged as.
+pub fn tag_id(field: Location) -> SmallVec<[u8; 32]> {
+    // 32 bytes is the maximum length the ID string can have.
+    let mut buf = SmallVec::new();
+    _ = write!(&mut buf, "field{:016x}", field.hash() as u64);
+    buf
+}
+
+/// Checks that the document's form fields can be written.
+pub fn check_form_fields(
+    document: &PagedDocument,
+    options: &PdfOptions,
+) -> SourceResult<()> {
+    let fields = document.introspector.query(&FieldElem::ELEM.select());
+    let Some(first) = fields.first() else { return Ok(()) };
+    edit::check_unvalidated(
+        options,
+        first.span(),
+        "form fields cannot be used",
+        "remove the fields",
+    )?;
+
+    let mut names = FxHashSet::default();
+    for field in &fields {
+        let field = field.to_packed::<FieldElem>().unwrap();
+        if !names.insert(&field.name) {
+            bail!(
+                field.span(),
+                "duplicate form field name `{}`", field.name;
+                hint: "each field must have a unique name";
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the fields with their widgets into a finished PDF.
+pub fn apply_form_fields(
+    editor: &mut Editor,
+    document: &PagedDocument,
+    widgets: &[PlacedWidget],
+) -> SourceResult<()> {
+    let fields = document.introspector.query(&FieldElem::ELEM.select());
+    if fields.is_empty() || widgets.is_empty() {
+        return Ok(());
+    }
+
+    write_fields(editor.document()?, &fields, widgets)
+        .expect_internal("failed to write form fields")
+        .at(Span::detached())
+}
+
+/// Writes the fields, their widgets, and the form dictionary.
+fn write_fields(
+    document: &mut Document,
+    fields: &[Content],
+    widgets: &[PlacedWidget],
+) -> Option<()> {
+    let mut ctx = FormContext {
+        pages: edit::pages(document),
+        elems: struct_elems(document),
+        fonts: vec![],
+    };
+
+    let mut refs = vec![];
+    for field in fields {
+        let field = field.to_packed::<FieldElem>()?;
+        let location = field.location()?;
+        let placed: Vec<&PlacedWidget> = widgets
+            .iter()
+            .filter(|placed| placed.widget.field == location)
+            .collect();
+        if !placed.is_empty() {
+            refs.push(Object::Reference(write_field(
+                document, &mut ctx, field, &placed,
+            )?));
+        }
+    }
+
+    let mut form = dictionary! { "Fields" => refs };
+    if !ctx.fonts.is_empty() {
+        let mut fonts = Dictionary::new();
+        for (_, name, id) in &ctx.fonts {
+            fonts.set(name.as_str(), *id);
+        }
+        form.set("DR", dictionary! { "Font" => fonts });
+    }
+    edit::catalog(document)?.set("AcroForm", form);
+
+    Some(())
+}
+
+/// State that is shared between the fields of a form.
+struct FormContext {
+    /// The object ids of the pages.
+    pages: Vec<ObjectId>,
+    /// The structure elements that fields are tagged as, by their ID.
+    elems: FxHashMap<Vec<u8>, ObjectId>,
+    /// The embedded fonts with their resource names.
+    fonts: Vec<(Font, String, ObjectId)>,
+}
+
+impl FormContext {
+    /// Embeds a font, if it isn't yet, and returns its resource name and
+    /// object id.
+    fn font(
+        &mut self,
+        document: &mut Document,
+        font: &Font,
+    ) -> Option<(String, ObjectId)> {
+        if let Some((_, name, id)) = self.fonts.iter().find(|(f, _, _)| f == font) {
+            return Some((name.clone(), *id));
+        }
+
+        let id = write_font(document, font)?;
+        let name = format!("F{}", self.fonts.len());
+        self.fonts.push((font.clone(), name.clone(), id));
+        Some((name, id))
+    }
+}
+
+/// Writes a field and its widgets and returns the field's object id.
+fn write_field(
+    document: &mut Document,
+    ctx: &mut FormContext,
+    field: &Packed<FieldElem>,
+    placed: &[&PlacedWidget],
+) -> Option<ObjectId> {
+    let styles = StyleChain::default();
+    let kind = field.kind.get(styles);
+    let options = field.options.get_ref(styles);
+    let value = field.value.get_ref(styles);
+
+    let tooltip = field.tooltip.get_ref(styles).as_ref().unwrap_or(&field.name);
+    let mut dict = dictionary! {
+        "T" => edit::text_string(&field.name),
+        "TU" => edit::text_string(tooltip),
+    };
+
+    let mut flags = 0;
+    if field.required.get(styles) {
+        flags |= REQUIRED;
+    }
+
+    let selected = match value {
+        Some(FieldValue::Str(value)) => options.iter().position(|option| option == value),
+        _ => None,
+    };
+
+    let appearance = match kind {
+        FieldKind::Text => {
+            dict.set("FT", "Tx");
+            let text = match value {
+                Some(FieldValue::Str(text)) => {
+                    dict.set("V", edit::text_string(text));
+                    dict.set("DV", edit::text_string(text));
+                    text.as_str()
+                }
+                _ => "",
+            };
+            Appearance::Text(text)
+        }
+        FieldKind::Checkbox => {
+            let checked = matches!(value, Some(FieldValue::Bool(true)));
+            let state = if checked { "Yes" } else { "Off" };
+            dict.set("FT", "Btn");
+            dict.set("V", state);
+            dict.set("DV", state);
+            Appearance::Check(checked)
+        }
+        FieldKind::Radio => {
+            flags |= RADIO | NO_TOGGLE_TO_OFF;
+            let state = match selected {
+                Some(index) => index.to_string(),
+                None => "Off".into(),
+            };
+            dict.set("FT", "Btn");
+            dict.set("V", state.as_str());
+            dict.set("DV", state);
+
+            // The export value of each widget, in the order of the kids.
+            let values = placed
+                .iter()
+                .map(|placed| edit::text_string(&options[placed.widget.index]))
+                .collect::<Vec<_>>();
+            dict.set("Opt", values);
+            Appearance::Dot(selected)
+        }
+        FieldKind::Dropdown => {
+            flags |= COMBO;
+            dict.set("FT", "Ch");
+            let values = options.iter().map(|option| edit::text_string(option));
+            dict.set("Opt", values.collect::<Vec<_>>());
+            let text = match selected {
+                Some(index) => {
+                    dict.set("V", edit::text_string(&options[index]));
+                    dict.set("DV", edit::text_string(&options[index]));
+                    options[index].as_str()
+                }
+                None => "",
+            };
+            Appearance::Text(text)
+        }
+        FieldKind::Signature => {
+            dict.set("FT", "Sig");
+            Appearance::Empty
+        }
+    };
+
+    if flags != 0 {
+        dict.set("Ff", flags);
+    }
+
+    let parent = document.new_object_id();
+    let elem = ctx.elems.get(tag_id(field.location()?).as_slice()).copied();
+    let mut kids = vec![];
+    for placed in placed {
+        let page = *ctx.pages.get(placed.page)?;
+        let (ap, state) = match appearance {
+            Appearance::Text(text) => {
+                let font = ctx.font(document, &placed.widget.font)?;
+                let (stream, da) = text_appearance(placed, font, text);
+                dict.set("DA", Object::string_literal(da));
+                (document.add_object(stream).into(), None)
+            }
+            _ => button_appearance(document, placed, appearance),
+        };
+
+        let mut widget = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "Rect" => edit::rect(
+                placed.rect[0],
+                placed.rect[1],
+                placed.rect[2],
+                placed.rect[3],
+            ),
+            "F" => PRINT,
+            "P" => page,
+            "Parent" => parent,
+            "AP" => dictionary! { "N" => ap },
+        };
+        if let Some(state) = state {
+            widget.set("AS", state);
+        }
+
+        let id = document.add_object(widget);
+        add_annotation(document, page, id)?;
+        if let Some(elem) = elem {
+            tag_widget(document, elem, id, page)?;
+        }
+        kids.push(Object::Reference(id));
+    }
+
+    dict.set("Kids", kids);
+    document.set_object(parent, dict);
+
+    Some(parent)
+}
+
+/// How the widgets of a field are drawn.
+#[derive(Copy, Clone)]
+enum Appearance<'a> {
+    /// Nothing is drawn.
+    Empty,
+    /// A text field or dropdown, with its value.
+    Text(&'a str),
+    /// A checkbox, with whether it is checked.
+    Check(bool),
+    /// A radio button, with the index of the selected option.
+    Dot(Option<usize>),
+}
+
+/// Writes the appearance of a text field or dropdown.
+///
+/// Returns the appearance stream and the default appearance string, with
+/// which readers draw the text once it is edited.
+fn text_appearance(
+    placed: &PlacedWidget,
+    (name, font): (String, ObjectId),
+    text: &str,
+) -> (Stream, String) {
+    let widget = &placed.widget;
+    let (width, height) = rect_size(placed.rect);
+    let metrics = widget.font.metrics();
+    let extent = (metrics.ascender - metrics.descender).get().max(0.1);
+
+    // Shrink the text if it doesn't fit into the field.
+    let size = widget.size.to_pt().min((height - 2.0 * PADDING) / extent).max(1.0);
+    let [r, g, b, _] = widget.color.to_space(ColorSpace::Srgb).to_vec4();
+    let da = format!("/{name} {size:.2} Tf {r:.3} {g:.3} {b:.3} rg");
+
+    // Center the text vertically.
+    let baseline = (height - extent * size) / 2.0 - metrics.descender.get() * size;
+    let mut content = String::from("/Tx BMC q ");
+    if !text.is_empty() {
+        write!(
+            content,
+            "{PADDING:.2} {PADDING:.2} {:.2} {:.2} re W n BT {da} {PADDING:.2} {baseline:.2} Td <",
+            (width - 2.0 * PADDING).max(0.0),
+            (height - 2.0 * PADDING).max(0.0),
+        )
+        .unwrap();
+        for byte in text.chars().filter_map(win_ansi) {
+            write!(content, "{byte:02X}").unwrap();
+        }
+        content.push_str("> Tj ET ");
+    }
+    content.push_str("Q EMC");
+
+    let mut dict = form_xobject(width, height);
+    dict.set("Resources", dictionary! { "Font" => dictionary! { name => font } });
+    (Stream::new(dict, content.into_bytes()), da)
+}
+
+/// Writes the appearance of a checkbox, radio button, or signature field.
+///
+/// Returns the appearance and the appearance state.
+fn button_appearance(
+    document: &mut Document,
+    placed: &PlacedWidget,
+    appearance: Appearance,
+) -> (Object, Option<String>) {
+    let size = rect_size(placed.rect);
+    let mut stream = |content: String| {
+        let dict = form_xobject(size.0, size.1);
+        document.add_object(Stream::new(dict, content.into_bytes()))
+    };
+
+    let off = stream(String::new());
+    match appearance {
+        Appearance::Check(checked) => {
+            let on = stream(check_mark(size));
+            let state = if checked { "Yes" } else { "Off" };
+            (dictionary! { "Yes" => on, "Off" => off }.into(), Some(state.into()))
+        }
+        Appearance::Dot(selected) => {
+            let index = placed.widget.index;
+            let on = stream(dot(size));
+            let name = index.to_string();
+            let state = if selected == Some(index) { name.clone() } else { "Off".into() };
+            (dictionary! { name => on, "Off" => off }.into(), Some(state))
+        }
+        Appearance::Empty | Appearance::Text(_) => (off.into(), None),
+    }
+}
+
+/// The dictionary of an appearance stream of the given size.
+fn form_xobject(width: f64, height: f64) -> Dictionary {
+    dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Form",
+        "BBox" => edit::rect(0.0, 0.0, width, height),
+    }
+}
+
+/// The width and height of a rectangle.
+fn rect_size([x1, y1, x2, y2]: [f64; 4]) -> (f64, f64) {
+    ((x2 - x1).abs(), (y2 - y1).abs())
+}
+
+/// Draws the check mark of a checked checkbox.
+fn check_mark((w, h): (f64, f64)) -> String {
+    let width = 0.12 * w.min(h);
+    format!(
+        "q 0 G {width:.2} w 1 J 1 j {:.2} {:.2} m {:.2} {:.2} l {:.2} {:.2} l S Q",
+        0.2 * w,
+        0.5 * h,
+        0.42 * w,
+        0.25 * h,
+        0.8 * w,
+        0.78 * h,
+    )
+}
+
+/// Draws the dot of a selected radio button.
+fn dot((w, h): (f64, f64)) -> String {
+    // The distance of the Bézier control points from the on-curve points
+    // for approximating a circle.
+    const KAPPA: f64 = 0.5523;
+
+    let (cx, cy) = (w / 2.0, h / 2.0);
+    let r = 0.25 * w.min(h);
+    let k = KAPPA * r;
+    let mut path = String::from("q 0 g ");
+    write!(path, "{:.2} {cy:.2} m ", cx + r).unwrap();
+    for (p1, p2, p3) in [
+        ((cx + r, cy + k), (cx + k, cy + r), (cx, cy + r)),
+        ((cx - k, cy + r), (cx - r, cy + k), (cx - r, cy)),
+        ((cx - r, cy - k), (cx - k, cy - r), (cx, cy - r)),
+        ((cx + k, cy - r), (cx + r, cy - k), (cx + r, cy)),
+    ] {
+        write!(
+            path,
+            "{:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c ",
+            p1.0, p1.1, p2.0, p2.1, p3.0, p3.1
+        )
+        .unwrap();
+    }
+    path.push_str("f Q");
+    path
+}
+
+/// Embeds a font in full as a TrueType font with the WinAnsi encoding.
+///
+/// Readers draw the text that is entered into a field with this font, so it
+/// cannot be subset. The font is converted into a static TrueType font, which
+/// also handles font collections, CFF outlines, and variable fonts.
+fn write_font(document: &mut Document, font: &Font) -> Option<ObjectId> {
+    let data = instance::instantiate(font.ttf())?;
+    let mut file = Stream::new(dictionary! { "Length1" => data.len() as i64 }, data);
+    file.compress().ok()?;
+    let file = document.add_object(file);
+
+    let ttf = font.ttf();
+    let to_units = |em: f64| (1000.0 * em).round() as i64;
+    let scale = 1000.0 / font.units_per_em();
+    let widths = (32..=255u8)
+        .map(|code| {
+            let glyph = char_from_win_ansi(code)
+                .and_then(|c| ttf.glyph_index(c))
+                .unwrap_or_default();
+            let advance = ttf.glyph_hor_advance(glyph).unwrap_or(0);
+            Object::Integer((f64::from(advance) * scale).round() as i64)
+        })
+        .collect::<Vec<_>>();
+
+    let info = font.info();
+    let mut flags = 1 << 5;
+    if info.flags.contains(FontFlags::MONOSPACE) {
+        flags |= 1 << 0;
+    }
+    if info.flags.contains(FontFlags::SERIF) {
+        flags |= 1 << 1;
+    }
+    if info.variant.style != FontStyle::Normal {
+        flags |= 1 << 6;
+    }
+
+    let name = base_font(font);
+    let bbox = ttf.global_bounding_box();
+    let metrics = font.metrics();
+    let descriptor = document.add_object(dictionary! {
+        "Type" => "FontDescriptor",
+        "FontName" => name.as_str(),
+        "Flags" => flags,
+        "FontBBox" => edit::rect(
+            f64::from(bbox.x_min) * scale,
+            f64::from(bbox.y_min) * scale,
+            f64::from(bbox.x_max) * scale,
+            f64::from(bbox.y_max) * scale,
+        ),
+        "ItalicAngle" => ttf.italic_angle(),
+        "Ascent" => to_units(metrics.ascender.get()),
+        "Descent" => to_units(metrics.descender.get()),
+        "CapHeight" => to_units(metrics.cap_height.get()),
+        "StemV" => 80,
+        "FontFile2" => file,
+    });
+
+    Some(document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "TrueType",
+        "BaseFont" => name,
+        "FirstChar" => 32,
+        "LastChar" => 255,
+        "Widths" => widths,
+        "Encoding" => "WinAnsiEncoding",
+        "FontDescriptor" => descriptor,
+    }))
+}
+
+/// The PostScript name of a font, restricted to characters that are valid in
+/// a name.
+fn base_font(font: &Font) -> String {
+    let name: String = font
+        .find_name(ttf_parser::name_id::POST_SCRIPT_NAME)
+        .unwrap_or_else(|| font.info().family.replace(' ', ""))
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    if name.is_empty() { "Font".into() } else { name }
+}
+
+/// The characters of the WinAnsi encoding at codes 128 to 159, where it
+/// differs from Latin-1.
+const WIN_ANSI: [Option<char>; 32] = [
+    Some('€'),
+    None,
+    Some('‚'),
+    Some('ƒ'),
+    Some('„'),
+    Some('…'),
+    Some('†'),
+    Some('‡'),
+    Some('ˆ'),
+    Some('‰'),
+    Some('Š'),
+    Some('‹'),
+    Some('Œ'),
+    None,
+    Some('Ž'),
+    None,
+    None,
+    Some('‘'),
+    Some('’'),
+    Some('“'),
+    Some('”'),
+    Some('•'),
+    Some('–'),
+    Some('—'),
+    Some('˜'),
+    Some('™'),
+    Some('š'),
+    Some('›'),
+    Some('œ'),
+    None,
+    Some('ž'),
+    Some('Ÿ'),
+];
+
+/// Encodes a character with the WinAnsi encoding.
+fn win_ansi(c: char) -> Option<u8> {
+    match c as u32 {
+        0x20..=0x7e | 0xa0..=0xff => Some(c as u8),
+        _ => WIN_ANSI.iter().position(|&v| v == Some(c)).map(|i| 0x80 + i as u8),
+    }
+}
+
+/// Decodes a character of the WinAnsi encoding.
+fn char_from_win_ansi(code: u8) -> Option<char> {
+    match code {
+        0x80..=0x9f => WIN_ANSI[usize::from(code - 0x80)],
+        0x7f => None,
+        _ => Some(char::from(code)),
+    }
+}
+
+/// Adds an annotation to a page.
+fn add_annotation(
+    document: &mut Document,
+    page: ObjectId,
+    annot: ObjectId,
+) -> Option<()> {
+    let dict = document.get_dictionary(page).ok()?;
+    match dict.get(b"Annots").ok().cloned() {
+        Some(Object::Reference(id)) => {
+            document
+                .get_object_mut(id)
+                .ok()?
+                .as_array_mut()
+                .ok()?
+                .push(annot.into());
+        }
+        Some(Object::Array(mut annots)) => {
+            annots.push(annot.into());
+            edit::dict(document, page)?.set("Annots", annots);
+        }
+        _ => edit::dict(document, page)?.set("Annots", vec![annot.into()]),
+    }
+    Some(())
+}
+
+/// Finds the structure elements that fields are tagged as.
+fn struct_elems(document: &Document) -> FxHashMap<Vec<u8>, ObjectId> {
+    document
+        .objects
+        .iter()
+        .filter_map(|(&id, object)| {
+            let dict = object.as_dict().ok()?;
+            let key = dict.get(b"ID").and_then(Object::as_str).ok()?;
+            (dict.has(b"S") && key.starts_with(b"field")).then(|| (key.to_vec(), id))
+        })
+        .collect()
+}
+
+/// Turns the structure element of a field into a `Form` element and adds a
+/// widget to it.
+fn tag_widget(
+    document: &mut Document,
+    elem: ObjectId,
+    widget: ObjectId,
+    page: ObjectId,
+) -> Option<()> {
+    let key = add_struct_parent(document, elem)?;
+    edit::dict(document, widget)?.set("StructParent", key);
+
+    let objr = dictionary! { "Type" => "OBJR", "Obj" => widget, "Pg" => page };
+    let dict = edit::dict(document, elem)?;
+    dict.set("S", "Form");
+    let kids = match dict.remove(b"K") {
+        Some(Object::Array(mut kids)) => {
+            kids.push(objr.into());
+            kids
+        }
+        Some(kid) => vec![kid, objr.into()],
+        None => vec![objr.into()],
+    };
+    dict.set("K", kids);
+
+    Some(())
+}
+
+/// Adds a structure element to the parent tree and returns its key.
+fn add_struct_parent(document: &mut Document, elem: ObjectId) -> Option<i64> {
+    let catalog = document.catalog().ok()?;
+    let root = catalog.get(b"StructTreeRoot").ok()?.as_reference().ok()?;
+    let dict = document.get_dictionary(root).ok()?;
+    let tree = edit::get(document, dict, "ParentTree")?.as_dict().ok()?;
+    let key = match dict.get(b"ParentTreeNextKey") {
+        Ok(next) => next.as_i64().ok()?,
+        Err(_) => max_key(document, tree, 0).map_or(0, |max| max + 1),
+    };
+
+    // Number trees with kids must not have numbers in their root, so the
+    // entry then becomes a new kid.
+    let has_kids = tree.has(b"Kids");
+    let kid = has_kids.then(|| {
+        document.add_object(dictionary! {
+            "Limits" => vec![key.into(), key.into()],
+            "Nums" => vec![key.into(), elem.into()],
+        })
+    });
+
+    edit::edit_entry(document, root, "ParentTree", |tree| match kid {
+        Some(kid) => {
+            if let Ok(kids) = tree.get_mut(b"Kids").and_then(Object::as_array_mut) {
+                kids.push(kid.into());
+            }
+        }
+        None => match tree.get_mut(b"Nums").and_then(Object::as_array_mut) {
+            Ok(nums) => nums.extend([key.into(), elem.into()]),
+            Err(_) => tree.set("Nums", vec![key.into(), elem.into()]),
+        },
+    })?;
+
+    edit::dict(document, root)?.set("ParentTreeNextKey", key + 1);
+    Some(key)
+}
+
+/// The largest key in a number tree.
+fn max_key(document: &Document, node: &Dictionary, depth: usize) -> Option<i64> {
+    if depth > 32 {
+        return None;
+    }
+
+    let mut max = None;
+    if let Some(nums) = edit::get(document, node, "Nums") {
+        for pair in nums.as_array().ok()?.chunks(2) {
+            max = max.max(pair[0].as_i64().ok());
+        }
+    }
+
+    if let Some(kids) = edit::get(document, node, "Kids") {
+        for kid in kids.as_array().ok()? {
+            let kid = document.dereference(kid).ok()?.1.as_dict().ok()?;
+            max = max.max(max_key(document, kid, depth + 1));
+        }
+    }
+
+    max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a document with a page and a structure tree that holds an
+    /// element of a field.
+    fn sample(parent_tree: Dictionary) -> (Document, ObjectId, ObjectId) {
+        let mut document = Document::with_version("1.7");
+        let pages = document.new_object_id();
+        let page = document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages,
+            "MediaBox" => edit::rect(0.0, 0.0, 100.0, 100.0),
+        });
+        document.set_object(
+            pages,
+            dictionary! { "Type" => "Pages", "Kids" => vec![page.into()], "Count" => 1 },
+        );
+
+        let root = document.new_object_id();
+        let elem = document.add_object(dictionary! {
+            "Type" => "StructElem",
+            "S" => "Span",
+            "P" => root,
+            "ID" => Object::string_literal("field0000000000000001"),
+            "K" => 0,
+        });
+        let tree = document.add_object(parent_tree);
+        document.set_object(
+            root,
+            dictionary! {
+                "Type" => "StructTreeRoot",
+                "K" => vec![elem.into()],
+                "ParentTree" => tree,
+            },
+        );
+
+        let catalog = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages,
+            "StructTreeRoot" => root,
+        });
+        document.trailer.set("Root", catalog);
+        (document, page, elem)
+    }
+
+    /// The parent tree of the sample.
+    fn parent_tree(document: &Document) -> &Dictionary {
+        let catalog = document.catalog().unwrap();
+        let root = catalog.get(b"StructTreeRoot").unwrap().as_reference().unwrap();
+        let root = document.get_dictionary(root).unwrap();
+        edit::get(document, root, "ParentTree").unwrap().as_dict().unwrap()
+    }
+
+    #[test]
+    fn test_win_ansi() {
+        for code in 32..=255 {
+            if let Some(c) = char_from_win_ansi(code) {
+                assert_eq!(win_ansi(c), Some(code));
+            }
+        }
+        assert_eq!(win_ansi('€'), Some(0x80));
+        assert_eq!(win_ansi('ä'), Some(0xe4));
+        assert_eq!(win_ansi('→'), None);
+    }
+
+    #[test]
+    fn test_tag_widget() {
+        let (mut document, page, elem) = sample(dictionary! {
+            "Nums" => vec![0.into(), Object::Array(vec![])],
+        });
+        let widget = document.add_object(dictionary! { "Subtype" => "Widget" });
+        add_annotation(&mut document, page, widget).unwrap();
+        tag_widget(&mut document, elem, widget, page).unwrap();
+
+        let annots = document.get_dictionary(page).unwrap().get(b"Annots").unwrap();
+        assert_eq!(annots.as_array().unwrap(), &[Object::Reference(widget)]);
+
+        let dict = document.get_dictionary(widget).unwrap();
+        assert_eq!(dict.get(b"StructParent").unwrap().as_i64().unwrap(), 1);
+
+        let dict = document.get_dictionary(elem).unwrap();
+        assert_eq!(dict.get(b"S").unwrap().as_name().unwrap(), b"Form");
+        let kids = dict.get(b"K").unwrap().as_array().unwrap();
+        assert_eq!(kids[0].as_i64().unwrap(), 0);
+        let objr = kids[1].as_dict().unwrap();
+        assert_eq!(objr.get(b"Obj").unwrap().as_reference().unwrap(), widget);
+
+        let nums = parent_tree(&document).get(b"Nums").unwrap().as_array().unwrap();
+        assert_eq!(nums[2..], [1.into(), elem.into()]);
+    }
+
+    #[test]
+    fn test_add_struct_parent_kids() {
+        let (mut document, _, elem) = sample(dictionary! {
+            "Kids" => vec![Object::Dictionary(dictionary! {
+                "Limits" => vec![0.into(), 4.into()],
+                "Nums" => vec![4.into(), Object::Array(vec![])],
+            })],
+        });
+        assert_eq!(add_struct_parent(&mut document, elem), Some(5));
+        assert_eq!(add_struct_parent(&mut document, elem), Some(6));
+
+        let kids = parent_tree(&document).get(b"Kids").unwrap().as_array().unwrap();
+        assert_eq!(kids.len(), 3);
+        let kid = document.get_dictionary(kids[2].as_reference().unwrap()).unwrap();
+        let nums = kid.get(b"Nums").unwrap().as_array().unwrap();
+        assert_eq!(nums, &[6.into(), elem.into()]);
+    }
+}
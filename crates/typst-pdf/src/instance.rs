@@ -0,0 +1,406 @@
+//! Static instances of variable fonts.
+//!
+//! PDF has no notion of font variations, so a non-default instance of a
+//! variable font is embedded as a static TrueType font that contains the
+//! instanced outlines and advances. Glyph IDs are preserved, so the glyph runs
+//! of the text item can be written as-is.
+
+use ttf_parser::{Face, GlyphId, OutlineBuilder, Tag};
+
+/// Tables that don't depend on the variation coordinates and are copied from
+/// the variable font as-is.
+const COPIED: [&[u8; 4]; 4] = [b"OS/2", b"cmap", b"name", b"post"];
+
+/// The largest deviation, in font units, when approximating a cubic Bézier
+/// curve with quadratic ones.
+const TOLERANCE: f32 = 0.5;
+
+/// Builds a static TrueType font from the outlines and advances of the given
+/// face at its current variation coordinates.
+///
+/// Returns `None` if the face lacks tables that are required for this.
+pub fn instantiate(face: &Face) -> Option<Vec<u8>> {
+    let raw = face.raw_face();
+    let num_glyphs = face.number_of_glyphs();
+
+    let mut glyf = Vec::new();
+    let mut loca = vec![0u32];
+    let mut hmtx = Vec::with_capacity(4 * num_glyphs as usize);
+    let mut max_advance = 0;
+    let mut max_points = 0;
+    let mut max_contours = 0;
+
+    for id in 0..num_glyphs {
+        let id = GlyphId(id);
+        let mut builder = GlyfBuilder::default();
+        face.outline_glyph(id, &mut builder);
+        let glyph = builder.finish();
+
+        let advance = face.glyph_hor_advance(id).unwrap_or(0);
+        max_advance = max_advance.max(advance);
+        hmtx.extend(advance.to_be_bytes());
+        hmtx.extend(glyph.as_ref().map_or(0, |g| g.bbox[0]).to_be_bytes());
+
+        if let Some(glyph) = glyph {
+            max_points = max_points.max(glyph.points.len());
+            max_contours = max_contours.max(glyph.end_points.len());
+            glyph.write(&mut glyf);
+        }
+
+        loca.push(glyf.len() as u32);
+    }
+
+    let mut head = raw.table(Tag::from_bytes(b"head"))?.to_vec();
+    let mut hhea = raw.table(Tag::from_bytes(b"hhea"))?.to_vec();
+    if head.len() < 54 || hhea.len() < 36 {
+        return None;
+    }
+
+    // Reset the checksum adjustment, which is recomputed at the end, and
+    // switch to long loca offsets.
+    head[8..12].copy_from_slice(&[0; 4]);
+    head[50..52].copy_from_slice(&1u16.to_be_bytes());
+
+    // Every glyph gets its own metrics.
+    hhea[10..12].copy_from_slice(&max_advance.to_be_bytes());
+    hhea[34..36].copy_from_slice(&num_glyphs.to_be_bytes());
+
+    let mut maxp = Vec::with_capacity(32);
+    maxp.extend(0x00010000u32.to_be_bytes());
+    maxp.extend(num_glyphs.to_be_bytes());
+    maxp.extend((max_points.min(u16::MAX as usize) as u16).to_be_bytes());
+    maxp.extend((max_contours.min(u16::MAX as usize) as u16).to_be_bytes());
+    // Composite points and contours, zones, and the instruction limits, which
+    // don't apply since all glyphs are simple and unhinted.
+    maxp.extend([0, 0, 0, 0, 0, 2]);
+    maxp.extend([0; 16]);
+
+    let loca: Vec<u8> = loca.iter().flat_map(|offset| offset.to_be_bytes()).collect();
+
+    let mut tables = vec![
+        (*b"glyf", glyf),
+        (*b"head", head),
+        (*b"hhea", hhea),
+        (*b"hmtx", hmtx),
+        (*b"loca", loca),
+        (*b"maxp", maxp),
+    ];
+
+    for tag in COPIED {
+        if let Some(data) = raw.table(Tag::from_bytes(tag)) {
+            tables.push((*tag, data.to_vec()));
+        }
+    }
+
+    Some(write_sfnt(tables))
+}
+
+/// Writes an OpenType font file with TrueType outlines from its tables.
+fn write_sfnt(mut tables: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range: u16 = 16 << entry_selector;
+
+    let mut data = Vec::new();
+    data.extend(0x00010000u32.to_be_bytes());
+    data.extend(count.to_be_bytes());
+    data.extend(search_range.to_be_bytes());
+    data.extend(entry_selector.to_be_bytes());
+    data.extend((count * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, table) in &tables {
+        data.extend(tag);
+        data.extend(checksum(table).to_be_bytes());
+        data.extend((offset as u32).to_be_bytes());
+        data.extend((table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+
+    let mut head_offset = 0;
+    for (tag, table) in &tables {
+        if tag == b"head" {
+            head_offset = data.len();
+        }
+        data.extend(table);
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+
+    let adjustment = 0xB1B0AFBAu32.wrapping_sub(checksum(&data));
+    data[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    data
+}
+
+/// Computes the checksum of an OpenType table.
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// A simple glyph in the `glyf` table.
+struct SimpleGlyph {
+    /// The glyph's bounding box as `[x_min, y_min, x_max, y_max]`.
+    bbox: [i16; 4],
+    /// The index of the last point of each contour.
+    end_points: Vec<u16>,
+    /// The points along with whether they are on the curve.
+    points: Vec<(i16, i16, bool)>,
+}
+
+impl SimpleGlyph {
+    /// Appends the glyph's `glyf` table entry, padded to a two-byte boundary.
+    fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend((self.end_points.len() as i16).to_be_bytes());
+        for value in self.bbox {
+            buf.extend(value.to_be_bytes());
+        }
+        for end in &self.end_points {
+            buf.extend(end.to_be_bytes());
+        }
+
+        // No instructions.
+        buf.extend(0u16.to_be_bytes());
+
+        // All coordinates are written as 16-bit deltas, so the flags only
+        // carry the on-curve bit.
+        buf.extend(self.points.iter().map(|&(_, _, on)| on as u8));
+
+        let mut prev = 0;
+        for &(x, _, _) in &self.points {
+            buf.extend(x.wrapping_sub(prev).to_be_bytes());
+            prev = x;
+        }
+
+        let mut prev = 0;
+        for &(_, y, _) in &self.points {
+            buf.extend(y.wrapping_sub(prev).to_be_bytes());
+            prev = y;
+        }
+
+        if !buf.len().is_multiple_of(2) {
+            buf.push(0);
+        }
+    }
+}
+
+/// Collects glyph outlines as quadratic TrueType contours.
+#[derive(Default)]
+struct GlyfBuilder {
+    end_points: Vec<u16>,
+    points: Vec<(i16, i16, bool)>,
+    /// The start of the current contour in `points`.
+    start: usize,
+    /// The current point, at full precision.
+    current: (f32, f32),
+}
+
+impl GlyfBuilder {
+    fn push(&mut self, x: f32, y: f32, on: bool) {
+        self.points.push((x.round() as i16, y.round() as i16, on));
+    }
+
+    /// Finishes the glyph. Returns `None` for glyphs without an outline.
+    fn finish(mut self) -> Option<SimpleGlyph> {
+        self.close();
+        if self.points.is_empty() || self.points.len() > u16::MAX as usize {
+            return None;
+        }
+
+        let mut bbox = [i16::MAX, i16::MAX, i16::MIN, i16::MIN];
+        for &(x, y, _) in &self.points {
+            bbox = [bbox[0].min(x), bbox[1].min(y), bbox[2].max(x), bbox[3].max(y)];
+        }
+
+        Some(SimpleGlyph {
+            bbox,
+            end_points: self.end_points,
+            points: self.points,
+        })
+    }
+}
+
+impl OutlineBuilder for GlyfBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.close();
+        self.push(x, y, true);
+        self.current = (x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push(x, y, true);
+        self.current = (x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.push(x1, y1, false);
+        self.push(x, y, true);
+        self.current = (x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        // Split the cubic into pieces that are each close enough to a
+        // quadratic. The error of the midpoint approximation shrinks with the
+        // cube of the number of pieces.
+        let (x0, y0) = self.current;
+        let dx = x - 3.0 * x2 + 3.0 * x1 - x0;
+        let dy = y - 3.0 * y2 + 3.0 * y1 - y0;
+        let error = 3f32.sqrt() / 36.0 * dx.hypot(dy);
+        let n = (error / TOLERANCE).cbrt().ceil().clamp(1.0, 16.0) as usize;
+
+        let at = |t: f32| {
+            let mt = 1.0 - t;
+            let a = mt * mt * mt;
+            let b = 3.0 * mt * mt * t;
+            let c = 3.0 * mt * t * t;
+            let d = t * t * t;
+            (a * x0 + b * x1 + c * x2 + d * x, a * y0 + b * y1 + c * y2 + d * y)
+        };
+
+        let derivative = |t: f32| {
+            let mt = 1.0 - t;
+            let a = 3.0 * mt * mt;
+            let b = 6.0 * mt * t;
+            let c = 3.0 * t * t;
+            (
+                a * (x1 - x0) + b * (x2 - x1) + c * (x - x2),
+                a * (y1 - y0) + b * (y2 - y1) + c * (y - y2),
+            )
+        };
+
+        for i in 0..n {
+            let t0 = i as f32 / n as f32;
+            let t1 = (i + 1) as f32 / n as f32;
+            let (px, py) = at(t0);
+            let (qx, qy) = at(t1);
+            let (dx0, dy0) = derivative(t0);
+            let (dx1, dy1) = derivative(t1);
+            let h = (t1 - t0) / 4.0;
+
+            // The midpoint of the control points of the two quadratics that
+            // match the piece's tangents at either end.
+            let cx = ((px + dx0 * h * 2.0) + (qx - dx1 * h * 2.0)) / 2.0;
+            let cy = ((py + dy0 * h * 2.0) + (qy - dy1 * h * 2.0)) / 2.0;
+            self.push(cx, cy, false);
+            self.push(qx, qy, true);
+        }
+
+        self.current = (x, y);
+    }
+
+    fn close(&mut self) {
+        if self.points.len() == self.start {
+            return;
+        }
+
+        // A closing point that coincides with the start is implied.
+        let first = self.points[self.start];
+        if self.points.len() > self.start + 1 && self.points.last() == Some(&first) {
+            self.points.pop();
+        }
+
+        self.end_points.push((self.points.len() - 1) as u16);
+        self.start = self.points.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a font with an empty `.notdef` glyph and a square.
+    fn square_font() -> Vec<u8> {
+        let mut head = vec![0; 54];
+        head[0..4].copy_from_slice(&0x00010000u32.to_be_bytes());
+        head[12..16].copy_from_slice(&0x5F0F3CF5u32.to_be_bytes());
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        head[50..52].copy_from_slice(&1u16.to_be_bytes());
+
+        let mut hhea = vec![0; 36];
+        hhea[0..4].copy_from_slice(&0x00010000u32.to_be_bytes());
+        hhea[34..36].copy_from_slice(&2u16.to_be_bytes());
+
+        let mut maxp = vec![0; 6];
+        maxp[0..4].copy_from_slice(&0x00005000u32.to_be_bytes());
+        maxp[4..6].copy_from_slice(&2u16.to_be_bytes());
+
+        let hmtx = [500u16, 0, 700, 100].iter().flat_map(|v| v.to_be_bytes()).collect();
+
+        let mut builder = GlyfBuilder::default();
+        builder.move_to(100.0, 0.0);
+        builder.line_to(600.0, 0.0);
+        builder.line_to(600.0, 500.0);
+        builder.line_to(100.0, 500.0);
+        builder.close();
+        let mut glyf = vec![];
+        builder.finish().unwrap().write(&mut glyf);
+        let loca = [0, 0, glyf.len() as u32]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+
+        write_sfnt(vec![
+            (*b"head", head),
+            (*b"hhea", hhea),
+            (*b"maxp", maxp),
+            (*b"hmtx", hmtx),
+            (*b"loca", loca),
+            (*b"glyf", glyf),
+        ])
+    }
+
+    #[test]
+    fn test_instantiate_round_trip() {
+        let data = square_font();
+        let face = Face::parse(&data, 0).unwrap();
+        let bbox = face.outline_glyph(GlyphId(1), &mut GlyfBuilder::default()).unwrap();
+        assert_eq!((bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max), (100, 0, 600, 500));
+
+        let instance = instantiate(&face).unwrap();
+        assert_eq!(checksum(&instance), 0xB1B0AFBA);
+
+        let face = Face::parse(&instance, 0).unwrap();
+        assert_eq!(face.number_of_glyphs(), 2);
+        assert_eq!(face.glyph_hor_advance(GlyphId(0)), Some(500));
+        assert_eq!(face.glyph_hor_advance(GlyphId(1)), Some(700));
+        assert!(face.outline_glyph(GlyphId(0), &mut GlyfBuilder::default()).is_none());
+        let bbox = face.outline_glyph(GlyphId(1), &mut GlyfBuilder::default()).unwrap();
+        assert_eq!((bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max), (100, 0, 600, 500));
+    }
+
+    #[test]
+    fn test_cubic_to_quadratics() {
+        let cubic = |t: f32| {
+            let mt = 1.0 - t;
+            (
+                3.0 * mt * t * t * 1000.0 + t * t * t * 1000.0,
+                3.0 * mt * mt * t * 800.0 + 3.0 * mt * t * t * 800.0,
+            )
+        };
+
+        let mut builder = GlyfBuilder::default();
+        builder.move_to(0.0, 0.0);
+        builder.curve_to(0.0, 800.0, 1000.0, 800.0, 1000.0, 0.0);
+        let glyph = builder.finish().unwrap();
+
+        let points = &glyph.points;
+        let n = (points.len() - 1) / 2;
+        assert!(n > 1);
+        assert_eq!(points.last(), Some(&(1000, 0, true)));
+
+        // The midpoint of each quadratic piece must be close to the cubic.
+        for (i, piece) in points.windows(3).step_by(2).enumerate() {
+            let [(px, py, true), (cx, cy, false), (qx, qy, true)] = piece else {
+                panic!("unexpected point sequence: {piece:?}");
+            };
+            let mx = (*px as f32 + 2.0 * *cx as f32 + *qx as f32) / 4.0;
+            let my = (*py as f32 + 2.0 * *cy as f32 + *qy as f32) / 4.0;
+            let (ex, ey) = cubic((i as f32 + 0.5) / n as f32);
+            assert!((mx - ex).hypot(my - ey) <= TOLERANCE + 1.0);
+        }
+    }
+}
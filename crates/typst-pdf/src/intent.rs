@@ -0,0 +1,59 @@
+//! Output intents.
+//!
+//! krilla only writes the output intent that PDF/A requires, so a custom one
+//! is edited into the finished document.
+
+use lopdf::{Document, Stream, dictionary};
+use typst_library::diag::{At, ExpectInternal, SourceResult};
+use typst_library::layout::PagedDocument;
+use typst_library::pdf::OutputIntent;
+use typst_syntax::Span;
+
+use crate::PdfOptions;
+use crate::edit::{self, Editor};
+
+/// Checks that the document's output intent can be written.
+pub fn check_output_intent(
+    document: &PagedDocument,
+    options: &PdfOptions,
+) -> SourceResult<()> {
+    if document.info.output_intent.is_none() {
+        return Ok(());
+    }
+
+    edit::check_unvalidated(
+        options,
+        Span::detached(),
+        "the document cannot have a custom output intent",
+        "remove the output intent",
+    )
+}
+
+/// Embeds the document's output intent into a finished PDF.
+pub fn apply_output_intent(
+    editor: &mut Editor,
+    document: &PagedDocument,
+) -> SourceResult<()> {
+    let Some(intent) = &document.info.output_intent else { return Ok(()) };
+    write_output_intent(editor.document()?, intent)
+        .expect_internal("failed to write output intent")
+        .at(Span::detached())
+}
+
+/// Adds the profile and references it from the catalog.
+fn write_output_intent(document: &mut Document, intent: &OutputIntent) -> Option<()> {
+    let mut profile = Stream::new(
+        dictionary! { "N" => intent.components() as i64 },
+        intent.data().to_vec(),
+    );
+    profile.compress().ok()?;
+    let profile = document.add_object(profile);
+    let intent = dictionary! {
+        "Type" => "OutputIntent",
+        "S" => "GTS_PDFX",
+        "OutputConditionIdentifier" => edit::text_string("Custom"),
+        "DestOutputProfile" => profile,
+    };
+    edit::catalog(document)?.set("OutputIntents", vec![intent.into()]);
+    Some(())
+}
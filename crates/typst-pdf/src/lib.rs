@@ -2,16 +2,24 @@
 
 mod attach;
 mod convert;
+mod edit;
+mod encrypt;
+mod form;
 mod image;
+mod instance;
+mod intent;
 mod link;
 mod metadata;
 mod outline;
 mod page;
 mod paint;
+mod prepress;
 mod shape;
+mod spot;
 mod tags;
 mod text;
 mod util;
+mod viewer;
 
 pub use self::metadata::{Timestamp, Timezone};
 
@@ -23,6 +31,7 @@ use serde::{Deserialize, Serialize};
 use typst_library::diag::{SourceResult, StrResult, bail};
 use typst_library::foundations::Smart;
 use typst_library::layout::{PageRanges, PagedDocument};
+use typst_library::pdf::{PageLayout, PageMode, ViewerPreferences};
 
 /// Export a document into a PDF file.
 ///
@@ -60,6 +69,22 @@ pub struct PdfOptions<'a> {
     /// circumstances, for example when trying to reduce the size of a document,
     /// it can be desirable to disable tagged PDF.
     pub tagged: bool,
+    /// How PDF viewers should arrange the pages when opening the document.
+    /// Only used if `set document(page-layout: ..)` is `none`.
+    pub page_layout: Option<PageLayout>,
+    /// Which panel PDF viewers should show when opening the document. Only
+    /// used if `set document(page-mode: ..)` is `none`.
+    pub page_mode: Option<PageMode>,
+    /// Settings for the window of PDF viewers. Only used if
+    /// `set document(viewer: ..)` is `none`.
+    pub viewer_preferences: Option<ViewerPreferences>,
+    /// If not `None`, the PDF is encrypted with AES-256, protected by the
+    /// given passwords and permissions. Encryption is not allowed in PDF/A
+    /// documents.
+    pub encryption: Option<PdfEncryption>,
+    /// Which printer's marks to draw around each page. If any are enabled,
+    /// the pages are enlarged to make room for them.
+    pub marks: PdfMarks,
 }
 
 impl PdfOptions<'_> {
@@ -68,6 +93,14 @@ impl PdfOptions<'_> {
     pub(crate) fn is_pdf_ua(&self) -> bool {
         self.standards.config.validator() == Validator::UA1
     }
+
+    /// Whether the export is validated against a PDF/A or PDF/UA standard.
+    ///
+    /// krilla's validation does not see edits of the finished document, so
+    /// features that need them are refused for validated exports.
+    pub(crate) fn is_validated(&self) -> bool {
+        self.standards.config.validator() != Validator::None
+    }
 }
 
 impl Default for PdfOptions<'_> {
@@ -78,6 +111,11 @@ impl Default for PdfOptions<'_> {
             page_ranges: None,
             standards: PdfStandards::default(),
             tagged: true,
+            page_layout: None,
+            page_mode: None,
+            viewer_preferences: None,
+            encryption: None,
+            marks: PdfMarks::default(),
         }
     }
 }
@@ -172,6 +210,83 @@ impl Default for PdfStandards {
     }
 }
 
+/// Passwords and permissions for encrypting a PDF.
+#[derive(Clone, Default)]
+pub struct PdfEncryption {
+    /// The password needed to open the document. If it is empty, anyone can
+    /// open the document, but the permissions still apply.
+    pub user_password: String,
+    /// The password that grants all permissions. If it is empty, a secret
+    /// password is used, so that the permissions cannot be lifted.
+    pub owner_password: String,
+    /// What readers that only know the user password may do.
+    pub permissions: PdfPermissions,
+}
+
+impl Debug for PdfEncryption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Don't leak the passwords into logs.
+        f.debug_struct("PdfEncryption")
+            .field("permissions", &self.permissions)
+            .finish_non_exhaustive()
+    }
+}
+
+/// What readers of an encrypted PDF may do without the owner password.
+///
+/// PDF readers are trusted to enforce these permissions, so they do not
+/// protect the document against determined users.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PdfPermissions {
+    /// Whether the document may be printed.
+    pub print: bool,
+    /// Whether text and graphics may be copied.
+    pub copy: bool,
+    /// Whether the document may be modified, including inserting, rotating,
+    /// and deleting pages.
+    pub modify: bool,
+    /// Whether annotations may be added and changed. This also allows
+    /// filling in form fields.
+    pub annotate: bool,
+    /// Whether form fields may be filled in.
+    pub fill_forms: bool,
+    /// Whether assistive technology like screen readers may extract text and
+    /// graphics. PDF/UA requires this.
+    pub accessibility: bool,
+}
+
+impl Default for PdfPermissions {
+    fn default() -> Self {
+        Self {
+            print: true,
+            copy: true,
+            modify: true,
+            annotate: true,
+            fill_forms: true,
+            accessibility: true,
+        }
+    }
+}
+
+/// Printer's marks that are drawn outside of the trimmed page and its bleed.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PdfMarks {
+    /// Lines at the page's corners that show where to cut it.
+    pub crop: bool,
+    /// Crosshairs at the middle of each side for aligning the printing
+    /// plates.
+    pub registration: bool,
+    /// Patches of the process colors for checking the print's colors.
+    pub color_bars: bool,
+}
+
+impl PdfMarks {
+    /// Whether any marks are enabled.
+    pub(crate) fn any(self) -> bool {
+        self.crop || self.registration || self.color_bars
+    }
+}
+
 /// A PDF standard that Typst can enforce conformance with.
 ///
 /// Support for more standards is planned.
@@ -16,8 +16,8 @@ use typst_library::visualize::{
 use typst_utils::Numeric;
 
 use crate::convert::{FrameContext, GlobalContext, State, handle_frame};
-use crate::tags;
 use crate::util::{AbsExt, FillRuleExt, LineCapExt, LineJoinExt, TransformExt};
+use crate::{spot, tags};
 
 pub(crate) fn convert_fill(
     gc: &mut GlobalContext,
@@ -77,6 +77,9 @@ fn convert_paint(
     }
 
     match paint {
+        Paint::Solid(Color::Spot(spot)) => {
+            Ok((spot::convert_spot(gc, *spot, surface)?, 255))
+        }
         Paint::Solid(c) => {
             let (c, a) = convert_solid(c);
             Ok((c.into(), a))
@@ -101,7 +104,7 @@ fn convert_solid(color: &Color) -> (color::Color, u8) {
     }
 }
 
-fn convert_cmyk(color: &Color) -> cmyk::Color {
+pub(crate) fn convert_cmyk(color: &Color) -> cmyk::Color {
     let components = color.to_space(ColorSpace::Cmyk).to_vec4_u8();
 
     cmyk::Color::new(components[0], components[1], components[2], components[3])
@@ -0,0 +1,257 @@
+//! Page boxes and printer's marks for print production.
+//!
+//! Pages are still sized to their trimmed size in krilla, with the bleed and
+//! the marks drawn beyond their edges. The media box is enlarged to include
+//! them and the trim, bleed, and art boxes are set in the page's settings.
+//! The marks are drawn as page artifacts, so that they are not part of the
+//! tagged content.
+
+use std::borrow::Cow;
+
+use krilla::geom::Rect;
+use krilla::page::PageSettings;
+use typst_library::diag::{At, ExpectInternal, SourceResult};
+use typst_library::layout::{Abs, Frame, FrameItem, Page, Point, Sides, Size};
+use typst_library::visualize::{Cmyk, Color, Curve, FixedStroke, Geometry, Paint};
+use typst_syntax::Span;
+
+use crate::PdfMarks;
+use crate::util::AbsExt;
+
+/// The distance between the bleed and the marks, in points.
+const OFFSET: f64 = 6.0;
+/// The length of crop marks and the space reserved for the other marks, in
+/// points.
+const LENGTH: f64 = 18.0;
+/// The space beyond the bleed that is reserved for marks, in points.
+const SLUG: f64 = 30.0;
+/// The thickness of the lines of marks, in points.
+const HAIRLINE: f64 = 0.25;
+/// The size of a color bar's patches and of a registration mark's circle, in
+/// points.
+const PATCH: f64 = 10.0;
+
+/// Returns the frame to draw for a page and the fill to draw behind it.
+///
+/// If the page has a bleed, the fill becomes part of the frame so that it
+/// can cover the bleed.
+pub fn page_frame(page: &Page) -> (Cow<'_, Frame>, Option<Paint>) {
+    let fill = page.fill_or_transparent();
+    let bleed = page.bleed;
+    if bleed.iter().all(|side| side.is_zero()) {
+        return (Cow::Borrowed(&page.frame), fill);
+    }
+
+    let mut frame = page.frame.clone();
+    if let Some(fill) = fill {
+        let shape = Geometry::Rect(frame.size() + bleed.sum_by_axis()).filled(fill);
+        frame.prepend(
+            Point::new(-bleed.left, -bleed.top),
+            FrameItem::Shape(shape, Span::detached()),
+        );
+    }
+
+    (Cow::Owned(frame), None)
+}
+
+/// Returns a frame with the printer's marks around a page and its bleed, if
+/// any are enabled.
+///
+/// The marks are not part of the page's content and are thus drawn
+/// separately, as an artifact.
+pub fn marks_frame(page: &Page, marks: PdfMarks) -> Option<Frame> {
+    marks.any().then(|| draw_marks(page.frame.size(), page.bleed, marks))
+}
+
+/// Draws the enabled marks around a page of the given size and bleed.
+fn draw_marks(size: Size, bleed: Sides<Abs>, marks: PdfMarks) -> Frame {
+    let mut frame = Frame::soft(size);
+    if marks.crop {
+        crop_marks(&mut frame, size, bleed);
+    }
+    if marks.registration {
+        registration_marks(&mut frame, size, bleed);
+    }
+    if marks.color_bars {
+        color_bars(&mut frame, bleed);
+    }
+    frame
+}
+
+/// Draws lines at the corners along the page's edges, which show where to
+/// cut it.
+fn crop_marks(frame: &mut Frame, size: Size, bleed: Sides<Abs>) {
+    let (offset, length) = (Abs::pt(OFFSET), Abs::pt(LENGTH));
+    let left = -bleed.left - offset;
+    let right = size.x + bleed.right + offset;
+    let top = -bleed.top - offset;
+    let bottom = size.y + bleed.bottom + offset;
+
+    for y in [Abs::zero(), size.y] {
+        line(frame, Point::new(left, y), Point::with_x(-length));
+        line(frame, Point::new(right, y), Point::with_x(length));
+    }
+
+    for x in [Abs::zero(), size.x] {
+        line(frame, Point::new(x, top), Point::with_y(-length));
+        line(frame, Point::new(x, bottom), Point::with_y(length));
+    }
+}
+
+/// Draws crosshairs in circles at the middle of each side, which are used to
+/// align the plates of the different inks.
+fn registration_marks(frame: &mut Frame, size: Size, bleed: Sides<Abs>) {
+    let (length, patch) = (Abs::pt(LENGTH), Abs::pt(PATCH));
+    let distance = Abs::pt(OFFSET) + length / 2.0;
+    let centers = [
+        Point::new(-bleed.left - distance, size.y / 2.0),
+        Point::new(size.x / 2.0, -bleed.top - distance),
+        Point::new(size.x + bleed.right + distance, size.y / 2.0),
+        Point::new(size.x / 2.0, size.y + bleed.bottom + distance),
+    ];
+
+    for center in centers {
+        let circle = Geometry::Curve(Curve::ellipse(Size::splat(patch)))
+            .stroked(FixedStroke::from_pair(registration(), Abs::pt(HAIRLINE)));
+        frame.push(
+            center - Point::splat(patch / 2.0),
+            FrameItem::Shape(circle, Span::detached()),
+        );
+
+        line(frame, center - Point::with_x(length / 2.0), Point::with_x(length));
+        line(frame, center - Point::with_y(length / 2.0), Point::with_y(length));
+    }
+}
+
+/// Draws patches of the process colors, their overprints, and tints of black
+/// above the page, which are used to check the print's colors.
+fn color_bars(frame: &mut Frame, bleed: Sides<Abs>) {
+    let patches = [
+        (1.0, 0.0, 0.0, 0.0),
+        (0.0, 1.0, 0.0, 0.0),
+        (0.0, 0.0, 1.0, 0.0),
+        (0.0, 0.0, 0.0, 1.0),
+        (1.0, 1.0, 0.0, 0.0),
+        (1.0, 0.0, 1.0, 0.0),
+        (0.0, 1.0, 1.0, 0.0),
+        (0.0, 0.0, 0.0, 0.75),
+        (0.0, 0.0, 0.0, 0.5),
+        (0.0, 0.0, 0.0, 0.25),
+    ];
+
+    let patch = Abs::pt(PATCH);
+    let top = -bleed.top - Abs::pt(OFFSET) - (Abs::pt(LENGTH) + patch) / 2.0;
+    for (i, (c, m, y, k)) in patches.into_iter().enumerate() {
+        let color = Color::Cmyk(Cmyk { c, m, y, k });
+        let shape = Geometry::Rect(Size::splat(patch)).filled(color);
+        frame.push(
+            Point::new(patch * i as f64, top),
+            FrameItem::Shape(shape, Span::detached()),
+        );
+    }
+}
+
+/// Draws a hairline from `start` in the given direction.
+fn line(frame: &mut Frame, start: Point, delta: Point) {
+    let stroke = FixedStroke::from_pair(registration(), Abs::pt(HAIRLINE));
+    let shape = Geometry::Line(delta).stroked(stroke);
+    frame.push(start, FrameItem::Shape(shape, Span::detached()));
+}
+
+/// The color in which marks are drawn, which uses all inks so that the marks
+/// appear on every plate.
+fn registration() -> Color {
+    Color::Cmyk(Cmyk { c: 1.0, m: 1.0, y: 1.0, k: 1.0 })
+}
+
+/// Adds the trim, bleed, and art boxes to a page's settings and enlarges its
+/// media box to include the bleed and the marks.
+pub fn page_boxes(
+    settings: PageSettings,
+    page: &Page,
+    marks: PdfMarks,
+) -> SourceResult<PageSettings> {
+    let bleed = page.bleed;
+    if bleed.iter().all(|side| side.is_zero()) && !marks.any() {
+        return Ok(settings);
+    }
+
+    // Use the same size as in `convert_pages`.
+    let trim = Size::new(
+        Abs::pt(page.frame.width().to_pt().max(3.0)),
+        Abs::pt(page.frame.height().to_pt().max(3.0)),
+    );
+    let slug = if marks.any() { Abs::pt(SLUG) } else { Abs::zero() };
+    let media = bleed.map(|side| side + slug);
+    let zero = Sides::splat(Abs::zero());
+    Ok(settings
+        .with_media_box(Some(rect(trim, media)?))
+        .with_bleed_box(Some(rect(trim, bleed)?))
+        .with_trim_box(Some(rect(trim, zero)?))
+        .with_art_box(Some(rect(trim, zero)?)))
+}
+
+/// The rectangle that extends a page of the given size by `outset`.
+fn rect(size: Size, outset: Sides<Abs>) -> SourceResult<Rect> {
+    Rect::from_ltrb(
+        (-outset.left).to_f32(),
+        (-outset.top).to_f32(),
+        (size.x + outset.right).to_f32(),
+        (size.y + outset.bottom).to_f32(),
+    )
+    .expect_internal("invalid page box")
+    .at(Span::detached())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect() {
+        let size = Size::new(Abs::pt(200.0), Abs::pt(100.0));
+        let bleed = Sides::new(Abs::pt(3.0), Abs::pt(1.0), Abs::pt(2.0), Abs::zero());
+        let ltrb = |rect: Rect| (rect.left(), rect.top(), rect.right(), rect.bottom());
+        assert_eq!(
+            ltrb(rect(size, Sides::splat(Abs::zero())).unwrap()),
+            (0.0, 0.0, 200.0, 100.0)
+        );
+        assert_eq!(ltrb(rect(size, bleed).unwrap()), (-3.0, -1.0, 202.0, 100.0));
+    }
+
+    #[test]
+    fn test_marks_placement() {
+        let size = Size::new(Abs::pt(200.0), Abs::pt(100.0));
+        let bleed = Sides::new(Abs::pt(3.0), Abs::pt(1.0), Abs::pt(2.0), Abs::zero());
+        let marks = PdfMarks { crop: true, registration: true, color_bars: true };
+        let frame = draw_marks(size, bleed, marks);
+        assert_eq!(frame.size(), size);
+        assert_eq!(frame.items().count(), 8 + 4 * 3 + 10);
+
+        // Every mark lies beyond the bleed and within the slug.
+        let eps = Abs::pt(HAIRLINE);
+        let slug = Abs::pt(SLUG);
+        for (pos, item) in frame.items() {
+            let FrameItem::Shape(shape, _) = item else { panic!("expected shape") };
+            let bbox = shape.geometry.bbox();
+            let (min, max) = (*pos + bbox.min, *pos + bbox.max);
+            let outside = max.x <= -bleed.left
+                || max.y <= -bleed.top
+                || min.x >= size.x + bleed.right
+                || min.y >= size.y + bleed.bottom;
+            let inside = min.x >= -bleed.left - slug - eps
+                && min.y >= -bleed.top - slug - eps
+                && max.x <= size.x + bleed.right + slug + eps
+                && max.y <= size.y + bleed.bottom + slug + eps;
+            assert!(outside && inside, "mark at {min:?} to {max:?}");
+        }
+    }
+
+    #[test]
+    fn test_marks_crop_only() {
+        let size = Size::new(Abs::pt(200.0), Abs::pt(100.0));
+        let marks = PdfMarks { crop: true, ..PdfMarks::default() };
+        let frame = draw_marks(size, Sides::splat(Abs::zero()), marks);
+        assert_eq!(frame.items().count(), 8);
+    }
+}
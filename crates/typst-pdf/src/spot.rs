@@ -0,0 +1,516 @@
+//! Spot colors.
+//!
+//! krilla cannot write separation color spaces. Instead, each spot color is
+//! drawn with a placeholder: a tiling pattern whose cell is filled with the
+//! fallback color and whose steps identify the spot color. In the finished
+//! document, the content streams are rewritten to select a separation color
+//! space wherever they select a placeholder, and the placeholders are removed.
+
+use ecow::eco_format;
+use krilla::geom::{PathBuilder, Rect};
+use krilla::num::NormalizedF32;
+use krilla::paint::{Fill, FillRule, Pattern};
+use krilla::surface::Surface;
+use lopdf::content::{Content, Operation};
+use lopdf::{Dictionary, Document, Object, ObjectId, dictionary};
+use rustc_hash::{FxHashMap, FxHashSet};
+use typst_library::diag::{At, ExpectInternal, SourceResult};
+use typst_library::layout::Transform;
+use typst_library::visualize::{Cmyk, Color, Spot};
+use typst_syntax::Span;
+
+use crate::PdfOptions;
+use crate::convert::GlobalContext;
+use crate::edit::{self, Editor};
+use crate::util::TransformExt;
+use crate::{paint, tags};
+
+/// The horizontal step of the placeholder patterns. Their vertical step is
+/// one more than the spot color's index.
+const MARKER: f32 = 0.0917;
+
+/// Converts a spot color into a tiling pattern that is later replaced with
+/// the separation.
+pub(crate) fn convert_spot(
+    gc: &mut GlobalContext,
+    spot: Spot,
+    surface: &mut Surface,
+) -> SourceResult<krilla::paint::Paint> {
+    let index = match gc.spots.iter().position(|&s| s == spot) {
+        Some(index) => index,
+        None => {
+            gc.spots.push(spot);
+            gc.spots.len() - 1
+        }
+    };
+
+    let width = MARKER;
+    let height = (index + 1) as f32;
+    let rect = Rect::from_xywh(0.0, 0.0, width, height)
+        .expect_internal("invalid spot color cell")
+        .at(Span::detached())?;
+
+    let mut path_builder = PathBuilder::new();
+    path_builder.push_rect(rect);
+    let path = path_builder
+        .finish()
+        .expect_internal("invalid spot color cell")
+        .at(Span::detached())?;
+
+    let mut stream_builder = surface.stream_builder();
+    let mut surface = stream_builder.surface();
+    tags::tiling(gc, &mut surface, |_, surface| {
+        // If the pattern is not replaced, the fallback color is shown.
+        surface.set_fill(Some(Fill {
+            paint: paint::convert_cmyk(&Color::Spot(spot)).into(),
+            rule: FillRule::NonZero,
+            opacity: NormalizedF32::ONE,
+        }));
+        surface.set_stroke(None);
+        surface.draw_path(&path);
+    });
+    surface.finish();
+
+    Ok(Pattern {
+        stream: stream_builder.finish(),
+        transform: Transform::identity().to_krilla(),
+        width,
+        height,
+    }
+    .into())
+}
+
+/// Checks that the spot colors used in the document can be exported.
+pub fn check_spot_colors(options: &PdfOptions, spots: &[Spot]) -> SourceResult<()> {
+    let Some(spot) = spots.first() else { return Ok(()) };
+    edit::check_unvalidated(
+        options,
+        Span::detached(),
+        &eco_format!("the spot color {:?} cannot be used", spot.name.resolve().as_str()),
+        "use a CMYK color instead",
+    )
+}
+
+/// Replaces the placeholders of spot colors with separations.
+pub fn apply_spot_colors(editor: &mut Editor, spots: &[Spot]) -> SourceResult<()> {
+    if spots.is_empty() {
+        return Ok(());
+    }
+
+    write_separations(editor.document()?, spots)
+        .expect_internal("failed to write spot colors")
+        .at(Span::detached())
+}
+
+/// Defines a separation color space for each ink and selects it instead of
+/// the placeholders.
+fn write_separations(document: &mut Document, spots: &[Spot]) -> Option<()> {
+    let placeholders: FxHashMap<ObjectId, usize> = document
+        .objects
+        .iter()
+        .filter_map(|(&id, object)| {
+            Some((id, placeholder(&object.as_stream().ok()?.dict)?))
+        })
+        .collect();
+    if placeholders.is_empty() {
+        return Some(());
+    }
+
+    // All tints of an ink share a color space.
+    let mut inks = vec![];
+    let mut selections = FxHashMap::default();
+    for (&id, &index) in &placeholders {
+        let spot = spots.get(index)?;
+        let ink = match inks.iter().position(|&(name, _)| name == spot.name) {
+            Some(ink) => ink,
+            None => {
+                let name = spot.name.resolve();
+                let space = document.add_object(separation(name.as_str(), spot.fallback));
+                inks.push((spot.name, space));
+                inks.len() - 1
+            }
+        };
+        selections.insert(id, (ink, number(spot.tint)));
+    }
+
+    // Find the content streams that select placeholders, before any
+    // resources are changed, since resources can be shared.
+    let mut users = vec![];
+    for (&id, object) in &document.objects {
+        if let Some(user) = placeholder_user(document, id, object, &selections) {
+            users.push(user);
+        }
+    }
+
+    let mut rewritten = FxHashSet::default();
+    let mut resources = FxHashMap::default();
+    for user in &users {
+        for &stream in &user.streams {
+            if rewritten.insert(stream) {
+                rewrite_stream(document, stream, &user.selections)?;
+            }
+        }
+        resources.entry(user.resources.clone()).or_insert(&user.selections);
+    }
+
+    for (location, selections) in resources {
+        edit_resources(document, location, |resources| {
+            let mut spaces = match resources.remove(b"ColorSpace") {
+                Some(Object::Dictionary(spaces)) => spaces,
+                _ => Dictionary::new(),
+            };
+            if let Ok(patterns) =
+                resources.get_mut(b"Pattern").and_then(Object::as_dict_mut)
+            {
+                for (name, &(ink, _)) in selections {
+                    patterns.remove(name);
+                    spaces.set(ink_name(ink), inks[ink].1);
+                }
+            }
+            resources.set("ColorSpace", spaces);
+        })?;
+    }
+
+    for id in placeholders.keys() {
+        document.objects.remove(id);
+    }
+
+    Some(())
+}
+
+/// An object whose content streams select placeholders.
+struct PlaceholderUser {
+    /// Where the resources of the content streams are.
+    resources: ResourcesLocation,
+    /// The content streams.
+    streams: Vec<ObjectId>,
+    /// The ink and tint selected instead of each placeholder, by the
+    /// placeholder's resource name.
+    selections: FxHashMap<Vec<u8>, (usize, f32)>,
+}
+
+/// Where the resources of an object are.
+#[derive(Clone, Eq, PartialEq, Hash)]
+enum ResourcesLocation {
+    /// Directly in the object.
+    Inline(ObjectId),
+    /// In an object of their own.
+    Indirect(ObjectId),
+}
+
+/// Checks whether an object has content streams that select placeholders.
+///
+/// Pages, form XObjects, tiling patterns, and Type 3 fonts have content
+/// streams.
+fn placeholder_user(
+    document: &Document,
+    id: ObjectId,
+    object: &Object,
+    placeholders: &FxHashMap<ObjectId, (usize, f32)>,
+) -> Option<PlaceholderUser> {
+    let dict = match object {
+        Object::Dictionary(dict) => dict,
+        Object::Stream(stream) => &stream.dict,
+        _ => return None,
+    };
+
+    let resources = dict.get(b"Resources").ok()?;
+    let location = match resources {
+        Object::Reference(resources) => ResourcesLocation::Indirect(*resources),
+        _ => ResourcesLocation::Inline(id),
+    };
+    let resources = document.dereference(resources).ok()?.1.as_dict().ok()?;
+    let patterns = edit::get(document, resources, "Pattern")?.as_dict().ok()?;
+    let selections: FxHashMap<_, _> = patterns
+        .iter()
+        .filter_map(|(name, pattern)| {
+            Some((name.clone(), *placeholders.get(&pattern.as_reference().ok()?)?))
+        })
+        .collect();
+    if selections.is_empty() {
+        return None;
+    }
+
+    let references = |object: &Object| match object {
+        Object::Reference(id) => vec![*id],
+        Object::Array(items) => {
+            items.iter().filter_map(|item| item.as_reference().ok()).collect()
+        }
+        Object::Dictionary(dict) => {
+            dict.iter().filter_map(|(_, item)| item.as_reference().ok()).collect()
+        }
+        _ => vec![],
+    };
+
+    let streams = match object {
+        Object::Stream(_) => vec![id],
+        _ if dict.has(b"CharProcs") => {
+            references(edit::get(document, dict, "CharProcs")?)
+        }
+        _ => references(dict.get(b"Contents").ok()?),
+    };
+
+    Some(PlaceholderUser { resources: location, streams, selections })
+}
+
+/// Selects separations instead of placeholders in a content stream.
+fn rewrite_stream(
+    document: &mut Document,
+    id: ObjectId,
+    selections: &FxHashMap<Vec<u8>, (usize, f32)>,
+) -> Option<()> {
+    let stream = document.get_object_mut(id).ok()?.as_stream_mut().ok()?;
+    let content = Content::decode(&stream.get_plain_content().ok()?).ok()?;
+    let operations = rewrite_operations(content.operations, selections);
+    stream.set_plain_content(Content { operations }.encode().ok()?);
+    stream.compress().ok()?;
+    Some(())
+}
+
+/// Selects separations instead of placeholders in a list of operations.
+///
+/// Color operators of placeholders look like `/Pattern cs /P0 scn`, so a
+/// separation is selected in their stead. The content stream then no longer
+/// has the pattern color space that it assumes for subsequent patterns, so it
+/// is selected again before those.
+fn rewrite_operations(
+    operations: Vec<Operation>,
+    selections: &FxHashMap<Vec<u8>, (usize, f32)>,
+) -> Vec<Operation> {
+    // Whether a separation is selected where a pattern is assumed, for
+    // filling and stroking.
+    let mut replaced = [false; 2];
+    let mut stack = vec![];
+    let mut rewritten = Vec::with_capacity(operations.len());
+    for operation in operations {
+        match operation.operator.as_str() {
+            "q" => stack.push(replaced),
+            "Q" => replaced = stack.pop().unwrap_or_default(),
+            "cs" | "g" | "rg" | "k" => replaced[0] = false,
+            "CS" | "G" | "RG" | "K" => replaced[1] = false,
+            "scn" | "SCN" => {
+                let (i, space) =
+                    if operation.operator == "scn" { (0, "cs") } else { (1, "CS") };
+                let selection = operation
+                    .operands
+                    .last()
+                    .and_then(|name| name.as_name().ok())
+                    .and_then(|name| selections.get(name));
+                if let Some(&(ink, tint)) = selection {
+                    let name = Object::Name(ink_name(ink).into_bytes());
+                    rewritten.push(Operation::new(space, vec![name]));
+                    rewritten
+                        .push(Operation::new(&operation.operator, vec![tint.into()]));
+                    replaced[i] = true;
+                    continue;
+                } else if replaced[i] {
+                    let name = Object::Name(b"Pattern".to_vec());
+                    rewritten.push(Operation::new(space, vec![name]));
+                    replaced[i] = false;
+                }
+            }
+            _ => {}
+        }
+        rewritten.push(operation);
+    }
+    rewritten
+}
+
+/// Edits the resources of an object.
+fn edit_resources(
+    document: &mut Document,
+    location: ResourcesLocation,
+    f: impl FnOnce(&mut Dictionary),
+) -> Option<()> {
+    let resources = match location {
+        ResourcesLocation::Indirect(id) => edit::dict(document, id)?,
+        ResourcesLocation::Inline(id) => match document.get_object_mut(id).ok()? {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &mut stream.dict,
+            _ => return None,
+        }
+        .get_mut(b"Resources")
+        .ok()?
+        .as_dict_mut()
+        .ok()?,
+    };
+
+    // Resolve the entries that are edited, so that they can be changed
+    // in place.
+    let mut resolved = resources.clone();
+    for key in ["Pattern", "ColorSpace"] {
+        if let Ok(Object::Reference(id)) = resolved.get(key.as_bytes()) {
+            let id = *id;
+            let entry = document.get_dictionary(id).ok()?.clone();
+            resolved.set(key, entry);
+        }
+    }
+
+    f(&mut resolved);
+
+    match location {
+        ResourcesLocation::Indirect(id) => document.set_object(id, resolved),
+        ResourcesLocation::Inline(id) => match document.get_object_mut(id).ok()? {
+            Object::Dictionary(dict) => dict.set("Resources", resolved),
+            Object::Stream(stream) => stream.dict.set("Resources", resolved),
+            _ => return None,
+        },
+    }
+
+    Some(())
+}
+
+/// Returns the index of the spot color that a pattern is a placeholder for.
+fn placeholder(dict: &Dictionary) -> Option<usize> {
+    if dict.get(b"PatternType").ok()?.as_i64().ok()? != 1 {
+        return None;
+    }
+
+    let width = dict.get(b"XStep").ok()?.as_float().ok()?;
+    let height = dict.get(b"YStep").ok()?.as_float().ok()?;
+    if (width - MARKER).abs() > 1e-4 || height < 1.0 || height.fract() != 0.0 {
+        return None;
+    }
+
+    Some(height as usize - 1)
+}
+
+/// The resource name of an ink's color space.
+fn ink_name(ink: usize) -> String {
+    format!("Spot{ink}")
+}
+
+/// Encodes a separation color space that falls back to the given CMYK color.
+fn separation(name: &str, fallback: Cmyk) -> Object {
+    let Cmyk { c, m, y, k } = fallback;
+    let function = dictionary! {
+        "FunctionType" => 2,
+        "Domain" => vec![0.into(), 1.into()],
+        "C0" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+        "C1" => [c, m, y, k].map(|v| Object::Real(number(v))).to_vec(),
+        "N" => 1,
+    };
+    vec!["Separation".into(), name.into(), "DeviceCMYK".into(), function.into()].into()
+}
+
+/// Rounds a color component for writing.
+fn number(value: f32) -> f32 {
+    (value * 10000.0).round() / 10000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use typst_utils::PicoStr;
+
+    use super::*;
+
+    /// A placeholder for the spot color with the given index.
+    fn placeholder_pattern(index: usize) -> lopdf::Stream {
+        let dict = dictionary! {
+            "Type" => "Pattern",
+            "PatternType" => 1,
+            "XStep" => MARKER,
+            "YStep" => (index + 1) as i64,
+        };
+        lopdf::Stream::new(dict, vec![])
+    }
+
+    /// Decodes operations.
+    fn operations(content: &str) -> Vec<Operation> {
+        Content::decode(content.as_bytes()).unwrap().operations
+    }
+
+    /// Encodes operations, so that they can be compared.
+    fn encode(operations: Vec<Operation>) -> String {
+        String::from_utf8(Content { operations }.encode().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_spot_placeholder() {
+        assert_eq!(placeholder(&placeholder_pattern(2).dict), Some(2));
+
+        let tiling = dictionary! { "PatternType" => 1, "XStep" => 10, "YStep" => 3 };
+        assert_eq!(placeholder(&tiling), None);
+
+        let shading = dictionary! { "PatternType" => 2, "XStep" => MARKER, "YStep" => 3 };
+        assert_eq!(placeholder(&shading), None);
+    }
+
+    #[test]
+    fn test_spot_rewrite_operations() {
+        let selections = FxHashMap::from_iter([(b"P0".to_vec(), (1, 0.5))]);
+        let rewritten = rewrite_operations(
+            operations(
+                "q /Pattern cs /P0 scn 0 0 5 5 re f Q \
+                 /Pattern cs /P0 scn /P1 scn f /Pattern CS /P0 SCN S 0 g f",
+            ),
+            &selections,
+        );
+        let expected = operations(
+            "q /Pattern cs /Spot1 cs 0.5 scn 0 0 5 5 re f Q \
+             /Pattern cs /Spot1 cs 0.5 scn /Pattern cs /P1 scn f \
+             /Pattern CS /Spot1 CS 0.5 SCN S 0 g f",
+        );
+        assert_eq!(encode(rewritten), encode(expected));
+    }
+
+    #[test]
+    fn test_spot_write_separations() {
+        let mut document = Document::with_version("1.7");
+        let placeholder = document.add_object(placeholder_pattern(0));
+        let content = b"/Pattern cs /P0 scn 0 0 5 5 re f".to_vec();
+        let contents = document.add_object(lopdf::Stream::new(dictionary! {}, content));
+        let resources = document.add_object(dictionary! {
+            "Pattern" => dictionary! { "P0" => placeholder },
+        });
+        let page = document.add_object(dictionary! {
+            "Type" => "Page",
+            "Resources" => resources,
+            "Contents" => contents,
+        });
+
+        let spot = Spot {
+            name: PicoStr::intern("PANTONE 286 C"),
+            tint: 0.25,
+            fallback: Cmyk { c: 1.0, m: 0.66, y: 0.0, k: 0.02 },
+        };
+        write_separations(&mut document, &[spot]).unwrap();
+        assert!(!document.objects.contains_key(&placeholder));
+        assert!(document.get_dictionary(page).is_ok());
+
+        let resources = document.get_dictionary(resources).unwrap();
+        let patterns = resources.get(b"Pattern").unwrap().as_dict().unwrap();
+        assert!(patterns.is_empty());
+        let spaces = resources.get(b"ColorSpace").unwrap().as_dict().unwrap();
+        let space = spaces.get(b"Spot0").unwrap().as_reference().unwrap();
+        let space = document.get_object(space).unwrap().as_array().unwrap();
+        assert_eq!(space[1].as_name().unwrap(), b"PANTONE 286 C");
+
+        let stream = document.get_object(contents).unwrap().as_stream().unwrap();
+        let content = stream.get_plain_content().unwrap();
+        let expected = operations("/Pattern cs /Spot0 cs 0.25 scn 0 0 5 5 re f");
+        assert_eq!(
+            encode(Content::decode(&content).unwrap().operations),
+            encode(expected)
+        );
+    }
+
+    #[test]
+    fn test_spot_separation() {
+        let fallback = Cmyk { c: 1.0, m: 0.66, y: 0.0, k: 0.02 };
+        let space = separation("PANTONE 286 C", fallback);
+        let space = space.as_array().unwrap();
+        assert_eq!(space[0].as_name().unwrap(), b"Separation");
+        assert_eq!(space[1].as_name().unwrap(), b"PANTONE 286 C");
+        let function = space[3].as_dict().unwrap();
+        let c1: Vec<f32> = function
+            .get(b"C1")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_float().unwrap())
+            .collect();
+        assert_eq!(c1, [1.0, 0.66, 0.0, 0.02]);
+    }
+}
@@ -37,6 +37,8 @@ pub type AnnotationId = Id<krilla::annotation::Annotation>;
 
 pub struct Tags {
     pub in_tiling: bool,
+    /// Whether printer's marks are being drawn.
+    pub in_marks: bool,
     pub tree: Tree,
     /// A list of placeholders for annotations in the tag tree.
     pub annotations: Annotations,
@@ -46,6 +48,7 @@ impl Tags {
     pub fn new(tree: Tree) -> Self {
         Self {
             in_tiling: false,
+            in_marks: false,
             tree,
             annotations: Annotations::new(),
         }
@@ -116,11 +116,32 @@ pub fn tiling<T>(
     res
 }
 
+/// Printer's marks are page artifacts, in which tags are disabled.
+pub fn marks<T>(
+    gc: &mut GlobalContext,
+    surface: &mut Surface,
+    f: impl FnOnce(&mut GlobalContext, &mut Surface) -> T,
+) -> T {
+    if disabled(gc) {
+        return f(gc, surface);
+    }
+
+    gc.tags.in_marks = true;
+    surface.start_tagged(ContentTag::Artifact(ArtifactType::Page));
+
+    let res = f(gc, surface);
+
+    surface.end_tagged();
+    gc.tags.in_marks = false;
+
+    res
+}
+
 /// Whether tag generation is currently disabled. Either because it has been
 /// disabled by the user using the [`PdfOptions::tagged`] flag, or we're inside
-/// a tiling.
+/// a tiling or printer's marks.
 pub fn disabled(gc: &GlobalContext) -> bool {
-    !gc.options.tagged || gc.tags.in_tiling
+    !gc.options.tagged || gc.tags.in_tiling || gc.tags.in_marks
 }
 
 /// Add all annotations that were found in the page frame.
@@ -16,7 +16,7 @@ use std::num::NonZeroU16;
 use std::ops::ControlFlow;
 
 use ecow::EcoVec;
-use krilla::tagging::{ArtifactType, ListNumbering, Tag, TagKind};
+use krilla::tagging::{self as kt, ArtifactType, ListNumbering, Tag, TagKind};
 use rustc_hash::FxHashMap;
 use smallvec::SmallVec;
 use typst_library::diag::{
@@ -32,10 +32,10 @@ use typst_library::layout::{
 use typst_library::math::EquationElem;
 use typst_library::model::{
     EmphElem, EnumElem, FigureCaption, FigureElem, FootnoteElem, FootnoteEntry,
-    HeadingElem, LinkMarker, ListElem, Outlinable, OutlineEntry, ParElem, QuoteElem,
-    StrongElem, TableCell, TableElem, TermsElem, TitleElem,
+    HeadingElem, LinkMarker, ListElem, MarginNoteElem, Outlinable, OutlineEntry, ParElem,
+    QuoteElem, StrongElem, TableCell, TableElem, TermsElem, TitleElem,
 };
-use typst_library::pdf::{ArtifactElem, PdfMarkerTag, PdfMarkerTagKind};
+use typst_library::pdf::{ArtifactElem, FieldElem, PdfMarkerTag, PdfMarkerTagKind};
 use typst_library::text::{
     HighlightElem, OverlineElem, RawElem, RawLine, StrikeElem, SubElem, SuperElem,
     UnderlineElem,
@@ -44,6 +44,7 @@ use typst_library::visualize::ImageElem;
 use typst_syntax::Span;
 
 use crate::PdfOptions;
+use crate::form;
 use crate::tags::GroupId;
 use crate::tags::context::{Ctx, FigureCtx, GridCtx, ListCtx, OutlineCtx, TableCtx};
 use crate::tags::groups::{BreakOpportunity, BreakPriority, GroupKind, Groups};
@@ -252,6 +253,7 @@ fn visit_frame(tree: &mut TreeBuilder, frame: &Frame) -> SourceResult<()> {
             FrameItem::Shape(..) => (),
             FrameItem::Image(..) => (),
             FrameItem::Link(..) => (),
+            FrameItem::Widget(..) => (),
         }
     }
     Ok(())
@@ -265,6 +267,7 @@ fn visit_frame(tree: &mut TreeBuilder, frame: &Frame) -> SourceResult<()> {
 /// Currently the the frame parent is only set for:
 /// - place elements [`PlaceElem`]
 /// - footnote entries [`FootnoteEntry`]
+/// - margin notes [`MarginNoteElem`]
 /// - broken table/grid cells [`TableCell`]/[`GridCell`]
 fn visit_group_frame(tree: &mut TreeBuilder, group: &GroupItem) -> SourceResult<()> {
     let Some(parent) = group.parent else {
@@ -372,6 +375,11 @@ fn progress_tree_start(tree: &mut TreeBuilder, elem: &Content) -> GroupId {
         }
     } else if let Some(link) = elem.to_packed::<LinkMarker>() {
         push_group(tree, elem, GroupKind::Link(link.clone(), None))
+    } else if let Some(_) = elem.to_packed::<FieldElem>() {
+        // The element is turned into a `Form` element once the widgets of the
+        // field are written.
+        let id = kt::TagId::from(form::tag_id(elem.location().unwrap()));
+        push_tag(tree, elem, Tag::Span.with_id(Some(id)))
     } else if let Some(_) = elem.to_packed::<TitleElem>() {
         push_tag(tree, elem, Tag::Title)
     } else if let Some(entry) = elem.to_packed::<OutlineEntry>() {
@@ -472,10 +480,19 @@ fn progress_tree_start(tree: &mut TreeBuilder, elem: &Content) -> GroupId {
             });
         }
         push_tag(tree, elem, Tag::Hn(level, Some(title)))
-    } else if let Some(_) = elem.to_packed::<FootnoteElem>() {
-        push_located(tree, elem, GroupKind::LogicalParent(elem.clone()))
+    } else if let Some(note) = elem.to_packed::<FootnoteElem>() {
+        if note.is_endnote() {
+            // The entry is read where the endnotes are listed, so the marker
+            // doesn't adopt it.
+            no_progress(tree)
+        } else {
+            push_located(tree, elem, GroupKind::LogicalParent(elem.clone()))
+        }
     } else if let Some(_) = elem.to_packed::<FootnoteEntry>() {
         push_tag(tree, elem, Tag::Note)
+    } else if let Some(_) = elem.to_packed::<MarginNoteElem>() {
+        // The note's body is laid out in the margin, but read at its anchor.
+        push_located(tree, elem, GroupKind::LogicalParent(elem.clone()))
     } else if let Some(quote) = elem.to_packed::<QuoteElem>() {
         // TODO: should the attribution be handled somehow?
         if quote.block.val() {
@@ -13,7 +13,7 @@ use typst_utils::defer;
 
 use crate::convert::{FrameContext, GlobalContext};
 use crate::util::{AbsExt, TransformExt, display_font};
-use crate::{paint, tags};
+use crate::{instance, paint, tags};
 
 #[typst_macros::time(name = "handle text")]
 pub(crate) fn handle_text(
@@ -81,10 +81,23 @@ fn convert_font(
 
 #[comemo::memoize]
 fn build_font(typst_font: Font) -> SourceResult<krilla::text::Font> {
-    let font_data: Arc<dyn AsRef<[u8]> + Send + Sync> =
-        Arc::new(typst_font.data().clone());
+    // PDF can't vary fonts, so instances of variable fonts are embedded as
+    // static fonts with the same glyph IDs.
+    let (font_data, index): (Arc<dyn AsRef<[u8]> + Send + Sync>, u32) =
+        if typst_font.is_instance() {
+            match instance::instantiate(typst_font.ttf()) {
+                Some(data) => (Arc::new(data), 0),
+                None => bail!(
+                    Span::detached(),
+                    "failed to instantiate {}",
+                    display_font(Some(&typst_font)),
+                ),
+            }
+        } else {
+            (Arc::new(typst_font.data().clone()), typst_font.index())
+        };
 
-    match krilla::text::Font::new(font_data.into(), typst_font.index()) {
+    match krilla::text::Font::new(font_data.into(), index) {
         Some(f) => Ok(f),
         None => {
             bail!(
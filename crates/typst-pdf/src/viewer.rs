@@ -0,0 +1,217 @@
+//! Viewer settings and page transitions.
+//!
+//! These are not supported by krilla, so they are edited into the finished
+//! document.
+
+use lopdf::{Dictionary, Document, dictionary};
+use typst_library::diag::{At, ExpectInternal, SourceResult};
+use typst_library::foundations::Duration;
+use typst_library::layout::{Axis, Dir, Page, PagedDocument};
+use typst_library::pdf::{
+    PageLayout, PageMode, PageTransition, TransitionStyle, ViewerPreferences,
+};
+use typst_syntax::Span;
+
+use crate::PdfOptions;
+use crate::convert::PageIndexConverter;
+use crate::edit::{self, Editor};
+
+/// Checks that the document's viewer settings can be written.
+pub fn check_viewer_settings(
+    document: &PagedDocument,
+    options: &PdfOptions,
+) -> SourceResult<()> {
+    if Settings::new(document, options).is_none() {
+        return Ok(());
+    }
+
+    edit::check_unvalidated(
+        options,
+        Span::detached(),
+        "viewer settings and page transitions cannot be used",
+        "remove them",
+    )
+}
+
+/// Writes the page layout, page mode, viewer preferences, and page
+/// transitions into a finished PDF.
+pub fn apply_viewer_settings(
+    editor: &mut Editor,
+    document: &PagedDocument,
+    options: &PdfOptions,
+) -> SourceResult<()> {
+    let Some(settings) = Settings::new(document, options) else { return Ok(()) };
+    write_settings(editor.document()?, &settings)
+        .expect_internal("failed to write viewer settings")
+        .at(Span::detached())
+}
+
+/// The viewer settings of a document and its exported pages.
+struct Settings<'a> {
+    layout: Option<PageLayout>,
+    mode: Option<PageMode>,
+    preferences: Option<ViewerPreferences>,
+    pages: Vec<&'a Page>,
+}
+
+impl<'a> Settings<'a> {
+    /// Determines the settings, or `None` if there are none to write.
+    fn new(document: &'a PagedDocument, options: &PdfOptions) -> Option<Self> {
+        let converter = PageIndexConverter::new(document, options);
+        let settings = Self {
+            layout: document.info.page_layout.or(options.page_layout),
+            mode: document.info.page_mode.or(options.page_mode),
+            preferences: document.info.viewer.or(options.viewer_preferences),
+            pages: document
+                .pages
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| converter.pdf_page_index(*i).is_some())
+                .map(|(_, page)| page)
+                .collect(),
+        };
+
+        let timed = settings
+            .pages
+            .iter()
+            .any(|page| page.transition.is_some() || page.duration.is_some());
+        (settings.layout.is_some()
+            || settings.mode.is_some()
+            || settings.preferences.is_some()
+            || timed)
+            .then_some(settings)
+    }
+}
+
+/// Edits the catalog and the page dictionaries.
+fn write_settings(document: &mut Document, settings: &Settings) -> Option<()> {
+    if let Some(layout) = settings.layout {
+        edit::catalog(document)?.set("PageLayout", page_layout_name(layout));
+    }
+
+    if let Some(mode) = settings.mode {
+        edit::catalog(document)?.set("PageMode", page_mode_name(mode));
+    }
+
+    if let Some(preferences) = settings.preferences {
+        write_preferences(document, preferences)?;
+    }
+
+    let ids = edit::pages(document);
+    if ids.len() != settings.pages.len() {
+        return None;
+    }
+
+    for (id, page) in ids.into_iter().zip(&settings.pages) {
+        let dict = edit::dict(document, id)?;
+        if let Some(transition) = page.transition {
+            dict.set("Trans", transition_dict(transition));
+        }
+        if let Some(duration) = page.duration {
+            dict.set("Dur", seconds(duration));
+        }
+    }
+
+    Some(())
+}
+
+/// Merges the enabled viewer preferences into the catalog's existing ones.
+///
+/// Preferences that are disabled are left untouched, as krilla may already
+/// have enabled them, e.g. `DisplayDocTitle` for PDF/UA.
+fn write_preferences(
+    document: &mut Document,
+    preferences: ViewerPreferences,
+) -> Option<()> {
+    let flags = [
+        (preferences.hide_toolbar, "HideToolbar"),
+        (preferences.hide_menubar, "HideMenubar"),
+        (preferences.hide_window_ui, "HideWindowUI"),
+        (preferences.fit_window, "FitWindow"),
+        (preferences.center_window, "CenterWindow"),
+        (preferences.display_title, "DisplayDocTitle"),
+    ];
+
+    edit::edit_catalog_entry(document, "ViewerPreferences", |dict| {
+        for (_, key) in flags.iter().filter(|(on, _)| *on) {
+            dict.set(*key, true);
+        }
+    })
+}
+
+/// Encodes a page transition as a transition dictionary.
+fn transition_dict(transition: PageTransition) -> Dictionary {
+    let style = match transition.style {
+        TransitionStyle::Split => "Split",
+        TransitionStyle::Blinds => "Blinds",
+        TransitionStyle::Box => "Box",
+        TransitionStyle::Wipe => "Wipe",
+        TransitionStyle::Dissolve => "Dissolve",
+        TransitionStyle::Glitter => "Glitter",
+        TransitionStyle::Replace => "R",
+        TransitionStyle::Fly => "Fly",
+        TransitionStyle::Push => "Push",
+        TransitionStyle::Cover => "Cover",
+        TransitionStyle::Uncover => "Uncover",
+        TransitionStyle::Fade => "Fade",
+    };
+
+    let mut dict = dictionary! { "Type" => "Trans", "S" => style };
+    if let Some(duration) = transition.duration {
+        dict.set("D", seconds(duration));
+    }
+
+    if let Some(dir) = transition.direction {
+        match transition.style {
+            TransitionStyle::Split | TransitionStyle::Blinds => {
+                let dimension = if dir.axis() == Axis::X { "H" } else { "V" };
+                dict.set("Dm", dimension);
+            }
+            TransitionStyle::Wipe
+            | TransitionStyle::Glitter
+            | TransitionStyle::Fly
+            | TransitionStyle::Push
+            | TransitionStyle::Cover
+            | TransitionStyle::Uncover => {
+                // Directions are measured counterclockwise from left to
+                // right.
+                let angle = match dir {
+                    Dir::LTR => 0,
+                    Dir::BTT => 90,
+                    Dir::RTL => 180,
+                    Dir::TTB => 270,
+                };
+                dict.set("Di", angle);
+            }
+            _ => {}
+        }
+    }
+
+    dict
+}
+
+fn page_layout_name(layout: PageLayout) -> &'static str {
+    match layout {
+        PageLayout::SinglePage => "SinglePage",
+        PageLayout::OneColumn => "OneColumn",
+        PageLayout::TwoColumnLeft => "TwoColumnLeft",
+        PageLayout::TwoColumnRight => "TwoColumnRight",
+        PageLayout::TwoPageLeft => "TwoPageLeft",
+        PageLayout::TwoPageRight => "TwoPageRight",
+    }
+}
+
+fn page_mode_name(mode: PageMode) -> &'static str {
+    match mode {
+        PageMode::Outline => "UseOutlines",
+        PageMode::Thumbnails => "UseThumbs",
+        PageMode::FullScreen => "FullScreen",
+        PageMode::Attachments => "UseAttachments",
+    }
+}
+
+/// Converts a duration to a number of seconds, rounded to milliseconds.
+fn seconds(duration: Duration) -> f32 {
+    let seconds = duration.seconds().max(0.0);
+    ((seconds * 1000.0).round() / 1000.0) as f32
+}
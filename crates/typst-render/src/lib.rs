@@ -7,8 +7,8 @@ mod text;
 
 use tiny_skia as sk;
 use typst_library::layout::{
-    Abs, Axes, Frame, FrameItem, FrameKind, GroupItem, Page, PagedDocument, Point, Size,
-    Transform,
+    Abs, Axes, Frame, FrameItem, FrameKind, GroupItem, Page, PagedDocument, Point, Rect,
+    Size, Transform,
 };
 use typst_library::visualize::{Color, Geometry, Paint};
 
@@ -18,14 +18,86 @@ use typst_library::visualize::{Color, Geometry, Paint};
 /// the resulting `tiny-skia` pixel buffer.
 #[typst_macros::time(name = "render")]
 pub fn render(page: &Page, pixel_per_pt: f32) -> sk::Pixmap {
-    let size = page.frame.size();
-    let pxw = (pixel_per_pt * size.x.to_f32()).round().max(1.0) as u32;
-    let pxh = (pixel_per_pt * size.y.to_f32()).round().max(1.0) as u32;
+    let size = render_size(page, pixel_per_pt);
+    render_pixels(page, pixel_per_pt, Axes::splat(0), size)
+}
 
-    let ts = sk::Transform::from_scale(pixel_per_pt, pixel_per_pt);
-    let state = State::new(size, ts, pixel_per_pt);
+/// Determine the size in pixels of the image that [`render`] produces for a
+/// page.
+pub fn render_size(page: &Page, pixel_per_pt: f32) -> Axes<u32> {
+    page.frame
+        .size()
+        .map(|extent| (pixel_per_pt * extent.to_f32()).round().max(1.0) as u32)
+}
 
-    let mut canvas = sk::Pixmap::new(pxw, pxh).unwrap();
+/// Export a region of a page into a raster image.
+///
+/// The region is given in points relative to the top-left corner of the page
+/// and is extended outwards to whole pixels, so that the result lines up with
+/// the pixels of a full rendering with [`render`].
+#[typst_macros::time(name = "render region")]
+pub fn render_region(page: &Page, region: Rect, pixel_per_pt: f32) -> sk::Pixmap {
+    let min = region.min.to_size().map(|v| (pixel_per_pt * v.to_f32()).floor());
+    let max = region.max.to_size().map(|v| (pixel_per_pt * v.to_f32()).ceil());
+    let size =
+        Axes::new((max.x - min.x).max(1.0) as u32, (max.y - min.y).max(1.0) as u32);
+    render_pixels(page, pixel_per_pt, min.map(|v| v as i32), size)
+}
+
+/// A rectangular part of a page rendered by [`render_tiled`].
+pub struct Tile {
+    /// The horizontal offset of the tile's left edge in pixels.
+    pub x: u32,
+    /// The vertical offset of the tile's top edge in pixels.
+    pub y: u32,
+    /// The tile's pixels.
+    pub pixmap: sk::Pixmap,
+}
+
+/// Export a page into a raster image tile by tile.
+///
+/// This renders the same image as [`render`], but only ever keeps one tile of
+/// at most `tile_size` pixels in memory, which makes it possible to render
+/// large pages at high resolutions. Tiles at the right and bottom edge are
+/// cut to the page's size (see [`render_size`]).
+///
+/// The tiles are passed to `f` in row-major order. If it returns an error,
+/// rendering stops and the error is returned.
+pub fn render_tiled<E>(
+    page: &Page,
+    pixel_per_pt: f32,
+    tile_size: Axes<u32>,
+    mut f: impl FnMut(Tile) -> Result<(), E>,
+) -> Result<(), E> {
+    let size = render_size(page, pixel_per_pt);
+    let tile_size = tile_size.map(|extent| extent.max(1));
+    for y in (0..size.y).step_by(tile_size.y as usize) {
+        for x in (0..size.x).step_by(tile_size.x as usize) {
+            let extent =
+                Axes::new(tile_size.x.min(size.x - x), tile_size.y.min(size.y - y));
+            let origin = Axes::new(x as i32, y as i32);
+            let pixmap = render_pixels(page, pixel_per_pt, origin, extent);
+            f(Tile { x, y, pixmap })?;
+        }
+    }
+    Ok(())
+}
+
+/// Render the pixels of a page in a rectangle that starts at the given pixel
+/// offset.
+fn render_pixels(
+    page: &Page,
+    pixel_per_pt: f32,
+    origin: Axes<i32>,
+    size: Axes<u32>,
+) -> sk::Pixmap {
+    // Translating by whole pixels keeps glyph rasterization and
+    // anti-aliasing identical to a full rendering.
+    let ts = sk::Transform::from_translate(-origin.x as f32, -origin.y as f32)
+        .pre_scale(pixel_per_pt, pixel_per_pt);
+    let state = State::new(page.frame.size(), ts, pixel_per_pt);
+
+    let mut canvas = sk::Pixmap::new(size.x, size.y).unwrap();
 
     if let Some(fill) = page.fill_or_white() {
         if let Paint::Solid(color) = fill {
@@ -165,6 +237,7 @@ fn render_frame(canvas: &mut sk::Pixmap, state: State, frame: &Frame) {
                 image::render_image(canvas, state.pre_translate(*pos), image, *size);
             }
             FrameItem::Link(_, _) => {}
+            FrameItem::Widget(_, _) => {}
             FrameItem::Tag(_) => {}
         }
     }
@@ -5,7 +5,9 @@ mod paint;
 mod shape;
 mod text;
 
-pub use image::{convert_image_scaling, convert_image_to_base64_url};
+pub use image::{
+    convert_image_scaling, convert_image_to_base64_url, convert_image_to_bytes,
+};
 use rustc_hash::FxHashMap;
 use typst_library::introspection::Introspector;
 use typst_library::model::Destination;
@@ -280,6 +282,7 @@ impl<'a> SVGRenderer<'a> {
                     self.render_image(&state, image, size)
                 }
                 FrameItem::Link(dest, size) => self.render_link(&state, dest, *size),
+                FrameItem::Widget(_, _) => {}
                 FrameItem::Tag(_) => {}
             };
         }
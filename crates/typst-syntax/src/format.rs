@@ -0,0 +1,711 @@
+use std::fmt::{self, Display, Formatter};
+
+use ecow::EcoString;
+
+use crate::{SyntaxKind, SyntaxNode, is_newline, parse};
+
+/// The number of spaces per indentation level.
+const INDENT: usize = 2;
+
+/// How to format Typst source code.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct FormatConfig {
+    /// The maximum number of characters per line. Lines may still exceed it
+    /// where there is no place to break them.
+    pub width: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self { width: 80 }
+    }
+}
+
+/// Why source code could not be formatted.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FormatError {
+    /// The source code contains syntax errors.
+    Syntax,
+    /// Formatting would have changed the meaning of the source code.
+    Unstable,
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax => f.pad("the source code contains syntax errors"),
+            Self::Unstable => {
+                f.pad("formatting would have changed the meaning of the source code")
+            }
+        }
+    }
+}
+
+/// Formats a Typst file.
+///
+/// In code, operators, commas, and colons are spaced uniformly. Argument
+/// lists, arrays, dictionaries, and parameter lists are broken into one item
+/// per line if they don't fit into the configured width or if they already
+/// started with a line break. Code blocks that span multiple lines get one
+/// statement per line.
+///
+/// Markup is kept as written, except that its indentation is normalized to
+/// follow the nesting of content blocks and list items. Comments, raw blocks,
+/// strings, and equations are kept verbatim.
+pub fn format(text: &str, config: FormatConfig) -> Result<String, FormatError> {
+    let root = parse(text);
+    if root.erroneous() {
+        return Err(FormatError::Syntax);
+    }
+
+    let mut docs = vec![];
+    markup(&mut docs, &root);
+    let output = print(&docs, config.width);
+
+    // Make sure that only insignificant whitespace and separators changed.
+    let reparsed = parse(&output);
+    let mut expected = vec![];
+    let mut found = vec![];
+    signature(&mut expected, &root, SyntaxKind::End);
+    signature(&mut found, &reparsed, SyntaxKind::End);
+    if reparsed.erroneous() || expected != found {
+        return Err(FormatError::Unstable);
+    }
+
+    Ok(output)
+}
+
+/// The intermediate representation of formatted code, from which the final
+/// layout is determined.
+#[derive(Debug, Clone)]
+enum Doc {
+    /// Text without line breaks.
+    Text(EcoString),
+    /// Text that is emitted as is, possibly spanning multiple lines.
+    Verbatim(EcoString),
+    /// A space if the enclosing group is flat and a line break otherwise.
+    Line,
+    /// Nothing if the enclosing group is flat and a line break otherwise.
+    SoftLine,
+    /// A line break.
+    HardLine,
+    /// A line break followed by an empty line.
+    BlankLine,
+    /// Forces the enclosing group to break without emitting anything.
+    Break,
+    /// Text that is only emitted if the enclosing group is broken.
+    IfBroken(&'static str),
+    /// Indents the line breaks within by one level.
+    Indent(Vec<Doc>),
+    /// Indents the line breaks within relative to the column at which the
+    /// content starts.
+    Align(usize, Vec<Doc>),
+    /// Content that is laid out on a single line if it fits and broken
+    /// otherwise.
+    Group(Vec<Doc>),
+}
+
+impl Doc {
+    /// Creates text from a string without line breaks.
+    fn text(text: impl Into<EcoString>) -> Self {
+        Self::Text(text.into())
+    }
+}
+
+/// Converts the children of a markup node.
+fn markup(out: &mut Vec<Doc>, node: &SyntaxNode) {
+    for child in node.children() {
+        match child.kind() {
+            SyntaxKind::Space if child.text().chars().any(is_newline) => {
+                out.push(Doc::HardLine)
+            }
+            SyntaxKind::Space => out.push(Doc::text(" ")),
+            SyntaxKind::Parbreak => out.push(Doc::BlankLine),
+            SyntaxKind::Markup
+            | SyntaxKind::Strong
+            | SyntaxKind::Emph
+            | SyntaxKind::Heading
+            | SyntaxKind::Ref => markup(out, child),
+            SyntaxKind::ListItem | SyntaxKind::EnumItem | SyntaxKind::TermItem => {
+                let mut body = vec![];
+                markup(&mut body, child);
+                let rest = split_trailing_lines(&mut body);
+                out.push(Doc::Align(INDENT, body));
+                out.extend(rest);
+            }
+            SyntaxKind::Raw | SyntaxKind::Equation => out.push(verbatim(child)),
+            _ if child.children().len() == 0 => out.push(leaf(child)),
+            _ => code(out, child),
+        }
+    }
+}
+
+/// Converts a code expression.
+fn code(out: &mut Vec<Doc>, node: &SyntaxNode) {
+    match node.kind() {
+        SyntaxKind::Raw | SyntaxKind::Equation => out.push(verbatim(node)),
+        SyntaxKind::ContentBlock => content_block(out, node),
+        SyntaxKind::CodeBlock => code_block(out, node),
+        SyntaxKind::Args
+        | SyntaxKind::Array
+        | SyntaxKind::Dict
+        | SyntaxKind::Params
+        | SyntaxKind::Destructuring
+            if node
+                .children()
+                .next()
+                .is_some_and(|child| child.kind() == SyntaxKind::LeftParen) =>
+        {
+            list(out, node)
+        }
+        _ if node.children().len() == 0 => out.push(leaf(node)),
+        _ => join(out, node),
+    }
+}
+
+/// Converts a content block.
+///
+/// If the markup starts on a new line, it is indented by one level. Otherwise,
+/// its lines are aligned with its start, so that list items keep their nesting.
+fn content_block(out: &mut Vec<Doc>, node: &SyntaxNode) {
+    for child in node.children() {
+        if child.kind() == SyntaxKind::Markup {
+            let mut body = vec![];
+            markup(&mut body, child);
+            let rest = split_trailing_lines(&mut body);
+            if matches!(body.first(), Some(Doc::HardLine | Doc::BlankLine)) {
+                out.push(Doc::Indent(body));
+            } else {
+                out.push(Doc::Align(0, body));
+            }
+            out.extend(rest);
+        } else {
+            out.push(leaf(child));
+        }
+    }
+}
+
+/// Converts a code block.
+///
+/// A block that spans multiple lines gets one statement per line, while a
+/// block on a single line stays on a single line.
+fn code_block(out: &mut Vec<Doc>, node: &SyntaxNode) {
+    let children = node.children().flat_map(|child| match child.kind() {
+        SyntaxKind::Code => child.children().as_slice(),
+        _ => std::slice::from_ref(child),
+    });
+
+    // Statements and comments with the number of line breaks before them.
+    let mut entries = vec![];
+    let mut newlines = 0;
+    let mut multiline = false;
+    for child in children {
+        match child.kind() {
+            SyntaxKind::LeftBrace | SyntaxKind::RightBrace | SyntaxKind::Semicolon => {}
+            SyntaxKind::Space => {
+                let count = child.text().chars().filter(|&c| is_newline(c)).count();
+                newlines += count;
+                multiline |= count > 0;
+            }
+            kind => {
+                multiline |= kind == SyntaxKind::LineComment;
+                entries.push((child, newlines));
+                newlines = 0;
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        out.push(Doc::text("{}"));
+        return;
+    }
+
+    if !multiline {
+        out.push(Doc::text("{ "));
+        let mut prev = None;
+        for &(child, _) in &entries {
+            if let Some(prev) = prev {
+                let separated = is_comment(prev) || is_comment(child.kind());
+                out.push(Doc::text(if separated { " " } else { "; " }));
+            }
+            code(out, child);
+            prev = Some(child.kind());
+        }
+        out.push(Doc::text(" }"));
+        return;
+    }
+
+    let mut body = vec![];
+    for (i, &(child, newlines)) in entries.iter().enumerate() {
+        if i > 0 && newlines == 0 && is_comment(child.kind()) {
+            // A comment behind a statement stays on its line.
+            body.push(Doc::text(" "));
+        } else if i > 0 && newlines > 1 {
+            body.push(Doc::BlankLine);
+        } else {
+            body.push(Doc::HardLine);
+        }
+        code(&mut body, child);
+    }
+
+    out.push(Doc::text("{"));
+    out.push(Doc::Indent(body));
+    out.push(Doc::HardLine);
+    out.push(Doc::text("}"));
+}
+
+/// Converts a parenthesized, comma-separated list of items.
+///
+/// The list is laid out on a single line if it fits and if there is no line
+/// break after the opening parenthesis. Otherwise, each item is put on its
+/// own line with a trailing comma.
+fn list(out: &mut Vec<Doc>, node: &SyntaxNode) {
+    /// An item of the list with its surrounding comments.
+    struct Item<'a> {
+        leading: Vec<&'a SyntaxNode>,
+        node: &'a SyntaxNode,
+        trailing: Option<&'a SyntaxNode>,
+    }
+
+    let mut items: Vec<Item> = vec![];
+    let mut pending = vec![];
+    let mut blocks = vec![];
+    let mut colon = false;
+    let mut closed = false;
+    let mut newline = false;
+    let mut broken = false;
+
+    for child in node.children().skip(1) {
+        match child.kind() {
+            _ if closed => code(&mut blocks, child),
+            SyntaxKind::RightParen => closed = true,
+            SyntaxKind::Comma => {}
+            SyntaxKind::Colon => colon = true,
+            SyntaxKind::Space => {
+                if child.text().chars().any(is_newline) {
+                    newline = true;
+                    broken |= items.is_empty() && pending.is_empty();
+                }
+            }
+            SyntaxKind::LineComment | SyntaxKind::BlockComment => {
+                match items.last_mut() {
+                    Some(item) if !newline && item.trailing.is_none() => {
+                        item.trailing = Some(child)
+                    }
+                    _ => pending.push(child),
+                }
+                newline = false;
+            }
+            _ => {
+                let leading = std::mem::take(&mut pending);
+                items.push(Item { leading, node: child, trailing: None });
+                newline = false;
+            }
+        }
+    }
+
+    if items.is_empty() && pending.is_empty() {
+        out.push(Doc::text(if colon { "(:)" } else { "()" }));
+        out.extend(blocks);
+        return;
+    }
+
+    // A single array item needs a comma to not be parsed as a parenthesized
+    // expression.
+    let needs_comma = items.len() == 1
+        && matches!(node.kind(), SyntaxKind::Array | SyntaxKind::Destructuring);
+
+    let mut inner = vec![Doc::SoftLine];
+    if broken {
+        inner.push(Doc::Break);
+    }
+
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            inner.push(Doc::Line);
+        }
+
+        for &comment in &item.leading {
+            inner.push(leaf(comment));
+            inner.push(match comment.kind() {
+                SyntaxKind::LineComment => Doc::HardLine,
+                _ => Doc::Line,
+            });
+        }
+
+        code(&mut inner, item.node);
+        if i + 1 < items.len() || needs_comma {
+            inner.push(Doc::text(","));
+        } else {
+            inner.push(Doc::IfBroken(","));
+        }
+
+        if let Some(comment) = item.trailing {
+            inner.push(Doc::text(" "));
+            inner.push(leaf(comment));
+            if comment.kind() == SyntaxKind::LineComment {
+                inner.push(Doc::Break);
+            }
+        }
+    }
+
+    for (i, &comment) in pending.iter().enumerate() {
+        if i > 0 || !items.is_empty() {
+            inner.push(Doc::Line);
+        }
+        inner.push(leaf(comment));
+        if comment.kind() == SyntaxKind::LineComment {
+            inner.push(Doc::Break);
+        }
+    }
+
+    out.push(Doc::Group(vec![
+        Doc::text(if colon { "(:" } else { "(" }),
+        Doc::Indent(inner),
+        Doc::SoftLine,
+        Doc::text(")"),
+    ]));
+    out.extend(blocks);
+}
+
+/// Converts any other code node by joining its children with single spaces
+/// or no spaces at all.
+fn join(out: &mut Vec<Doc>, node: &SyntaxNode) {
+    let parent = node.kind();
+    let mut prev = None;
+    for child in node.children() {
+        let kind = child.kind();
+        match kind {
+            SyntaxKind::Space => {}
+            SyntaxKind::LineComment => {
+                if prev.is_some() {
+                    out.push(Doc::text(" "));
+                }
+                out.push(leaf(child));
+                out.push(Doc::HardLine);
+                prev = None;
+            }
+            _ => {
+                if let Some(prev) = prev
+                    && (is_comment(prev) || !is_tight(parent, prev, kind))
+                {
+                    out.push(Doc::text(" "));
+                }
+                code(out, child);
+                prev = Some(kind);
+            }
+        }
+    }
+}
+
+/// Whether two adjacent children of a code node are joined without a space.
+fn is_tight(parent: SyntaxKind, prev: SyntaxKind, next: SyntaxKind) -> bool {
+    match parent {
+        SyntaxKind::FieldAccess
+        | SyntaxKind::FuncCall
+        | SyntaxKind::Args
+        | SyntaxKind::Spread
+        | SyntaxKind::ImportItemPath
+        | SyntaxKind::Parenthesized => true,
+        SyntaxKind::Unary => prev != SyntaxKind::Not,
+        _ => {
+            matches!(
+                next,
+                SyntaxKind::Comma
+                    | SyntaxKind::Colon
+                    | SyntaxKind::Semicolon
+                    | SyntaxKind::RightParen
+                    | SyntaxKind::Args
+            ) || prev == SyntaxKind::LeftParen
+                || (prev == SyntaxKind::Ident && next == SyntaxKind::Params)
+        }
+    }
+}
+
+/// Whether a node of this kind is a comment.
+fn is_comment(kind: SyntaxKind) -> bool {
+    matches!(kind, SyntaxKind::LineComment | SyntaxKind::BlockComment)
+}
+
+/// Converts a leaf node.
+fn leaf(node: &SyntaxNode) -> Doc {
+    let text = node.text();
+    if text.chars().any(is_newline) {
+        Doc::Verbatim(text.clone())
+    } else {
+        Doc::Text(text.clone())
+    }
+}
+
+/// Converts a node into its text, as is.
+fn verbatim(node: &SyntaxNode) -> Doc {
+    Doc::Verbatim(node.clone().into_text())
+}
+
+/// Removes the line breaks at the end of converted content.
+///
+/// They are moved out of indented content so that the content following them
+/// isn't indented.
+fn split_trailing_lines(docs: &mut Vec<Doc>) -> Vec<Doc> {
+    let end = docs
+        .iter()
+        .rposition(|doc| !matches!(doc, Doc::HardLine | Doc::BlankLine))
+        .map_or(0, |i| i + 1);
+    docs.split_off(end)
+}
+
+/// Lays out documents into lines of the given width.
+fn print(docs: &[Doc], width: usize) -> String {
+    let mut printer = Printer::default();
+    let mut stack: Vec<(usize, bool, &Doc)> =
+        docs.iter().rev().map(|doc| (0, false, doc)).collect();
+
+    while let Some((indent, flat, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(text) | Doc::Verbatim(text) => printer.text(text),
+            Doc::Line if flat => printer.text(" "),
+            Doc::SoftLine if flat => {}
+            Doc::Line | Doc::SoftLine | Doc::HardLine => printer.newline(indent),
+            Doc::BlankLine => printer.blank_line(indent),
+            Doc::Break => {}
+            Doc::IfBroken(text) => {
+                if !flat {
+                    printer.text(text);
+                }
+            }
+            Doc::Indent(docs) => {
+                let indent = indent + INDENT;
+                stack.extend(docs.iter().rev().map(|doc| (indent, flat, doc)));
+            }
+            Doc::Align(offset, docs) => {
+                let indent = printer.column() + offset;
+                stack.extend(docs.iter().rev().map(|doc| (indent, flat, doc)));
+            }
+            Doc::Group(docs) => {
+                let flat = flat || fits(docs, width.saturating_sub(printer.column()));
+                stack.extend(docs.iter().rev().map(|doc| (indent, flat, doc)));
+            }
+        }
+    }
+
+    printer.out
+}
+
+/// Whether documents fit into the remaining width of a line when laid out
+/// flat.
+fn fits(docs: &[Doc], mut remaining: usize) -> bool {
+    let mut stack: Vec<&Doc> = docs.iter().rev().collect();
+    while let Some(doc) = stack.pop() {
+        let text = match doc {
+            Doc::Text(text) => text.as_str(),
+            Doc::Verbatim(text) => match text.split_once(is_newline) {
+                Some((first, _)) => return first.chars().count() <= remaining,
+                None => text.as_str(),
+            },
+            Doc::Line => " ",
+            Doc::SoftLine | Doc::IfBroken(_) => continue,
+            Doc::HardLine | Doc::BlankLine | Doc::Break => return false,
+            Doc::Indent(docs) | Doc::Align(_, docs) | Doc::Group(docs) => {
+                stack.extend(docs.iter().rev());
+                continue;
+            }
+        };
+
+        let count = text.chars().count();
+        if count > remaining {
+            return false;
+        }
+        remaining -= count;
+    }
+    true
+}
+
+/// Writes the final text.
+#[derive(Default)]
+struct Printer {
+    /// The text written so far.
+    out: String,
+    /// The column in the current line.
+    col: usize,
+    /// The indentation to write before the next text, if the current line is
+    /// still empty.
+    pending: Option<usize>,
+}
+
+impl Printer {
+    /// Writes text.
+    fn text(&mut self, text: &str) {
+        if let Some(indent) = self.pending.take() {
+            self.out.extend(std::iter::repeat_n(' ', indent));
+            self.col = indent;
+        }
+        self.out.push_str(text);
+        match text.rfind(is_newline) {
+            Some(i) => self.col = text[i..].chars().count() - 1,
+            None => self.col += text.chars().count(),
+        }
+    }
+
+    /// Starts a new line with the given indentation.
+    fn newline(&mut self, indent: usize) {
+        let len = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(len);
+        self.out.push('\n');
+        self.col = 0;
+        self.pending = Some(indent);
+    }
+
+    /// Starts a new line after an empty line.
+    fn blank_line(&mut self, indent: usize) {
+        self.newline(indent);
+        if !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    /// The column at which the next text starts.
+    fn column(&self) -> usize {
+        self.pending.unwrap_or(self.col)
+    }
+}
+
+/// An event in the traversal of a syntax tree.
+#[derive(Debug, PartialEq)]
+enum Event {
+    Enter(SyntaxKind),
+    Leaf(SyntaxKind, EcoString),
+    Exit,
+}
+
+/// Describes the parts of a syntax tree that formatting must not change.
+///
+/// This is the structure of the tree and the text of its leaves, except for
+/// whitespace in code, the amount of whitespace in markup, and optional
+/// separators. Since the lexer splits markup text at runs of multiple spaces,
+/// adjacent text and spaces in markup are merged.
+fn signature(out: &mut Vec<Event>, node: &SyntaxNode, parent: SyntaxKind) {
+    let kind = node.kind();
+    let in_markup = matches!(
+        parent,
+        SyntaxKind::Markup
+            | SyntaxKind::ListItem
+            | SyntaxKind::EnumItem
+            | SyntaxKind::TermItem
+            | SyntaxKind::Heading
+    );
+
+    match kind {
+        SyntaxKind::Space | SyntaxKind::Text if in_markup => {
+            let text = if kind == SyntaxKind::Space { " " } else { node.text() };
+            match out.last_mut() {
+                Some(Event::Leaf(SyntaxKind::Text, prev)) => {
+                    if !(prev.ends_with(' ') && text == " ") {
+                        prev.push_str(text);
+                    }
+                }
+                _ => out.push(Event::Leaf(SyntaxKind::Text, text.into())),
+            }
+        }
+        SyntaxKind::Space => {}
+        SyntaxKind::Parbreak => out.push(Event::Leaf(kind, EcoString::new())),
+        SyntaxKind::Comma
+            if matches!(
+                parent,
+                SyntaxKind::Args
+                    | SyntaxKind::Array
+                    | SyntaxKind::Dict
+                    | SyntaxKind::Params
+                    | SyntaxKind::Destructuring
+            ) => {}
+        SyntaxKind::Semicolon if parent == SyntaxKind::Code => {}
+        _ if node.children().len() == 0 => {
+            out.push(Event::Leaf(kind, node.text().clone()))
+        }
+        _ => {
+            out.push(Event::Enter(kind));
+            for child in node.children() {
+                signature(out, child, kind);
+            }
+            out.push(Event::Exit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn test(text: &str, expected: &str) {
+        test_width(text, 80, expected);
+    }
+
+    #[track_caller]
+    fn test_width(text: &str, width: usize, expected: &str) {
+        let config = FormatConfig { width };
+        let formatted = format(text, config).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format(&formatted, config).unwrap(), formatted, "not idempotent");
+    }
+
+    #[test]
+    fn test_format_code_spacing() {
+        test("#let x=1+  2", "#let x = 1 + 2");
+        test("#let f(a,b:2)=a*b", "#let f(a, b: 2) = a * b");
+        test("#f(x,y:-1 , ..z)", "#f(x, y: -1, ..z)");
+        test("#show heading:it=>it.body", "#show heading: it => it.body");
+        test("#set text(red)if x", "#set text(red) if x");
+        test("#(not  x)", "#(not x)");
+        test("#let (a,)=(1,)", "#let (a,) = (1,)");
+        test("#let d=( : )", "#let d = (:)");
+        test("#(: ..a,..b)", "#(:..a, ..b)");
+        test("#import \"a.typ\":x,y as z", "#import \"a.typ\": x, y as z");
+    }
+
+    #[test]
+    fn test_format_args_wrapping() {
+        test_width(
+            "#figure(image(\"a.png\"), caption: [A caption])",
+            20,
+            "#figure(\n  image(\"a.png\"),\n  caption: [A caption],\n)",
+        );
+        test("#f(\n  a, b)[body]", "#f(\n  a,\n  b,\n)[body]");
+        test("#f(a, b,)", "#f(a, b)");
+    }
+
+    #[test]
+    fn test_format_code_block() {
+        test("#{let x=1;x}", "#{ let x = 1; x }");
+        test("#{\nlet x=1\n\n\n    x }", "#{\n  let x = 1\n\n  x\n}");
+        test("#if x {a} else {b}", "#if x { a } else { b }");
+    }
+
+    #[test]
+    fn test_format_markup_indent() {
+        test("- a\n    - b\n     c\n- d\n", "- a\n  - b\n    c\n- d\n");
+        test("#box[\n      text   \n     more\n]", "#box[\n  text\n  more\n]");
+        test("+ a\n\n\n\n  b\n", "+ a\n\n  b\n");
+        test("/ Term: a\n   b", "/ Term: a\n  b");
+    }
+
+    #[test]
+    fn test_format_preserves_verbatim() {
+        test("```rust\n  fn  main() {}\n```", "```rust\n  fn  main() {}\n```");
+        test("$a  +b$ `x  y`", "$a  +b$ `x  y`");
+        test("#let x = \"a  b\"", "#let x = \"a  b\"");
+    }
+
+    #[test]
+    fn test_format_comments() {
+        test("#f(a, // first\n  b)", "#f(\n  a, // first\n  b,\n)");
+        test("#{\n  // note\n  x /* y */\n}", "#{\n  // note\n  x /* y */\n}");
+        test("#let x = (\n  // lone\n)", "#let x = (\n  // lone\n)");
+        test("a // comment\nb", "a // comment\nb");
+        test("Some   text  here.", "Some text here.");
+    }
+
+    #[test]
+    fn test_format_errors() {
+        assert_eq!(format("#f(", FormatConfig::default()), Err(FormatError::Syntax));
+    }
+}
@@ -4,6 +4,7 @@ pub mod ast;
 pub mod package;
 
 mod file;
+mod format;
 mod highlight;
 mod kind;
 mod lexer;
@@ -17,6 +18,7 @@ mod source;
 mod span;
 
 pub use self::file::FileId;
+pub use self::format::{FormatConfig, FormatError, format};
 pub use self::highlight::{Tag, highlight, highlight_html};
 pub use self::kind::SyntaxKind;
 pub use self::lexer::{
@@ -15,6 +15,13 @@ use crate::is_ident;
 /// where values are completely discarded.
 pub type UnknownFields = BTreeMap<EcoString, IgnoredAny>;
 
+/// The `[dependencies]` key in a manifest.
+///
+/// Maps packages to the versions that may be used when they are imported
+/// without an explicit version. A version matches if it is equal to the bound
+/// in all components the bound specifies, i.e. `"0.3"` allows any `0.3.x`.
+pub type Dependencies = BTreeMap<VersionlessPackageSpec, VersionBound>;
+
 /// A parsed package manifest.
 ///
 /// The `unknown_fields` contains fields which were found but not expected.
@@ -28,6 +35,9 @@ pub struct PackageManifest {
     /// The tools section for third-party configuration.
     #[serde(default)]
     pub tool: ToolInfo,
+    /// Version requirements for packages imported without a version.
+    #[serde(default, skip_serializing_if = "Dependencies::is_empty")]
+    pub dependencies: Dependencies,
     /// All parsed but unknown fields, this can be used for validation.
     #[serde(flatten, skip_serializing)]
     pub unknown_fields: UnknownFields,
@@ -149,6 +159,7 @@ impl PackageManifest {
             package,
             template: None,
             tool: ToolInfo::default(),
+            dependencies: Dependencies::new(),
             unknown_fields: UnknownFields::new(),
         }
     }
@@ -266,7 +277,7 @@ impl Display for PackageSpec {
 }
 
 /// Identifies a package, but not a specific version of it.
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct VersionlessPackageSpec {
     /// The namespace the package lives in.
     pub namespace: EcoString,
@@ -311,6 +322,78 @@ impl Display for VersionlessPackageSpec {
     }
 }
 
+impl Serialize for VersionlessPackageSpec {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionlessPackageSpec {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let string = EcoString::deserialize(d)?;
+        string.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A parsed lockfile (`typst.lock`).
+///
+/// Pins the exact versions that packages imported without a version resolve
+/// to. There is at most one locked version per package.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageLock {
+    /// The locked packages, sorted by namespace and name.
+    #[serde(default, rename = "package", skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A `[[package]]` entry in the lockfile.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    /// The namespace the package lives in.
+    pub namespace: EcoString,
+    /// The name of the package within its namespace.
+    pub name: EcoString,
+    /// The locked version.
+    pub version: PackageVersion,
+}
+
+impl PackageLock {
+    /// The locked version of a package, if any.
+    pub fn get(&self, spec: &VersionlessPackageSpec) -> Option<PackageVersion> {
+        self.packages
+            .iter()
+            .find(|locked| locked.namespace == spec.namespace && locked.name == spec.name)
+            .map(|locked| locked.version)
+    }
+
+    /// Locks a package to a version, replacing any previously locked version
+    /// of it.
+    pub fn insert(&mut self, spec: PackageSpec) {
+        let key =
+            |locked: &LockedPackage| (locked.namespace.clone(), locked.name.clone());
+        let locked = LockedPackage {
+            namespace: spec.namespace,
+            name: spec.name,
+            version: spec.version,
+        };
+        match self.packages.binary_search_by_key(&key(&locked), key) {
+            Ok(i) => self.packages[i] = locked,
+            Err(i) => self.packages.insert(i, locked),
+        }
+    }
+}
+
+impl LockedPackage {
+    /// The full specification of the locked package.
+    pub fn spec(&self) -> PackageSpec {
+        PackageSpec {
+            namespace: self.namespace.clone(),
+            name: self.name.clone(),
+            version: self.version,
+        }
+    }
+}
+
 fn parse_namespace<'s>(s: &mut Scanner<'s>) -> Result<&'s str, EcoString> {
     if !s.eat_if('@') {
         Err("package specification must start with '@'")?;
@@ -591,6 +674,7 @@ mod tests {
                 ),
                 template: None,
                 tool: ToolInfo { sections: BTreeMap::new() },
+                dependencies: BTreeMap::new(),
                 unknown_fields: BTreeMap::new(),
             })
         );
@@ -655,4 +739,61 @@ mod tests {
 
         assert!(manifest.unknown_fields.contains_key("unknown"));
     }
+
+    #[test]
+    fn dependencies() {
+        let manifest: PackageManifest = toml::from_str(
+            r#"
+            [package]
+            name = "package"
+            version = "0.1.0"
+            entrypoint = "src/lib.typ"
+
+            [dependencies]
+            "@preview/cetz" = "0.3"
+            "@local/common" = "1"
+        "#,
+        )
+        .unwrap();
+
+        let cetz = VersionlessPackageSpec::from_str("@preview/cetz").unwrap();
+        let bound = manifest.dependencies[&cetz];
+        assert_eq!(bound, VersionBound { major: 0, minor: Some(3), patch: None });
+        assert_eq!(manifest.dependencies.len(), 2);
+        assert!(manifest.unknown_fields.is_empty());
+
+        assert!(
+            toml::from_str::<PackageManifest>(
+                r#"
+                [package]
+                name = "package"
+                version = "0.1.0"
+                entrypoint = "src/lib.typ"
+
+                [dependencies]
+                "@preview/cetz:0.3.0" = "0.3"
+            "#
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn lockfile() {
+        let mut lock = PackageLock::default();
+        lock.insert(PackageSpec::from_str("@preview/cetz:0.3.1").unwrap());
+        lock.insert(PackageSpec::from_str("@local/common:1.0.0").unwrap());
+        lock.insert(PackageSpec::from_str("@preview/cetz:0.3.2").unwrap());
+
+        let cetz = VersionlessPackageSpec::from_str("@preview/cetz").unwrap();
+        assert_eq!(
+            lock.get(&cetz),
+            Some(PackageVersion { major: 0, minor: 3, patch: 2 })
+        );
+        assert_eq!(lock.packages.len(), 2);
+        assert_eq!(lock.packages[0].namespace, "local");
+
+        let string = toml::to_string(&lock).unwrap();
+        assert_eq!(toml::from_str::<PackageLock>(&string), Ok(lock));
+    }
 }
@@ -1,10 +1,16 @@
 use std::fmt::Write;
+use std::sync::Arc;
 
+use hayro_syntax::Pdf;
+use hayro_syntax::content::ops::TypedInstruction;
+use hayro_syntax::object::dict::keys;
+use hayro_syntax::object::{Array, Dict, Name, Number, Stream};
 use typst::World;
 use typst::foundations::Smart;
 use typst::introspection::{Location, Tag};
-use typst::layout::{Frame, FrameItem, PagedDocument};
+use typst::layout::{Axes, Frame, FrameItem, PagedDocument};
 use typst::model::DocumentInfo;
+use typst_pdf::{PdfMarks, PdfOptions, PdfStandard, PdfStandards};
 
 use crate::collect::Test;
 use crate::world::TestWorld;
@@ -46,6 +52,50 @@ pub fn check(test: &Test, world: &TestWorld, doc: Option<&PagedDocument>) -> Str
                 sink.push_str("missing document");
             }
         }
+        "gradient-conic-text"
+        | "image-png"
+        | "text-chinese-basic"
+        | "tiling-lines"
+        | "transform-rotate-and-scale" => {
+            if let Some(doc) = doc {
+                if let Err(message) = check_tiled(doc) {
+                    sink.push_str(message);
+                }
+            } else {
+                sink.push_str("missing document");
+            }
+        }
+        "pdf-page-transition" => {
+            check_pdf_pages(&mut sink, doc, &PdfOptions::default(), check_transitions)
+        }
+        "pdf-page-bleed" => {
+            check_pdf_pages(
+                &mut sink,
+                doc,
+                &PdfOptions::default(),
+                |sink, pdf, pages| {
+                    check_page_boxes(sink, pages, 0.0);
+                    test_eq!(sink, page_artifacts(pdf), 0);
+                },
+            );
+            let marks = PdfMarks { crop: true, registration: true, color_bars: true };
+            let options = PdfOptions { marks, ..Default::default() };
+            check_pdf_pages(&mut sink, doc, &options, |sink, pdf, pages| {
+                check_page_boxes(sink, pages, 30.0);
+                test_eq!(sink, page_artifacts(pdf), 1);
+            });
+        }
+        "pdf-field" => {
+            check_pdf_pages(&mut sink, doc, &PdfOptions::default(), check_form);
+            let standards = PdfStandards::new(&[PdfStandard::Ua_1]).unwrap();
+            let options = PdfOptions { standards, ..Default::default() };
+            check_pdf_pages(&mut sink, doc, &options, |sink, pdf, _| {
+                test_eq!(sink, form_elems(pdf), 4);
+            });
+        }
+        "pdf-spot-color" => {
+            check_pdf_pages(&mut sink, doc, &PdfOptions::default(), check_separations)
+        }
         _ => {}
     }
     sink
@@ -92,3 +142,297 @@ fn check_balanced(doc: &PagedDocument) -> Result<(), &'static str> {
     let mut stack = Vec::new();
     doc.pages.iter().try_for_each(|page| visit(&mut stack, &page.frame))
 }
+
+/// Checks that rendering the pages tile by tile yields exactly the same pixels
+/// as rendering them in one go.
+///
+/// The tiles are deliberately small and have odd sizes, so that glyphs,
+/// gradients, images, and transformed shapes all cross tile boundaries.
+fn check_tiled(doc: &PagedDocument) -> Result<(), &'static str> {
+    let pixel_per_pt = 2.5;
+    for page in &doc.pages {
+        let full = typst_render::render(page, pixel_per_pt);
+        let mut tiled = tiny_skia::Pixmap::new(full.width(), full.height()).unwrap();
+        let stride = 4 * full.width() as usize;
+        typst_render::render_tiled(
+            page,
+            pixel_per_pt,
+            Axes::new(37, 23),
+            |tile| -> Result<(), &'static str> {
+                let row = 4 * tile.pixmap.width() as usize;
+                for (i, pixels) in tile.pixmap.data().chunks(row).enumerate() {
+                    let start = (tile.y as usize + i) * stride + 4 * tile.x as usize;
+                    tiled.data_mut()[start..start + row].copy_from_slice(pixels);
+                }
+                Ok(())
+            },
+        )?;
+        if tiled.data() != full.data() {
+            return Err("tiled rendering differs from full rendering");
+        }
+    }
+    Ok(())
+}
+
+/// Exports the document to PDF and runs a check on the dictionaries of its
+/// pages.
+fn check_pdf_pages(
+    sink: &mut String,
+    doc: Option<&PagedDocument>,
+    options: &PdfOptions,
+    check: impl FnOnce(&mut String, &Pdf, &[Dict]),
+) {
+    let Some(doc) = doc else {
+        sink.push_str("missing document");
+        return;
+    };
+    let Ok(pdf) = typst_pdf::pdf(doc, options) else {
+        sink.push_str("failed to export PDF");
+        return;
+    };
+    let Ok(pdf) = Pdf::new(Arc::new(pdf)) else {
+        sink.push_str("failed to load PDF");
+        return;
+    };
+    let xref = pdf.xref();
+    let Some(kids) = xref
+        .get::<Dict>(xref.root_id())
+        .and_then(|catalog| catalog.get::<Dict>(keys::PAGES))
+        .and_then(|pages| pages.get::<Array>(keys::KIDS))
+    else {
+        sink.push_str("missing page tree");
+        return;
+    };
+    let pages: Vec<Dict> = kids.iter::<Dict>().collect();
+    check(sink, &pdf, &pages);
+}
+
+/// Checks the `/Trans` and `/Dur` entries written for the pages of the
+/// `pdf-page-transition` test.
+fn check_transitions(sink: &mut String, _: &Pdf, pages: &[Dict]) {
+    test_eq!(sink, pages.len(), 3);
+    let name = |dict: &Dict, key: &[u8]| {
+        dict.get::<Name>(key).map(|name| name.as_str().to_string())
+    };
+    let number = |dict: &Dict, key: &[u8]| dict.get::<Number>(key).map(|n| n.as_f64());
+    let trans =
+        |i: usize| pages.get(i).and_then(|page| page.get::<Dict>(b"Trans".as_slice()));
+    let dur = |i: usize| pages.get(i).and_then(|page| number(page, b"Dur"));
+
+    let Some(push) = trans(0) else {
+        sink.push_str("missing transition on first page");
+        return;
+    };
+    test_eq!(sink, name(&push, b"Type").as_deref(), Some("Trans"));
+    test_eq!(sink, name(&push, b"S").as_deref(), Some("Push"));
+    test_eq!(sink, number(&push, b"D"), Some(0.5));
+    test_eq!(sink, number(&push, b"Di"), Some(180.0));
+    test_eq!(sink, dur(0), Some(2.5));
+
+    let Some(blinds) = trans(1) else {
+        sink.push_str("missing transition on second page");
+        return;
+    };
+    test_eq!(sink, name(&blinds, b"S").as_deref(), Some("Blinds"));
+    test_eq!(sink, name(&blinds, b"Dm").as_deref(), Some("V"));
+    test_eq!(sink, number(&blinds, b"D"), None);
+    test_eq!(sink, dur(1), Some(1.0));
+
+    test_eq!(sink, trans(2).is_some(), false);
+    test_eq!(sink, dur(2), None);
+}
+
+/// Checks the page boxes of the `pdf-page-bleed` test, whose page is
+/// 100pt × 50pt large with a bleed of 3pt on the left and 2pt elsewhere.
+///
+/// The media box must leave `slug` points of space around the bleed for
+/// printer's marks.
+fn check_page_boxes(sink: &mut String, pages: &[Dict], slug: f64) {
+    let Some(page) = pages.first() else {
+        sink.push_str("missing page");
+        return;
+    };
+    let rect = |key: &[u8]| {
+        page.get::<Array>(key)
+            .map(|array| array.iter::<Number>().map(|n| n.as_f64()).collect::<Vec<_>>())
+            .filter(|rect| rect.len() == 4)
+    };
+    let (Some(media), Some(bleed), Some(trim), Some(art)) =
+        (rect(b"MediaBox"), rect(b"BleedBox"), rect(b"TrimBox"), rect(b"ArtBox"))
+    else {
+        sink.push_str("missing page boxes");
+        return;
+    };
+
+    // The boxes are compared relative to the media box, whose origin is up to
+    // the PDF writer.
+    let relative = |rect: &[f64]| {
+        let round = |v: f64| (v * 100.0).round() / 100.0;
+        [
+            round(rect[0] - media[0]),
+            round(rect[1] - media[1]),
+            round(media[2] - rect[2]),
+            round(media[3] - rect[3]),
+        ]
+    };
+    test_eq!(sink, relative(&bleed), [slug; 4]);
+    test_eq!(sink, relative(&trim), [slug + 3.0, slug + 2.0, slug + 2.0, slug + 2.0]);
+    test_eq!(sink, art, trim);
+}
+
+/// Counts the marked-content sequences on the first page that are page
+/// artifacts, in which printer's marks are drawn.
+fn page_artifacts(pdf: &Pdf) -> usize {
+    let Some(page) = pdf.pages().iter().next() else { return 0 };
+    page.typed_operations()
+        .filter_map(|op| match op {
+            TypedInstruction::BeginMarkedContentWithProperties(mc)
+                if mc.0.as_str() == "Artifact" =>
+            {
+                mc.1.into_dict()
+            }
+            _ => None,
+        })
+        .filter(|props| {
+            props.get::<Name>(keys::TYPE).is_some_and(|ty| ty.as_str() == "Page")
+        })
+        .count()
+}
+
+/// Checks the form of the `pdf-field` test, which has a text field, a
+/// dropdown, a checkbox, and a radio group with two buttons.
+fn check_form(sink: &mut String, pdf: &Pdf, pages: &[Dict]) {
+    let xref = pdf.xref();
+    let Some(form) = xref
+        .get::<Dict>(xref.root_id())
+        .and_then(|catalog| catalog.get::<Dict>(b"AcroForm".as_slice()))
+    else {
+        sink.push_str("missing form");
+        return;
+    };
+    test_eq!(sink, form.contains_key(b"NeedAppearances".as_slice()), false);
+
+    // The fields' values are drawn with a font that is embedded in full.
+    let font = form
+        .get::<Dict>(b"DR".as_slice())
+        .and_then(|resources| resources.get::<Dict>(b"Font".as_slice()))
+        .and_then(|fonts| fonts.get::<Dict>(b"F0".as_slice()));
+    let file = font
+        .as_ref()
+        .and_then(|font| font.get::<Dict>(b"FontDescriptor".as_slice()))
+        .and_then(|descriptor| descriptor.get::<Stream>(b"FontFile2".as_slice()));
+    test_eq!(sink, file.is_some(), true);
+
+    let fields: Vec<Dict> = form
+        .get::<Array>(b"Fields".as_slice())
+        .map(|fields| fields.iter::<Dict>().collect())
+        .unwrap_or_default();
+    let name = |dict: &Dict, key: &[u8]| {
+        dict.get::<Name>(key).map(|name| name.as_str().to_string())
+    };
+    let kinds: Vec<_> = fields.iter().map(|field| name(field, b"FT")).collect();
+    test_eq!(
+        sink,
+        kinds.iter().map(Option::as_deref).collect::<Vec<_>>(),
+        [Some("Tx"), Some("Ch"), Some("Btn"), Some("Btn")]
+    );
+
+    let widgets: Vec<Vec<Dict>> = fields
+        .iter()
+        .map(|field| {
+            field
+                .get::<Array>(keys::KIDS)
+                .map(|kids| kids.iter::<Dict>().collect())
+                .unwrap_or_default()
+        })
+        .collect();
+    test_eq!(sink, widgets.iter().map(Vec::len).collect::<Vec<_>>(), [1, 1, 1, 2]);
+    for widget in widgets.iter().flatten() {
+        test_eq!(sink, name(widget, b"Subtype").as_deref(), Some("Widget"));
+        let has_appearance = widget
+            .get::<Dict>(b"AP".as_slice())
+            .is_some_and(|ap| ap.contains_key(b"N".as_slice()));
+        test_eq!(sink, has_appearance, true);
+    }
+
+    // The values of the text field and the dropdown are drawn into their
+    // appearance streams.
+    for (i, text) in [(0, "Jane Doe"), (1, "M")] {
+        let content = widgets[i]
+            .first()
+            .and_then(|widget| widget.get::<Dict>(b"AP".as_slice()))
+            .and_then(|ap| ap.get::<Stream>(b"N".as_slice()))
+            .and_then(|stream| stream.decoded().ok())
+            .unwrap_or_default();
+        let hex: String = text.bytes().map(|byte| format!("{byte:02X}")).collect();
+        let shown = String::from_utf8_lossy(&content).contains(&format!("<{hex}> Tj"));
+        test_eq!(sink, shown, true);
+    }
+
+    // The checkbox is checked and the second radio button is selected.
+    let state =
+        |i: usize, j: usize| widgets[i].get(j).and_then(|widget| name(widget, b"AS"));
+    test_eq!(sink, state(2, 0).as_deref(), Some("Yes"));
+    test_eq!(sink, state(3, 0).as_deref(), Some("Off"));
+    test_eq!(sink, state(3, 1).as_deref(), Some("1"));
+
+    let annots = pages
+        .first()
+        .and_then(|page| page.get::<Array>(b"Annots".as_slice()))
+        .map_or(0, |annots| annots.iter::<Dict>().count());
+    test_eq!(sink, annots, 5);
+}
+
+/// Counts the `Form` structure elements in a tagged PDF.
+fn form_elems(pdf: &Pdf) -> usize {
+    fn visit(dict: &Dict, depth: usize) -> usize {
+        if depth > 64 {
+            return 0;
+        }
+        let own = dict
+            .get::<Name>(b"S".as_slice())
+            .is_some_and(|s| s.as_str() == "Form");
+        let kids = match dict.get::<Array>(b"K".as_slice()) {
+            Some(kids) => kids.iter::<Dict>().map(|kid| visit(&kid, depth + 1)).sum(),
+            None => dict
+                .get::<Dict>(b"K".as_slice())
+                .map_or(0, |kid| visit(&kid, depth + 1)),
+        };
+        usize::from(own) + kids
+    }
+
+    let xref = pdf.xref();
+    xref.get::<Dict>(xref.root_id())
+        .and_then(|catalog| catalog.get::<Dict>(b"StructTreeRoot".as_slice()))
+        .map_or(0, |root| visit(&root, 0))
+}
+
+/// Checks that the `pdf-spot-color` test selects a separation in the content
+/// stream of its page.
+fn check_separations(sink: &mut String, _: &Pdf, pages: &[Dict]) {
+    let Some(page) = pages.first() else {
+        sink.push_str("missing page");
+        return;
+    };
+
+    let separation = page
+        .get::<Dict>(keys::RESOURCES)
+        .and_then(|resources| resources.get::<Dict>(b"ColorSpace".as_slice()))
+        .and_then(|spaces| spaces.get::<Array>(b"Spot0".as_slice()))
+        .map(|space| {
+            space
+                .iter::<Name>()
+                .map(|name| name.as_str().to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    test_eq!(sink, separation, ["Separation", "PANTONE 286 C", "DeviceCMYK"]);
+
+    let content = page
+        .get::<Stream>(keys::CONTENTS)
+        .and_then(|stream| stream.decoded().ok())
+        .unwrap_or_default();
+    let content = String::from_utf8_lossy(&content);
+    test_eq!(sink, content.contains("/Spot0 cs"), true);
+    test_eq!(sink, content.contains("/Spot0 CS"), true);
+}
@@ -103,6 +103,13 @@ impl TestWorld {
     }
 }
 
+/// Fonts that are specific to the test suite.
+///
+/// `TypstTestVariable.ttf` is a minimal variable font with a `wght` axis from
+/// 100 to 900. It maps `I` to a bar whose right edge and advance move by 200
+/// units towards the heaviest and by -60 units towards the lightest weight.
+const TEST_FONTS: &[&[u8]] = &[include_bytes!("../fonts/TypstTestVariable.ttf")];
+
 /// Shared foundation of all test worlds.
 struct TestBase {
     library: LazyHash<Library>,
@@ -115,6 +122,7 @@ impl Default for TestBase {
     fn default() -> Self {
         let fonts: Vec<_> = typst_assets::fonts()
             .chain(typst_dev_assets::fonts())
+            .chain(TEST_FONTS.iter().copied())
             .flat_map(|data| Font::iter(Bytes::new(data)))
             .collect();
 
@@ -0,0 +1,7 @@
+--- html-split-single-file html ---
+// When exporting to a single file, split points produce nothing.
+= Introduction
+Hello
+
+#html.split(title: "Details")
+World
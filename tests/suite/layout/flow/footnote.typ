@@ -408,3 +408,91 @@ C
     Hello #footnote[Footnote 1]
   ]
 })
+
+--- footnote-series paged html ---
+#show footnote.where(series: "variants"): set footnote(numbering: "a")
+A#footnote[First note.]
+B#footnote(series: "variants")[First variant.]
+C#footnote[Second note.]
+D#footnote(series: "variants")[Second variant.]
+
+--- footnote-series-counter paged ---
+#let variant = footnote.with(series: "variants")
+A#footnote[Note.]
+B#variant[First variant.]
+C#variant[Second variant.]
+#context test(counter(footnote).get(), (1,))
+#context test(counter(footnote.where(series: "variants")).get(), (2,))
+
+--- footnote-series-counter-update paged ---
+#counter(footnote).update(4)
+A#footnote[Note.]
+B#footnote(series: "variants")[Variant.]
+#context test(counter(footnote).get(), (5,))
+#context test(counter(footnote.where(series: "variants")).get(), (1,))
+
+--- footnote-series-separator paged ---
+#show footnote.where(series: "variants"): set footnote(
+  numbering: "a",
+  separator: line(length: 100%, stroke: 0.5pt + red),
+)
+A#footnote(series: "variants")[Variant.]
+B#footnote[Note.]
+
+--- footnote-series-break-across-pages paged ---
+#set page(height: 80pt)
+A#footnote(series: "variants")[#lorem(20)]
+B#footnote[Note.]
+
+--- footnote-endnotes paged html ---
+#set footnote(kind: "endnote")
+
+= Chapter
+Endnotes are marked like footnotes.#footnote[First note.]
+Their entries are listed elsewhere.#footnote[Second note.]
+
+#endnotes()
+
+--- footnote-endnotes-per-chapter paged ---
+#set footnote(kind: "endnote")
+A#footnote[First.]
+#endnotes(title: [Notes to chapter 1])
+B#footnote[Second.]
+C#footnote[Third.]
+#endnotes(title: [Notes to chapter 2])
+
+--- footnote-endnotes-mixed paged ---
+A#footnote[Footnote.]
+B#footnote(kind: "endnote")[Endnote.]
+#endnotes(title: none)
+
+--- footnote-endnotes-series paged html ---
+#let variant = footnote.with(series: "variants", kind: "endnote")
+#show footnote.where(series: "variants"): set footnote(numbering: "a")
+A#variant[First variant.]
+B#footnote(kind: "endnote")[First note.]
+C#variant[Second variant.]
+#endnotes(series: "variants", title: [Variants])
+#endnotes(title: [Notes])
+
+--- footnote-endnotes-ref paged ---
+A#footnote(kind: "endnote")[Endnote.]<en>
+B#footnote(<en>)
+#endnotes(title: none)
+
+--- footnote-endnotes-unlisted paged ---
+// Warning: 2-36 endnote is not listed by any `endnotes` element
+// Hint: 2-36 place an `endnotes` element of the same series after it
+#footnote(kind: "endnote")[Missing]
+
+--- footnote-endnotes-unlisted-before paged ---
+#endnotes(title: none)
+// Warning: 2-33 endnote is not listed by any `endnotes` element
+// Hint: 2-33 place an `endnotes` element of the same series after it
+#footnote(kind: "endnote")[Late]
+
+--- footnote-endnotes-unlisted-series paged ---
+// Warning: 2-56 endnote is not listed by any `endnotes` element
+// Hint: 2-56 place an `endnotes` element of the same series after it
+#footnote(series: "variants", kind: "endnote")[Variant]
+#endnotes(title: none)
@@ -66,9 +66,9 @@ Lריווח #h(1cm) R
 לתכנת בעברית `אם א == ב:`
 
 --- bidi-vertical paged ---
-// Test setting a vertical direction.
-// Error: 16-19 text direction must be horizontal
-#set text(dir: ttb)
+// Test setting a bottom-to-top direction.
+// Error: 16-19 text direction must be horizontal or top-to-bottom
+#set text(dir: btt)
 
 --- issue-1373-bidi-tofus paged ---
 // Test that shaping missing characters in both left-to-right and
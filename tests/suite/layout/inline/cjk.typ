@@ -110,3 +110,17 @@ abc字\
 时间#footnote[有空白]
 
 时间#sub[123]#super[时间]B
+
+--- text-vertical-dir paged ---
+#set text(dir: ttb)
+#context test(text.dir, ttb)
+
+--- text-vertical-columns paged ---
+// Test that vertical text is broken into columns as long as the region is
+// high, which progress to the left.
+#set text(dir: ttb, font: "Noto Serif CJK SC")
+#context {
+  let size = measure(height: 4em)[吾輩は猫である。名前はまだ無い。]
+  test(size.height, 4em.to-absolute())
+  test(size.width > 3em.to-absolute(), true)
+}
@@ -100,6 +100,49 @@ fi vs. #text(features: (liga: 0))[No fi]
 // Hint: 21-35 to set features with custom values, consider supplying a dictionary
 #set text(features: ("tag", false))
 
+--- text-variations-fold paged ---
+// Test that variation axes are folded.
+#set text(variations: (wght: 500))
+#context test(text.variations, (wght: 500.0))
+#set text(variations: (opsz: 12, wght: 600))
+#context test(text.variations, (wght: 600.0, opsz: 12.0))
+
+--- text-variations-advance paged ---
+// Test that instances of a variable font have their own advances. The bar of
+// the test font is 200 units wide by default and grows to 400 units at the
+// heaviest weight.
+#set text(font: "Typst Test Variable")
+#let width(..args) = calc.round(measure(text(..args)[I]).width.pt(), digits: 2)
+#test(width(), 2.0)
+#test(width(weight: 100), 1.4)
+#test(width(weight: 900), 4.0)
+#test(width(weight: "bold"), 3.2)
+#test(width(variations: (wght: 650)), 3.0)
+#test(width(weight: 100, variations: (wght: 900)), 4.0)
+
+--- text-variations-render paged ---
+// Test that instances of a variable font are drawn with their own outlines.
+#set text(font: "Typst Test Variable", size: 20pt)
+#for weight in (100, 400, 700, 900) [
+  #text(weight: weight)[III] \
+]
+#text(variations: (wght: 650))[III]
+
+--- text-variations-bad paged ---
+// Error: 23-28 expected dictionary, found boolean
+#set text(variations: false)
+
+--- text-variations-bad-value paged ---
+// Error: 23-37 expected float, found string
+// Hint: 23-37 occurred in tag at index 0 (`"wght"`)
+#set text(variations: (wght: "bold"))
+
+--- text-variations-overlong-tag paged ---
+// Error: 23-39 feature tag must be one to four characters in length
+// Hint: 23-39 found 6 characters
+// Hint: 23-39 occurred in tag at index 0 (`"weight"`)
+#set text(variations: (weight: 700))
+
 --- text-tracking-negative paged ---
 // Test tracking.
 #set text(tracking: -0.01em)
@@ -269,6 +269,11 @@ $ q &= sum_k ln A \
 #box($ - - \ a $, fill: silver)
 #box($ #metadata(none) - - \ a $, fill: silver)
 
+--- math-equation-html html ---
+The formula $a^2 + b^2 = c^2$ holds.
+
+$ sum_(i=1)^n i = (n(n+1))/2 $
+
 --- issue-4187-alignment-point-affects-row-height paged ---
 // In this bug, a row of "-" only should have a very small height; but
 // after adding an alignment point "&", the row gains a larger height.
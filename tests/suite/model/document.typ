@@ -52,3 +52,7 @@ Hello
   set document(author: "Changed") if "Normal" in document.author
   set document(title: "Changed") if document.title ==  "Normal"
 }
+
+--- document-output-intent-invalid paged ---
+// Error: 30-46 file is not a valid ICC profile
+#set document(output-intent: bytes((1, 2, 3)))
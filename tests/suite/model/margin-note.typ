@@ -0,0 +1,88 @@
+// Test margin notes.
+
+--- margin-note-basic paged html pdftags ---
+#set page(width: 120pt, height: 80pt, margin: (left: 10pt, right: 50pt))
+A
+#margin-note[Note]
+line with a note.
+
+--- margin-note-clearance paged ---
+#set page(width: 120pt, height: 80pt, margin: (left: 10pt, right: 50pt))
+#set margin-note(clearance: 2pt)
+Closer
+#margin-note[Close by]
+to the text.
+
+--- margin-note-two-sided paged ---
+// On two-sided pages bound on the left, notes alternate between the right
+// margin on odd pages and the left margin on even pages.
+#set page(width: 120pt, height: 60pt, margin: (inside: 10pt, outside: 50pt, y: 10pt))
+Odd #margin-note[Right]
+#pagebreak()
+Even #margin-note[Left]
+#pagebreak()
+Odd #margin-note[Right]
+
+--- margin-note-two-sided-binding-right paged ---
+#set page(
+  width: 120pt,
+  height: 60pt,
+  binding: right,
+  margin: (inside: 10pt, outside: 50pt, y: 10pt),
+)
+Odd #margin-note[Left]
+#pagebreak()
+Even #margin-note[Right]
+
+--- margin-note-binding-right paged ---
+// One-sided pages bound on the right have their notes in the left margin.
+#set page(width: 120pt, height: 60pt, binding: right, margin: (left: 50pt, rest: 10pt))
+A #margin-note[Left]
+#pagebreak()
+B #margin-note[Left]
+
+--- margin-note-collision paged ---
+// Notes that would overlap are pushed down, with the gap between them.
+#set page(width: 120pt, height: 100pt, margin: (left: 10pt, right: 50pt))
+#set margin-note(gap: 4pt)
+A #margin-note[First] #margin-note[Second] \
+B #margin-note[Third, which is longer] \
+C #margin-note[Fourth]
+
+--- margin-note-next-page paged ---
+// Notes that don't fit onto the page move to the top of the next page's
+// margin, even if the next page has no notes of its own.
+#set page(width: 120pt, height: 60pt, margin: (left: 10pt, right: 50pt, y: 10pt))
+A #margin-note[One] #margin-note[Two] #margin-note[Three] #margin-note[Four]
+#pagebreak()
+B
+
+--- margin-note-next-page-with-notes paged ---
+// Moved notes come before the notes anchored on the next page.
+#set page(width: 120pt, height: 60pt, margin: (left: 10pt, right: 50pt, y: 10pt))
+A #margin-note[One] #margin-note[Two] #margin-note[Three] #margin-note[Four]
+#pagebreak()
+B #margin-note[Five]
+
+--- margin-note-columns paged ---
+// With multiple columns, notes from all columns go into the outside margin,
+// sorted by the height of their anchors.
+#set page(width: 160pt, height: 80pt, columns: 2, margin: (left: 10pt, right: 50pt))
+#lines(2) #margin-note[First column]
+#colbreak()
+Top #margin-note[Second column]
+
+--- margin-note-page-height-auto paged ---
+// Pages that size to their content grow to fit the notes.
+#set page(width: 120pt, height: auto, margin: (left: 10pt, right: 50pt, y: 10pt))
+A #margin-note[A long note that needs more than a single line]
+
+--- margin-note-in-header paged ---
+// Notes in the header are ignored.
+#set page(
+  width: 120pt,
+  height: 60pt,
+  margin: (left: 10pt, right: 50pt),
+  header: [Header #margin-note[Ignored]],
+)
+Body
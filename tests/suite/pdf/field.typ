@@ -0,0 +1,38 @@
+// Test interactive form fields. The exported form is checked in `custom.rs`.
+
+--- pdf-field paged ---
+#set page(width: 160pt, height: auto, margin: 8pt)
+Name: #pdf.field("name", tooltip: "Full name", value: "Jane Doe") \
+Size: #pdf.field(
+  "size",
+  kind: "dropdown",
+  options: ("S", "M", "L"),
+  value: "M",
+  tooltip: "Size",
+) \
+#pdf.field("newsletter", kind: "checkbox", value: true, tooltip: "Newsletter")
+Newsletter \
+#pdf.field(
+  "contact",
+  kind: "radio",
+  options: ("Email", "Phone"),
+  value: "Phone",
+  tooltip: "Contact",
+)
+
+--- pdf-field-styled paged ---
+#set page(width: 120pt, height: auto, margin: 8pt)
+#set text(font: "DejaVu Sans Mono", size: 8pt, fill: blue)
+#pdf.field("code", value: "Größe", width: 100%, fill: luma(240), stroke: none)
+
+--- pdf-field-pdf-a paged pdfstandard(a-2b) ---
+// Error: 2-52 PDF/A-2b error: form fields cannot be used
+// Hint: 2-52 remove the fields or export without PDF/A-2b
+#pdf.field("name", value: "Typst", tooltip: "Name")
+#pdf.field("size", kind: "dropdown", options: ("S", "M"), value: "S", tooltip: "Size")
+
+--- pdf-field-duplicate-name paged ---
+#pdf.field("name")
+// Error: 2-19 duplicate form field name `name`
+// Hint: 2-19 each field must have a unique name
+#pdf.field("name")
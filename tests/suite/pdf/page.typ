@@ -0,0 +1,27 @@
+// Test PDF page properties. These are checked on the exported file in
+// `custom.rs`.
+
+--- pdf-page-transition paged ---
+#set page(width: 40pt, height: 30pt)
+#page(
+  transition: (style: "push", duration: 0.5s, direction: rtl),
+  duration: 2.5s,
+)[A]
+#page(transition: (style: "blinds", direction: ttb), duration: 1s)[B]
+#page[C]
+
+--- pdf-page-transition-pdf-ua paged pdfstandard(ua-1) ---
+// Error: PDF/UA-1 error: viewer settings and page transitions cannot be used
+// Hint: remove them or export without PDF/UA-1
+#set document(title: "Slides")
+#page(transition: (style: "fade"))[A]
+
+--- pdf-page-bleed paged ---
+#set page(
+  width: 100pt,
+  height: 50pt,
+  bleed: (left: 3pt, rest: 2pt),
+  fill: aqua,
+)
+#place(top + left, dx: -3pt, dy: -2pt, square(size: 10pt, fill: red))
+A
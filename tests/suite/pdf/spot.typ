@@ -0,0 +1,15 @@
+// Test spot colors in PDF export. The separations are checked on the exported
+// file in `custom.rs`.
+
+--- pdf-spot-color paged ---
+#let blue = color.spot("PANTONE 286 C", cmyk(100%, 66%, 0%, 2%))
+#set page(width: 60pt, height: 40pt, margin: 5pt)
+#box(square(size: 10pt, fill: blue))
+#box(square(size: 10pt, fill: blue.lighten(50%), stroke: 1pt + blue))
+#box(square(size: 10pt, fill: tiling(size: (4pt, 4pt), square(size: 2pt, fill: blue))))
+#text(fill: blue)[A]
+
+--- pdf-spot-color-pdf-a paged pdfstandard(a-2b) ---
+// Error: PDF/A-2b error: the spot color "PANTONE 286 C" cannot be used
+// Hint: use a CMYK color instead or export without PDF/A-2b
+#square(fill: color.spot("PANTONE 286 C", cmyk(100%, 66%, 0%, 2%)))
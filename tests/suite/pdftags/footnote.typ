@@ -10,3 +10,11 @@ Footnote #footnote[
 
 --- footnote-tags-ref-to-other-footnote pdftags pdfstandard(ua-1) ---
 This #footnote[content]<note> and #footnote(<note>).
+
+--- footnote-tags-series pdftags pdfstandard(ua-1) ---
+A #footnote[Note] and B #footnote(series: "variants")[Variant].
+
+--- footnote-tags-endnotes pdftags pdfstandard(ua-1) ---
+A #footnote(kind: "endnote")[Endnote] and B #footnote[Footnote].
+
+#endnotes()
@@ -249,6 +249,31 @@
   box(square(size: 9pt, fill: c.darken(x * 10%)))
 }
 
+--- color-spot paged ---
+// Test spot colors, which are shown with their fallback color.
+#let blue = color.spot("PANTONE 286 C", cmyk(100%, 66%, 0%, 2%))
+#stack(
+  dir: ltr,
+  spacing: 1fr,
+  rect(width: 1cm, fill: blue),
+  rect(width: 1cm, fill: blue.lighten(50%), stroke: 2pt + blue),
+  rect(width: 1cm, fill: color.spot("PANTONE 286 C", blue, tint: 20%)),
+)
+#text(fill: blue)[Spot]
+
+--- color-spot-ops paged ---
+#let blue = color.spot("PANTONE 286 C", cmyk(100%, 66%, 0%, 2%), tint: 50%)
+#test(blue.space(), cmyk)
+#test(
+  blue.lighten(50%),
+  color.spot("PANTONE 286 C", cmyk(100%, 66%, 0%, 2%), tint: 25%),
+)
+#test(blue.darken(0%) == blue, false)
+
+--- color-spot-empty-name paged ---
+// Error: 2-40 spot color name must not be empty
+#color.spot("", cmyk(0%, 0%, 0%, 100%))
+
 --- color-outside-srgb-gamut paged ---
 // Colors outside the sRGB gamut.
 #box(square(size: 9pt, fill: oklab(90%, -0.2, -0.1)))